@@ -0,0 +1,24 @@
+-- Add library roots as relative-path anchors
+-- Files under a root store their path relative to it, so moving a root is a single update
+-- that rewrites the paths of its files
+
+CREATE TABLE LibraryRoots (
+    root_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    path TEXT NOT NULL UNIQUE, -- Absolute path without trailing separator
+    label TEXT,
+    created_at INTEGER NOT NULL -- Unix timestamp
+);
+
+ALTER TABLE Files
+ADD COLUMN root_id INTEGER DEFAULT NULL REFERENCES LibraryRoots(root_id) ON DELETE SET NULL;
+
+ALTER TABLE Files
+ADD COLUMN relative_path TEXT DEFAULT NULL; -- Path below the root incl. leading separator; set with root_id
+
+CREATE INDEX idx_files_root_id ON Files(root_id);
+
+-- root path || relative_path is the full path
+CREATE TRIGGER library_roots_relocate AFTER UPDATE OF path ON LibraryRoots BEGIN
+    UPDATE Files SET original_path = new.path || relative_path
+    WHERE root_id = new.root_id AND relative_path IS NOT NULL;
+END;
@@ -0,0 +1,14 @@
+-- Add embedded metadata extracted at import (EXIF, XMP, PNG text chunks)
+-- Stored as key/value rows so new fields don't need schema changes
+
+CREATE TABLE FileMetadata (
+    metadata_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_hash TEXT NOT NULL,
+    source TEXT NOT NULL, -- 'exif', 'xmp', 'png' or 'sd' (parsed Stable Diffusion parameters)
+    key TEXT NOT NULL,    -- e.g. 'DateTimeOriginal', 'Model', 'subject', 'parameters', 'prompt'
+    value TEXT NOT NULL,
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_file_metadata_file_hash ON FileMetadata(file_hash);
+CREATE INDEX idx_file_metadata_key ON FileMetadata(key, value);
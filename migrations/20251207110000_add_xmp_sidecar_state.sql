@@ -0,0 +1,11 @@
+-- Track XMP sidecar sync state for conflict detection
+-- A sidecar whose mtime differs from synced_mtime was edited by another tool since our last sync
+
+CREATE TABLE XmpSidecars (
+    file_hash TEXT PRIMARY KEY,
+    sidecar_path TEXT NOT NULL,
+    synced_mtime INTEGER NOT NULL, -- Sidecar mtime in Unix milliseconds right after our last read/write
+    synced_at INTEGER NOT NULL,    -- Unix timestamp
+    rating INTEGER DEFAULT NULL,   -- xmp:Rating, carried through round-trips
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
@@ -0,0 +1,19 @@
+-- Add source URLs per file (several per file, e.g. pixiv + danbooru + twitter)
+-- domain/site/post_id are derived from the URL when it is added, so lookups don't parse URLs
+
+CREATE TABLE FileSources (
+    source_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_hash TEXT NOT NULL,
+    url TEXT NOT NULL,
+    domain TEXT NOT NULL,        -- Lowercase host without "www."
+    site TEXT DEFAULT NULL,      -- 'pixiv', 'danbooru', 'gelbooru', 'twitter', ... when recognized
+    post_id TEXT DEFAULT NULL,   -- Post/illust/status ID on that site
+    page INTEGER DEFAULT NULL,   -- Page within a multi-image post
+    added_at INTEGER NOT NULL,   -- Unix timestamp
+    UNIQUE(file_hash, url),
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_file_sources_file_hash ON FileSources(file_hash);
+CREATE INDEX idx_file_sources_domain ON FileSources(domain);
+CREATE INDEX idx_file_sources_site_post ON FileSources(site, post_id);
@@ -0,0 +1,35 @@
+-- Add works: ordered groups of files that belong together (multi-page pixiv posts, manga, variants)
+-- A file belongs to at most one work
+
+CREATE TABLE Works (
+    work_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    title TEXT DEFAULT NULL,
+    site TEXT DEFAULT NULL,       -- Set for works grouped automatically from filenames
+    post_id TEXT DEFAULT NULL,
+    cover_hash TEXT DEFAULT NULL, -- NULL means the first member is the cover
+    created_at INTEGER NOT NULL,  -- Unix timestamp
+    FOREIGN KEY (cover_hash) REFERENCES Files(file_hash) ON DELETE SET NULL
+);
+
+-- One automatic work per post
+CREATE UNIQUE INDEX idx_works_site_post ON Works(site, post_id) WHERE post_id IS NOT NULL;
+
+CREATE TABLE WorkMembers (
+    work_id INTEGER NOT NULL,
+    file_hash TEXT NOT NULL UNIQUE,
+    position INTEGER NOT NULL,    -- Sort key within the work; page number for automatic works
+    PRIMARY KEY (work_id, file_hash),
+    FOREIGN KEY (work_id) REFERENCES Works(work_id) ON DELETE CASCADE,
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_work_members_position ON WorkMembers(work_id, position);
+
+-- A member's cover goes when it leaves, and the whole work once its last member is gone
+CREATE TRIGGER work_members_delete AFTER DELETE ON WorkMembers BEGIN
+    UPDATE Works SET cover_hash = NULL
+    WHERE work_id = old.work_id AND cover_hash = old.file_hash;
+    DELETE FROM Works
+    WHERE work_id = old.work_id
+      AND NOT EXISTS (SELECT 1 FROM WorkMembers WHERE work_id = old.work_id);
+END;
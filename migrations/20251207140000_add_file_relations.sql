@@ -0,0 +1,21 @@
+-- Add typed relations between files (booru-style parent/child, variants, edits)
+-- Read an edge as "file_hash <relation_type> related_hash":
+--   parent:       related_hash is the parent of file_hash
+--   derived_from: file_hash was made from related_hash (crop, edit, upscale)
+--   variant:      alternate versions of each other (symmetric, file_hash < related_hash)
+--   same_source:  taken from the same post or source (symmetric, file_hash < related_hash)
+
+CREATE TABLE FileRelations (
+    relation_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_hash TEXT NOT NULL,
+    related_hash TEXT NOT NULL,
+    relation_type TEXT NOT NULL CHECK(relation_type IN ('parent', 'variant', 'derived_from', 'same_source')),
+    created_at INTEGER NOT NULL, -- Unix timestamp
+    UNIQUE(file_hash, related_hash, relation_type),
+    CHECK(file_hash != related_hash),
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE,
+    FOREIGN KEY (related_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_file_relations_file_hash ON FileRelations(file_hash, relation_type);
+CREATE INDEX idx_file_relations_related_hash ON FileRelations(related_hash, relation_type);
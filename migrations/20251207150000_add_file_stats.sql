@@ -0,0 +1,17 @@
+-- Add star ratings and view statistics per file
+-- A file without a row has no rating and was never viewed
+
+CREATE TABLE FileStats (
+    file_hash TEXT PRIMARY KEY,
+    rating INTEGER DEFAULT NULL CHECK(rating BETWEEN 0 AND 5), -- Stars; NULL means unrated
+    view_count INTEGER NOT NULL DEFAULT 0,
+    last_viewed_at INTEGER DEFAULT NULL,                       -- Unix timestamp
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_file_stats_rating ON FileStats(rating);
+CREATE INDEX idx_file_stats_last_viewed ON FileStats(last_viewed_at);
+
+-- Ratings read from XMP sidecars so far were only kept for round-trips
+INSERT INTO FileStats (file_hash, rating)
+SELECT file_hash, rating FROM XmpSidecars WHERE rating BETWEEN 0 AND 5;
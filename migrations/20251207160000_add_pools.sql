@@ -0,0 +1,23 @@
+-- Add pools: ordered, curated collections (comic pools, themed sets)
+-- Unlike works, a file can be in many pools, and appear in a pool more than once
+
+CREATE TABLE Pools (
+    pool_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    name TEXT NOT NULL UNIQUE COLLATE NOCASE,
+    description TEXT DEFAULT NULL,
+    created_at INTEGER NOT NULL, -- Unix timestamp
+    updated_at INTEGER NOT NULL  -- Unix timestamp
+);
+
+CREATE TABLE PoolMembers (
+    member_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    pool_id INTEGER NOT NULL,
+    file_hash TEXT NOT NULL,
+    position INTEGER NOT NULL,   -- Sort key, renumbered 0..n whenever the pool is edited
+    added_at INTEGER NOT NULL,   -- Unix timestamp
+    FOREIGN KEY (pool_id) REFERENCES Pools(pool_id) ON DELETE CASCADE,
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_pool_members_position ON PoolMembers(pool_id, position);
+CREATE INDEX idx_pool_members_file_hash ON PoolMembers(file_hash);
@@ -0,0 +1,39 @@
+-- Add region notes: boxes with text over an image (Danbooru-style translation notes)
+-- Coordinates are fractions of the displayed image (after EXIF orientation), so they survive resizing
+
+CREATE TABLE FileNotes (
+    note_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    file_hash TEXT NOT NULL,
+    x REAL NOT NULL CHECK(x >= 0 AND x <= 1),           -- Left edge
+    y REAL NOT NULL CHECK(y >= 0 AND y <= 1),           -- Top edge
+    width REAL NOT NULL CHECK(width > 0 AND width <= 1),
+    height REAL NOT NULL CHECK(height > 0 AND height <= 1),
+    body TEXT NOT NULL,
+    language TEXT DEFAULT NULL,  -- Language of the body, e.g. 'en', 'zh'
+    created_at INTEGER NOT NULL, -- Unix timestamp
+    updated_at INTEGER NOT NULL, -- Unix timestamp
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_file_notes_file_hash ON FileNotes(file_hash);
+
+-- Full-text index over note bodies, kept in sync by triggers
+CREATE VIRTUAL TABLE FileNotesFts USING fts5(
+    body,
+    content='FileNotes',
+    content_rowid='note_id',
+    tokenize='unicode61 remove_diacritics 2'
+);
+
+CREATE TRIGGER file_notes_fts_insert AFTER INSERT ON FileNotes BEGIN
+    INSERT INTO FileNotesFts(rowid, body) VALUES (new.note_id, new.body);
+END;
+
+CREATE TRIGGER file_notes_fts_delete AFTER DELETE ON FileNotes BEGIN
+    INSERT INTO FileNotesFts(FileNotesFts, rowid, body) VALUES ('delete', old.note_id, old.body);
+END;
+
+CREATE TRIGGER file_notes_fts_update AFTER UPDATE OF body ON FileNotes BEGIN
+    INSERT INTO FileNotesFts(FileNotesFts, rowid, body) VALUES ('delete', old.note_id, old.body);
+    INSERT INTO FileNotesFts(rowid, body) VALUES (new.note_id, new.body);
+END;
@@ -0,0 +1,118 @@
+-- Full-text indexes for search_everything, kept in sync by triggers
+-- Tag names/aliases and file paths use the trigram tokenizer: it matches inside words and
+-- works for CJK text, which has no spaces between words. Notes (FileNotesFts) already have one.
+
+-- Tag names and aliases
+CREATE VIRTUAL TABLE TagsFts USING fts5(
+    name,
+    alias,
+    content='Tags',
+    content_rowid='tag_id',
+    tokenize='trigram'
+);
+INSERT INTO TagsFts(TagsFts) VALUES ('rebuild');
+
+CREATE TRIGGER tags_fts_insert AFTER INSERT ON Tags BEGIN
+    INSERT INTO TagsFts(rowid, name, alias) VALUES (new.tag_id, new.name, new.alias);
+END;
+
+CREATE TRIGGER tags_fts_delete AFTER DELETE ON Tags BEGIN
+    INSERT INTO TagsFts(TagsFts, rowid, name, alias) VALUES ('delete', old.tag_id, old.name, old.alias);
+END;
+
+CREATE TRIGGER tags_fts_update AFTER UPDATE OF name, alias ON Tags BEGIN
+    INSERT INTO TagsFts(TagsFts, rowid, name, alias) VALUES ('delete', old.tag_id, old.name, old.alias);
+    INSERT INTO TagsFts(rowid, name, alias) VALUES (new.tag_id, new.name, new.alias);
+END;
+
+-- Original paths, for file name and folder matches. Files has no integer key (its rowids may
+-- change on VACUUM), so FileSearchKeys gives each file a stable one (declared INTEGER PRIMARY
+-- KEY, so VACUUM keeps it) and FilePathsFts is an external-content index over Files through it.
+-- Deleting by an UNINDEXED file_hash column instead would scan the whole index per row
+CREATE TABLE FileSearchKeys (
+    file_key INTEGER PRIMARY KEY,
+    file_hash TEXT NOT NULL UNIQUE
+);
+INSERT INTO FileSearchKeys (file_hash) SELECT file_hash FROM Files ORDER BY date_imported;
+
+CREATE VIEW FilePathsContent AS
+SELECT k.file_key, f.file_hash, f.original_path
+FROM FileSearchKeys k
+JOIN Files f ON f.file_hash = k.file_hash;
+
+CREATE VIRTUAL TABLE FilePathsFts USING fts5(
+    file_hash UNINDEXED,
+    original_path,
+    content='FilePathsContent',
+    content_rowid='file_key',
+    tokenize='trigram'
+);
+INSERT INTO FilePathsFts(FilePathsFts) VALUES ('rebuild');
+
+CREATE TRIGGER file_paths_fts_insert AFTER INSERT ON Files BEGIN
+    INSERT INTO FileSearchKeys (file_hash) VALUES (new.file_hash);
+    INSERT INTO FilePathsFts(rowid, file_hash, original_path)
+    SELECT file_key, new.file_hash, new.original_path FROM FileSearchKeys WHERE file_hash = new.file_hash;
+END;
+
+CREATE TRIGGER file_paths_fts_delete AFTER DELETE ON Files BEGIN
+    INSERT INTO FilePathsFts(FilePathsFts, rowid, file_hash, original_path)
+    SELECT 'delete', file_key, old.file_hash, old.original_path FROM FileSearchKeys WHERE file_hash = old.file_hash;
+    DELETE FROM FileSearchKeys WHERE file_hash = old.file_hash;
+END;
+
+CREATE TRIGGER file_paths_fts_update AFTER UPDATE OF file_hash, original_path ON Files BEGIN
+    INSERT INTO FilePathsFts(FilePathsFts, rowid, file_hash, original_path)
+    SELECT 'delete', file_key, old.file_hash, old.original_path FROM FileSearchKeys WHERE file_hash = old.file_hash;
+    UPDATE FileSearchKeys SET file_hash = new.file_hash WHERE file_hash = old.file_hash;
+    INSERT INTO FilePathsFts(rowid, file_hash, original_path)
+    SELECT file_key, new.file_hash, new.original_path FROM FileSearchKeys WHERE file_hash = new.file_hash;
+END;
+
+-- Source URLs, split into words (site, artist names, post IDs)
+CREATE VIRTUAL TABLE FileSourcesFts USING fts5(
+    url,
+    content='FileSources',
+    content_rowid='source_id',
+    tokenize='unicode61'
+);
+INSERT INTO FileSourcesFts(FileSourcesFts) VALUES ('rebuild');
+
+CREATE TRIGGER file_sources_fts_insert AFTER INSERT ON FileSources BEGIN
+    INSERT INTO FileSourcesFts(rowid, url) VALUES (new.source_id, new.url);
+END;
+
+CREATE TRIGGER file_sources_fts_delete AFTER DELETE ON FileSources BEGIN
+    INSERT INTO FileSourcesFts(FileSourcesFts, rowid, url) VALUES ('delete', old.source_id, old.url);
+END;
+
+CREATE TRIGGER file_sources_fts_update AFTER UPDATE OF url ON FileSources BEGIN
+    INSERT INTO FileSourcesFts(FileSourcesFts, rowid, url) VALUES ('delete', old.source_id, old.url);
+    INSERT INTO FileSourcesFts(rowid, url) VALUES (new.source_id, new.url);
+END;
+
+-- Prompts parsed from generation parameters; only these metadata rows are indexed, so the
+-- index keeps its own copy of the text instead of reading FileMetadata
+CREATE VIRTUAL TABLE FilePromptsFts USING fts5(
+    value,
+    tokenize='unicode61'
+);
+INSERT INTO FilePromptsFts(rowid, value)
+SELECT metadata_id, value FROM FileMetadata
+WHERE source = 'sd' AND key IN ('prompt', 'negative_prompt');
+
+CREATE TRIGGER file_prompts_fts_insert AFTER INSERT ON FileMetadata
+WHEN new.source = 'sd' AND new.key IN ('prompt', 'negative_prompt') BEGIN
+    INSERT INTO FilePromptsFts(rowid, value) VALUES (new.metadata_id, new.value);
+END;
+
+CREATE TRIGGER file_prompts_fts_delete AFTER DELETE ON FileMetadata BEGIN
+    DELETE FROM FilePromptsFts WHERE rowid = old.metadata_id;
+END;
+
+CREATE TRIGGER file_prompts_fts_update AFTER UPDATE ON FileMetadata BEGIN
+    DELETE FROM FilePromptsFts WHERE rowid = old.metadata_id;
+    INSERT INTO FilePromptsFts(rowid, value)
+    SELECT new.metadata_id, new.value
+    WHERE new.source = 'sd' AND new.key IN ('prompt', 'negative_prompt');
+END;
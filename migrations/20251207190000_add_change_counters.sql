@@ -0,0 +1,21 @@
+-- Counters bumped by triggers whenever a table changes, so in-memory caches
+-- (the tag autocomplete index) know when to rebuild
+
+CREATE TABLE ChangeCounters (
+    name TEXT PRIMARY KEY NOT NULL, -- What changed, e.g. 'tags'
+    version INTEGER NOT NULL DEFAULT 0
+);
+
+INSERT INTO ChangeCounters (name, version) VALUES ('tags', 0);
+
+CREATE TRIGGER tags_counter_insert AFTER INSERT ON Tags BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
+
+CREATE TRIGGER tags_counter_delete AFTER DELETE ON Tags BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
+
+CREATE TRIGGER tags_counter_update AFTER UPDATE OF name, alias ON Tags BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
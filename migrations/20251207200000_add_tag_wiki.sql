@@ -0,0 +1,31 @@
+-- Add tag wiki pages: a Markdown description per tag, external links and example images
+
+CREATE TABLE TagWikis (
+    tag_id INTEGER PRIMARY KEY NOT NULL,
+    body TEXT NOT NULL DEFAULT '', -- Markdown
+    source TEXT NOT NULL DEFAULT 'user' CHECK(source IN ('user', 'danbooru')), -- Who wrote the body
+    updated_at INTEGER NOT NULL, -- Unix timestamp
+    FOREIGN KEY (tag_id) REFERENCES Tags(tag_id) ON DELETE CASCADE
+);
+
+CREATE TABLE TagWikiLinks (
+    link_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tag_id INTEGER NOT NULL,
+    url TEXT NOT NULL,
+    label TEXT DEFAULT NULL,
+    position INTEGER NOT NULL, -- 0-based order on the page
+    UNIQUE(tag_id, url),
+    FOREIGN KEY (tag_id) REFERENCES TagWikis(tag_id) ON DELETE CASCADE
+);
+
+CREATE TABLE TagWikiExamples (
+    tag_id INTEGER NOT NULL,
+    file_hash TEXT NOT NULL,
+    position INTEGER NOT NULL, -- 0-based order on the page
+    PRIMARY KEY (tag_id, file_hash),
+    FOREIGN KEY (tag_id) REFERENCES TagWikis(tag_id) ON DELETE CASCADE,
+    FOREIGN KEY (file_hash) REFERENCES Files(file_hash) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tag_wiki_links_tag_id ON TagWikiLinks(tag_id, position);
+CREATE INDEX idx_tag_wiki_examples_file_hash ON TagWikiExamples(file_hash);
@@ -0,0 +1,11 @@
+-- Add a tag hierarchy (clothing > swimsuit > school_swimsuit) as a parent pointer on Tags
+-- Cycles are rejected by the app before a parent is set
+
+ALTER TABLE Tags ADD COLUMN parent_tag_id INTEGER DEFAULT NULL REFERENCES Tags(tag_id) ON DELETE SET NULL;
+
+CREATE INDEX idx_tags_parent_tag_id ON Tags(parent_tag_id);
+
+-- Deleting a tag moves its children up to its own parent instead of making them roots
+CREATE TRIGGER tags_delete_reparent BEFORE DELETE ON Tags BEGIN
+    UPDATE Tags SET parent_tag_id = old.parent_tag_id WHERE parent_tag_id = old.tag_id;
+END;
@@ -0,0 +1,38 @@
+-- Canonical tag data from Danbooru tag dumps: categories for copyright and meta tags,
+-- alias and implication rules, and a deprecated flag
+
+INSERT OR IGNORE INTO TagCategories (name, color_code, is_builtin, sort_order) VALUES
+    ('COPYRIGHT', '#8B5CF6', TRUE, 5),
+    ('META', '#6B7280', TRUE, 6);
+
+-- Deprecated tags stay on files but are no longer suggested
+ALTER TABLE Tags ADD COLUMN is_deprecated BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Names that stand for another tag (`swimwear` -> `swimsuit`); adding or searching an alias
+-- uses its tag
+CREATE TABLE TagAliases (
+    alias_name TEXT PRIMARY KEY NOT NULL COLLATE NOCASE,
+    tag_id INTEGER NOT NULL,
+    FOREIGN KEY (tag_id) REFERENCES Tags(tag_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tag_aliases_tag_id ON TagAliases(tag_id);
+
+-- Files tagged with tag_id are also tagged with implied_tag_id (`school_swimsuit` -> `swimsuit`)
+CREATE TABLE TagImplications (
+    tag_id INTEGER NOT NULL,
+    implied_tag_id INTEGER NOT NULL,
+    PRIMARY KEY (tag_id, implied_tag_id),
+    CHECK (tag_id != implied_tag_id),
+    FOREIGN KEY (tag_id) REFERENCES Tags(tag_id) ON DELETE CASCADE,
+    FOREIGN KEY (implied_tag_id) REFERENCES Tags(tag_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tag_implications_implied_tag_id ON TagImplications(implied_tag_id);
+
+-- Deprecating a tag changes autocomplete suggestions too
+DROP TRIGGER tags_counter_update;
+
+CREATE TRIGGER tags_counter_update AFTER UPDATE OF name, alias, is_deprecated ON Tags BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
@@ -0,0 +1,51 @@
+-- Tag translations in every language, side by side. Tags.alias keeps the translation in the
+-- selected language (what the UI shows) and is copied from here when the language changes
+
+CREATE TABLE TagTranslations (
+    translation_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    tag_id INTEGER NOT NULL,
+    lang TEXT NOT NULL, -- Language code, e.g. 'zh'
+    text TEXT NOT NULL,
+    source TEXT NOT NULL DEFAULT 'dictionary' CHECK(source IN ('dictionary', 'user')), -- User translations survive dictionary refreshes
+    UNIQUE(tag_id, lang),
+    FOREIGN KEY (tag_id) REFERENCES Tags(tag_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tag_translations_lang ON TagTranslations(lang);
+
+-- Search terms match translations by exact text
+CREATE INDEX idx_tag_translations_text ON TagTranslations(text COLLATE NOCASE);
+
+-- Translations in any language, for search_everything
+CREATE VIRTUAL TABLE TagTranslationsFts USING fts5(
+    text,
+    content='TagTranslations',
+    content_rowid='translation_id',
+    tokenize='trigram'
+);
+
+CREATE TRIGGER tag_translations_fts_insert AFTER INSERT ON TagTranslations BEGIN
+    INSERT INTO TagTranslationsFts(rowid, text) VALUES (new.translation_id, new.text);
+END;
+
+CREATE TRIGGER tag_translations_fts_delete AFTER DELETE ON TagTranslations BEGIN
+    INSERT INTO TagTranslationsFts(TagTranslationsFts, rowid, text) VALUES ('delete', old.translation_id, old.text);
+END;
+
+CREATE TRIGGER tag_translations_fts_update AFTER UPDATE OF text ON TagTranslations BEGIN
+    INSERT INTO TagTranslationsFts(TagTranslationsFts, rowid, text) VALUES ('delete', old.translation_id, old.text);
+    INSERT INTO TagTranslationsFts(rowid, text) VALUES (new.translation_id, new.text);
+END;
+
+-- Translations are autocomplete keys too
+CREATE TRIGGER tag_translations_counter_insert AFTER INSERT ON TagTranslations BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
+
+CREATE TRIGGER tag_translations_counter_delete AFTER DELETE ON TagTranslations BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
+
+CREATE TRIGGER tag_translations_counter_update AFTER UPDATE OF text ON TagTranslations BEGIN
+    UPDATE ChangeCounters SET version = version + 1 WHERE name = 'tags';
+END;
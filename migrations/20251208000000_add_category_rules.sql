@@ -0,0 +1,16 @@
+-- Rules that put tags into categories automatically (`.*_hair$` -> "Hair"). Conditions set on a
+-- rule must all match; rules are tried by priority and the first match wins.
+
+CREATE TABLE CategoryRules (
+    rule_id INTEGER PRIMARY KEY AUTOINCREMENT,
+    category_id INTEGER NOT NULL,
+    name_pattern TEXT DEFAULT NULL, -- Regex matched against the tag name
+    model_category INTEGER DEFAULT NULL, -- Label map category of the AI model (0 general, 4 character, 9 rating)
+    tag_type TEXT DEFAULT NULL, -- Tags.type, e.g. from a Danbooru tags dump
+    priority INTEGER NOT NULL DEFAULT 0, -- Lower is tried first
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    CHECK(name_pattern IS NOT NULL OR model_category IS NOT NULL OR tag_type IS NOT NULL),
+    FOREIGN KEY (category_id) REFERENCES TagCategories(category_id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_category_rules_priority ON CategoryRules(priority, rule_id);
@@ -0,0 +1,18 @@
+-- Where each label map category of an AI model goes, per model. A model without rows uses the
+-- built-in mapping (general, artist, copyright, character, meta and rating); deleting a
+-- category drops its mapping, and unmapped model categories are not tagged
+
+CREATE TABLE ModelCategoryMappings (
+    model_name TEXT NOT NULL, -- Stem of the model file, e.g. 'swin-v2-tagger-v3'
+    model_category INTEGER NOT NULL, -- Label map category (0 general, 1 artist, 3 copyright, 4 character, 5 meta, 9 rating)
+    category_id INTEGER NOT NULL, -- Category of tags the model creates
+    tag_type TEXT NOT NULL DEFAULT 'general', -- Tags.type of tags the model creates
+    threshold REAL DEFAULT NULL, -- NULL uses the inference config threshold
+    mcut_enabled BOOLEAN DEFAULT NULL, -- NULL uses the inference config setting
+    argmax BOOLEAN NOT NULL DEFAULT FALSE, -- Take only the most confident label (ratings)
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    PRIMARY KEY (model_name, model_category),
+    CHECK(tag_type IN ('general', 'character', 'artist', 'series', 'rating')),
+    CHECK(threshold IS NULL OR (threshold >= 0 AND threshold <= 1)),
+    FOREIGN KEY (category_id) REFERENCES TagCategories(category_id) ON DELETE CASCADE
+);
@@ -1,10 +1,13 @@
+use crate::csv_table::{self, CsvReport, LineDiagnostic};
 use crate::error::AppError;
 use image::DynamicImage;
 use ndarray::Array4;
 use once_cell::sync::Lazy;
 use ort::session::builder::GraphOptimizationLevel;
 use ort::session::Session;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufReader, Read};
 use std::path::{Path, PathBuf};
 use std::sync::{Arc, Mutex};
 
@@ -16,7 +19,10 @@ use std::sync::{Arc, Mutex};
 pub struct TagPrediction {
 	pub name: String,
 	pub confidence: f32,
+	/// Name of the label map category (`general`, `artist`, ...), see `category_name`
 	pub category: String,
+	/// Category number from the label map (0 general, 4 character, 9 rating, ...)
+	pub model_category: u32,
 }
 
 /// Enhanced prediction with category and index information for debugging
@@ -24,7 +30,7 @@ pub struct TagPrediction {
 pub struct PredictionDetail {
 	pub name: String,
 	pub confidence: f32,
-	pub category: String, // rating/general/character
+	pub category: String, // general/artist/copyright/character/meta/rating, see `category_name`
 	pub tag_id: usize,
 	pub index: usize, // 在输出数组中的位置
 }
@@ -32,12 +38,21 @@ pub struct PredictionDetail {
 /// Separated predictions by category
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct CategoryPredictions {
-	pub rating: Vec<PredictionDetail>,
-	pub general: Vec<PredictionDetail>,
-	pub character: Vec<PredictionDetail>,
+	/// Predictions of each category the label map has, by category number
+	pub categories: BTreeMap<u32, Vec<PredictionDetail>>,
 	pub all: Vec<PredictionDetail>,
 }
 
+impl CategoryPredictions {
+	/// Predictions of one label map category; empty when the model has no such category
+	pub fn category(&self, category: u32) -> &[PredictionDetail] {
+		self.categories
+			.get(&category)
+			.map(Vec::as_slice)
+			.unwrap_or_default()
+	}
+}
+
 // ============================================================================
 // Constants
 // ============================================================================
@@ -52,22 +67,98 @@ static DETECTED_MODEL_INPUT_SIZE: std::sync::atomic::AtomicU32 =
 // Label Map and Model Loading
 // ============================================================================
 
-/// Label map: tag_id -> (tag_name, category_id)
-type LabelMapResult = Result<HashMap<usize, (String, u32)>, AppError>;
+/// A model output, from a row of selected_tags.csv
+#[derive(Debug, Clone)]
+pub struct Label {
+	/// Danbooru tag id; the row position when the file has no tag_id column
+	pub tag_id: usize,
+	pub name: String,
+	pub category: u32,
+}
+
+/// Labels keyed by model output index, with the problems found reading them
+/// Outputs follow the file's row order, so the index is the position among data rows
+/// (counting rows that could not be read), not the tag_id column.
+pub struct LabelMap {
+	pub labels: HashMap<usize, Label>,
+	pub report: CsvReport,
+}
+
+type LabelMapResult = Result<LabelMap, AppError>;
 
 /// Categories for tag classification
 pub const RATING_CATEGORY: u32 = 9;
 pub const GENERAL_CATEGORY: u32 = 0;
+pub const ARTIST_CATEGORY: u32 = 1;
+pub const COPYRIGHT_CATEGORY: u32 = 3;
 pub const CHARACTER_CATEGORY: u32 = 4;
+pub const META_CATEGORY: u32 = 5;
+
+/// Name of the model, the stem of its file; category mappings are stored per model
+pub const MODEL_NAME: &str = "swin-v2-tagger-v3";
+
+/// Columns of selected_tags.csv, in order when it has no header row
+const LABEL_MAP_COLUMNS: [&str; 4] = ["tag_id", "name", "category", "count"];
 
 static LABEL_MAP: Lazy<LabelMapResult> = Lazy::new(load_label_map);
 
 /// ONNX model session wrapped in Mutex for interior mutability
 static MODEL_SESSION: Lazy<Result<Arc<Mutex<Session>>, AppError>> = Lazy::new(load_model);
 
+/// Parse a label map CSV (tag_id,name,category,count)
+pub fn parse_label_map(data: impl Read) -> LabelMapResult {
+	let mut labels = HashMap::new();
+	// Line of the row each tag_id was first seen on
+	let mut seen_tag_ids: HashMap<usize, u64> = HashMap::new();
+	let report = csv_table::read_rows(data, &LABEL_MAP_COLUMNS, |row| {
+		let name = row.get("name").ok_or("missing name")?;
+		let category = row.get("category").ok_or("missing category")?;
+		let category = category
+			.parse::<u32>()
+			.map_err(|_| format!("invalid category '{category}'"))?;
+		let tag_id = match row.get("tag_id") {
+			Some(tag_id) => tag_id
+				.parse::<usize>()
+				.map_err(|_| format!("invalid tag_id '{tag_id}'"))?,
+			None => row.index,
+		};
+		// Outputs are matched by row, so the label stays; a repeated tag_id still means the
+		// file is damaged or not the model's
+		let first_line = *seen_tag_ids.entry(tag_id).or_insert(row.line);
+		labels.insert(
+			row.index,
+			Label {
+				tag_id,
+				name: name.to_string(),
+				category,
+			},
+		);
+		if first_line != row.line {
+			return Err(format!(
+				"duplicate tag_id {tag_id} (first on line {first_line})"
+			));
+		}
+		Ok(())
+	})?;
+
+	Ok(LabelMap { labels, report })
+}
+
+/// Danbooru name of a label map category
+pub fn category_name(category: u32) -> String {
+	match category {
+		GENERAL_CATEGORY => "general".to_string(),
+		ARTIST_CATEGORY => "artist".to_string(),
+		COPYRIGHT_CATEGORY => "copyright".to_string(),
+		CHARACTER_CATEGORY => "character".to_string(),
+		META_CATEGORY => "meta".to_string(),
+		RATING_CATEGORY => "rating".to_string(),
+		other => format!("category {other}"),
+	}
+}
+
 /// Load label map from CSV file
-/// CSV format: tag_id,name,category,count
-fn load_label_map() -> Result<HashMap<usize, (String, u32)>, AppError> {
+fn load_label_map() -> LabelMapResult {
 	ai_debug!("[AI Model] Loading label map...");
 
 	let csv_path = match get_models_dir() {
@@ -90,8 +181,8 @@ fn load_label_map() -> Result<HashMap<usize, (String, u32)>, AppError> {
 	}
 
 	ai_debug!("[AI Model] Reading label map file...");
-	let content = match std::fs::read_to_string(&csv_path) {
-		Ok(c) => c,
+	let file = match File::open(&csv_path) {
+		Ok(file) => BufReader::new(file),
 		Err(e) => {
 			let error_msg = format!("Failed to read label map: {e}");
 			ai_error!("[AI Model] ERROR: {error_msg}");
@@ -100,34 +191,27 @@ fn load_label_map() -> Result<HashMap<usize, (String, u32)>, AppError> {
 	};
 
 	ai_debug!("[AI Model] Parsing label map...");
-	let mut map = HashMap::new();
-	for (idx, line) in content.lines().enumerate() {
-		if idx == 0 {
-			continue; // Skip header
+	let label_map = match parse_label_map(file) {
+		Ok(label_map) if !label_map.labels.is_empty() => label_map,
+		Ok(_) => {
+			let error_msg = "Label map is empty or invalid".to_string();
+			ai_error!("[AI Model] ERROR: {error_msg}");
+			return Err(AppError::Custom(error_msg));
 		}
-
-		let parts: Vec<&str> = line.split(',').collect();
-		if parts.len() >= 4 {
-			// Use row index as key, not tag_id
-			let row_index = idx - 1; // Skip header row
-			if let Ok(category) = parts[2].parse::<u32>() {
-				let name = parts[1].to_string();
-				map.insert(row_index, (name, category));
-			}
+		Err(e) => {
+			ai_error!("[AI Model] ERROR: {e}");
+			return Err(e);
 		}
-	}
-
-	if map.is_empty() {
-		let error_msg = "Label map is empty or invalid".to_string();
-		ai_error!("[AI Model] ERROR: {error_msg}");
-		return Err(AppError::Custom(error_msg));
+	};
+	for diagnostic in &label_map.report.diagnostics {
+		ai_error!("[AI Model] WARNING: Label map {diagnostic}");
 	}
 
 	ai_debug!(
 		"[AI Model] Label map loaded successfully! {} tags",
-		map.len()
+		label_map.labels.len()
 	);
-	Ok(map)
+	Ok(label_map)
 }
 
 /// Load ONNX model
@@ -135,7 +219,7 @@ fn load_model() -> Result<Arc<Mutex<Session>>, AppError> {
 	ai_debug!("[AI Model] Starting model load...");
 
 	let model_path = match get_models_dir() {
-		Ok(dir) => dir.join("swin-v2-tagger-v3.onnx"),
+		Ok(dir) => dir.join(format!("{MODEL_NAME}.onnx")),
 		Err(e) => {
 			ai_error!("[AI Model] ERROR: Failed to get models directory: {e}");
 			return Err(e);
@@ -318,6 +402,8 @@ fn get_models_dir() -> Result<PathBuf, AppError> {
 /// 5. Convert RGB channels to BGR format
 /// 6. Keep pixel values in [0.0, 255.0] range (SwinV2/WD14 models expect this range)
 /// 7. Convert to NHWC format (batch, height, width, channels) - model expects this format
+///
+/// Expects an image already rotated per EXIF orientation (see `metadata::open_image_oriented`)
 fn preprocess_image(image: DynamicImage) -> Result<Array4<f32>, AppError> {
 	// Step 1: Convert to RGBA if needed
 	let rgba_image = image.to_rgba8();
@@ -466,23 +552,52 @@ fn apply_threshold_filter(
 // Inference and Postprocessing
 // ============================================================================
 
+/// How the predictions of one label map category are selected
+#[derive(Debug, Clone)]
+pub struct CategoryParams {
+	pub model_category: u32,
+	pub threshold: f32,
+	pub mcut_enabled: bool,
+	/// Lowest confidence MCut may keep
+	pub min_threshold: Option<f32>,
+	/// Take only the most confident label, whatever its confidence (ratings)
+	pub argmax: bool,
+}
+
 /// Inference configuration for postprocessing
 #[derive(Debug, Clone)]
 pub struct InferenceParams {
-	pub general_threshold: f32,
-	pub character_threshold: f32,
-	pub general_mcut_enabled: bool,
-	pub character_mcut_enabled: bool,
+	/// Categories to tag; predictions in other categories are dropped
+	pub categories: Vec<CategoryParams>,
 	pub max_tags: u32,
 }
 
 impl Default for InferenceParams {
 	fn default() -> Self {
 		Self {
-			general_threshold: 0.35,
-			character_threshold: 0.85,
-			general_mcut_enabled: false,
-			character_mcut_enabled: false,
+			categories: vec![
+				CategoryParams {
+					model_category: RATING_CATEGORY,
+					threshold: 0.0,
+					mcut_enabled: false,
+					min_threshold: None,
+					argmax: true,
+				},
+				CategoryParams {
+					model_category: GENERAL_CATEGORY,
+					threshold: 0.35,
+					mcut_enabled: false,
+					min_threshold: None,
+					argmax: false,
+				},
+				CategoryParams {
+					model_category: CHARACTER_CATEGORY,
+					threshold: 0.85,
+					mcut_enabled: false,
+					min_threshold: Some(0.15),
+					argmax: false,
+				},
+			],
 			max_tags: 50,
 		}
 	}
@@ -514,9 +629,8 @@ pub async fn classify_image_with_params(
 
 	ai_debug!("[AI Tagging] Model is available, proceeding with inference");
 
-	// Load image
-	let image = image::open(image_path)
-		.map_err(|e| AppError::Custom(format!("Failed to load image: {e}")))?;
+	// Load image upright so the model sees what the user sees
+	let image = crate::metadata::open_image_oriented(image_path)?;
 
 	// Preprocess image
 	let input_tensor = preprocess_image(image)?;
@@ -595,7 +709,7 @@ pub async fn classify_image_with_params(
 		.collect();
 	debug_vec.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
 	for (idx, conf) in debug_vec {
-		if let Some((name, category)) = label_map.get(&idx) {
+		if let Some(Label { name, category, .. }) = label_map.labels.get(&idx) {
 			ai_debug!(
 				"[AI Debug]  {} ({}): {} - category {}",
 				idx,
@@ -607,76 +721,51 @@ pub async fn classify_image_with_params(
 	}
 
 	// Separate predictions by category
-	let mut rating_predictions = Vec::new();
-	let mut general_predictions = Vec::new();
-	let mut character_predictions = Vec::new();
-
+	let mut category_predictions: HashMap<u32, Vec<(usize, f32)>> = HashMap::new();
 	for (idx, &confidence) in predictions.iter().enumerate() {
-		if let Some((_name, category)) = label_map.get(&idx) {
-			let prediction = (idx, confidence);
-
-			match *category {
-				RATING_CATEGORY => rating_predictions.push(prediction),
-				GENERAL_CATEGORY => general_predictions.push(prediction),
-				CHARACTER_CATEGORY => character_predictions.push(prediction),
-				_ => {} // Skip other categories for now
-			}
+		if let Some(label) = label_map.labels.get(&idx) {
+			category_predictions
+				.entry(label.category)
+				.or_default()
+				.push((idx, confidence));
 		}
 	}
 
 	let mut results: Vec<TagPrediction> = Vec::new();
 
-	// Process rating tags (use argmax - take the highest confidence rating)
-	if !rating_predictions.is_empty() {
-		// Sort by confidence descending and take the top one
-		rating_predictions.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
-		if let Some((idx, confidence)) = rating_predictions.first() {
-			if let Some((name, _)) = label_map.get(idx) {
-				results.push(TagPrediction {
-					name: name.clone(),
-					confidence: *confidence,
-					category: "rating".to_string(),
-				});
-			}
-		}
-	}
-
-	// Process general tags with threshold and optional MCut
-	let general_indices = apply_threshold_filter(
-		&general_predictions,
-		params.general_threshold,
-		params.general_mcut_enabled,
-		None,
-	);
-
-	for &idx in &general_indices {
-		if let Some((tag_idx, confidence)) = general_predictions.iter().find(|(i, _)| *i == idx) {
-			if let Some((name, _)) = label_map.get(tag_idx) {
-				results.push(TagPrediction {
-					name: name.clone(),
-					confidence: *confidence,
-					category: "general".to_string(),
-				});
-			}
-		}
-	}
-
-	// Process character tags with threshold and optional MCut
-	let character_indices = apply_threshold_filter(
-		&character_predictions,
-		params.character_threshold,
-		params.character_mcut_enabled,
-		Some(0.15), // Minimum threshold for character tags when using MCut
-	);
-
-	for &idx in &character_indices {
-		if let Some((tag_idx, confidence)) = character_predictions.iter().find(|(i, _)| *i == idx) {
-			if let Some((name, _)) = label_map.get(tag_idx) {
-				results.push(TagPrediction {
-					name: name.clone(),
-					confidence: *confidence,
-					category: "character".to_string(),
-				});
+	for category_params in &params.categories {
+		let Some(candidates) = category_predictions.get_mut(&category_params.model_category) else {
+			continue;
+		};
+
+		let indices = if category_params.argmax {
+			// Take the highest confidence label (e.g. one rating per image)
+			candidates.sort_by(|a, b| b.1.partial_cmp(&a.1).unwrap());
+			candidates
+				.first()
+				.map(|(idx, _)| *idx)
+				.into_iter()
+				.collect()
+		} else {
+			// Threshold with optional MCut
+			apply_threshold_filter(
+				candidates,
+				category_params.threshold,
+				category_params.mcut_enabled,
+				category_params.min_threshold,
+			)
+		};
+
+		for idx in indices {
+			if let Some((tag_idx, confidence)) = candidates.iter().find(|(i, _)| *i == idx) {
+				if let Some(label) = label_map.labels.get(tag_idx) {
+					results.push(TagPrediction {
+						name: label.name.clone(),
+						confidence: *confidence,
+						category: category_name(label.category),
+						model_category: label.category,
+					});
+				}
 			}
 		}
 	}
@@ -714,9 +803,8 @@ pub async fn classify_image_debug_with_params(
 		return Err(AppError::Custom(error_msg));
 	}
 
-	// Load image
-	let image = image::open(image_path)
-		.map_err(|e| AppError::Custom(format!("Failed to load image: {e}")))?;
+	// Load image upright so the model sees what the user sees
+	let image = crate::metadata::open_image_oriented(image_path)?;
 
 	// Preprocess image
 	let input_tensor = preprocess_image(image)?;
@@ -784,49 +872,32 @@ pub async fn classify_image_debug_with_params(
 		.map_err(|e| AppError::Custom(format!("Label map not loaded: {e}")))?;
 
 	// Create detailed predictions with categories
-	let mut rating_predictions = Vec::new();
-	let mut general_predictions = Vec::new();
-	let mut character_predictions = Vec::new();
+	let mut categories: BTreeMap<u32, Vec<PredictionDetail>> = BTreeMap::new();
 	let mut all_predictions = Vec::new();
 
 	for (index, &confidence) in predictions.iter().enumerate() {
-		if let Some((name, category_id)) = label_map.get(&index) {
-			let category = match *category_id {
-				RATING_CATEGORY => "rating",
-				GENERAL_CATEGORY => "general",
-				CHARACTER_CATEGORY => "character",
-				_ => "other",
-			};
-
+		if let Some(label) = label_map.labels.get(&index) {
 			let detail = PredictionDetail {
-				name: name.clone(),
+				name: label.name.clone(),
 				confidence,
-				category: category.to_string(),
-				tag_id: index,
+				category: category_name(label.category),
+				tag_id: label.tag_id,
 				index,
 			};
 
 			all_predictions.push(detail.clone());
-
-			match category {
-				"rating" => rating_predictions.push(detail),
-				"general" => general_predictions.push(detail),
-				"character" => character_predictions.push(detail),
-				_ => {} // Skip "other" categories for now
-			}
+			categories.entry(label.category).or_default().push(detail);
 		}
 	}
 
 	// Sort all categories by confidence descending
-	rating_predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
-	general_predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
-	character_predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+	for category_predictions in categories.values_mut() {
+		category_predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
+	}
 	all_predictions.sort_by(|a, b| b.confidence.partial_cmp(&a.confidence).unwrap());
 
 	Ok(CategoryPredictions {
-		rating: rating_predictions,
-		general: general_predictions,
-		character: character_predictions,
+		categories,
 		all: all_predictions,
 	})
 }
@@ -857,10 +928,35 @@ pub fn is_model_available() -> bool {
 	label_ok && session_ok
 }
 
+/// Number of labels in each label map category; empty when no label map is loaded
+pub fn label_category_counts() -> BTreeMap<u32, usize> {
+	let mut counts = BTreeMap::new();
+	if let Ok(label_map) = LABEL_MAP.as_ref() {
+		for label in label_map.labels.values() {
+			*counts.entry(label.category).or_insert(0) += 1;
+		}
+	}
+	counts
+}
+
+/// Label map category of each label by tag name; empty when no label map is loaded
+pub fn label_categories() -> HashMap<String, u32> {
+	LABEL_MAP
+		.as_ref()
+		.map(|label_map| {
+			label_map
+				.labels
+				.values()
+				.map(|label| (label.name.clone(), label.category))
+				.collect()
+		})
+		.unwrap_or_default()
+}
+
 /// Get model status information for debugging
 pub fn get_model_status() -> Result<ModelStatus, AppError> {
 	let models_dir = get_models_dir()?;
-	let model_path = models_dir.join("swin-v2-tagger-v3.onnx");
+	let model_path = models_dir.join(format!("{MODEL_NAME}.onnx"));
 	let csv_path = models_dir.join("selected_tags.csv");
 
 	Ok(ModelStatus {
@@ -872,6 +968,10 @@ pub fn get_model_status() -> Result<ModelStatus, AppError> {
 		label_map_loaded: LABEL_MAP.is_ok(),
 		model_session_loaded: MODEL_SESSION.is_ok(),
 		label_map_error: LABEL_MAP.as_ref().err().map(|e| format!("{e}")),
+		label_map_diagnostics: LABEL_MAP
+			.as_ref()
+			.map(|label_map| label_map.report.diagnostics.clone())
+			.unwrap_or_default(),
 		model_session_error: MODEL_SESSION.as_ref().err().map(|e| format!("{e}")),
 	})
 }
@@ -887,6 +987,8 @@ pub struct ModelStatus {
 	pub label_map_loaded: bool,
 	pub model_session_loaded: bool,
 	pub label_map_error: Option<String>,
+	/// Label map rows that could not be read
+	pub label_map_diagnostics: Vec<LineDiagnostic>,
 	pub model_session_error: Option<String>,
 }
 
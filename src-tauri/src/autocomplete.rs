@@ -0,0 +1,430 @@
+// Tag autocomplete: an in-memory index of tag names and translations with typo-tolerant,
+// word-order independent and romanized (pinyin/romaji) matching
+
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use pinyin::ToPinyin;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use wana_kana::{ConvertJapanese, IsJapaneseChar};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Which form of a tag a query matched
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MatchKind {
+	Name,
+	Alias,
+	/// Pinyin or romaji reading of the alias, or its pinyin initials
+	Romanized,
+}
+
+#[derive(Debug, Clone)]
+pub struct AutocompleteMatch {
+	pub tag_id: i64,
+	pub kind: MatchKind,
+	/// 0-1; 1 for an exact match
+	pub quality: f64,
+}
+
+/// One searchable form of a tag
+struct Key {
+	kind: MatchKind,
+	tokens: Vec<String>,
+	/// Tokens without separators, for queries typed without spaces
+	compact: String,
+	/// `char_mask` of `compact`, for ruling the key out before scoring it
+	mask: u64,
+}
+
+struct Entry {
+	tag_id: i64,
+	file_count: i64,
+	keys: Vec<Key>,
+}
+
+pub struct TagIndex {
+	entries: Vec<Entry>,
+}
+
+/// Identifies the tags an index was built from; the counter alone can repeat after a
+/// backup restore swaps in another database
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct IndexVersion {
+	counter: i64,
+	tag_count: i64,
+	max_tag_id: i64,
+}
+
+type CachedIndex = (IndexVersion, Arc<TagIndex>);
+
+/// `(tag_id, name, alias, file_count)` of a tag to index
+pub type IndexedTag = (i64, String, Option<String>, i64);
+
+/// Built lazily and rebuilt by `current_index` when tags change
+static INDEX: Lazy<RwLock<Option<CachedIndex>>> = Lazy::new(|| RwLock::new(None));
+
+/// Romanized keys score a little below the text they were derived from
+const ROMANIZED_FACTOR: f64 = 0.9;
+
+// ============================================================================
+// Matching
+// ============================================================================
+
+/// Lowercase words of a tag name, alias or query; `_`, `-`, `/`, `,`, brackets and
+/// whitespace separate words. Text that is all separators (e.g. `^_^`) is one word.
+fn tokenize(text: &str) -> Vec<String> {
+	let text = text.to_lowercase();
+	let tokens: Vec<String> = text
+		.split(|c: char| c.is_whitespace() || "_-/,()[]・".contains(c))
+		.filter(|t| !t.is_empty())
+		.map(str::to_string)
+		.collect();
+	if tokens.is_empty() && !text.trim().is_empty() {
+		vec![text.trim().to_string()]
+	} else {
+		tokens
+	}
+}
+
+/// Reading of one word: pinyin for Han characters, romaji for kana, other characters kept
+/// Japanese kanji get their Chinese reading. Also returns pinyin initials when there are Han
+/// characters (`初音未来` → `chuyinweilai`, `cywl`).
+fn romanize_word(word: &str) -> (String, Option<String>) {
+	let mut reading = String::new();
+	let mut initials = String::new();
+	let mut has_han = false;
+	let mut kana = String::new();
+
+	for c in word.chars() {
+		if c.is_kana() {
+			kana.push(c);
+			continue;
+		}
+		if !kana.is_empty() {
+			reading.push_str(&kana.as_str().to_romaji());
+			kana.clear();
+		}
+		match c.to_pinyin() {
+			Some(pinyin) => {
+				has_han = true;
+				reading.push_str(pinyin.plain());
+				initials.push_str(pinyin.first_letter());
+			}
+			None => {
+				reading.push(c);
+				initials.push(c);
+			}
+		}
+	}
+	if !kana.is_empty() {
+		reading.push_str(&kana.as_str().to_romaji());
+	}
+
+	(reading, has_han.then_some(initials))
+}
+
+fn key(kind: MatchKind, tokens: Vec<String>) -> Option<Key> {
+	(!tokens.is_empty()).then(|| {
+		let compact = tokens.concat();
+		Key {
+			kind,
+			mask: char_mask(&compact),
+			compact,
+			tokens,
+		}
+	})
+}
+
+/// Name of a tag, and text, reading and initials of each alias
+fn tag_keys<'a>(name: &str, aliases: impl IntoIterator<Item = &'a str>) -> Vec<Key> {
+	let mut keys: Vec<Key> = key(MatchKind::Name, tokenize(name)).into_iter().collect();
+	for alias in aliases.into_iter().filter(|a| !a.trim().is_empty()) {
+		keys.extend(alias_keys(alias));
+	}
+	keys
+}
+
+/// Text, reading and initials of an alias
+fn alias_keys(alias: &str) -> Vec<Key> {
+	let mut keys = Vec::new();
+	let alias_tokens = tokenize(alias);
+	let romanized: Vec<(String, Option<String>)> =
+		alias_tokens.iter().map(|t| romanize_word(t)).collect();
+	let needs_reading = romanized
+		.iter()
+		.zip(&alias_tokens)
+		.any(|((reading, _), token)| reading != token);
+
+	keys.extend(key(MatchKind::Alias, alias_tokens));
+	if needs_reading {
+		let initials: Vec<String> = romanized.iter().filter_map(|(_, i)| i.clone()).collect();
+		keys.extend(key(
+			MatchKind::Romanized,
+			romanized.into_iter().map(|(reading, _)| reading).collect(),
+		));
+		keys.extend(key(MatchKind::Romanized, initials));
+	}
+	keys
+}
+
+/// Typos allowed for a query word of this many characters
+fn allowed_typos(len: usize) -> usize {
+	match len {
+		0..=3 => 0,
+		4..=6 => 1,
+		_ => 2,
+	}
+}
+
+/// One bit per character, folded into 64 bits
+fn char_mask(text: &str) -> u64 {
+	text.chars().fold(0, |mask, c| mask | 1 << (c as u32 % 64))
+}
+
+/// A query word with what `may_match` needs to know about it
+struct QueryWord {
+	mask: u64,
+	typos: usize,
+}
+
+impl QueryWord {
+	fn new(word: &str) -> Self {
+		Self {
+			mask: char_mask(word),
+			typos: allowed_typos(word.chars().count()),
+		}
+	}
+
+	/// Every edit brings in at most one character, so a word matching within its typos
+	/// can't miss more characters of the key than that; colliding bits only let more keys
+	/// through
+	fn may_match(&self, key: &Key) -> bool {
+		(self.mask & !key.mask).count_ones() as usize <= self.typos
+	}
+}
+
+/// How well one query word matches one tag word (0 when it doesn't)
+fn word_quality(query: &str, word: &str) -> f64 {
+	if query == word {
+		return 1.0;
+	}
+	let query_len = query.chars().count();
+	let word_len = word.chars().count();
+	if word.starts_with(query) {
+		// Longer prefixes of the word are better
+		return 0.8 + 0.1 * query_len as f64 / word_len as f64;
+	}
+	if query_len >= 2 && word.contains(query) {
+		return 0.6;
+	}
+
+	let typos = allowed_typos(query_len);
+	if typos == 0 {
+		return 0.0;
+	}
+	// A typo in the whole word, or in what has been typed of it so far
+	let whole = if word_len.abs_diff(query_len) <= typos {
+		strsim::osa_distance(query, word)
+	} else {
+		usize::MAX
+	};
+	let typed = if word_len > query_len {
+		let prefix: String = word.chars().take(query_len).collect();
+		strsim::osa_distance(query, &prefix)
+	} else {
+		usize::MAX
+	};
+
+	if whole <= typos && whole <= typed {
+		0.5 - 0.1 * (whole - 1) as f64
+	} else if typed <= typos {
+		0.45 - 0.1 * (typed - 1) as f64
+	} else {
+		0.0
+	}
+}
+
+/// How well query words match a key, in any order; every query word must match
+fn key_quality(query_tokens: &[String], query_compact: &str, key: &Key) -> f64 {
+	let mut total = 0.0;
+	let mut matched_words = 0;
+	for query in query_tokens {
+		let best = key
+			.tokens
+			.iter()
+			.map(|word| word_quality(query, word))
+			.fold(0.0, f64::max);
+		if best == 0.0 {
+			total = 0.0;
+			break;
+		}
+		total += best;
+		matched_words += 1;
+	}
+	let by_words = if matched_words == 0 {
+		0.0
+	} else {
+		// Keys with fewer unmatched words rank higher (`miku` over `hatsune_miku (cosplay)`)
+		let coverage = (matched_words as f64 / key.tokens.len() as f64).min(1.0);
+		total / query_tokens.len() as f64 * (0.9 + 0.1 * coverage)
+	};
+
+	// `hatsunemiku`, `chuyinweilai`
+	let by_compact = word_quality(query_compact, &key.compact);
+	let quality = by_words.max(by_compact);
+
+	if key.kind == MatchKind::Romanized {
+		quality * ROMANIZED_FACTOR
+	} else {
+		quality
+	}
+}
+
+/// Combined rank of a match; popular tags win among similar matches, and looser matches
+/// need many more files to outrank closer ones
+pub fn rank_score(quality: f64, file_count: i64) -> f64 {
+	quality * quality * (1.0 + (file_count.max(0) as f64).ln_1p())
+}
+
+impl TagIndex {
+	/// `translations` are each tag's translations in other languages than its alias
+	pub fn build(tags: Vec<IndexedTag>, translations: HashMap<i64, Vec<String>>) -> Self {
+		let entries = tags
+			.into_iter()
+			.map(|(tag_id, name, alias, file_count)| {
+				let other_languages = translations.get(&tag_id).into_iter().flatten();
+				Entry {
+					tag_id,
+					file_count,
+					keys: tag_keys(
+						&name,
+						alias
+							.as_deref()
+							.into_iter()
+							.chain(other_languages.map(String::as_str)),
+					),
+				}
+			})
+			.collect();
+		Self { entries }
+	}
+
+	/// Best matches for a query, ranked by match quality and file count at build time
+	pub fn search(&self, query: &str, limit: usize) -> Vec<AutocompleteMatch> {
+		let query_tokens = tokenize(query);
+		if query_tokens.is_empty() {
+			return Vec::new();
+		}
+		let query_compact = query_tokens.concat();
+		let query_words: Vec<QueryWord> = query_tokens.iter().map(|t| QueryWord::new(t)).collect();
+		let compact_word = QueryWord::new(&query_compact);
+		// Scoring runs edit distances, so most keys are ruled out by their characters first
+		let may_match = |key: &Key| {
+			compact_word.may_match(key) || query_words.iter().all(|word| word.may_match(key))
+		};
+
+		let mut matches: Vec<(f64, AutocompleteMatch)> = self
+			.entries
+			.iter()
+			.filter_map(|entry| {
+				let (quality, kind) = entry
+					.keys
+					.iter()
+					.filter(|key| may_match(key))
+					.map(|key| (key_quality(&query_tokens, &query_compact, key), key.kind))
+					.fold((0.0, MatchKind::Name), |best, current| {
+						if current.0 > best.0 {
+							current
+						} else {
+							best
+						}
+					});
+				(quality > 0.0).then(|| {
+					(
+						rank_score(quality, entry.file_count),
+						AutocompleteMatch {
+							tag_id: entry.tag_id,
+							kind,
+							quality,
+						},
+					)
+				})
+			})
+			.collect();
+
+		matches.sort_by(|a, b| b.0.total_cmp(&a.0));
+		matches.truncate(limit);
+		matches.into_iter().map(|(_, m)| m).collect()
+	}
+}
+
+// ============================================================================
+// Index Cache
+// ============================================================================
+
+async fn index_version(pool: &SqlitePool) -> Result<IndexVersion, AppError> {
+	let row = sqlx::query(
+		r#"
+        SELECT
+            (SELECT version FROM ChangeCounters WHERE name = 'tags') as counter,
+            (SELECT COUNT(*) FROM Tags) as tag_count,
+            (SELECT COALESCE(MAX(tag_id), 0) FROM Tags) as max_tag_id
+        "#,
+	)
+	.fetch_one(pool)
+	.await?;
+	Ok(IndexVersion {
+		counter: row.get::<Option<i64>, _>("counter").unwrap_or(0),
+		tag_count: row.get("tag_count"),
+		max_tag_id: row.get("max_tag_id"),
+	})
+}
+
+/// The autocomplete index for the current tags, rebuilt first if tags changed since it was built
+pub async fn current_index(pool: &SqlitePool) -> Result<Arc<TagIndex>, AppError> {
+	let version = index_version(pool).await?;
+	if let Some((built_from, index)) = INDEX.read().unwrap_or_else(|e| e.into_inner()).as_ref() {
+		if *built_from == version {
+			return Ok(index.clone());
+		}
+	}
+
+	let tags: Vec<IndexedTag> = sqlx::query_as(
+		r#"
+        SELECT t.tag_id, t.name, t.alias,
+               (SELECT COUNT(*) FROM FileTags ft WHERE ft.tag_id = t.tag_id) as file_count
+        FROM Tags t
+        WHERE t.is_deprecated = 0
+        "#,
+	)
+	.fetch_all(pool)
+	.await?;
+
+	// Tags match in every language, not only the one shown
+	let mut translations: HashMap<i64, Vec<String>> = HashMap::new();
+	let rows: Vec<(i64, String)> = sqlx::query_as(
+		r#"
+        SELECT tr.tag_id, tr.text FROM TagTranslations tr
+        JOIN Tags t ON t.tag_id = tr.tag_id
+        WHERE t.is_deprecated = 0 AND tr.text IS NOT t.alias
+        "#,
+	)
+	.fetch_all(pool)
+	.await?;
+	for (tag_id, text) in rows {
+		translations.entry(tag_id).or_default().push(text);
+	}
+
+	let index = Arc::new(
+		tokio::task::spawn_blocking(move || TagIndex::build(tags, translations))
+			.await
+			.map_err(|e| AppError::Custom(format!("Failed to build autocomplete index: {e}")))?,
+	);
+
+	*INDEX.write().unwrap_or_else(|e| e.into_inner()) = Some((version, index.clone()));
+	Ok(index)
+}
@@ -1,7 +1,7 @@
 use super::files::ProgressEvent;
+use crate::db::DbPool;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 use tauri::{AppHandle, Emitter};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -14,9 +14,10 @@ pub struct ClearDatabaseResult {
 #[tauri::command]
 pub async fn clear_database(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	confirmation: String,
 ) -> Result<ClearDatabaseResult, AppError> {
+	let pool = db.get();
 	// Verify confirmation text matches expected value
 	let required_confirmation = "CLEAR_ALL_DATA_PERMANENTLY";
 	if confirmation != required_confirmation {
@@ -38,7 +39,7 @@ pub async fn clear_database(
 
 	// Disable foreign keys for faster deletion
 	sqlx::query("PRAGMA foreign_keys = OFF")
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	let tables = [
@@ -69,7 +70,7 @@ pub async fn clear_database(
 		.map_err(|e| AppError::Custom(format!("Failed to emit progress: {e}")))?;
 
 		let result = sqlx::query(&format!("DELETE FROM {table}"))
-			.execute(pool.inner())
+			.execute(&pool)
 			.await?;
 
 		let deleted_count = result.rows_affected();
@@ -94,7 +95,7 @@ pub async fn clear_database(
 	.map_err(|e| AppError::Custom(format!("Failed to emit progress: {e}")))?;
 
 	sqlx::query("PRAGMA foreign_keys = ON")
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	// Reset auto-increment sequences
@@ -111,7 +112,7 @@ pub async fn clear_database(
 	.map_err(|e| AppError::Custom(format!("Failed to emit progress: {e}")))?;
 
 	sqlx::query("DELETE FROM sqlite_sequence")
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	// Emit completion event
@@ -137,23 +138,24 @@ pub async fn clear_database(
 // Additional admin commands can be added here
 #[tauri::command]
 pub async fn get_database_stats(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 ) -> Result<serde_json::Value, AppError> {
+	let pool = db.get();
 	// Query counts from each table
 	let files_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM Files")
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 	let tags_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM Tags")
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 	let folders_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM Folders")
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 	let persons_count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM Persons")
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 	Ok(serde_json::json!({
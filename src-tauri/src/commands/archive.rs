@@ -0,0 +1,1974 @@
+use super::categories::TagCategory;
+use super::favorites::Favorite;
+use super::files::{calculate_blake3_hash, get_thumbnail_dir, FileRecord, ProgressEvent};
+use super::model_categories::ModelCategoryMapping;
+use super::notes::{note_from_row, FileNote};
+use super::relations::{insert_relation, relation_from_row, FileRelation};
+use super::safe_mode::is_safe_mode_active;
+use super::tag_tree::is_in_subtree;
+use super::tags::Tag;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::{SqliteConnectOptions, SqlitePoolOptions};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Read, Write};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Identifies our archives and the layout version written by this build
+const ARCHIVE_FORMAT: &str = "piximoe-library";
+/// Version 1 only grows additively: new snapshot collections are `#[serde(default)]` and
+/// SQLite snapshots are migrated on import, so older version 1 archives stay readable
+const ARCHIVE_FORMAT_VERSION: u32 = 1;
+
+/// Settings stores that travel with a library
+const SETTINGS_STORES: &[&str] = &[
+	".settings.json",
+	"ai-settings.json",
+	"translation-settings.json",
+	"backup-settings.json",
+	"metadata-settings.json",
+	"filename-patterns.json",
+];
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DatabaseFormat {
+	Json,
+	Sqlite,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ArchiveManifest {
+	pub format: String,
+	pub format_version: u32,
+	pub app_version: String,
+	pub created_at: i64, // Unix timestamp
+	pub database_format: DatabaseFormat,
+	pub file_count: usize,
+	pub includes_thumbnails: bool,
+	pub includes_originals: bool,
+	pub includes_translations: bool,
+	pub includes_settings: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileTagLink {
+	pub file_hash: String,
+	pub tag_id: i64,
+}
+
+/// Library contents independent of the on-disk database
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct LibrarySnapshot {
+	pub categories: Vec<TagCategory>,
+	pub tags: Vec<Tag>,
+	pub files: Vec<FileRecord>,
+	pub file_tags: Vec<FileTagLink>,
+	pub favorites: Vec<Favorite>,
+	// Collections added after the first version 1 archives
+	#[serde(default)]
+	pub metadata: Vec<MetadataRecord>,
+	#[serde(default)]
+	pub sources: Vec<SourceRecord>,
+	#[serde(default)]
+	pub works: Vec<WorkRecord>,
+	#[serde(default)]
+	pub work_members: Vec<WorkMemberRecord>,
+	#[serde(default)]
+	pub relations: Vec<FileRelation>,
+	#[serde(default)]
+	pub stats: Vec<StatsRecord>,
+	#[serde(default)]
+	pub pools: Vec<PoolRecord>,
+	#[serde(default)]
+	pub pool_members: Vec<PoolMemberRecord>,
+	#[serde(default)]
+	pub notes: Vec<FileNote>,
+	#[serde(default)]
+	pub wikis: Vec<WikiRecord>,
+	#[serde(default)]
+	pub wiki_links: Vec<WikiLinkRecord>,
+	#[serde(default)]
+	pub wiki_examples: Vec<WikiExampleRecord>,
+	#[serde(default)]
+	pub tag_aliases: Vec<TagAliasRecord>,
+	#[serde(default)]
+	pub tag_implications: Vec<TagImplicationRecord>,
+	#[serde(default)]
+	pub translations: Vec<TranslationRecord>,
+	#[serde(default)]
+	pub category_rules: Vec<CategoryRuleRecord>,
+	#[serde(default)]
+	pub model_category_mappings: Vec<ModelMappingRecord>,
+}
+
+// Snapshot rows keep the archive's own ids; merging maps them to local ones
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct MetadataRecord {
+	pub file_hash: String,
+	pub source: String,
+	pub key: String,
+	pub value: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SourceRecord {
+	pub file_hash: String,
+	pub url: String,
+	pub domain: String,
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	pub page: Option<i64>,
+	pub added_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkRecord {
+	pub work_id: i64,
+	pub title: Option<String>,
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	pub cover_hash: Option<String>,
+	pub created_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkMemberRecord {
+	pub work_id: i64,
+	pub file_hash: String,
+	pub position: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct StatsRecord {
+	pub file_hash: String,
+	pub rating: Option<i64>,
+	pub view_count: i64,
+	pub last_viewed_at: Option<i64>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PoolRecord {
+	pub pool_id: i64,
+	pub name: String,
+	pub description: Option<String>,
+	pub created_at: i64,
+	pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PoolMemberRecord {
+	pub pool_id: i64,
+	pub file_hash: String,
+	pub position: i64,
+	pub added_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WikiRecord {
+	pub tag_id: i64,
+	pub body: String,
+	pub source: String,
+	pub updated_at: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WikiLinkRecord {
+	pub tag_id: i64,
+	pub url: String,
+	pub label: Option<String>,
+	pub position: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WikiExampleRecord {
+	pub tag_id: i64,
+	pub file_hash: String,
+	pub position: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagAliasRecord {
+	pub alias_name: String,
+	pub tag_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagImplicationRecord {
+	pub tag_id: i64,
+	pub implied_tag_id: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TranslationRecord {
+	pub tag_id: i64,
+	pub lang: String,
+	pub text: String,
+	pub source: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryRuleRecord {
+	pub category_id: i64,
+	pub name_pattern: Option<String>,
+	pub model_category: Option<i64>,
+	pub tag_type: Option<String>,
+	pub priority: i64,
+	pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelMappingRecord {
+	pub model_name: String,
+	#[serde(flatten)]
+	pub mapping: ModelCategoryMapping,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportOptions {
+	pub output_path: String,
+	pub database_format: Option<DatabaseFormat>, // Default: json
+	pub include_thumbnails: Option<bool>,        // Default: true
+	pub include_originals: Option<bool>,         // Default: false
+	pub include_translations: Option<bool>,      // Default: true
+	pub include_settings: Option<bool>,          // Default: true
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportResult {
+	pub archive_path: String,
+	pub manifest: ArchiveManifest,
+	pub originals_missing: usize,
+}
+
+/// What to do when an imported tag name already exists
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TagConflictStrategy {
+	/// Keep the local type, category and alias
+	#[default]
+	KeepExisting,
+	/// Take type, category and alias from the archive
+	Overwrite,
+}
+
+/// What to do when an imported category name already exists
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum CategoryConflictStrategy {
+	/// Use the local category as-is
+	#[default]
+	Merge,
+	/// Update color and sort order from the archive (custom categories only)
+	Overwrite,
+	/// Create a separate "<name> (imported)" category when the definitions differ
+	Rename,
+}
+
+/// How favorites from the archive are applied
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum FavoriteConflictStrategy {
+	/// Favorite everything that is a favorite on either side
+	#[default]
+	Union,
+	/// Only apply archive favorites to files that are new to this library
+	KeepExisting,
+	/// Files in the archive take their favorite status from the archive
+	Replace,
+}
+
+/// Rewrite `original_path` values starting with `from` to start with `to`
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PathRemap {
+	pub from: String,
+	pub to: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportOptions {
+	pub archive_path: String,
+	pub tag_strategy: Option<TagConflictStrategy>,
+	pub category_strategy: Option<CategoryConflictStrategy>,
+	pub favorite_strategy: Option<FavoriteConflictStrategy>,
+	pub path_remaps: Option<Vec<PathRemap>>,
+	/// Extract bundled originals into this directory and point imported files at them
+	pub extract_originals_to: Option<String>,
+	pub import_translations: Option<bool>, // Default: false
+	pub import_settings: Option<bool>,     // Default: false
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct ImportSummary {
+	pub files_added: usize,
+	pub files_existing: usize,
+	pub files_missing_on_disk: usize,
+	pub tags_added: usize,
+	pub tags_updated: usize,
+	pub categories_added: usize,
+	pub categories_updated: usize,
+	pub tag_links_added: usize,
+	/// Links to files that are neither in the archive nor in this library
+	pub tag_links_skipped: usize,
+	pub favorites_added: usize,
+	pub favorites_removed: usize,
+	pub metadata_added: usize,
+	pub sources_added: usize,
+	pub works_added: usize,
+	pub work_members_added: usize,
+	pub relations_added: usize,
+	pub stats_merged: usize,
+	pub pools_added: usize,
+	pub pool_members_added: usize,
+	pub notes_added: usize,
+	pub wikis_added: usize,
+	pub tag_aliases_added: usize,
+	pub tag_implications_added: usize,
+	pub translations_added: usize,
+	pub category_rules_added: usize,
+	/// Models whose category mappings came from the archive (local customizations are kept)
+	pub model_mappings_imported: usize,
+	pub thumbnails_restored: usize,
+	pub originals_extracted: usize,
+	pub translations_imported: bool,
+	pub settings_imported: bool,
+	/// Imported settings that are only read at startup (such as the loaded AI model) take
+	/// effect after a restart
+	pub restart_required: bool,
+}
+
+/// Archive handle handed back from the extraction task, with thumbnail count and extracted originals
+type ExtractedFiles = (ZipArchive<File>, usize, HashMap<String, String>);
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn archive_error(e: zip::result::ZipError) -> AppError {
+	AppError::Custom(format!("Archive error: {e}"))
+}
+
+fn emit_progress(app: &AppHandle, event: &str, stage: &str, message: String) {
+	app.emit(
+		event,
+		ProgressEvent {
+			stage: stage.to_string(),
+			message,
+			file_hash: None,
+			current: None,
+			total: None,
+		},
+	)
+	.ok();
+}
+
+/// Read the library tables from any connection (live pool or an extracted archive database)
+pub async fn read_snapshot(conn: &mut SqliteConnection) -> Result<LibrarySnapshot, AppError> {
+	let categories = sqlx::query(
+		"SELECT category_id, name, color_code, is_builtin, sort_order FROM TagCategories",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| TagCategory {
+		category_id: row.get("category_id"),
+		name: row.get("name"),
+		color_code: row.get("color_code"),
+		is_builtin: row.get("is_builtin"),
+		sort_order: row.get("sort_order"),
+	})
+	.collect();
+
+	let tags = sqlx::query(
+		"SELECT tag_id, name, type, category_id, alias, parent_tag_id, is_deprecated FROM Tags",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| Tag {
+		tag_id: row.get("tag_id"),
+		name: row.get("name"),
+		tag_type: row.get("type"),
+		category_id: row.get("category_id"),
+		alias: row.get("alias"),
+		file_count: None,
+		has_wiki: false,
+		parent_tag_id: row.get("parent_tag_id"),
+		is_deprecated: row.get("is_deprecated"),
+	})
+	.collect();
+
+	let files = sqlx::query(
+		r#"
+        SELECT file_hash, original_path, file_size_bytes, file_last_modified, width, height,
+               date_imported, is_missing, COALESCE(thumbnail_health, 0) as thumbnail_health, last_health_check
+        FROM Files
+        ORDER BY date_imported ASC
+        "#,
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| FileRecord {
+		file_hash: row.get("file_hash"),
+		original_path: row.get("original_path"),
+		file_size_bytes: row.get("file_size_bytes"),
+		file_last_modified: row.get("file_last_modified"),
+		width: row.get("width"),
+		height: row.get("height"),
+		date_imported: row.get("date_imported"),
+		is_missing: row.get("is_missing"),
+		thumbnail_health: Some(row.get("thumbnail_health")),
+		last_health_check: row.get("last_health_check"),
+	})
+	.collect();
+
+	let file_tags = sqlx::query("SELECT file_hash, tag_id FROM FileTags")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| FileTagLink {
+			file_hash: row.get("file_hash"),
+			tag_id: row.get("tag_id"),
+		})
+		.collect();
+
+	let favorites = sqlx::query(
+		"SELECT favorite_id, file_hash, datetime(created_at) as created_at FROM Favorites",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| Favorite {
+		favorite_id: row.get("favorite_id"),
+		file_hash: row.get("file_hash"),
+		created_at: row
+			.get::<Option<String>, _>("created_at")
+			.unwrap_or_default(),
+	})
+	.collect();
+
+	let metadata =
+		sqlx::query("SELECT file_hash, source, key, value FROM FileMetadata ORDER BY metadata_id")
+			.fetch_all(&mut *conn)
+			.await?
+			.into_iter()
+			.map(|row| MetadataRecord {
+				file_hash: row.get("file_hash"),
+				source: row.get("source"),
+				key: row.get("key"),
+				value: row.get("value"),
+			})
+			.collect();
+
+	let sources = sqlx::query(
+		"SELECT file_hash, url, domain, site, post_id, page, added_at FROM FileSources ORDER BY source_id",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| SourceRecord {
+		file_hash: row.get("file_hash"),
+		url: row.get("url"),
+		domain: row.get("domain"),
+		site: row.get("site"),
+		post_id: row.get("post_id"),
+		page: row.get("page"),
+		added_at: row.get("added_at"),
+	})
+	.collect();
+
+	let works =
+		sqlx::query("SELECT work_id, title, site, post_id, cover_hash, created_at FROM Works")
+			.fetch_all(&mut *conn)
+			.await?
+			.into_iter()
+			.map(|row| WorkRecord {
+				work_id: row.get("work_id"),
+				title: row.get("title"),
+				site: row.get("site"),
+				post_id: row.get("post_id"),
+				cover_hash: row.get("cover_hash"),
+				created_at: row.get("created_at"),
+			})
+			.collect();
+
+	let work_members = sqlx::query(
+		"SELECT work_id, file_hash, position FROM WorkMembers ORDER BY work_id, position",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| WorkMemberRecord {
+		work_id: row.get("work_id"),
+		file_hash: row.get("file_hash"),
+		position: row.get("position"),
+	})
+	.collect();
+
+	let relations = sqlx::query(
+		"SELECT relation_id, file_hash, related_hash, relation_type, created_at FROM FileRelations",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.iter()
+	.map(relation_from_row)
+	.collect::<Result<Vec<_>, _>>()?;
+
+	let stats = sqlx::query("SELECT file_hash, rating, view_count, last_viewed_at FROM FileStats")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| StatsRecord {
+			file_hash: row.get("file_hash"),
+			rating: row.get("rating"),
+			view_count: row.get("view_count"),
+			last_viewed_at: row.get("last_viewed_at"),
+		})
+		.collect();
+
+	let pools = sqlx::query("SELECT pool_id, name, description, created_at, updated_at FROM Pools")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| PoolRecord {
+			pool_id: row.get("pool_id"),
+			name: row.get("name"),
+			description: row.get("description"),
+			created_at: row.get("created_at"),
+			updated_at: row.get("updated_at"),
+		})
+		.collect();
+
+	let pool_members = sqlx::query(
+		"SELECT pool_id, file_hash, position, added_at FROM PoolMembers ORDER BY pool_id, position",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| PoolMemberRecord {
+		pool_id: row.get("pool_id"),
+		file_hash: row.get("file_hash"),
+		position: row.get("position"),
+		added_at: row.get("added_at"),
+	})
+	.collect();
+
+	let notes = sqlx::query(
+		r#"
+        SELECT note_id, file_hash, x, y, width, height, body, language, created_at, updated_at
+        FROM FileNotes
+        "#,
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.iter()
+	.map(note_from_row)
+	.collect();
+
+	let wikis = sqlx::query("SELECT tag_id, body, source, updated_at FROM TagWikis")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| WikiRecord {
+			tag_id: row.get("tag_id"),
+			body: row.get("body"),
+			source: row.get("source"),
+			updated_at: row.get("updated_at"),
+		})
+		.collect();
+
+	let wiki_links = sqlx::query(
+		"SELECT tag_id, url, label, position FROM TagWikiLinks ORDER BY tag_id, position",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| WikiLinkRecord {
+		tag_id: row.get("tag_id"),
+		url: row.get("url"),
+		label: row.get("label"),
+		position: row.get("position"),
+	})
+	.collect();
+
+	let wiki_examples = sqlx::query(
+		"SELECT tag_id, file_hash, position FROM TagWikiExamples ORDER BY tag_id, position",
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| WikiExampleRecord {
+		tag_id: row.get("tag_id"),
+		file_hash: row.get("file_hash"),
+		position: row.get("position"),
+	})
+	.collect();
+
+	let tag_aliases = sqlx::query("SELECT alias_name, tag_id FROM TagAliases")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| TagAliasRecord {
+			alias_name: row.get("alias_name"),
+			tag_id: row.get("tag_id"),
+		})
+		.collect();
+
+	let tag_implications = sqlx::query("SELECT tag_id, implied_tag_id FROM TagImplications")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| TagImplicationRecord {
+			tag_id: row.get("tag_id"),
+			implied_tag_id: row.get("implied_tag_id"),
+		})
+		.collect();
+
+	let translations = sqlx::query("SELECT tag_id, lang, text, source FROM TagTranslations")
+		.fetch_all(&mut *conn)
+		.await?
+		.into_iter()
+		.map(|row| TranslationRecord {
+			tag_id: row.get("tag_id"),
+			lang: row.get("lang"),
+			text: row.get("text"),
+			source: row.get("source"),
+		})
+		.collect();
+
+	let category_rules = sqlx::query(
+		r#"
+        SELECT category_id, name_pattern, model_category, tag_type, priority, enabled
+        FROM CategoryRules
+        ORDER BY priority, rule_id
+        "#,
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| CategoryRuleRecord {
+		category_id: row.get("category_id"),
+		name_pattern: row.get("name_pattern"),
+		model_category: row.get("model_category"),
+		tag_type: row.get("tag_type"),
+		priority: row.get("priority"),
+		enabled: row.get("enabled"),
+	})
+	.collect();
+
+	let model_category_mappings = sqlx::query(
+		r#"
+        SELECT model_name, model_category, category_id, tag_type, threshold, mcut_enabled, argmax, enabled
+        FROM ModelCategoryMappings
+        ORDER BY model_name, model_category
+        "#,
+	)
+	.fetch_all(&mut *conn)
+	.await?
+	.into_iter()
+	.map(|row| ModelMappingRecord {
+		model_name: row.get("model_name"),
+		mapping: ModelCategoryMapping {
+			model_category: row.get::<i64, _>("model_category") as u32,
+			category_id: row.get("category_id"),
+			tag_type: row.get("tag_type"),
+			threshold: row.get::<Option<f64>, _>("threshold").map(|t| t as f32),
+			mcut_enabled: row.get("mcut_enabled"),
+			argmax: row.get("argmax"),
+			enabled: row.get("enabled"),
+		},
+	})
+	.collect();
+
+	Ok(LibrarySnapshot {
+		categories,
+		tags,
+		files,
+		file_tags,
+		favorites,
+		metadata,
+		sources,
+		works,
+		work_members,
+		relations,
+		stats,
+		pools,
+		pool_members,
+		notes,
+		wikis,
+		wiki_links,
+		wiki_examples,
+		tag_aliases,
+		tag_implications,
+		translations,
+		category_rules,
+		model_category_mappings,
+	})
+}
+
+/// Apply prefix remaps to a path, longest matching prefix wins
+pub fn remap_path(path: &str, remaps: &[PathRemap]) -> String {
+	remaps
+		.iter()
+		.filter(|r| !r.from.is_empty() && path.starts_with(&r.from))
+		.max_by_key(|r| r.from.len())
+		.map(|r| format!("{}{}", r.to, &path[r.from.len()..]))
+		.unwrap_or_else(|| path.to_string())
+}
+
+fn add_file_to_zip<W: Write + std::io::Seek>(
+	zip: &mut ZipWriter<W>,
+	name: &str,
+	source: &Path,
+	compression: CompressionMethod,
+) -> Result<(), AppError> {
+	let size = std::fs::metadata(source)?.len();
+	let options = SimpleFileOptions::default()
+		.compression_method(compression)
+		.large_file(size >= u32::MAX as u64);
+	zip.start_file(name, options).map_err(archive_error)?;
+	let mut reader = BufReader::new(File::open(source)?);
+	std::io::copy(&mut reader, zip)?;
+	Ok(())
+}
+
+fn add_bytes_to_zip<W: Write + std::io::Seek>(
+	zip: &mut ZipWriter<W>,
+	name: &str,
+	bytes: &[u8],
+) -> Result<(), AppError> {
+	let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+	zip.start_file(name, options).map_err(archive_error)?;
+	zip.write_all(bytes)?;
+	Ok(())
+}
+
+fn read_zip_entry(archive: &mut ZipArchive<File>, name: &str) -> Result<Vec<u8>, AppError> {
+	let mut entry = archive.by_name(name).map_err(archive_error)?;
+	let mut buffer = Vec::with_capacity(entry.size() as usize);
+	entry.read_to_end(&mut buffer)?;
+	Ok(buffer)
+}
+
+fn extract_zip_entry(
+	archive: &mut ZipArchive<File>,
+	name: &str,
+	target: &Path,
+) -> Result<(), AppError> {
+	if let Some(parent) = target.parent() {
+		std::fs::create_dir_all(parent)?;
+	}
+	let mut entry = archive.by_name(name).map_err(archive_error)?;
+	let mut writer = BufWriter::new(File::create(target)?);
+	std::io::copy(&mut entry, &mut writer)?;
+	writer.flush()?;
+	Ok(())
+}
+
+fn open_archive(path: &Path) -> Result<(ZipArchive<File>, ArchiveManifest), AppError> {
+	let mut archive = ZipArchive::new(File::open(path)?).map_err(archive_error)?;
+	let manifest: ArchiveManifest =
+		serde_json::from_slice(&read_zip_entry(&mut archive, "manifest.json")?)
+			.map_err(|e| AppError::Custom(format!("Invalid archive manifest: {e}")))?;
+
+	if manifest.format != ARCHIVE_FORMAT {
+		return Err(AppError::Custom(format!(
+			"Not a PixiMoe library archive (format: {})",
+			manifest.format
+		)));
+	}
+	if manifest.format_version > ARCHIVE_FORMAT_VERSION {
+		return Err(AppError::Custom(format!(
+			"Archive format version {} is newer than supported version {ARCHIVE_FORMAT_VERSION}",
+			manifest.format_version
+		)));
+	}
+
+	Ok((archive, manifest))
+}
+
+/// Load the snapshot stored in an archive, whichever database format it uses
+async fn load_archive_snapshot(
+	archive: &mut ZipArchive<File>,
+	manifest: &ArchiveManifest,
+	scratch_dir: &Path,
+) -> Result<LibrarySnapshot, AppError> {
+	match manifest.database_format {
+		DatabaseFormat::Json => serde_json::from_slice(&read_zip_entry(archive, "database.json")?)
+			.map_err(|e| AppError::Custom(format!("Invalid database.json: {e}"))),
+		DatabaseFormat::Sqlite => {
+			std::fs::create_dir_all(scratch_dir)?;
+			let db_path = scratch_dir.join("import.sqlite");
+			extract_zip_entry(archive, "database.sqlite", &db_path)?;
+
+			// Bring older snapshots up to the current schema before reading
+			let options = SqliteConnectOptions::new().filename(&db_path);
+			let pool = SqlitePoolOptions::new()
+				.max_connections(1)
+				.connect_with(options)
+				.await?;
+			let result = async {
+				sqlx::migrate!("../migrations")
+					.run(&pool)
+					.await
+					.map_err(|e| {
+						AppError::Custom(format!("Archive database migration failed: {e}"))
+					})?;
+				let mut conn = pool.acquire().await?;
+				read_snapshot(&mut conn).await
+			}
+			.await;
+			pool.close().await;
+			std::fs::remove_file(&db_path).ok();
+			result
+		}
+	}
+}
+
+/// Where to extract an original: an existing file with the same content is reused (returned
+/// with `true`), otherwise the first free name among `name`, `{hash8}_name`, `{hash8}_2_name`, ...
+fn original_target(
+	dir: &Path,
+	file_name: &str,
+	file_hash: &str,
+) -> Result<(PathBuf, bool), AppError> {
+	let prefix = &file_hash[..8];
+	for attempt in 0.. {
+		let candidate = match attempt {
+			0 => dir.join(file_name),
+			1 => dir.join(format!("{prefix}_{file_name}")),
+			n => dir.join(format!("{prefix}_{n}_{file_name}")),
+		};
+		if !candidate.exists() {
+			return Ok((candidate, false));
+		}
+		if candidate.is_file() && calculate_blake3_hash(&candidate)? == file_hash {
+			return Ok((candidate, true));
+		}
+	}
+	unreachable!("candidate names are unbounded")
+}
+
+/// Content hashes are lowercase hex BLAKE3; archive hashes become file names, so anything else
+/// is rejected
+fn is_valid_file_hash(hash: &str) -> bool {
+	hash.len() == 64 && hash.bytes().all(|b| matches!(b, b'0'..=b'9' | b'a'..=b'f'))
+}
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+// ============================================================================
+// Merge
+// ============================================================================
+
+/// Whether a file is in the library, remembering the answer per hash
+async fn file_known<'a>(
+	conn: &mut SqliteConnection,
+	known: &mut HashMap<&'a str, bool>,
+	file_hash: &'a str,
+) -> Result<bool, AppError> {
+	if let Some(&exists) = known.get(file_hash) {
+		return Ok(exists);
+	}
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+		.bind(file_hash)
+		.fetch_one(&mut *conn)
+		.await?;
+	known.insert(file_hash, exists);
+	Ok(exists)
+}
+
+/// Merge a snapshot into the live database inside one transaction
+/// `original_overrides` points imported files at extracted originals instead of their old path.
+/// Returns the created tags left in GENERAL, for category rules to sort.
+async fn merge_snapshot(
+	pool: &SqlitePool,
+	snapshot: &LibrarySnapshot,
+	options: &ImportOptions,
+	original_overrides: &HashMap<String, String>,
+	summary: &mut ImportSummary,
+) -> Result<Vec<(String, Option<u32>)>, AppError> {
+	let tag_strategy = options.tag_strategy.unwrap_or_default();
+	let category_strategy = options.category_strategy.unwrap_or_default();
+	let favorite_strategy = options.favorite_strategy.unwrap_or_default();
+	let remaps = options.path_remaps.clone().unwrap_or_default();
+
+	let mut tx = pool.begin().await?;
+
+	// 1. Categories, matched by name
+	let mut category_map: HashMap<i64, i64> = HashMap::new();
+	for category in &snapshot.categories {
+		let existing = sqlx::query(
+			"SELECT category_id, color_code, is_builtin, sort_order FROM TagCategories WHERE name = ?",
+		)
+		.bind(&category.name)
+		.fetch_optional(&mut *tx)
+		.await?;
+
+		let local_id = match existing {
+			Some(row) => {
+				let local_id: i64 = row.get("category_id");
+				let is_builtin: bool = row.get("is_builtin");
+				let differs = row.get::<String, _>("color_code") != category.color_code
+					|| row.get::<i64, _>("sort_order") != category.sort_order;
+
+				match category_strategy {
+					CategoryConflictStrategy::Overwrite if differs && !is_builtin => {
+						sqlx::query(
+							"UPDATE TagCategories SET color_code = ?, sort_order = ? WHERE category_id = ?",
+						)
+						.bind(&category.color_code)
+						.bind(category.sort_order)
+						.bind(local_id)
+						.execute(&mut *tx)
+						.await?;
+						summary.categories_updated += 1;
+						local_id
+					}
+					CategoryConflictStrategy::Rename if differs && !is_builtin => {
+						let renamed = format!("{} (imported)", category.name);
+						let id: i64 = sqlx::query_scalar(
+							r#"
+                            INSERT INTO TagCategories (name, color_code, is_builtin, sort_order)
+                            VALUES (?, ?, FALSE, ?)
+                            ON CONFLICT(name) DO UPDATE SET name = name
+                            RETURNING category_id
+                            "#,
+						)
+						.bind(&renamed)
+						.bind(&category.color_code)
+						.bind(category.sort_order)
+						.fetch_one(&mut *tx)
+						.await?;
+						summary.categories_added += 1;
+						id
+					}
+					_ => local_id,
+				}
+			}
+			None => {
+				let id: i64 = sqlx::query_scalar(
+					r#"
+                    INSERT INTO TagCategories (name, color_code, is_builtin, sort_order)
+                    VALUES (?, ?, FALSE, ?)
+                    RETURNING category_id
+                    "#,
+				)
+				.bind(&category.name)
+				.bind(&category.color_code)
+				.bind(category.sort_order)
+				.fetch_one(&mut *tx)
+				.await?;
+				summary.categories_added += 1;
+				id
+			}
+		};
+
+		category_map.insert(category.category_id, local_id);
+	}
+
+	// 2. Tags, matched by name
+	let mut tag_map: HashMap<i64, i64> = HashMap::new();
+	let mut uncategorized_tags = Vec::new();
+	for tag in &snapshot.tags {
+		// Unknown categories fall back to GENERAL (1)
+		let category_id = category_map.get(&tag.category_id).copied().unwrap_or(1);
+
+		let existing: Option<i64> = sqlx::query_scalar("SELECT tag_id FROM Tags WHERE name = ?")
+			.bind(&tag.name)
+			.fetch_optional(&mut *tx)
+			.await?;
+
+		let local_id = match existing {
+			Some(local_id) => {
+				if tag_strategy == TagConflictStrategy::Overwrite {
+					sqlx::query(
+						"UPDATE Tags SET type = ?, category_id = ?, alias = ?, is_deprecated = ? WHERE tag_id = ?",
+					)
+					.bind(&tag.tag_type)
+					.bind(category_id)
+					.bind(&tag.alias)
+					.bind(tag.is_deprecated)
+					.bind(local_id)
+					.execute(&mut *tx)
+					.await?;
+					summary.tags_updated += 1;
+				}
+				local_id
+			}
+			None => {
+				let id: i64 = sqlx::query_scalar(
+					"INSERT INTO Tags (name, type, category_id, alias, is_deprecated) VALUES (?, ?, ?, ?, ?) RETURNING tag_id",
+				)
+				.bind(&tag.name)
+				.bind(&tag.tag_type)
+				.bind(category_id)
+				.bind(&tag.alias)
+				.bind(tag.is_deprecated)
+				.fetch_one(&mut *tx)
+				.await?;
+				summary.tags_added += 1;
+				if category_id == 1 {
+					uncategorized_tags.push((tag.name.clone(), None));
+				}
+				id
+			}
+		};
+
+		tag_map.insert(tag.tag_id, local_id);
+	}
+
+	// Tag parents, once every tag has a local id; local parents are kept unless tags are
+	// overwritten, and parents that would create a cycle are skipped
+	for tag in &snapshot.tags {
+		let (Some(&local_id), Some(local_parent)) = (
+			tag_map.get(&tag.tag_id),
+			tag.parent_tag_id
+				.and_then(|parent| tag_map.get(&parent).copied()),
+		) else {
+			continue;
+		};
+		let current: Option<i64> =
+			sqlx::query_scalar("SELECT parent_tag_id FROM Tags WHERE tag_id = ?")
+				.bind(local_id)
+				.fetch_one(&mut *tx)
+				.await?;
+		if current == Some(local_parent)
+			|| (current.is_some() && tag_strategy != TagConflictStrategy::Overwrite)
+			|| is_in_subtree(&mut tx, local_parent, local_id).await?
+		{
+			continue;
+		}
+		sqlx::query("UPDATE Tags SET parent_tag_id = ? WHERE tag_id = ?")
+			.bind(local_parent)
+			.bind(local_id)
+			.execute(&mut *tx)
+			.await?;
+	}
+
+	// 3. Files, keyed by content hash
+	let mut new_files: HashSet<&str> = HashSet::new();
+	for file in &snapshot.files {
+		let exists: Option<String> =
+			sqlx::query_scalar("SELECT file_hash FROM Files WHERE file_hash = ?")
+				.bind(&file.file_hash)
+				.fetch_optional(&mut *tx)
+				.await?;
+
+		if exists.is_some() {
+			summary.files_existing += 1;
+			continue;
+		}
+
+		let original_path = original_overrides
+			.get(&file.file_hash)
+			.cloned()
+			.unwrap_or_else(|| remap_path(&file.original_path, &remaps));
+		let is_missing = !Path::new(&original_path).exists();
+		if is_missing {
+			summary.files_missing_on_disk += 1;
+		}
+
+		sqlx::query(
+			r#"
+            INSERT INTO Files (file_hash, original_path, file_size_bytes, file_last_modified, width, height, date_imported, is_missing)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+		)
+		.bind(&file.file_hash)
+		.bind(&original_path)
+		.bind(file.file_size_bytes)
+		.bind(file.file_last_modified)
+		.bind(file.width)
+		.bind(file.height)
+		.bind(file.date_imported)
+		.bind(is_missing as i64)
+		.execute(&mut *tx)
+		.await?;
+
+		new_files.insert(&file.file_hash);
+		summary.files_added += 1;
+	}
+
+	// Every archive file is in Files now; anything else it references must already be local
+	let mut known_files: HashMap<&str, bool> = snapshot
+		.files
+		.iter()
+		.map(|file| (file.file_hash.as_str(), true))
+		.collect();
+
+	// 4. File-tag links (union)
+	for link in &snapshot.file_tags {
+		let Some(tag_id) = tag_map.get(&link.tag_id) else {
+			continue;
+		};
+		if !file_known(&mut tx, &mut known_files, &link.file_hash).await? {
+			summary.tag_links_skipped += 1;
+			continue;
+		}
+		let result =
+			sqlx::query("INSERT OR IGNORE INTO FileTags (file_hash, tag_id) VALUES (?, ?)")
+				.bind(&link.file_hash)
+				.bind(tag_id)
+				.execute(&mut *tx)
+				.await?;
+		summary.tag_links_added += result.rows_affected() as usize;
+	}
+
+	// 5. Favorites
+	let archive_favorites: HashSet<&str> = snapshot
+		.favorites
+		.iter()
+		.map(|f| f.file_hash.as_str())
+		.collect();
+
+	for favorite in &snapshot.favorites {
+		if favorite_strategy == FavoriteConflictStrategy::KeepExisting
+			&& !new_files.contains(favorite.file_hash.as_str())
+		{
+			continue;
+		}
+		if !file_known(&mut tx, &mut known_files, &favorite.file_hash).await? {
+			continue;
+		}
+		let result = sqlx::query(
+			"INSERT OR IGNORE INTO Favorites (file_hash, created_at) VALUES (?, COALESCE(NULLIF(?, ''), CURRENT_TIMESTAMP))",
+		)
+		.bind(&favorite.file_hash)
+		.bind(&favorite.created_at)
+			.execute(&mut *tx)
+			.await?;
+		summary.favorites_added += result.rows_affected() as usize;
+	}
+
+	if favorite_strategy == FavoriteConflictStrategy::Replace {
+		for file in &snapshot.files {
+			if archive_favorites.contains(file.file_hash.as_str()) {
+				continue;
+			}
+			let result = sqlx::query("DELETE FROM Favorites WHERE file_hash = ?")
+				.bind(&file.file_hash)
+				.execute(&mut *tx)
+				.await?;
+			summary.favorites_removed += result.rows_affected() as usize;
+		}
+	}
+
+	// 6. Embedded metadata, for new files only; existing files keep their own extraction
+	for metadata in &snapshot.metadata {
+		if !new_files.contains(metadata.file_hash.as_str()) {
+			continue;
+		}
+		sqlx::query("INSERT INTO FileMetadata (file_hash, source, key, value) VALUES (?, ?, ?, ?)")
+			.bind(&metadata.file_hash)
+			.bind(&metadata.source)
+			.bind(&metadata.key)
+			.bind(&metadata.value)
+			.execute(&mut *tx)
+			.await?;
+		summary.metadata_added += 1;
+	}
+
+	// 7. Sources (union)
+	for source in &snapshot.sources {
+		if !file_known(&mut tx, &mut known_files, &source.file_hash).await? {
+			continue;
+		}
+		let result = sqlx::query(
+			r#"
+            INSERT OR IGNORE INTO FileSources (file_hash, url, domain, site, post_id, page, added_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?)
+            "#,
+		)
+		.bind(&source.file_hash)
+		.bind(&source.url)
+		.bind(&source.domain)
+		.bind(&source.site)
+		.bind(&source.post_id)
+		.bind(source.page)
+		.bind(source.added_at)
+		.execute(&mut *tx)
+		.await?;
+		summary.sources_added += result.rows_affected() as usize;
+	}
+
+	// 8. Works; site works match by post, files already in a local work stay there, and works
+	// that would end up empty are not created
+	for work in &snapshot.works {
+		let mut members = Vec::new();
+		for member in snapshot
+			.work_members
+			.iter()
+			.filter(|m| m.work_id == work.work_id)
+		{
+			if !file_known(&mut tx, &mut known_files, &member.file_hash).await? {
+				continue;
+			}
+			let in_work: bool =
+				sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM WorkMembers WHERE file_hash = ?)")
+					.bind(&member.file_hash)
+					.fetch_one(&mut *tx)
+					.await?;
+			if !in_work {
+				members.push(member);
+			}
+		}
+
+		let existing: Option<i64> = match &work.post_id {
+			Some(post_id) => {
+				sqlx::query_scalar("SELECT work_id FROM Works WHERE site IS ? AND post_id = ?")
+					.bind(&work.site)
+					.bind(post_id)
+					.fetch_optional(&mut *tx)
+					.await?
+			}
+			None => None,
+		};
+		let work_id = match existing {
+			Some(work_id) => work_id,
+			None if members.is_empty() => continue,
+			None => {
+				let cover_hash = match &work.cover_hash {
+					Some(hash) if file_known(&mut tx, &mut known_files, hash).await? => Some(hash),
+					_ => None,
+				};
+				summary.works_added += 1;
+				sqlx::query_scalar(
+					r#"
+                    INSERT INTO Works (title, site, post_id, cover_hash, created_at)
+                    VALUES (?, ?, ?, ?, ?)
+                    RETURNING work_id
+                    "#,
+				)
+				.bind(&work.title)
+				.bind(&work.site)
+				.bind(&work.post_id)
+				.bind(cover_hash)
+				.bind(work.created_at)
+				.fetch_one(&mut *tx)
+				.await?
+			}
+		};
+
+		for member in members {
+			let result = sqlx::query(
+				"INSERT OR IGNORE INTO WorkMembers (work_id, file_hash, position) VALUES (?, ?, ?)",
+			)
+			.bind(work_id)
+			.bind(&member.file_hash)
+			.bind(member.position)
+			.execute(&mut *tx)
+			.await?;
+			summary.work_members_added += result.rows_affected() as usize;
+		}
+	}
+
+	// 9. Relations; ones that clash with local relations (second parent, cycle) are skipped
+	for relation in &snapshot.relations {
+		if !file_known(&mut tx, &mut known_files, &relation.file_hash).await?
+			|| !file_known(&mut tx, &mut known_files, &relation.related_hash).await?
+		{
+			continue;
+		}
+		let added = insert_relation(
+			&mut tx,
+			&relation.file_hash,
+			&relation.related_hash,
+			relation.relation_type,
+			relation.created_at,
+		)
+		.await?;
+		summary.relations_added += added as usize;
+	}
+
+	// 10. Stats: local star ratings win, view counts and last views take the larger value
+	for stats in &snapshot.stats {
+		if !file_known(&mut tx, &mut known_files, &stats.file_hash).await? {
+			continue;
+		}
+		let result = sqlx::query(
+			r#"
+            INSERT INTO FileStats (file_hash, rating, view_count, last_viewed_at)
+            VALUES (?, ?, ?, ?)
+            ON CONFLICT(file_hash) DO UPDATE SET
+                rating = COALESCE(FileStats.rating, excluded.rating),
+                view_count = MAX(FileStats.view_count, excluded.view_count),
+                last_viewed_at = COALESCE(MAX(FileStats.last_viewed_at, excluded.last_viewed_at),
+                                          FileStats.last_viewed_at, excluded.last_viewed_at)
+            "#,
+		)
+		.bind(&stats.file_hash)
+		.bind(stats.rating)
+		.bind(stats.view_count)
+		.bind(stats.last_viewed_at)
+		.execute(&mut *tx)
+		.await?;
+		summary.stats_merged += result.rows_affected() as usize;
+	}
+
+	// 11. Pools, matched by name; missing members are appended in archive order
+	for pool in &snapshot.pools {
+		let existing: Option<i64> = sqlx::query_scalar("SELECT pool_id FROM Pools WHERE name = ?")
+			.bind(&pool.name)
+			.fetch_optional(&mut *tx)
+			.await?;
+		let pool_id = match existing {
+			Some(pool_id) => pool_id,
+			None => {
+				summary.pools_added += 1;
+				sqlx::query_scalar(
+					r#"
+                    INSERT INTO Pools (name, description, created_at, updated_at)
+                    VALUES (?, ?, ?, ?)
+                    RETURNING pool_id
+                    "#,
+				)
+				.bind(&pool.name)
+				.bind(&pool.description)
+				.bind(pool.created_at)
+				.bind(pool.updated_at)
+				.fetch_one(&mut *tx)
+				.await?
+			}
+		};
+
+		let mut added = 0;
+		for member in snapshot
+			.pool_members
+			.iter()
+			.filter(|m| m.pool_id == pool.pool_id)
+		{
+			if !file_known(&mut tx, &mut known_files, &member.file_hash).await? {
+				continue;
+			}
+			let result = sqlx::query(
+				r#"
+                INSERT INTO PoolMembers (pool_id, file_hash, position, added_at)
+                SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0), ?3 FROM PoolMembers WHERE pool_id = ?1
+                HAVING NOT EXISTS (SELECT 1 FROM PoolMembers WHERE pool_id = ?1 AND file_hash = ?2)
+                "#,
+			)
+			.bind(pool_id)
+			.bind(&member.file_hash)
+			.bind(member.added_at)
+			.execute(&mut *tx)
+			.await?;
+			added += result.rows_affected() as usize;
+		}
+		if added > 0 && existing.is_some() {
+			sqlx::query("UPDATE Pools SET updated_at = ? WHERE pool_id = ?")
+				.bind(unix_now())
+				.bind(pool_id)
+				.execute(&mut *tx)
+				.await?;
+		}
+		summary.pool_members_added += added;
+	}
+
+	// 12. Region notes; notes identical to one the file already has are skipped
+	for note in &snapshot.notes {
+		let result = sqlx::query(
+			r#"
+            INSERT INTO FileNotes (file_hash, x, y, width, height, body, language, created_at, updated_at)
+            SELECT ?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9
+            WHERE EXISTS (SELECT 1 FROM Files WHERE file_hash = ?1)
+              AND NOT EXISTS (
+                SELECT 1 FROM FileNotes
+                WHERE file_hash = ?1 AND x = ?2 AND y = ?3 AND width = ?4 AND height = ?5 AND body = ?6
+              )
+            "#,
+		)
+		.bind(&note.file_hash)
+		.bind(note.x)
+		.bind(note.y)
+		.bind(note.width)
+		.bind(note.height)
+		.bind(&note.body)
+		.bind(&note.language)
+		.bind(note.created_at)
+		.bind(note.updated_at)
+		.execute(&mut *tx)
+		.await?;
+		summary.notes_added += result.rows_affected() as usize;
+	}
+
+	// 13. Wiki pages fill in local pages without a body; links and examples are appended
+	for wiki in &snapshot.wikis {
+		let Some(&tag_id) = tag_map.get(&wiki.tag_id) else {
+			continue;
+		};
+		let result = sqlx::query(
+			r#"
+            INSERT INTO TagWikis (tag_id, body, source, updated_at) VALUES (?, ?, ?, ?)
+            ON CONFLICT(tag_id) DO UPDATE SET
+                body = excluded.body, source = excluded.source, updated_at = excluded.updated_at
+            WHERE TagWikis.body = '' AND excluded.body != ''
+            "#,
+		)
+		.bind(tag_id)
+		.bind(&wiki.body)
+		.bind(&wiki.source)
+		.bind(wiki.updated_at)
+		.execute(&mut *tx)
+		.await?;
+		summary.wikis_added += result.rows_affected() as usize;
+	}
+	for link in &snapshot.wiki_links {
+		let Some(&tag_id) = tag_map.get(&link.tag_id) else {
+			continue;
+		};
+		sqlx::query(
+			r#"
+            INSERT OR IGNORE INTO TagWikiLinks (tag_id, url, label, position)
+            SELECT ?1, ?2, ?3, COALESCE(MAX(position) + 1, 0) FROM TagWikiLinks WHERE tag_id = ?1
+            "#,
+		)
+		.bind(tag_id)
+		.bind(&link.url)
+		.bind(&link.label)
+		.execute(&mut *tx)
+		.await?;
+	}
+	for example in &snapshot.wiki_examples {
+		let Some(&tag_id) = tag_map.get(&example.tag_id) else {
+			continue;
+		};
+		if !file_known(&mut tx, &mut known_files, &example.file_hash).await? {
+			continue;
+		}
+		sqlx::query(
+			r#"
+            INSERT OR IGNORE INTO TagWikiExamples (tag_id, file_hash, position)
+            SELECT ?1, ?2, COALESCE(MAX(position) + 1, 0) FROM TagWikiExamples WHERE tag_id = ?1
+            "#,
+		)
+		.bind(tag_id)
+		.bind(&example.file_hash)
+		.execute(&mut *tx)
+		.await?;
+	}
+
+	// 14. Tag aliases and implications (union); aliases naming an existing tag are skipped
+	for alias in &snapshot.tag_aliases {
+		let Some(&tag_id) = tag_map.get(&alias.tag_id) else {
+			continue;
+		};
+		let result = sqlx::query(
+			r#"
+            INSERT OR IGNORE INTO TagAliases (alias_name, tag_id)
+            SELECT ?1, ?2 WHERE NOT EXISTS (SELECT 1 FROM Tags WHERE name = ?1)
+            "#,
+		)
+		.bind(&alias.alias_name)
+		.bind(tag_id)
+		.execute(&mut *tx)
+		.await?;
+		summary.tag_aliases_added += result.rows_affected() as usize;
+	}
+	for implication in &snapshot.tag_implications {
+		let (Some(&tag_id), Some(&implied_tag_id)) = (
+			tag_map.get(&implication.tag_id),
+			tag_map.get(&implication.implied_tag_id),
+		) else {
+			continue;
+		};
+		if tag_id == implied_tag_id {
+			continue;
+		}
+		let result = sqlx::query(
+			"INSERT OR IGNORE INTO TagImplications (tag_id, implied_tag_id) VALUES (?, ?)",
+		)
+		.bind(tag_id)
+		.bind(implied_tag_id)
+		.execute(&mut *tx)
+		.await?;
+		summary.tag_implications_added += result.rows_affected() as usize;
+	}
+
+	// 15. Translations: local user translations win, archive user translations replace
+	// dictionary ones
+	for translation in &snapshot.translations {
+		let Some(&tag_id) = tag_map.get(&translation.tag_id) else {
+			continue;
+		};
+		let result = sqlx::query(
+			r#"
+            INSERT INTO TagTranslations (tag_id, lang, text, source) VALUES (?, ?, ?, ?)
+            ON CONFLICT(tag_id, lang) DO UPDATE SET text = excluded.text, source = excluded.source
+            WHERE TagTranslations.source = 'dictionary' AND excluded.source = 'user'
+            "#,
+		)
+		.bind(tag_id)
+		.bind(&translation.lang)
+		.bind(&translation.text)
+		.bind(&translation.source)
+		.execute(&mut *tx)
+		.await?;
+		summary.translations_added += result.rows_affected() as usize;
+	}
+
+	// 16. Category rules not already defined locally
+	for rule in &snapshot.category_rules {
+		let Some(&category_id) = category_map.get(&rule.category_id) else {
+			continue;
+		};
+		let result = sqlx::query(
+			r#"
+            INSERT INTO CategoryRules (category_id, name_pattern, model_category, tag_type, priority, enabled)
+            SELECT ?1, ?2, ?3, ?4, ?5, ?6
+            WHERE NOT EXISTS (
+                SELECT 1 FROM CategoryRules
+                WHERE category_id = ?1 AND name_pattern IS ?2 AND model_category IS ?3 AND tag_type IS ?4
+            )
+            "#,
+		)
+		.bind(category_id)
+		.bind(&rule.name_pattern)
+		.bind(rule.model_category)
+		.bind(&rule.tag_type)
+		.bind(rule.priority)
+		.bind(rule.enabled)
+		.execute(&mut *tx)
+		.await?;
+		summary.category_rules_added += result.rows_affected() as usize;
+	}
+
+	// 17. Model category mappings, for models still on the built-in mapping here
+	let mut mapped_models: HashSet<&str> = HashSet::new();
+	for record in &snapshot.model_category_mappings {
+		let model_name = record.model_name.as_str();
+		if !mapped_models.contains(model_name) {
+			let customized: bool = sqlx::query_scalar(
+				"SELECT EXISTS(SELECT 1 FROM ModelCategoryMappings WHERE model_name = ?)",
+			)
+			.bind(model_name)
+			.fetch_one(&mut *tx)
+			.await?;
+			if customized {
+				continue;
+			}
+			mapped_models.insert(model_name);
+			summary.model_mappings_imported += 1;
+		}
+		let Some(&category_id) = category_map.get(&record.mapping.category_id) else {
+			continue;
+		};
+		let mapping = &record.mapping;
+		sqlx::query(
+			r#"
+            INSERT INTO ModelCategoryMappings
+                (model_name, model_category, category_id, tag_type, threshold, mcut_enabled, argmax, enabled)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+		)
+		.bind(model_name)
+		.bind(mapping.model_category as i64)
+		.bind(category_id)
+		.bind(&mapping.tag_type)
+		.bind(mapping.threshold.map(f64::from))
+		.bind(mapping.mcut_enabled)
+		.bind(mapping.argmax)
+		.bind(mapping.enabled)
+		.execute(&mut *tx)
+		.await?;
+	}
+
+	tx.commit().await?;
+
+	Ok(uncategorized_tags)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Read the manifest of an archive without importing it
+#[tauri::command]
+pub async fn inspect_library_archive(archive_path: String) -> Result<ArchiveManifest, AppError> {
+	tokio::task::spawn_blocking(move || open_archive(Path::new(&archive_path)).map(|(_, m)| m))
+		.await
+		.map_err(|e| AppError::Custom(format!("Task join error: {e}")))?
+}
+
+/// Export the library into a portable zip archive
+#[tauri::command]
+pub async fn export_library(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	options: ExportOptions,
+) -> Result<ExportResult, AppError> {
+	// An archive is the whole library; it must not carry files safe mode is hiding
+	if is_safe_mode_active() {
+		return Err(AppError::Custom(
+			"Unlock or turn off safe mode before exporting the library".to_string(),
+		));
+	}
+	let pool = db.get();
+	let database_format = options.database_format.unwrap_or(DatabaseFormat::Json);
+	let include_thumbnails = options.include_thumbnails.unwrap_or(true);
+	let include_originals = options.include_originals.unwrap_or(false);
+	let include_translations = options.include_translations.unwrap_or(true);
+	let include_settings = options.include_settings.unwrap_or(true);
+
+	emit_progress(
+		&app,
+		"library_export_progress",
+		"reading_database",
+		"Reading library database...".to_string(),
+	);
+
+	let snapshot = {
+		let mut conn = pool.acquire().await?;
+		read_snapshot(&mut conn).await?
+	};
+
+	let app_data_dir = app
+		.path()
+		.app_data_dir()
+		.map_err(|e| AppError::Custom(format!("Failed to get app data dir: {e}")))?;
+
+	// The SQLite variant is a consistent VACUUM INTO copy of the live database
+	let sqlite_copy = if database_format == DatabaseFormat::Sqlite {
+		let path = app_data_dir.join(format!("export-{}.sqlite", unix_now()));
+		sqlx::query("VACUUM INTO ?")
+			.bind(path.display().to_string())
+			.execute(&pool)
+			.await?;
+		Some(path)
+	} else {
+		None
+	};
+
+	let translations_file =
+		super::settings::get_translations_dir(app.clone())?.join("translations.csv");
+	let include_translations = include_translations && translations_file.exists();
+
+	let mut settings = Vec::new();
+	if include_settings {
+		for name in SETTINGS_STORES {
+			let store = app.store(*name)?;
+			let entries: serde_json::Map<String, serde_json::Value> =
+				store.entries().into_iter().collect();
+			settings.push((name.to_string(), serde_json::Value::Object(entries)));
+		}
+	}
+
+	let manifest = ArchiveManifest {
+		format: ARCHIVE_FORMAT.to_string(),
+		format_version: ARCHIVE_FORMAT_VERSION,
+		app_version: env!("CARGO_PKG_VERSION").to_string(),
+		created_at: unix_now(),
+		database_format,
+		file_count: snapshot.files.len(),
+		includes_thumbnails: include_thumbnails,
+		includes_originals: include_originals,
+		includes_translations: include_translations,
+		includes_settings: include_settings,
+	};
+
+	let thumbnail_dir = get_thumbnail_dir(&app)?;
+	let archive_path = PathBuf::from(&options.output_path);
+	let manifest_for_task = manifest.clone();
+	let app_for_task = app.clone();
+	let sqlite_for_task = sqlite_copy.clone();
+
+	let write_result = tokio::task::spawn_blocking(move || -> Result<usize, AppError> {
+		let manifest = manifest_for_task;
+		let app = app_for_task;
+		let mut zip = ZipWriter::new(BufWriter::new(File::create(&archive_path)?));
+
+		let manifest_json = serde_json::to_vec_pretty(&manifest)
+			.map_err(|e| AppError::Custom(format!("Failed to serialize manifest: {e}")))?;
+		add_bytes_to_zip(&mut zip, "manifest.json", &manifest_json)?;
+
+		match &sqlite_for_task {
+			Some(path) => add_file_to_zip(
+				&mut zip,
+				"database.sqlite",
+				path,
+				CompressionMethod::Deflated,
+			)?,
+			None => {
+				let json = serde_json::to_vec(&snapshot)
+					.map_err(|e| AppError::Custom(format!("Failed to serialize database: {e}")))?;
+				add_bytes_to_zip(&mut zip, "database.json", &json)?;
+			}
+		}
+
+		if manifest.includes_translations {
+			add_file_to_zip(
+				&mut zip,
+				"translations/translations.csv",
+				&translations_file,
+				CompressionMethod::Deflated,
+			)?;
+		}
+
+		for (name, value) in &settings {
+			let json = serde_json::to_vec_pretty(value)
+				.map_err(|e| AppError::Custom(format!("Failed to serialize settings: {e}")))?;
+			add_bytes_to_zip(&mut zip, &format!("settings/{name}"), &json)?;
+		}
+
+		let total = snapshot.files.len();
+		let mut originals_missing = 0;
+		for (index, file) in snapshot.files.iter().enumerate() {
+			if manifest.includes_thumbnails {
+				let thumbnail = thumbnail_dir.join(format!("{}.webp", file.file_hash));
+				if thumbnail.exists() {
+					add_file_to_zip(
+						&mut zip,
+						&format!("thumbnails/{}.webp", file.file_hash),
+						&thumbnail,
+						CompressionMethod::Stored,
+					)?;
+				}
+			}
+
+			if manifest.includes_originals {
+				let original = Path::new(&file.original_path);
+				if original.exists() {
+					let extension = original
+						.extension()
+						.and_then(|e| e.to_str())
+						.map(|e| format!(".{e}"))
+						.unwrap_or_default();
+					add_file_to_zip(
+						&mut zip,
+						&format!("originals/{}{extension}", file.file_hash),
+						original,
+						CompressionMethod::Stored,
+					)?;
+				} else {
+					originals_missing += 1;
+				}
+			}
+
+			if index % 50 == 0 || index + 1 == total {
+				app.emit(
+					"library_export_progress",
+					ProgressEvent {
+						stage: "writing_files".to_string(),
+						message: format!("Archiving file {} of {total}", index + 1),
+						file_hash: Some(file.file_hash.clone()),
+						current: Some(index + 1),
+						total: Some(total),
+					},
+				)
+				.ok();
+			}
+		}
+
+		zip.finish().map_err(archive_error)?.flush()?;
+		Ok(originals_missing)
+	})
+	.await
+	.map_err(|e| AppError::Custom(format!("Task join error: {e}")));
+
+	if let Some(path) = &sqlite_copy {
+		std::fs::remove_file(path).ok();
+	}
+	let originals_missing = write_result??;
+
+	emit_progress(
+		&app,
+		"library_export_progress",
+		"complete",
+		format!("Exported {} files", manifest.file_count),
+	);
+
+	Ok(ExportResult {
+		archive_path: options.output_path,
+		manifest,
+		originals_missing,
+	})
+}
+
+/// Import a library archive, merging it into the current library by file hash
+#[tauri::command]
+pub async fn import_library(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	options: ImportOptions,
+) -> Result<ImportSummary, AppError> {
+	let pool = db.get();
+	let mut summary = ImportSummary::default();
+
+	let archive_path = PathBuf::from(&options.archive_path);
+	let (mut archive, manifest) = {
+		let path = archive_path.clone();
+		tokio::task::spawn_blocking(move || open_archive(&path))
+			.await
+			.map_err(|e| AppError::Custom(format!("Task join error: {e}")))??
+	};
+
+	emit_progress(
+		&app,
+		"library_import_progress",
+		"reading_database",
+		"Reading archive database...".to_string(),
+	);
+
+	let app_data_dir = app
+		.path()
+		.app_data_dir()
+		.map_err(|e| AppError::Custom(format!("Failed to get app data dir: {e}")))?;
+	let snapshot =
+		load_archive_snapshot(&mut archive, &manifest, &app_data_dir.join("import-tmp")).await?;
+
+	// Hashes name extracted thumbnails and originals, so check them all before extracting anything
+	if let Some(file) = snapshot
+		.files
+		.iter()
+		.find(|file| !is_valid_file_hash(&file.file_hash))
+	{
+		return Err(AppError::Custom(format!(
+			"Invalid file hash in archive: {:?}",
+			file.file_hash
+		)));
+	}
+
+	// Index bundled originals by hash so entries are only ever looked up, never trusted as paths
+	let original_entries: HashMap<String, String> = archive
+		.file_names()
+		.filter_map(|name| {
+			let file_name = name.strip_prefix("originals/")?;
+			let hash = file_name.split('.').next()?;
+			Some((hash.to_string(), name.to_string()))
+		})
+		.collect();
+
+	let thumbnail_dir = get_thumbnail_dir(&app)?;
+	let extract_dir = options.extract_originals_to.clone().map(PathBuf::from);
+	let files = snapshot.files.clone();
+	let app_for_task = app.clone();
+
+	// Extract thumbnails and (optionally) originals before touching the database
+	let (archive, thumbnails_restored, original_overrides) =
+		tokio::task::spawn_blocking(move || -> Result<ExtractedFiles, AppError> {
+			let app = app_for_task;
+			let mut thumbnails_restored = 0;
+			let mut original_overrides = HashMap::new();
+			let total = files.len();
+
+			for (index, file) in files.iter().enumerate() {
+				let thumbnail_name = format!("thumbnails/{}.webp", file.file_hash);
+				let thumbnail_path = thumbnail_dir.join(format!("{}.webp", file.file_hash));
+				if !thumbnail_path.exists() && archive.by_name(&thumbnail_name).is_ok() {
+					extract_zip_entry(&mut archive, &thumbnail_name, &thumbnail_path)?;
+					thumbnails_restored += 1;
+				}
+
+				if let (Some(dir), Some(entry)) =
+					(&extract_dir, original_entries.get(&file.file_hash))
+				{
+					let file_name = Path::new(&file.original_path)
+						.file_name()
+						.map(|n| n.to_string_lossy().to_string())
+						.unwrap_or_else(|| entry.trim_start_matches("originals/").to_string());
+					let (target, already_present) =
+						original_target(dir, &file_name, &file.file_hash)?;
+					if !already_present {
+						extract_zip_entry(&mut archive, entry, &target)?;
+					}
+					original_overrides.insert(file.file_hash.clone(), target.display().to_string());
+				}
+
+				if index % 50 == 0 || index + 1 == total {
+					app.emit(
+						"library_import_progress",
+						ProgressEvent {
+							stage: "extracting_files".to_string(),
+							message: format!("Extracting file {} of {total}", index + 1),
+							file_hash: Some(file.file_hash.clone()),
+							current: Some(index + 1),
+							total: Some(total),
+						},
+					)
+					.ok();
+				}
+			}
+
+			Ok((archive, thumbnails_restored, original_overrides))
+		})
+		.await
+		.map_err(|e| AppError::Custom(format!("Task join error: {e}")))??;
+	let mut archive = archive;
+	summary.thumbnails_restored = thumbnails_restored;
+	summary.originals_extracted = original_overrides.len();
+
+	emit_progress(
+		&app,
+		"library_import_progress",
+		"merging",
+		format!("Merging {} files into library...", snapshot.files.len()),
+	);
+
+	let since_tag_id = super::category_rules::last_tag_id(&pool).await?;
+	let new_tags = merge_snapshot(
+		&pool,
+		&snapshot,
+		&options,
+		&original_overrides,
+		&mut summary,
+	)
+	.await?;
+	super::category_rules::categorize_new_tags(&pool, since_tag_id, &new_tags).await?;
+
+	if options.import_translations.unwrap_or(false) && manifest.includes_translations {
+		let target = super::settings::get_translations_dir(app.clone())?.join("translations.csv");
+		extract_zip_entry(&mut archive, "translations/translations.csv", &target)?;
+		summary.translations_imported = true;
+	}
+
+	if options.import_settings.unwrap_or(false) && manifest.includes_settings {
+		for name in SETTINGS_STORES {
+			let Ok(bytes) = read_zip_entry(&mut archive, &format!("settings/{name}")) else {
+				continue;
+			};
+			let Ok(serde_json::Value::Object(entries)) = serde_json::from_slice(&bytes) else {
+				continue;
+			};
+			// The archive's store replaces the local one, so keys it lacks don't linger
+			let store = app.store(*name)?;
+			store.clear();
+			for (key, value) in entries {
+				store.set(key, value);
+			}
+			store.save()?;
+		}
+		summary.settings_imported = true;
+		summary.restart_required = true;
+	}
+
+	emit_progress(
+		&app,
+		"library_import_progress",
+		"complete",
+		format!(
+			"Imported {} new files ({} already in library)",
+			summary.files_added, summary.files_existing
+		),
+	);
+
+	Ok(summary)
+}
@@ -0,0 +1,236 @@
+use crate::db::backup::{self, BackupInfo, BackupKind, RetentionPolicy};
+use crate::db::{self, DbPool};
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::path::Path;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter, Manager};
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupSettings {
+	/// Take scheduled backups in the background
+	pub schedule_enabled: bool,
+	/// Minimum hours between scheduled backups
+	pub interval_hours: u32,
+	/// Keep at most this many automatic backups (0 = unlimited)
+	pub max_backups: u32,
+	/// Delete automatic backups older than this many days (None = keep forever)
+	pub max_age_days: Option<u32>,
+}
+
+impl Default for BackupSettings {
+	fn default() -> Self {
+		Self {
+			schedule_enabled: true,
+			interval_hours: 24,
+			max_backups: 10,
+			max_age_days: None,
+		}
+	}
+}
+
+impl BackupSettings {
+	fn retention(&self) -> RetentionPolicy {
+		RetentionPolicy {
+			max_backups: self.max_backups,
+			max_age_days: self.max_age_days,
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RestoreResult {
+	pub restored_from: String,
+	pub safety_backup: String,
+}
+
+/// How often the scheduler wakes up to check whether a backup is due
+const SCHEDULER_TICK: Duration = Duration::from_secs(15 * 60);
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn load_backup_settings(app: &AppHandle) -> Result<BackupSettings, AppError> {
+	let store = app.store("backup-settings.json")?;
+	match store.get("backup_settings") {
+		Some(value) => serde_json::from_value(value)
+			.map_err(|e| AppError::Custom(format!("Failed to parse backup settings: {e}"))),
+		None => Ok(BackupSettings::default()),
+	}
+}
+
+/// Background loop that takes scheduled backups and applies retention
+/// Settings are re-read on every tick so changes apply without restarting
+pub async fn run_backup_scheduler(app: AppHandle) {
+	let mut ticker = tokio::time::interval(SCHEDULER_TICK);
+
+	loop {
+		ticker.tick().await;
+
+		let settings = match load_backup_settings(&app) {
+			Ok(settings) => settings,
+			Err(e) => {
+				eprintln!("Backup scheduler: failed to load settings: {e}");
+				continue;
+			}
+		};
+
+		if !settings.schedule_enabled || settings.interval_hours == 0 {
+			continue;
+		}
+
+		let db = app.state::<DbPool>();
+		let backup_dir = backup::backup_dir_for(db.db_path());
+
+		let last_scheduled = backup::list_backups(&backup_dir)
+			.unwrap_or_default()
+			.into_iter()
+			.find(|b| b.kind == BackupKind::Scheduled)
+			.map(|b| b.created_at);
+
+		let now = SystemTime::now()
+			.duration_since(UNIX_EPOCH)
+			.unwrap_or(Duration::ZERO)
+			.as_secs() as i64;
+		let interval_secs = settings.interval_hours as i64 * 60 * 60;
+
+		if last_scheduled.is_some_and(|last| now - last < interval_secs) {
+			continue;
+		}
+
+		match backup::create_backup(&db.get(), BackupKind::Scheduled).await {
+			Ok(info) => {
+				eprintln!("Scheduled backup created: {}", info.file_name);
+				app.emit("backup_created", &info).ok();
+			}
+			Err(e) => {
+				eprintln!("Scheduled backup failed: {e}");
+				continue;
+			}
+		}
+
+		if let Err(e) = backup::prune_backups(&backup_dir, settings.retention()) {
+			eprintln!("Failed to prune old backups: {e}");
+		}
+	}
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_backup_settings(app: AppHandle) -> Result<BackupSettings, AppError> {
+	load_backup_settings(&app)
+}
+
+#[tauri::command]
+pub async fn set_backup_settings(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	settings: BackupSettings,
+) -> Result<(), AppError> {
+	let value = serde_json::to_value(&settings)
+		.map_err(|e| AppError::Custom(format!("Failed to serialize backup settings: {e}")))?;
+
+	let store = app.store("backup-settings.json")?;
+	store.set("backup_settings", value);
+	store.save()?;
+
+	// Apply tightened retention right away
+	backup::prune_backups(&backup::backup_dir_for(db.db_path()), settings.retention())?;
+
+	Ok(())
+}
+
+/// Take an on-demand backup of the database
+#[tauri::command]
+pub async fn create_database_backup(db: tauri::State<'_, DbPool>) -> Result<BackupInfo, AppError> {
+	backup::create_backup(&db.get(), BackupKind::Manual).await
+}
+
+/// List all backups, newest first
+#[tauri::command]
+pub async fn list_database_backups(
+	db: tauri::State<'_, DbPool>,
+) -> Result<Vec<BackupInfo>, AppError> {
+	backup::list_backups(&backup::backup_dir_for(db.db_path()))
+}
+
+#[tauri::command]
+pub async fn delete_database_backup(
+	db: tauri::State<'_, DbPool>,
+	file_name: String,
+) -> Result<(), AppError> {
+	let backup_dir = backup::backup_dir_for(db.db_path());
+	let path = backup::resolve_backup_path(&backup_dir, &file_name)?;
+	std::fs::remove_file(path)?;
+	Ok(())
+}
+
+/// Restore the database from a backup without restarting the app
+///
+/// 1. Verify the backup with `PRAGMA integrity_check`
+/// 2. Snapshot the current database as a pre-restore backup
+/// 3. Migrate a staged copy of the backup to the current schema
+/// 4. Close the pool, swap the staged file in and reopen
+///
+/// The slow steps run while the current pool keeps serving queries; it is only closed for the
+/// file swap, because the file can't be replaced under open connections. If the swap or reopen
+/// fails, the pre-restore backup is put back and reopened so the app stays usable, and the
+/// error is returned.
+#[tauri::command]
+pub async fn restore_database_backup(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	file_name: String,
+) -> Result<RestoreResult, AppError> {
+	let db_path = db.db_path().to_path_buf();
+	let backup_dir = backup::backup_dir_for(&db_path);
+	let backup_path = backup::resolve_backup_path(&backup_dir, &file_name)?;
+
+	backup::verify_backup(&backup_path).await?;
+
+	let safety_backup = backup::create_backup(&db.get(), BackupKind::PreRestore).await?;
+
+	// Backups may predate the current schema
+	let staged_path =
+		backup::stage_restore(&backup_path, &db_path, &sqlx::migrate!("../migrations")).await?;
+
+	let install_result = install_database(&db, &staged_path).await;
+	std::fs::remove_file(&staged_path).ok();
+	if let Err(e) = install_result {
+		eprintln!(
+			"Restore failed, putting back {}: {e}",
+			safety_backup.file_name
+		);
+		install_database(&db, Path::new(&safety_backup.file_path)).await?;
+		return Err(e);
+	}
+
+	app.emit("database_restored", &file_name).ok();
+
+	Ok(RestoreResult {
+		restored_from: file_name,
+		safety_backup: safety_backup.file_name,
+	})
+}
+
+/// Close the current pool, swap `source` in as the database file and install a pool on it
+async fn install_database(db: &DbPool, source: &Path) -> Result<(), AppError> {
+	// Wait for in-flight queries to finish and release the file
+	db.get().close().await;
+
+	let swap_result = backup::swap_database_file(source, db.db_path());
+
+	// Whatever is on disk is reopened even if the swap failed
+	let pool = db::init_pool(db.db_path()).await?;
+	db.replace(pool);
+	swap_result
+}
@@ -1,6 +1,6 @@
+use crate::db::DbPool;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
 
 // ============================================================================
 // Types
@@ -36,8 +36,9 @@ pub struct UpdateCategoryRequest {
 /// Get all tag categories
 #[tauri::command]
 pub async fn get_all_categories(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 ) -> Result<Vec<TagCategory>, AppError> {
+	let pool = db.get();
 	let categories = sqlx::query_as!(
 		TagCategory,
 		r#"
@@ -51,7 +52,7 @@ pub async fn get_all_categories(
         ORDER BY sort_order ASC, name ASC
         "#
 	)
-	.fetch_all(pool.inner())
+	.fetch_all(&pool)
 	.await?;
 
 	Ok(categories)
@@ -60,9 +61,10 @@ pub async fn get_all_categories(
 /// Get a single category by ID
 #[tauri::command]
 pub async fn get_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	category_id: i64,
 ) -> Result<Option<TagCategory>, AppError> {
+	let pool = db.get();
 	let category = sqlx::query_as!(
 		TagCategory,
 		r#"
@@ -77,7 +79,7 @@ pub async fn get_category(
         "#,
 		category_id
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	Ok(category)
@@ -86,9 +88,10 @@ pub async fn get_category(
 /// Create a new custom category
 #[tauri::command]
 pub async fn create_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	request: CreateCategoryRequest,
 ) -> Result<i64, AppError> {
+	let pool = db.get();
 	// Validate name is not empty
 	if request.name.trim().is_empty() {
 		return Err(AppError::Custom(
@@ -107,7 +110,7 @@ pub async fn create_category(
 	// Get max sort_order to append new category at the end
 	let max_sort_order: Option<i64> =
 		sqlx::query_scalar!("SELECT MAX(sort_order) FROM TagCategories")
-			.fetch_optional(pool.inner())
+			.fetch_optional(&pool)
 			.await?
 			.flatten();
 
@@ -127,7 +130,7 @@ pub async fn create_category(
 		color_code,
 		sort_order
 	)
-	.fetch_one(pool.inner())
+	.fetch_one(&pool)
 	.await?;
 
 	Ok(category.category_id)
@@ -136,16 +139,17 @@ pub async fn create_category(
 /// Update an existing category
 #[tauri::command]
 pub async fn update_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	category_id: i64,
 	request: UpdateCategoryRequest,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Check if category exists and is not builtin
 	let category = sqlx::query!(
 		"SELECT is_builtin FROM TagCategories WHERE category_id = ?",
 		category_id
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	let category = category
@@ -209,7 +213,7 @@ pub async fn update_category(
 	}
 	query_builder = query_builder.bind(category_id);
 
-	query_builder.execute(pool.inner()).await?;
+	query_builder.execute(&pool).await?;
 
 	Ok(())
 }
@@ -217,15 +221,16 @@ pub async fn update_category(
 /// Delete a category (only custom categories can be deleted)
 #[tauri::command]
 pub async fn delete_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	category_id: i64,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Check if category exists and is not builtin
 	let category = sqlx::query!(
 		"SELECT is_builtin FROM TagCategories WHERE category_id = ?",
 		category_id
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	let category = category
@@ -242,7 +247,7 @@ pub async fn delete_category(
 		"SELECT COUNT(*) FROM Tags WHERE category_id = ?",
 		category_id
 	)
-	.fetch_one(pool.inner())
+	.fetch_one(&pool)
 	.await?;
 
 	if tag_count > 0 {
@@ -256,7 +261,7 @@ pub async fn delete_category(
 		"DELETE FROM TagCategories WHERE category_id = ?",
 		category_id
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
 
 	Ok(())
@@ -265,9 +270,10 @@ pub async fn delete_category(
 /// Reorder categories by updating sort_order
 #[tauri::command]
 pub async fn reorder_categories(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	category_ids: Vec<i64>,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Update sort_order for each category based on its position in the array
 	for (index, category_id) in category_ids.iter().enumerate() {
 		let sort_order = index as i64;
@@ -276,7 +282,7 @@ pub async fn reorder_categories(
 			sort_order,
 			category_id
 		)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 	}
 
@@ -286,16 +292,17 @@ pub async fn reorder_categories(
 /// Assign a tag to a category
 #[tauri::command]
 pub async fn assign_tag_to_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	tag_id: i64,
 	category_id: i64,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Verify category exists
 	let category = sqlx::query!(
 		"SELECT category_id FROM TagCategories WHERE category_id = ?",
 		category_id
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	if category.is_none() {
@@ -310,7 +317,7 @@ pub async fn assign_tag_to_category(
 		category_id,
 		tag_id
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
 
 	Ok(())
@@ -319,16 +326,17 @@ pub async fn assign_tag_to_category(
 /// Bulk assign tags to a category
 #[tauri::command]
 pub async fn bulk_assign_tags_to_category(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	tag_ids: Vec<i64>,
 	category_id: i64,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	// Verify category exists
 	let category = sqlx::query!(
 		"SELECT category_id FROM TagCategories WHERE category_id = ?",
 		category_id
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	if category.is_none() {
@@ -347,7 +355,7 @@ pub async fn bulk_assign_tags_to_category(
 		query_builder = query_builder.bind(tag_id);
 	}
 
-	let result = query_builder.execute(pool.inner()).await?;
+	let result = query_builder.execute(&pool).await?;
 
 	Ok(result.rows_affected() as usize)
 }
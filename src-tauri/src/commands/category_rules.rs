@@ -0,0 +1,393 @@
+use crate::db::DbPool;
+use crate::error::AppError;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::collections::HashMap;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Puts tags into a category; conditions that are set must all match
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct CategoryRule {
+	pub rule_id: i64,
+	pub category_id: i64,
+	/// Regex matched anywhere in the tag name; anchor it (`^...$`) to match the whole name
+	pub name_pattern: Option<String>,
+	/// Label map category of the AI model (0 general, 4 character, 9 rating)
+	pub model_category: Option<i64>,
+	/// Tag type (`general`, `character`, `artist`, `series`, `rating`)
+	pub tag_type: Option<String>,
+	/// Lower is tried first
+	pub priority: i64,
+	pub enabled: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CategoryRuleRequest {
+	pub category_id: i64,
+	pub name_pattern: Option<String>,
+	pub model_category: Option<i64>,
+	pub tag_type: Option<String>,
+	pub priority: Option<i64>,
+	pub enabled: Option<bool>,
+}
+
+/// A category change made (or, in a dry run, that would be made) by a rule
+#[derive(Debug, Serialize, Clone)]
+pub struct CategoryAssignment {
+	pub tag_id: i64,
+	pub tag_name: String,
+	pub from_category_id: i64,
+	pub to_category_id: i64,
+	pub rule_id: i64,
+}
+
+/// A tag as seen by the rules
+struct RuleSubject<'a> {
+	name: &'a str,
+	tag_type: &'a str,
+	model_category: Option<u32>,
+}
+
+struct CompiledRule {
+	rule: CategoryRule,
+	name_regex: Option<Regex>,
+}
+
+const RULE_COLUMNS: &str =
+	"rule_id, category_id, name_pattern, model_category, tag_type, priority, enabled";
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn rule_from_row(row: &SqliteRow) -> CategoryRule {
+	CategoryRule {
+		rule_id: row.get("rule_id"),
+		category_id: row.get("category_id"),
+		name_pattern: row.get("name_pattern"),
+		model_category: row.get("model_category"),
+		tag_type: row.get("tag_type"),
+		priority: row.get("priority"),
+		enabled: row.get("enabled"),
+	}
+}
+
+impl CompiledRule {
+	fn matches(&self, subject: &RuleSubject) -> bool {
+		self.name_regex
+			.as_ref()
+			.map_or(true, |regex| regex.is_match(subject.name))
+			&& self.rule.model_category.map_or(true, |category| {
+				subject.model_category.map(i64::from) == Some(category)
+			}) && self
+			.rule
+			.tag_type
+			.as_deref()
+			.map_or(true, |tag_type| tag_type == subject.tag_type)
+	}
+}
+
+fn first_match<'a>(rules: &'a [CompiledRule], subject: &RuleSubject) -> Option<&'a CategoryRule> {
+	rules
+		.iter()
+		.find(|rule| rule.matches(subject))
+		.map(|rule| &rule.rule)
+}
+
+/// Enabled rules in the order they are tried
+async fn load_rules(pool: &SqlitePool) -> Result<Vec<CompiledRule>, AppError> {
+	let query = format!(
+		"SELECT {RULE_COLUMNS} FROM CategoryRules WHERE enabled = 1 ORDER BY priority, rule_id"
+	);
+	let rows = sqlx::query(&query).fetch_all(pool).await?;
+
+	let mut rules = Vec::new();
+	for row in &rows {
+		let rule = rule_from_row(row);
+		// Patterns are checked when saved, so this only skips rules edited by hand
+		let name_regex = match rule.name_pattern.as_deref().map(Regex::new).transpose() {
+			Ok(regex) => regex,
+			Err(e) => {
+				eprintln!("Skipping category rule {}: {e}", rule.rule_id);
+				continue;
+			}
+		};
+		rules.push(CompiledRule { rule, name_regex });
+	}
+	Ok(rules)
+}
+
+/// Check a rule request and return its trimmed pattern and tag type
+async fn validate_request(
+	pool: &SqlitePool,
+	request: &CategoryRuleRequest,
+) -> Result<(Option<String>, Option<String>), AppError> {
+	let name_pattern = request
+		.name_pattern
+		.as_deref()
+		.map(str::trim)
+		.filter(|p| !p.is_empty())
+		.map(str::to_string);
+	let tag_type = request
+		.tag_type
+		.as_deref()
+		.map(str::trim)
+		.filter(|t| !t.is_empty())
+		.map(str::to_lowercase);
+
+	if name_pattern.is_none() && request.model_category.is_none() && tag_type.is_none() {
+		return Err(AppError::Custom(
+			"A rule needs a name pattern, model category or tag type".to_string(),
+		));
+	}
+	if let Some(pattern) = &name_pattern {
+		Regex::new(pattern).map_err(|e| AppError::Custom(format!("Invalid name pattern: {e}")))?;
+	}
+
+	let exists: bool =
+		sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM TagCategories WHERE category_id = ?)")
+			.bind(request.category_id)
+			.fetch_one(pool)
+			.await?;
+	if !exists {
+		return Err(AppError::Custom(format!(
+			"Category with id {} not found",
+			request.category_id
+		)));
+	}
+
+	Ok((name_pattern, tag_type))
+}
+
+/// Largest tag_id, to find the tags an import or AI run creates after it
+pub(crate) async fn last_tag_id(pool: &SqlitePool) -> Result<i64, AppError> {
+	Ok(
+		sqlx::query_scalar("SELECT COALESCE(MAX(tag_id), 0) FROM Tags")
+			.fetch_one(pool)
+			.await?,
+	)
+}
+
+/// Put tags created after `since_tag_id` into the category of their first matching rule
+///
+/// `tags` are the names the caller created, with their model category when they come from the
+/// AI tagger; other tags created meanwhile are left to whoever created them. Returns the number
+/// of tags moved.
+pub(crate) async fn categorize_new_tags(
+	pool: &SqlitePool,
+	since_tag_id: i64,
+	tags: &[(String, Option<u32>)],
+) -> Result<usize, AppError> {
+	let rules = load_rules(pool).await?;
+	if rules.is_empty() || tags.is_empty() {
+		return Ok(0);
+	}
+	let model_categories: HashMap<&str, Option<u32>> = tags
+		.iter()
+		.map(|(name, category)| (name.as_str(), *category))
+		.collect();
+
+	let rows = sqlx::query("SELECT tag_id, name, type, category_id FROM Tags WHERE tag_id > ?")
+		.bind(since_tag_id)
+		.fetch_all(pool)
+		.await?;
+
+	let mut moved = 0;
+	for row in &rows {
+		let name: String = row.get("name");
+		let Some(&model_category) = model_categories.get(name.as_str()) else {
+			continue;
+		};
+		let tag_type: String = row.get("type");
+		let subject = RuleSubject {
+			name: &name,
+			tag_type: &tag_type,
+			model_category,
+		};
+		if let Some(rule) = first_match(&rules, &subject) {
+			if rule.category_id != row.get::<i64, _>("category_id") {
+				sqlx::query("UPDATE Tags SET category_id = ? WHERE tag_id = ?")
+					.bind(rule.category_id)
+					.bind(row.get::<i64, _>("tag_id"))
+					.execute(pool)
+					.await?;
+				moved += 1;
+			}
+		}
+	}
+
+	Ok(moved)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// All category rules, in the order they are tried
+#[tauri::command]
+pub async fn get_category_rules(
+	db: tauri::State<'_, DbPool>,
+) -> Result<Vec<CategoryRule>, AppError> {
+	let pool = db.get();
+	let query = format!("SELECT {RULE_COLUMNS} FROM CategoryRules ORDER BY priority, rule_id");
+	let rules = sqlx::query(&query)
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(rule_from_row)
+		.collect();
+
+	Ok(rules)
+}
+
+/// Create a rule; without a priority it is tried after the existing rules
+#[tauri::command]
+pub async fn create_category_rule(
+	db: tauri::State<'_, DbPool>,
+	request: CategoryRuleRequest,
+) -> Result<i64, AppError> {
+	let pool = db.get();
+	let (name_pattern, tag_type) = validate_request(&pool, &request).await?;
+
+	let priority = match request.priority {
+		Some(priority) => priority,
+		None => {
+			sqlx::query_scalar("SELECT COALESCE(MAX(priority) + 1, 0) FROM CategoryRules")
+				.fetch_one(&pool)
+				.await?
+		}
+	};
+
+	let rule_id: i64 = sqlx::query_scalar(
+		r#"
+        INSERT INTO CategoryRules (category_id, name_pattern, model_category, tag_type, priority, enabled)
+        VALUES (?, ?, ?, ?, ?, ?)
+        RETURNING rule_id
+        "#,
+	)
+	.bind(request.category_id)
+	.bind(name_pattern)
+	.bind(request.model_category)
+	.bind(tag_type)
+	.bind(priority)
+	.bind(request.enabled.unwrap_or(true))
+	.fetch_one(&pool)
+	.await?;
+
+	Ok(rule_id)
+}
+
+/// Replace a rule's category and conditions; priority and enabled are kept when omitted
+#[tauri::command]
+pub async fn update_category_rule(
+	db: tauri::State<'_, DbPool>,
+	rule_id: i64,
+	request: CategoryRuleRequest,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let (name_pattern, tag_type) = validate_request(&pool, &request).await?;
+
+	let result = sqlx::query(
+		r#"
+        UPDATE CategoryRules
+        SET category_id = ?, name_pattern = ?, model_category = ?, tag_type = ?,
+            priority = COALESCE(?, priority), enabled = COALESCE(?, enabled)
+        WHERE rule_id = ?
+        "#,
+	)
+	.bind(request.category_id)
+	.bind(name_pattern)
+	.bind(request.model_category)
+	.bind(tag_type)
+	.bind(request.priority)
+	.bind(request.enabled)
+	.bind(rule_id)
+	.execute(&pool)
+	.await?;
+
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!(
+			"Category rule with id {rule_id} not found"
+		)));
+	}
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn delete_category_rule(
+	db: tauri::State<'_, DbPool>,
+	rule_id: i64,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	sqlx::query("DELETE FROM CategoryRules WHERE rule_id = ?")
+		.bind(rule_id)
+		.execute(&pool)
+		.await?;
+
+	Ok(())
+}
+
+/// Run the rules over every tag; with `dry_run`, only list the changes they would make
+///
+/// Model categories come from the loaded label map, so rules on them only match tags the
+/// current model knows. Tags no rule matches keep their category.
+#[tauri::command]
+pub async fn apply_category_rules(
+	db: tauri::State<'_, DbPool>,
+	dry_run: bool,
+) -> Result<Vec<CategoryAssignment>, AppError> {
+	let pool = db.get();
+	let rules = load_rules(&pool).await?;
+	if rules.is_empty() {
+		return Ok(Vec::new());
+	}
+	let model_categories = if rules.iter().any(|rule| rule.rule.model_category.is_some()) {
+		crate::ai::tagger::label_categories()
+	} else {
+		HashMap::new()
+	};
+
+	let rows = sqlx::query("SELECT tag_id, name, type, category_id FROM Tags ORDER BY name")
+		.fetch_all(&pool)
+		.await?;
+	let assignments: Vec<CategoryAssignment> = rows
+		.iter()
+		.filter_map(|row| {
+			let name: String = row.get("name");
+			let tag_type: String = row.get("type");
+			let from_category_id: i64 = row.get("category_id");
+			let subject = RuleSubject {
+				name: &name,
+				tag_type: &tag_type,
+				model_category: model_categories.get(&name).copied(),
+			};
+			let rule = first_match(&rules, &subject)?;
+			(rule.category_id != from_category_id).then(|| CategoryAssignment {
+				tag_id: row.get("tag_id"),
+				tag_name: name.clone(),
+				from_category_id,
+				to_category_id: rule.category_id,
+				rule_id: rule.rule_id,
+			})
+		})
+		.collect();
+
+	if !dry_run {
+		let mut tx = pool.begin().await?;
+		for assignment in &assignments {
+			sqlx::query("UPDATE Tags SET category_id = ? WHERE tag_id = ?")
+				.bind(assignment.to_category_id)
+				.bind(assignment.tag_id)
+				.execute(&mut *tx)
+				.await?;
+		}
+		tx.commit().await?;
+	}
+
+	Ok(assignments)
+}
@@ -13,7 +13,7 @@ pub struct PreprocessImages {
 
 /// 生成预处理各阶段的图片数据，用于前端可视化
 pub fn generate_preprocess_visualization(path: &Path) -> Result<PreprocessImages, AppError> {
-	let original_img = image::open(path)?;
+	let original_img = crate::metadata::open_image_oriented(path)?;
 
 	// 生成原始图片的base64数据
 	let original_base64 = image_to_base64(&original_img, ImageFormat::Jpeg, 90)?;
@@ -1,12 +1,14 @@
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::Row;
 
 // ============================================================================
 // Types
 // ============================================================================
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct Favorite {
 	pub favorite_id: i64,
 	pub file_hash: String,
@@ -21,12 +23,13 @@ pub struct Favorite {
 /// Returns the new favorite status (true if added, false if removed)
 #[tauri::command]
 pub async fn toggle_favorite(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 ) -> Result<bool, AppError> {
+	let pool = db.get();
 	// Check if file exists
 	let file = sqlx::query!("SELECT file_hash FROM Files WHERE file_hash = ?", file_hash)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 	if file.is_none() {
@@ -38,19 +41,19 @@ pub async fn toggle_favorite(
 		"SELECT favorite_id FROM Favorites WHERE file_hash = ?",
 		file_hash
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	if existing.is_some() {
 		// Remove favorite
 		sqlx::query!("DELETE FROM Favorites WHERE file_hash = ?", file_hash)
-			.execute(pool.inner())
+			.execute(&pool)
 			.await?;
 		Ok(false)
 	} else {
 		// Add favorite
 		sqlx::query!("INSERT INTO Favorites (file_hash) VALUES (?)", file_hash)
-			.execute(pool.inner())
+			.execute(&pool)
 			.await?;
 		Ok(true)
 	}
@@ -59,14 +62,15 @@ pub async fn toggle_favorite(
 /// Get favorite status for a single file
 #[tauri::command]
 pub async fn get_favorite_status(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 ) -> Result<bool, AppError> {
+	let pool = db.get();
 	let favorite = sqlx::query!(
 		"SELECT favorite_id FROM Favorites WHERE file_hash = ?",
 		file_hash
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?;
 
 	Ok(favorite.is_some())
@@ -75,9 +79,10 @@ pub async fn get_favorite_status(
 /// Get favorite status for multiple files
 #[tauri::command]
 pub async fn get_favorite_statuses(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 ) -> Result<std::collections::HashMap<String, bool>, AppError> {
+	let pool = db.get();
 	if file_hashes.is_empty() {
 		return Ok(std::collections::HashMap::new());
 	}
@@ -96,7 +101,7 @@ pub async fn get_favorite_statuses(
 	}
 
 	let favorites = query_builder
-		.fetch_all(pool.inner())
+		.fetch_all(&pool)
 		.await?
 		.into_iter()
 		.map(|row| row.get::<String, _>("file_hash"))
@@ -111,30 +116,32 @@ pub async fn get_favorite_statuses(
 	Ok(result)
 }
 
-/// Get all favorites
+/// Get all favorites, except files hidden by safe mode
 #[tauri::command]
-pub async fn get_all_favorites(
-	pool: tauri::State<'_, SqlitePool>,
-) -> Result<Vec<Favorite>, AppError> {
-	let rows = sqlx::query!(
+pub async fn get_all_favorites(db: tauri::State<'_, DbPool>) -> Result<Vec<Favorite>, AppError> {
+	let pool = db.get();
+	let query = format!(
 		r#"
-        SELECT 
+        SELECT
             favorite_id,
             file_hash,
             datetime(created_at, 'localtime') as created_at
         FROM Favorites
+        WHERE {}
         ORDER BY created_at DESC
-        "#
-	)
-	.fetch_all(pool.inner())
-	.await?;
+        "#,
+		visible_files_condition("Favorites.file_hash")
+	);
+	let rows = sqlx::query(&query).fetch_all(&pool).await?;
 
 	let favorites = rows
-		.into_iter()
+		.iter()
 		.map(|row| Favorite {
-			favorite_id: row.favorite_id,
-			file_hash: row.file_hash,
-			created_at: row.created_at.unwrap_or_default(),
+			favorite_id: row.get("favorite_id"),
+			file_hash: row.get("file_hash"),
+			created_at: row
+				.get::<Option<String>, _>("created_at")
+				.unwrap_or_default(),
 		})
 		.collect();
 
@@ -144,9 +151,10 @@ pub async fn get_all_favorites(
 /// Add multiple files to favorites
 #[tauri::command]
 pub async fn add_favorites(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	if file_hashes.is_empty() {
 		return Ok(0);
 	}
@@ -156,7 +164,7 @@ pub async fn add_favorites(
 	for file_hash in file_hashes {
 		// Check if file exists
 		let file = sqlx::query!("SELECT file_hash FROM Files WHERE file_hash = ?", file_hash)
-			.fetch_optional(pool.inner())
+			.fetch_optional(&pool)
 			.await?;
 
 		if file.is_none() {
@@ -168,7 +176,7 @@ pub async fn add_favorites(
 			"INSERT OR IGNORE INTO Favorites (file_hash) VALUES (?)",
 			file_hash
 		)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 		if result.rows_affected() > 0 {
@@ -182,9 +190,10 @@ pub async fn add_favorites(
 /// Remove multiple files from favorites
 #[tauri::command]
 pub async fn remove_favorites(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	if file_hashes.is_empty() {
 		return Ok(0);
 	}
@@ -202,17 +211,20 @@ pub async fn remove_favorites(
 		query_builder = query_builder.bind(file_hash);
 	}
 
-	let result = query_builder.execute(pool.inner()).await?;
+	let result = query_builder.execute(&pool).await?;
 
 	Ok(result.rows_affected() as usize)
 }
 
-/// Get count of favorites
+/// Get count of favorites, except files hidden by safe mode
 #[tauri::command]
-pub async fn get_favorite_count(pool: tauri::State<'_, SqlitePool>) -> Result<i64, AppError> {
-	let count: i64 = sqlx::query_scalar!("SELECT COUNT(*) FROM Favorites")
-		.fetch_one(pool.inner())
-		.await?;
+pub async fn get_favorite_count(db: tauri::State<'_, DbPool>) -> Result<i64, AppError> {
+	let pool = db.get();
+	let query = format!(
+		"SELECT COUNT(*) FROM Favorites WHERE {}",
+		visible_files_condition("Favorites.file_hash")
+	);
+	let count: i64 = sqlx::query_scalar(&query).fetch_one(&pool).await?;
 
 	Ok(count)
 }
@@ -0,0 +1,383 @@
+use super::files::ProgressEvent;
+use super::metadata::keyword_to_tag_name;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::filename_patterns::{
+	builtin_pattern_names, FilenameMatch, FilenameParser, UserPatternDefinition,
+};
+use crate::metadata::keys;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::path::Path;
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FilenamePatternSettings {
+	/// Parse filenames at import
+	pub enabled: bool,
+	/// Create `artist`/`character` tags from parsed names
+	pub create_tags: bool,
+	/// Tried before the built-in patterns
+	pub user_patterns: Vec<UserPatternDefinition>,
+}
+
+impl Default for FilenamePatternSettings {
+	fn default() -> Self {
+		Self {
+			enabled: true,
+			create_tags: true,
+			user_patterns: Vec::new(),
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct FilenameParseResult {
+	pub processed: usize,
+	pub matched: usize,
+	pub tags_added: usize,
+}
+
+// Keys of filename-derived entries in FileMetadata
+const KEY_PATTERN: &str = "pattern";
+const KEY_SITE: &str = "site";
+const KEY_POST_ID: &str = "post_id";
+const KEY_PAGE: &str = "page";
+const KEY_ARTIST: &str = "artist";
+const KEY_CHARACTER: &str = "character";
+const KEY_SERIES: &str = "series";
+const KEY_MD5: &str = "md5";
+const KEY_SOURCE_URL: &str = "source_url";
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn load_filename_settings(app: &AppHandle) -> Result<FilenamePatternSettings, AppError> {
+	let store = app.store("filename-patterns.json")?;
+	match store.get("filename_pattern_settings") {
+		Some(value) => serde_json::from_value(value).map_err(|e| {
+			AppError::Custom(format!("Failed to parse filename pattern settings: {e}"))
+		}),
+		None => Ok(FilenamePatternSettings::default()),
+	}
+}
+
+fn build_parser(settings: &FilenamePatternSettings) -> Result<FilenameParser, AppError> {
+	FilenameParser::new(&settings.user_patterns).map_err(AppError::Custom)
+}
+
+/// Split Danbooru's `character_series` prefix using character/series tags we already know
+///
+/// Every underscore-delimited run of the prefix is a candidate name, looked up through the
+/// Tags name index instead of loading all character/series tags for each file.
+async fn resolve_tag_string(pool: &SqlitePool, parsed: &mut FilenameMatch) -> Result<(), AppError> {
+	let Some(tag_string) = parsed.tag_string.as_ref() else {
+		return Ok(());
+	};
+	let words: Vec<&str> = tag_string.split('_').collect();
+	let mut candidates = Vec::new();
+	for start in 0..words.len() {
+		for end in start + 1..=words.len() {
+			candidates.push(words[start..end].join("_"));
+		}
+	}
+	candidates.retain(|c| !c.is_empty());
+	candidates.sort();
+	candidates.dedup();
+	if candidates.is_empty() {
+		return Ok(());
+	}
+
+	let query = format!(
+		"SELECT name, type FROM Tags WHERE name IN ({}) AND type IN ('character', 'series') ORDER BY name",
+		vec!["?"; candidates.len()].join(", ")
+	);
+	let mut lookup = sqlx::query(&query);
+	for candidate in &candidates {
+		lookup = lookup.bind(candidate);
+	}
+	let known = lookup.fetch_all(pool).await?;
+
+	for row in known {
+		let name: String = row.get("name");
+		let target = if row.get::<String, _>("type") == "character" {
+			&mut parsed.characters
+		} else {
+			&mut parsed.series
+		};
+		if !target.contains(&name) {
+			target.push(name);
+		}
+	}
+
+	Ok(())
+}
+
+async fn tag_file(
+	pool: &SqlitePool,
+	file_hash: &str,
+	name: &str,
+	tag_type: &str,
+	category: &str,
+) -> Result<bool, AppError> {
+	let tag_name = keyword_to_tag_name(name);
+	if tag_name.is_empty() {
+		return Ok(false);
+	}
+
+	// Existing tags keep their type; new ones get the type and builtin category
+	let tag_id: i64 = sqlx::query_scalar(
+		r#"
+        INSERT INTO Tags (name, type, category_id)
+        VALUES (?, ?, COALESCE((SELECT category_id FROM TagCategories WHERE name = ?), 1))
+        ON CONFLICT(name) DO UPDATE SET name=name
+        RETURNING tag_id
+        "#,
+	)
+	.bind(&tag_name)
+	.bind(tag_type)
+	.bind(category)
+	.fetch_one(pool)
+	.await?;
+
+	let result = sqlx::query("INSERT OR IGNORE INTO FileTags (file_hash, tag_id) VALUES (?, ?)")
+		.bind(file_hash)
+		.bind(tag_id)
+		.execute(pool)
+		.await?;
+
+	Ok(result.rows_affected() > 0)
+}
+
+/// Store a filename match as metadata and optionally tag the file
+/// Returns the number of tags added to the file
+async fn apply_filename_match(
+	pool: &SqlitePool,
+	file_hash: &str,
+	parsed: &FilenameMatch,
+	create_tags: bool,
+) -> Result<usize, AppError> {
+	let mut entries: Vec<(&str, String)> = vec![(KEY_PATTERN, parsed.pattern.clone())];
+	entries.extend(parsed.site.clone().map(|v| (KEY_SITE, v)));
+	entries.extend(parsed.post_id.clone().map(|v| (KEY_POST_ID, v)));
+	entries.extend(parsed.page.map(|v| (KEY_PAGE, v.to_string())));
+	entries.extend(parsed.artists.iter().map(|v| (KEY_ARTIST, v.clone())));
+	entries.extend(parsed.characters.iter().map(|v| (KEY_CHARACTER, v.clone())));
+	entries.extend(parsed.series.iter().map(|v| (KEY_SERIES, v.clone())));
+	entries.extend(parsed.md5.clone().map(|v| (KEY_MD5, v)));
+	entries.extend(parsed.source_url.clone().map(|v| (KEY_SOURCE_URL, v)));
+
+	let mut tx = pool.begin().await?;
+	sqlx::query("DELETE FROM FileMetadata WHERE file_hash = ? AND source = ?")
+		.bind(file_hash)
+		.bind(keys::SOURCE_FILENAME)
+		.execute(&mut *tx)
+		.await?;
+	for (key, value) in entries {
+		sqlx::query("INSERT INTO FileMetadata (file_hash, source, key, value) VALUES (?, ?, ?, ?)")
+			.bind(file_hash)
+			.bind(keys::SOURCE_FILENAME)
+			.bind(key)
+			.bind(value)
+			.execute(&mut *tx)
+			.await?;
+	}
+	tx.commit().await?;
+
+	// The filename's page is more precise than the post URL it builds
+	if let Some(url) = &parsed.source_url {
+		super::sources::add_source(pool, file_hash, url, parsed.page).await?;
+	}
+
+	// Pages of the same post (`_p0`, `_p1`, ...) form a work
+	if let (Some(site), Some(post_id), Some(page)) = (&parsed.site, &parsed.post_id, parsed.page) {
+		super::works::add_to_post_work(pool, file_hash, site, post_id, page).await?;
+	}
+
+	if !create_tags {
+		return Ok(0);
+	}
+
+	let since_tag_id = super::category_rules::last_tag_id(pool).await?;
+	let mut tags_added = 0;
+	for artist in &parsed.artists {
+		tags_added += tag_file(pool, file_hash, artist, "artist", "ARTIST").await? as usize;
+	}
+	for character in &parsed.characters {
+		tags_added +=
+			tag_file(pool, file_hash, character, "character", "CHARACTER").await? as usize;
+	}
+	for series in &parsed.series {
+		tags_added += tag_file(pool, file_hash, series, "series", "COPYRIGHT").await? as usize;
+	}
+
+	let names: Vec<(String, Option<u32>)> = parsed
+		.artists
+		.iter()
+		.chain(&parsed.characters)
+		.chain(&parsed.series)
+		.map(|name| (keyword_to_tag_name(name), None))
+		.collect();
+	super::category_rules::categorize_new_tags(pool, since_tag_id, &names).await?;
+
+	Ok(tags_added)
+}
+
+async fn parse_and_apply(
+	pool: &SqlitePool,
+	parser: &FilenameParser,
+	settings: &FilenamePatternSettings,
+	file_hash: &str,
+	path: &Path,
+) -> Result<Option<usize>, AppError> {
+	let Some(file_name) = path.file_name().and_then(|n| n.to_str()) else {
+		return Ok(None);
+	};
+	let Some(mut parsed) = parser.parse(file_name) else {
+		return Ok(None);
+	};
+
+	resolve_tag_string(pool, &mut parsed).await?;
+	apply_filename_match(pool, file_hash, &parsed, settings.create_tags)
+		.await
+		.map(Some)
+}
+
+/// Parse the filename of a freshly imported file
+pub async fn parse_filename_on_import(
+	app: &AppHandle,
+	pool: &SqlitePool,
+	file_hash: &str,
+	path: &Path,
+) -> Result<(), AppError> {
+	let settings = load_filename_settings(app)?;
+	if !settings.enabled {
+		return Ok(());
+	}
+	let parser = build_parser(&settings)?;
+	parse_and_apply(pool, &parser, &settings, file_hash, path).await?;
+	Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_filename_pattern_settings(
+	app: AppHandle,
+) -> Result<FilenamePatternSettings, AppError> {
+	load_filename_settings(&app)
+}
+
+/// Save filename settings; user regexes are validated before anything is stored
+#[tauri::command]
+pub async fn set_filename_pattern_settings(
+	app: AppHandle,
+	settings: FilenamePatternSettings,
+) -> Result<(), AppError> {
+	build_parser(&settings)?;
+
+	let value = serde_json::to_value(&settings)
+		.map_err(|e| AppError::Custom(format!("Failed to serialize filename settings: {e}")))?;
+	let store = app.store("filename-patterns.json")?;
+	store.set("filename_pattern_settings", value);
+	store.save()?;
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn get_builtin_filename_patterns() -> Result<Vec<String>, AppError> {
+	Ok(builtin_pattern_names())
+}
+
+/// Preview what a filename parses to, optionally with unsaved user patterns
+#[tauri::command]
+pub async fn test_filename_pattern(
+	app: AppHandle,
+	file_name: String,
+	user_patterns: Option<Vec<UserPatternDefinition>>,
+) -> Result<Option<FilenameMatch>, AppError> {
+	let patterns = match user_patterns {
+		Some(patterns) => patterns,
+		None => load_filename_settings(&app)?.user_patterns,
+	};
+	let parser = FilenameParser::new(&patterns).map_err(AppError::Custom)?;
+	Ok(parser.parse(&file_name))
+}
+
+/// Parse filenames of existing files (all, or the given hashes)
+/// Emits filename_parse_progress events
+#[tauri::command]
+pub async fn parse_library_filenames(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	file_hashes: Option<Vec<String>>,
+) -> Result<FilenameParseResult, AppError> {
+	let pool = db.get();
+	let settings = load_filename_settings(&app)?;
+	let parser = build_parser(&settings)?;
+
+	let selected: Option<HashSet<String>> = file_hashes.map(|hashes| hashes.into_iter().collect());
+	let files: Vec<(String, String)> =
+		sqlx::query_as("SELECT file_hash, original_path FROM Files ORDER BY date_imported")
+			.fetch_all(&pool)
+			.await?
+			.into_iter()
+			.filter(|(hash, _): &(String, String)| {
+				selected
+					.as_ref()
+					.map_or(true, |hashes| hashes.contains(hash))
+			})
+			.collect();
+
+	let total = files.len();
+	let mut result = FilenameParseResult {
+		processed: 0,
+		matched: 0,
+		tags_added: 0,
+	};
+
+	for (index, (file_hash, original_path)) in files.into_iter().enumerate() {
+		match parse_and_apply(
+			&pool,
+			&parser,
+			&settings,
+			&file_hash,
+			Path::new(&original_path),
+		)
+		.await
+		{
+			Ok(Some(tags_added)) => {
+				result.matched += 1;
+				result.tags_added += tags_added;
+			}
+			Ok(None) => {}
+			Err(e) => eprintln!("Failed to parse filename of {original_path}: {e}"),
+		}
+		result.processed += 1;
+
+		if index % 50 == 0 || index + 1 == total {
+			app.emit(
+				"filename_parse_progress",
+				ProgressEvent {
+					stage: "parsing".to_string(),
+					message: format!("Parsing filename {} of {total}", index + 1),
+					file_hash: Some(file_hash),
+					current: Some(index + 1),
+					total: Some(total),
+				},
+			)
+			.ok();
+		}
+	}
+
+	Ok(result)
+}
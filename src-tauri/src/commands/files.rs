@@ -1,8 +1,11 @@
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
 use crate::error::AppError;
 use blake3::Hasher;
 use image::{DynamicImage, GenericImageView};
 use once_cell::sync::Lazy;
 use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
 use sqlx::{Row, SqlitePool};
 use std::fs::{self, File};
 use std::io::{BufReader, Read};
@@ -37,6 +40,17 @@ pub struct FileRecord {
 	pub last_health_check: Option<i64>,
 }
 
+/// A file in a listing; when works are collapsed it stands for its whole work
+#[derive(Debug, Serialize, Clone)]
+pub struct FileListItem {
+	#[serde(flatten)]
+	pub file: FileRecord,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub work_id: Option<i64>,
+	#[serde(skip_serializing_if = "Option::is_none")]
+	pub work_member_count: Option<i64>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct ImportResult {
 	pub file_hash: String,
@@ -98,9 +112,8 @@ pub fn generate_thumbnail(
 		fs::create_dir_all(parent)?;
 	}
 
-	// Load the image with optimized decoder
-	let img = image::open(image_path)
-		.map_err(|e| AppError::Custom(format!("Failed to load image: {e}")))?;
+	// Load the image upright, honoring EXIF orientation
+	let img = crate::metadata::open_image_oriented(image_path)?;
 
 	// Smart cropping: maintain aspect ratio and center crop
 	let thumb = resize_and_crop(&img, thumbnail_size);
@@ -187,6 +200,36 @@ pub fn get_thumbnail_dir(app: &AppHandle) -> Result<PathBuf, AppError> {
 	Ok(app_data_dir.join("thumbnails"))
 }
 
+/// Columns selected for a `FileRecord`, qualified with the `f` alias for `Files`
+pub(crate) const FILE_RECORD_COLUMNS: &str = "f.file_hash, f.original_path, f.file_size_bytes, \
+	f.file_last_modified, f.width, f.height, f.date_imported, f.is_missing, \
+	COALESCE(f.thumbnail_health, 0) as thumbnail_health, f.last_health_check";
+
+/// Map a row selected with `FILE_RECORD_COLUMNS` to a `FileRecord`
+pub(crate) fn file_record_from_row(row: &SqliteRow) -> FileRecord {
+	FileRecord {
+		file_hash: row.get("file_hash"),
+		original_path: row.get("original_path"),
+		file_size_bytes: row.get("file_size_bytes"),
+		file_last_modified: row.get("file_last_modified"),
+		width: row.get("width"),
+		height: row.get("height"),
+		date_imported: row.get("date_imported"),
+		is_missing: row.get("is_missing"),
+		thumbnail_health: Some(row.get("thumbnail_health")),
+		last_health_check: row.get("last_health_check"),
+	}
+}
+
+/// Map a listing row; work columns are only present after `collapse_works_query`
+pub(crate) fn file_list_item_from_row(row: &SqliteRow) -> FileListItem {
+	FileListItem {
+		file: file_record_from_row(row),
+		work_id: row.try_get("work_id").ok().flatten(),
+		work_member_count: row.try_get("work_member_count").ok().flatten(),
+	}
+}
+
 /// Automatically tag a file using AI
 /// Emits ai_tagging_progress events with stages: classifying, saving_tags, complete, error
 async fn tag_file_automatically(
@@ -224,21 +267,16 @@ async fn tag_file_automatically(
 	)
 	.ok();
 
-	// Load inference configuration
-	let inference_params = match crate::commands::settings::get_inference_config(app.clone()).await
-	{
-		Ok(config) => tagger::InferenceParams {
-			general_threshold: config.general_threshold,
-			character_threshold: config.character_threshold,
-			general_mcut_enabled: config.general_mcut_enabled,
-			character_mcut_enabled: config.character_mcut_enabled,
-			max_tags: config.max_tags,
-		},
+	// Load inference configuration and where each model category goes
+	let config = match crate::commands::settings::get_inference_config(app.clone()).await {
+		Ok(config) => config,
 		Err(e) => {
 			ai_debug!("[AI Tagging] Warning: Failed to load inference config: {e}, using defaults");
-			tagger::InferenceParams::default()
+			Default::default()
 		}
 	};
+	let mappings = super::model_categories::current_mappings(pool).await?;
+	let inference_params = super::model_categories::inference_params(&mappings, &config);
 
 	// Run AI classification with custom parameters
 	let predictions = match tagger::classify_image_with_params(file_path, &inference_params).await {
@@ -272,23 +310,37 @@ async fn tag_file_automatically(
 	.ok();
 
 	// Insert tags into database
+	let since_tag_id = super::category_rules::last_tag_id(pool).await?;
+	let predicted: Vec<(String, Option<u32>)> = predictions
+		.iter()
+		.map(|p| (p.name.clone(), Some(p.model_category)))
+		.collect();
 	let mut added_count = 0;
 	for prediction in predictions {
-		// Insert or get tag with correct AI category
-		let tag = match sqlx::query!(
+		// Only mapped categories are predicted
+		let Some(mapping) = mappings
+			.iter()
+			.find(|mapping| mapping.model_category == prediction.model_category)
+		else {
+			continue;
+		};
+
+		// Insert or get tag; new tags get the type and category of the mapping
+		let tag_id: i64 = match sqlx::query_scalar(
 			r#"
-            INSERT INTO Tags (name, type)
-            VALUES (?, ?)
+            INSERT INTO Tags (name, type, category_id)
+            VALUES (?, ?, ?)
             ON CONFLICT(name) DO UPDATE SET name=name
             RETURNING tag_id
             "#,
-			prediction.name,
-			prediction.category
 		)
+		.bind(&prediction.name)
+		.bind(&mapping.tag_type)
+		.bind(mapping.category_id)
 		.fetch_one(pool)
 		.await
 		{
-			Ok(t) => t,
+			Ok(tag_id) => tag_id,
 			Err(e) => {
 				eprintln!(
 					"[AI Tagging] ERROR: Failed to insert tag '{}' for {}: {}",
@@ -305,7 +357,7 @@ async fn tag_file_automatically(
             VALUES (?, ?)
             "#,
 			file_hash,
-			tag.tag_id
+			tag_id
 		)
 		.execute(pool)
 		.await
@@ -325,6 +377,18 @@ async fn tag_file_automatically(
 		}
 	}
 
+	// Tags implied by the predictions (`swimsuit` for `school_swimsuit`)
+	match super::tag_rules::add_implied_tags(pool, Some(file_hash)).await {
+		Ok(implied) => added_count += implied as usize,
+		Err(e) => eprintln!("[AI Tagging] ERROR: Failed to add implied tags for {file_hash}: {e}"),
+	}
+
+	// New tags go into the category of the first matching category rule
+	if let Err(e) = super::category_rules::categorize_new_tags(pool, since_tag_id, &predicted).await
+	{
+		eprintln!("[AI Tagging] ERROR: Failed to apply category rules for {file_hash}: {e}");
+	}
+
 	ai_debug!("[AI Tagging] Completed for {file_hash}: {added_count} tags added");
 	Ok(added_count)
 }
@@ -337,13 +401,20 @@ async fn tag_file_automatically(
 pub async fn import_file(
 	app: AppHandle,
 	path: String,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	tag_names: Option<Vec<String>>,
 	enable_ai_tagging: Option<bool>,
+	sources: Option<Vec<String>>,
 ) -> Result<ImportResult, AppError> {
+	let pool = db.get();
 	eprintln!("=== Starting import for: {path} ===");
 	let file_path = PathBuf::from(&path);
 
+	// Reject bad source URLs before anything is written
+	for url in sources.iter().flatten() {
+		crate::source_urls::parse_source_url(url).map_err(AppError::Custom)?;
+	}
+
 	// Emit progress: hashing
 	app.emit(
 		"import_progress",
@@ -371,7 +442,7 @@ pub async fn import_file(
 	// Check for duplicates
 	eprintln!("Checking for duplicates...");
 	let existing = sqlx::query!("SELECT file_hash FROM Files WHERE file_hash = ?", file_hash)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 	if existing.is_some() {
@@ -382,10 +453,9 @@ pub async fn import_file(
 		});
 	}
 
-	// Get image dimensions
+	// Get image dimensions (as displayed, after EXIF orientation)
 	eprintln!("Getting image dimensions...");
-	let (width, height) = image::image_dimensions(&file_path)
-		.map_err(|e| AppError::Custom(format!("Failed to get image dimensions: {e}")))?;
+	let (width, height) = crate::metadata::oriented_dimensions(&file_path)?;
 	eprintln!("Dimensions: {width}x{height}");
 
 	// Get file modified time (Unix timestamp)
@@ -429,14 +499,43 @@ pub async fn import_file(
         height,
         date_imported
     )
-    .execute(pool.inner())
+    .execute(&pool)
     .await?;
 	eprintln!("Database insert complete");
 
+	// Anchor to a library root so the file follows the root when it moves
+	super::library::assign_library_root(&pool, &file_hash, &path).await?;
+
+	// Read EXIF/XMP/PNG text metadata; a file without readable metadata still imports
+	if let Err(e) =
+		super::metadata::extract_and_store_metadata(&app, &pool, &file_hash, &file_path).await
+	{
+		eprintln!("Failed to extract metadata: {e}");
+	}
+
+	// Pick up tags from an existing .xmp sidecar (digiKam, darktable, ...)
+	if let Err(e) =
+		super::sidecar::read_sidecar_on_import(&app, &pool, &file_hash, &file_path).await
+	{
+		eprintln!("Failed to read XMP sidecar: {e}");
+	}
+
+	// Recover source, artist and characters from pixiv/booru style filenames
+	if let Err(e) =
+		super::filenames::parse_filename_on_import(&app, &pool, &file_hash, &file_path).await
+	{
+		eprintln!("Failed to parse filename: {e}");
+	}
+
 	// Apply tags if provided during import
 	if let Some(tags) = tag_names {
 		eprintln!("Applying {} tags during import...", tags.len());
+		let since_tag_id = super::category_rules::last_tag_id(&pool).await?;
+		let mut names = Vec::new();
 		for tag_name in tags {
+			let tag_name = super::tag_rules::canonical_tag_name(&pool, &tag_name).await?;
+			names.push((tag_name.clone(), None));
+
 			// Create or get tag
 			let tag = sqlx::query!(
 				r#"
@@ -447,7 +546,7 @@ pub async fn import_file(
                 "#,
 				tag_name
 			)
-			.fetch_one(pool.inner())
+			.fetch_one(&pool)
 			.await?;
 
 			// Associate tag with file
@@ -459,12 +558,22 @@ pub async fn import_file(
 				file_hash,
 				tag.tag_id
 			)
-			.execute(pool.inner())
+			.execute(&pool)
 			.await?;
 		}
+		super::tag_rules::add_implied_tags(&pool, Some(&file_hash)).await?;
+		super::category_rules::categorize_new_tags(&pool, since_tag_id, &names).await?;
 		eprintln!("Tags applied during import");
 	}
 
+	// Record source URLs if provided during import
+	if let Some(urls) = sources {
+		eprintln!("Adding {} sources during import...", urls.len());
+		for url in urls {
+			super::sources::add_source(&pool, &file_hash, &url, None).await?;
+		}
+	}
+
 	// Generate thumbnail in background thread after DB insert
 	let app_thumbnail = app.clone();
 	let file_path_thumbnail = file_path.clone();
@@ -592,180 +701,110 @@ pub async fn import_file(
 	})
 }
 
+/// Order a `SELECT {FILE_RECORD_COLUMNS} FROM Files f ...` query newest first,
+/// optionally collapsing each work to one representative
+pub(crate) fn newest_first(base_query: &str, collapse_works: bool) -> String {
+	let query = if collapse_works {
+		super::works::collapse_works_query(base_query)
+	} else {
+		base_query.to_string()
+	};
+	format!("{query} ORDER BY date_imported DESC")
+}
+
+/// Get files, newest first
+/// With `collapse_works`, each work is returned once (its cover) with its member count.
+/// Like every file listing, this leaves out files hidden by safe mode.
 #[tauri::command]
 pub async fn get_all_files(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	offset: Option<i64>,
 	limit: Option<i64>,
-) -> Result<Vec<FileRecord>, AppError> {
+	collapse_works: Option<bool>,
+) -> Result<Vec<FileListItem>, AppError> {
+	let pool = db.get();
 	let offset = offset.unwrap_or(0);
+	// limit None, 0 or negative returns all files
+	// SQLite requires LIMIT with OFFSET, so use LIMIT -1
+	let limit = limit.filter(|l| *l > 0).unwrap_or(-1);
 
-	// Use raw queries to avoid sqlx macro issues with COALESCE
-	let rows = if let Some(limit_val) = limit {
-		if limit_val > 0 {
-			sqlx::query(
-				r#"
-                SELECT file_hash, original_path, file_size_bytes, file_last_modified, width, height,
-                       date_imported, is_missing, COALESCE(thumbnail_health, 0) as thumbnail_health, last_health_check
-                FROM Files
-                ORDER BY date_imported DESC
-                LIMIT ? OFFSET ?
-                "#,
-			)
-			.bind(limit_val)
-			.bind(offset)
-			.fetch_all(pool.inner())
-			.await?
-		} else {
-			// limit is 0 or negative, return all files
-			// SQLite requires LIMIT with OFFSET, so use a very large LIMIT
-			sqlx::query(
-				r#"
-                SELECT file_hash, original_path, file_size_bytes, file_last_modified, width, height,
-                       date_imported, is_missing, COALESCE(thumbnail_health, 0) as thumbnail_health, last_health_check
-                FROM Files
-                ORDER BY date_imported DESC
-                LIMIT -1 OFFSET ?
-                "#,
-			)
-			.bind(offset)
-			.fetch_all(pool.inner())
-			.await?
-		}
-	} else {
-		// limit is None, return all files
-		// SQLite requires LIMIT with OFFSET, so use a very large LIMIT
-		sqlx::query(
-			r#"
-            SELECT file_hash, original_path, file_size_bytes, file_last_modified, width, height,
-                   date_imported, is_missing, COALESCE(thumbnail_health, 0) as thumbnail_health, last_health_check
-            FROM Files
-            ORDER BY date_imported DESC
-            LIMIT -1 OFFSET ?
-            "#,
+	let query = format!(
+		"{} LIMIT ? OFFSET ?",
+		newest_first(
+			&format!(
+				"SELECT {FILE_RECORD_COLUMNS} FROM Files f WHERE {}",
+				visible_files_condition("f.file_hash")
+			),
+			collapse_works.unwrap_or(false),
 		)
-		.bind(offset)
-		.fetch_all(pool.inner())
-		.await?
-	};
+	);
 
-	let files: Vec<FileRecord> = rows
-		.into_iter()
-		.map(|row| FileRecord {
-			file_hash: row.get("file_hash"),
-			original_path: row.get("original_path"),
-			file_size_bytes: row.get("file_size_bytes"),
-			file_last_modified: row.get("file_last_modified"),
-			width: row.get("width"),
-			height: row.get("height"),
-			date_imported: row.get("date_imported"),
-			is_missing: row.get("is_missing"),
-			thumbnail_health: Some(row.get("thumbnail_health")),
-			last_health_check: row.get("last_health_check"),
-		})
-		.collect();
+	let rows = sqlx::query(&query)
+		.bind(limit)
+		.bind(offset)
+		.fetch_all(&pool)
+		.await?;
 
-	Ok(files)
+	Ok(rows.iter().map(file_list_item_from_row).collect())
 }
 
 #[tauri::command]
 pub async fn get_file_by_hash(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 ) -> Result<Option<FileRecord>, AppError> {
-	let row = sqlx::query(
-		r#"
-        SELECT file_hash, original_path, file_size_bytes, file_last_modified, width, height,
-               date_imported, is_missing, COALESCE(thumbnail_health, 0) as thumbnail_health, last_health_check
-        FROM Files
-        WHERE file_hash = ?
-        "#,
-	)
-	.bind(&file_hash)
-	.fetch_optional(pool.inner())
-	.await?;
-
-	let file = row.map(|row| FileRecord {
-		file_hash: row.get("file_hash"),
-		original_path: row.get("original_path"),
-		file_size_bytes: row.get("file_size_bytes"),
-		file_last_modified: row.get("file_last_modified"),
-		width: row.get("width"),
-		height: row.get("height"),
-		date_imported: row.get("date_imported"),
-		is_missing: row.get("is_missing"),
-		thumbnail_health: Some(row.get("thumbnail_health")),
-		last_health_check: row.get("last_health_check"),
-	});
+	let pool = db.get();
+	let query = format!(
+		"SELECT {FILE_RECORD_COLUMNS} FROM Files f WHERE f.file_hash = ? AND {}",
+		visible_files_condition("f.file_hash")
+	);
+	let row = sqlx::query(&query)
+		.bind(&file_hash)
+		.fetch_optional(&pool)
+		.await?;
 
-	Ok(file)
+	Ok(row.as_ref().map(file_record_from_row))
 }
 
+/// Search files having any of the tags, optionally only favorites
+/// With `collapse_works`, each work is returned once with its member count
 #[tauri::command]
 pub async fn search_files_by_tags(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	tag_ids: Vec<i32>,
 	favorites_only: Option<bool>,
-) -> Result<Vec<FileRecord>, AppError> {
+	collapse_works: Option<bool>,
+) -> Result<Vec<FileListItem>, AppError> {
+	let pool = db.get();
 	let favorites_only = favorites_only.unwrap_or(false);
 
 	// Handle different filter combinations
 	if tag_ids.is_empty() && !favorites_only {
 		// No filters, return all files
-		return get_all_files(pool, None, None).await;
+		return get_all_files(db, None, None, collapse_works).await;
 	}
 
-	if tag_ids.is_empty() && favorites_only {
-		// Only favorites filter - include health status fields
-		let rows = sqlx::query(
-			r#"
-            SELECT DISTINCT f.file_hash, f.original_path, f.file_size_bytes, f.file_last_modified,
-                   f.width, f.height, f.date_imported, f.is_missing, COALESCE(f.thumbnail_health, 0) as thumbnail_health, f.last_health_check
-            FROM Files f
-            INNER JOIN Favorites fav ON f.file_hash = fav.file_hash
-            ORDER BY f.date_imported DESC
-            "#,
-		)
-		.fetch_all(pool.inner())
-		.await?;
-
-		let files = rows
-			.into_iter()
-			.map(|row| FileRecord {
-				file_hash: row.get("file_hash"),
-				original_path: row.get("original_path"),
-				file_size_bytes: row.get("file_size_bytes"),
-				file_last_modified: row.get("file_last_modified"),
-				width: row.get("width"),
-				height: row.get("height"),
-				date_imported: row.get("date_imported"),
-				is_missing: row.get("is_missing"),
-				thumbnail_health: Some(row.get("thumbnail_health")),
-				last_health_check: row.get("last_health_check"),
-			})
-			.collect();
-
-		return Ok(files);
+	let mut conditions = vec![visible_files_condition("f.file_hash")];
+	if favorites_only {
+		conditions.push("f.file_hash IN (SELECT fav.file_hash FROM Favorites fav)".to_string());
+	}
+	if !tag_ids.is_empty() {
+		let placeholders = tag_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+		// Tags also match files tagged with narrower tags below them
+		let tags = super::tag_tree::with_descendants_sql(&format!(
+			"SELECT tag_id FROM Tags WHERE tag_id IN ({placeholders})"
+		));
+		conditions.push(format!(
+			"f.file_hash IN (SELECT ft.file_hash FROM FileTags ft WHERE ft.tag_id IN ({tags}))"
+		));
 	}
 
-	// Build query with tags (and optionally favorites)
-	let placeholders = tag_ids.iter().map(|_| "?").collect::<Vec<_>>().join(",");
-	let favorites_join = if favorites_only {
-		"INNER JOIN Favorites fav ON f.file_hash = fav.file_hash"
-	} else {
-		""
-	};
-
-	let query = format!(
-		r#"
-        SELECT DISTINCT f.file_hash, f.original_path, f.file_size_bytes, f.file_last_modified,
-               f.width, f.height, f.date_imported, f.is_missing, COALESCE(f.thumbnail_health, 0) as thumbnail_health, f.last_health_check
-        FROM Files f
-        INNER JOIN FileTags ft ON f.file_hash = ft.file_hash
-        {favorites_join}
-        WHERE ft.tag_id IN ({placeholders})
-        ORDER BY f.date_imported DESC
-        "#
+	let query = newest_first(
+		&format!(
+			"SELECT {FILE_RECORD_COLUMNS} FROM Files f WHERE {}",
+			conditions.join(" AND ")
+		),
+		collapse_works.unwrap_or(false),
 	);
 
 	let mut query_builder = sqlx::query(&query);
@@ -773,25 +812,9 @@ pub async fn search_files_by_tags(
 		query_builder = query_builder.bind(tag_id);
 	}
 
-	let rows = query_builder.fetch_all(pool.inner()).await?;
-
-	let files = rows
-		.into_iter()
-		.map(|row| FileRecord {
-			file_hash: row.get("file_hash"),
-			original_path: row.get("original_path"),
-			file_size_bytes: row.get("file_size_bytes"),
-			file_last_modified: row.get("file_last_modified"),
-			width: row.get("width"),
-			height: row.get("height"),
-			date_imported: row.get("date_imported"),
-			is_missing: row.get("is_missing"),
-			thumbnail_health: Some(row.get("thumbnail_health")),
-			last_health_check: row.get("last_health_check"),
-		})
-		.collect();
+	let rows = query_builder.fetch_all(&pool).await?;
 
-	Ok(files)
+	Ok(rows.iter().map(file_list_item_from_row).collect())
 }
 
 /// Get the thumbnail URL for a given file hash
@@ -915,9 +938,10 @@ pub async fn regenerate_missing_thumbnails(
 #[tauri::command]
 pub async fn tag_file_with_ai(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	// Check if AI is enabled
 	let ai_enabled = crate::commands::settings::is_ai_enabled(app.clone()).await?;
 	if !ai_enabled {
@@ -930,7 +954,7 @@ pub async fn tag_file_with_ai(
 		"SELECT * FROM Files WHERE file_hash = ?",
 		file_hash
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?
 	.ok_or_else(|| AppError::Custom(format!("File not found: {file_hash}")))?;
 
@@ -943,7 +967,7 @@ pub async fn tag_file_with_ai(
 	}
 
 	// Run AI tagging
-	let tag_count = tag_file_automatically(&app, pool.inner(), &file_hash, &file_path).await?;
+	let tag_count = tag_file_automatically(&app, &pool, &file_hash, &file_path).await?;
 
 	// Emit complete event
 	app.emit(
@@ -965,9 +989,10 @@ pub async fn tag_file_with_ai(
 #[tauri::command]
 pub async fn tag_files_batch(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	// Check if AI is enabled
 	let ai_enabled = crate::commands::settings::is_ai_enabled(app.clone()).await?;
 	if !ai_enabled {
@@ -985,14 +1010,14 @@ pub async fn tag_files_batch(
 			"SELECT * FROM Files WHERE file_hash = ?",
 			file_hash
 		)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 		if let Some(file) = file_result {
 			let file_path = PathBuf::from(&file.original_path);
 			if file_path.exists() {
 				// Run AI tagging
-				match tag_file_automatically(&app, pool.inner(), &file_hash, &file_path).await {
+				match tag_file_automatically(&app, &pool, &file_hash, &file_path).await {
 					Ok(tag_count) => {
 						total_tags += tag_count;
 						processed += 1;
@@ -1208,23 +1233,24 @@ pub struct TagPredictionResult {
 #[tauri::command]
 pub async fn delete_file(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 	delete_from_disk: bool,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Get file info before deletion
 	let file = sqlx::query_as!(
 		FileRecord,
 		"SELECT * FROM Files WHERE file_hash = ?",
 		file_hash
 	)
-	.fetch_optional(pool.inner())
+	.fetch_optional(&pool)
 	.await?
 	.ok_or_else(|| AppError::Custom(format!("File not found: {file_hash}")))?;
 
 	// Delete from database (this will cascade delete file_tags associations)
 	let result = sqlx::query!("DELETE FROM Files WHERE file_hash = ?", file_hash)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	if result.rows_affected() == 0 {
@@ -1256,10 +1282,11 @@ pub async fn delete_file(
 #[tauri::command]
 pub async fn delete_files_batch(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 	delete_from_disk: bool,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	let mut deleted_count = 0;
 	let total = file_hashes.len();
 
@@ -1270,13 +1297,13 @@ pub async fn delete_files_batch(
 			"SELECT * FROM Files WHERE file_hash = ?",
 			file_hash
 		)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 		if let Some(file) = file {
 			// Delete from database (this will cascade delete file_tags associations)
 			let result = sqlx::query!("DELETE FROM Files WHERE file_hash = ?", file_hash)
-				.execute(pool.inner())
+				.execute(&pool)
 				.await?;
 
 			if result.rows_affected() > 0 {
@@ -1328,3 +1355,147 @@ pub async fn delete_files_batch(
 
 	Ok(deleted_count)
 }
+
+/// Resolve a duplicate: move its tags, favorite, rating, views, sources, work and pool
+/// membership, notes and relations to `keep_hash`, then remove the duplicate from the library
+#[tauri::command]
+pub async fn merge_duplicate_files(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	keep_hash: String,
+	duplicate_hash: String,
+	delete_from_disk: bool,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	if keep_hash == duplicate_hash {
+		return Err(AppError::Custom(
+			"Cannot merge a file into itself".to_string(),
+		));
+	}
+
+	let mut tx = pool.begin().await?;
+	let mut paths: Vec<String> = Vec::new();
+	for hash in [&keep_hash, &duplicate_hash] {
+		let path: Option<String> =
+			sqlx::query_scalar("SELECT original_path FROM Files WHERE file_hash = ?")
+				.bind(hash)
+				.fetch_optional(&mut *tx)
+				.await?;
+		paths.push(path.ok_or_else(|| AppError::Custom(format!("File not found: {hash}")))?);
+	}
+
+	sqlx::query(
+		"INSERT OR IGNORE INTO FileTags (file_hash, tag_id) SELECT ?, tag_id FROM FileTags WHERE file_hash = ?",
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	sqlx::query(
+		"INSERT OR IGNORE INTO Favorites (file_hash, created_at) SELECT ?, created_at FROM Favorites WHERE file_hash = ?",
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	sqlx::query(
+		r#"
+        INSERT OR IGNORE INTO FileSources (file_hash, url, domain, site, post_id, page, added_at)
+        SELECT ?, url, domain, site, post_id, page, added_at FROM FileSources WHERE file_hash = ?
+        "#,
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	// Keep the kept file's rating when it has one; views add up
+	sqlx::query(
+		r#"
+        INSERT INTO FileStats (file_hash, rating, view_count, last_viewed_at)
+        SELECT ?1, rating, view_count, last_viewed_at FROM FileStats WHERE file_hash = ?2 AND true
+        ON CONFLICT(file_hash) DO UPDATE SET
+            rating = COALESCE(rating, excluded.rating),
+            view_count = view_count + excluded.view_count,
+            last_viewed_at = NULLIF(MAX(COALESCE(last_viewed_at, 0), COALESCE(excluded.last_viewed_at, 0)), 0)
+        "#,
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	// Take the duplicate's place in its work unless the kept file already has one
+	sqlx::query(
+		r#"
+        UPDATE WorkMembers SET file_hash = ?1
+        WHERE file_hash = ?2 AND NOT EXISTS (SELECT 1 FROM WorkMembers WHERE file_hash = ?1)
+        "#,
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+	sqlx::query(
+		r#"
+        UPDATE Works SET cover_hash = ?1
+        WHERE cover_hash = ?2 AND work_id IN (SELECT work_id FROM WorkMembers WHERE file_hash = ?1)
+        "#,
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	// Pools may hold a file more than once, so the duplicate's entries keep their places
+	sqlx::query("UPDATE PoolMembers SET file_hash = ? WHERE file_hash = ?")
+		.bind(&keep_hash)
+		.bind(&duplicate_hash)
+		.execute(&mut *tx)
+		.await?;
+
+	// Wiki pages showing the duplicate as an example show the kept file instead
+	sqlx::query(
+		r#"
+        INSERT OR IGNORE INTO TagWikiExamples (tag_id, file_hash, position)
+        SELECT tag_id, ?, position FROM TagWikiExamples WHERE file_hash = ?
+        "#,
+	)
+	.bind(&keep_hash)
+	.bind(&duplicate_hash)
+	.execute(&mut *tx)
+	.await?;
+
+	// Note coordinates are normalized, so they fit a copy at another resolution too
+	sqlx::query("UPDATE FileNotes SET file_hash = ? WHERE file_hash = ?")
+		.bind(&keep_hash)
+		.bind(&duplicate_hash)
+		.execute(&mut *tx)
+		.await?;
+
+	super::relations::transfer_relations(&mut tx, &duplicate_hash, &keep_hash).await?;
+
+	sqlx::query("DELETE FROM Files WHERE file_hash = ?")
+		.bind(&duplicate_hash)
+		.execute(&mut *tx)
+		.await?;
+	tx.commit().await?;
+
+	// Delete the duplicate's thumbnail if it exists
+	let thumbnail_path = get_thumbnail_dir(&app)?.join(format!("{duplicate_hash}.webp"));
+	if thumbnail_path.exists() {
+		let _ = fs::remove_file(&thumbnail_path); // Ignore thumbnail deletion errors
+	}
+
+	// Optionally delete the duplicate's original from disk
+	if delete_from_disk {
+		let original_path = PathBuf::from(&paths[1]);
+		if original_path.exists() && paths[0] != paths[1] {
+			fs::remove_file(&original_path)?;
+		}
+	}
+
+	Ok(())
+}
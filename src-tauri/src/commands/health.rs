@@ -1,7 +1,9 @@
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
 use crate::error::AppError;
 use crate::health_check::ImageHealthChecker;
 use serde::{Deserialize, Serialize};
-use sqlx::{Row, SqlitePool};
+use sqlx::Row;
 use std::time::{SystemTime, UNIX_EPOCH};
 use tauri::{AppHandle, Emitter, State};
 
@@ -30,8 +32,9 @@ pub struct RecoveryResult {
 #[tauri::command]
 pub async fn check_all_images_health(
 	app_handle: AppHandle,
-	pool: State<'_, SqlitePool>,
+	db: State<'_, DbPool>,
 ) -> Result<crate::health_check::HealthCheckResult, AppError> {
+	let pool = db.get();
 	let health_checker = ImageHealthChecker::new();
 	health_checker
 		.check_all_files_health(&pool, &app_handle)
@@ -42,107 +45,42 @@ pub async fn check_all_images_health(
 #[tauri::command]
 pub async fn get_files_by_health_status(
 	health_status: String,
-	pool: State<'_, SqlitePool>,
+	db: State<'_, DbPool>,
 ) -> Result<Vec<FileWithHealthStatus>, AppError> {
-	let query_str = match health_status.as_str() {
-		"original_missing" => {
-			r#"
-            SELECT
-                file_hash,
-                original_path,
-                file_size_bytes,
-                file_last_modified,
-                width,
-                height,
-                date_imported,
-                is_missing,
-                COALESCE(thumbnail_health, 0) as thumbnail_health,
-                last_health_check
-            FROM Files
-            WHERE is_missing = 1
-            ORDER BY date_imported DESC
-            "#
-		}
-		"thumbnail_missing" => {
-			r#"
-            SELECT
-                file_hash,
-                original_path,
-                file_size_bytes,
-                file_last_modified,
-                width,
-                height,
-                date_imported,
-                is_missing,
-                COALESCE(thumbnail_health, 0) as thumbnail_health,
-                last_health_check
-            FROM Files
-            WHERE COALESCE(thumbnail_health, 0) = 1
-            ORDER BY date_imported DESC
-            "#
-		}
-		"thumbnail_corrupted" => {
-			r#"
-            SELECT
-                file_hash,
-                original_path,
-                file_size_bytes,
-                file_last_modified,
-                width,
-                height,
-                date_imported,
-                is_missing,
-                COALESCE(thumbnail_health, 0) as thumbnail_health,
-                last_health_check
-            FROM Files
-            WHERE COALESCE(thumbnail_health, 0) = 2
-            ORDER BY date_imported DESC
-            "#
-		}
-		"healthy" => {
-			r#"
-            SELECT
-                file_hash,
-                original_path,
-                file_size_bytes,
-                file_last_modified,
-                width,
-                height,
-                date_imported,
-                is_missing,
-                COALESCE(thumbnail_health, 0) as thumbnail_health,
-                last_health_check
-            FROM Files
-            WHERE is_missing = 0 AND COALESCE(thumbnail_health, 0) = 0
-            ORDER BY date_imported DESC
-            "#
-		}
-		"all_with_issues" => {
-			r#"
-            SELECT
-                file_hash,
-                original_path,
-                file_size_bytes,
-                file_last_modified,
-                width,
-                height,
-                date_imported,
-                is_missing,
-                COALESCE(thumbnail_health, 0) as thumbnail_health,
-                last_health_check
-            FROM Files
-            WHERE is_missing = 1 OR COALESCE(thumbnail_health, 0) != 0
-            ORDER BY date_imported DESC
-            "#
-		}
+	let pool = db.get();
+	let condition = match health_status.as_str() {
+		"original_missing" => "is_missing = 1",
+		"thumbnail_missing" => "COALESCE(thumbnail_health, 0) = 1",
+		"thumbnail_corrupted" => "COALESCE(thumbnail_health, 0) = 2",
+		"healthy" => "is_missing = 0 AND COALESCE(thumbnail_health, 0) = 0",
+		"all_with_issues" => "is_missing = 1 OR COALESCE(thumbnail_health, 0) != 0",
 		_ => {
 			return Err(AppError::Custom(format!(
 				"Unknown health status filter: {health_status}"
 			)));
 		}
 	};
+	let query = format!(
+		r#"
+        SELECT
+            file_hash,
+            original_path,
+            file_size_bytes,
+            file_last_modified,
+            width,
+            height,
+            date_imported,
+            is_missing,
+            COALESCE(thumbnail_health, 0) as thumbnail_health,
+            last_health_check
+        FROM Files
+        WHERE ({condition}) AND {visible}
+        ORDER BY date_imported DESC
+        "#,
+		visible = visible_files_condition("Files.file_hash")
+	);
 
-	let rows = sqlx::query(query_str).fetch_all(pool.inner()).await?;
+	let rows = sqlx::query(&query).fetch_all(&pool).await?;
 	let mut files = Vec::new();
 
 	for row in rows {
@@ -167,8 +105,9 @@ pub async fn get_files_by_health_status(
 #[tauri::command]
 pub async fn regenerate_missing_thumbnails_health(
 	app_handle: AppHandle,
-	pool: State<'_, SqlitePool>,
+	db: State<'_, DbPool>,
 ) -> Result<RecoveryResult, AppError> {
+	let pool = db.get();
 	// Import the thumbnail generation function from files module
 	use crate::commands::files::generate_thumbnail;
 
@@ -176,7 +115,7 @@ pub async fn regenerate_missing_thumbnails_health(
 	let files_with_missing_thumbnails = sqlx::query(
 		"SELECT file_hash, original_path FROM Files WHERE COALESCE(thumbnail_health, 0) = 1",
 	)
-	.fetch_all(pool.inner())
+	.fetch_all(&pool)
 	.await?;
 
 	let total_missing = files_with_missing_thumbnails.len();
@@ -248,7 +187,7 @@ pub async fn regenerate_missing_thumbnails_health(
                 )
                 .bind(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64)
                 .bind(&file_hash)
-                .execute(pool.inner())
+                .execute(&pool)
                 .await?;
 				regenerated_count += 1;
 			}
@@ -260,7 +199,7 @@ pub async fn regenerate_missing_thumbnails_health(
                 )
                 .bind(SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64)
                 .bind(&file_hash)
-                .execute(pool.inner())
+                .execute(&pool)
                 .await?;
 				error_count += 1;
 			}
@@ -289,8 +228,9 @@ pub async fn regenerate_missing_thumbnails_health(
 /// Get health status summary for the entire library
 #[tauri::command]
 pub async fn get_health_summary(
-	pool: State<'_, SqlitePool>,
+	db: State<'_, DbPool>,
 ) -> Result<crate::health_check::HealthCheckResult, AppError> {
+	let pool = db.get();
 	let row = sqlx::query(
         r#"
         SELECT
@@ -303,7 +243,7 @@ pub async fn get_health_summary(
         FROM Files
         "#
     )
-    .fetch_one(pool.inner())
+    .fetch_one(&pool)
     .await?;
 
 	let total_files: i64 = row.get("total_files");
@@ -331,14 +271,15 @@ pub async fn get_health_summary(
 #[tauri::command]
 pub async fn check_file_health(
 	file_hash: String,
-	pool: State<'_, SqlitePool>,
+	db: State<'_, DbPool>,
 ) -> Result<FileWithHealthStatus, AppError> {
+	let pool = db.get();
 	let health_checker = ImageHealthChecker::new();
 
 	// Get file info
 	let file_info_row = sqlx::query("SELECT original_path FROM Files WHERE file_hash = ?")
 		.bind(&file_hash)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 	let file_info = file_info_row
@@ -360,7 +301,7 @@ pub async fn check_file_health(
 				.as_secs() as i64,
 		)
 		.bind(&file_hash)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	// Return updated file info
@@ -382,7 +323,7 @@ pub async fn check_file_health(
         "#,
 	)
 	.bind(&file_hash)
-	.fetch_one(pool.inner())
+	.fetch_one(&pool)
 	.await?;
 
 	Ok(FileWithHealthStatus {
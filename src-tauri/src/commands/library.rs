@@ -0,0 +1,484 @@
+use super::files::calculate_blake3_hash;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use std::path::Path;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LibraryRoot {
+	pub root_id: i64,
+	pub path: String,
+	pub label: Option<String>,
+	pub created_at: i64, // Unix timestamp
+	pub file_count: i64,
+	pub missing_count: i64,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RemappedPath {
+	pub file_hash: String,
+	pub old_path: String,
+	pub new_path: String,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct VerificationFailure {
+	pub file_hash: String,
+	pub new_path: String,
+	pub reason: String,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct RemapResult {
+	pub dry_run: bool,
+	/// Whether the rewrite was written to the database
+	pub committed: bool,
+	pub matched_files: usize,
+	pub previously_missing: usize,
+	/// Files that were missing before and exist at their new path
+	pub now_healthy: usize,
+	/// Files that still don't exist at their new path
+	pub still_missing: usize,
+	pub roots_updated: usize,
+	pub verified_files: usize,
+	pub verification_failures: Vec<VerificationFailure>,
+	/// First few rewrites, for previewing a dry run
+	pub preview: Vec<RemappedPath>,
+}
+
+/// Number of rewrites returned in `RemapResult::preview`
+const PREVIEW_LIMIT: usize = 20;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Drop trailing separators so "D:\Pictures\" and "D:\Pictures" match the same files
+fn trim_separators(path: &str) -> &str {
+	path.trim_end_matches(['/', '\\'])
+}
+
+/// Return the remainder of `path` below `prefix`, only matching whole path components
+fn strip_path_prefix<'a>(path: &'a str, prefix: &str) -> Option<&'a str> {
+	let rest = path.strip_prefix(prefix)?;
+	if rest.is_empty() || rest.starts_with(['/', '\\']) {
+		Some(rest)
+	} else {
+		None
+	}
+}
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+/// The deepest root containing `path`, with the path below it (starting with a separator)
+fn anchor_for(roots: &[(i64, String)], path: &str) -> Option<(i64, String)> {
+	roots
+		.iter()
+		.filter_map(|(root_id, root_path)| {
+			let rest = strip_path_prefix(path, root_path)?;
+			Some((*root_id, root_path.len(), rest.to_string()))
+		})
+		.max_by_key(|(_, len, _)| *len)
+		.map(|(root_id, _, relative_path)| (root_id, relative_path))
+}
+
+async fn load_roots(conn: &mut SqliteConnection) -> Result<Vec<(i64, String)>, AppError> {
+	Ok(sqlx::query_as("SELECT root_id, path FROM LibraryRoots")
+		.fetch_all(&mut *conn)
+		.await?)
+}
+
+/// Anchor a file to the deepest library root containing its path
+pub async fn assign_library_root(
+	pool: &SqlitePool,
+	file_hash: &str,
+	original_path: &str,
+) -> Result<(), AppError> {
+	let roots = load_roots(&mut *pool.acquire().await?).await?;
+
+	if let Some((root_id, relative_path)) = anchor_for(&roots, original_path) {
+		sqlx::query("UPDATE Files SET root_id = ?, relative_path = ? WHERE file_hash = ?")
+			.bind(root_id)
+			.bind(relative_path)
+			.bind(file_hash)
+			.execute(pool)
+			.await?;
+	}
+
+	Ok(())
+}
+
+/// Anchor every file under a root that isn't already anchored to a deeper root
+async fn anchor_files_to_root(
+	pool: &SqlitePool,
+	root_id: i64,
+	root_path: &str,
+) -> Result<u64, AppError> {
+	let rows = sqlx::query(
+		r#"
+        SELECT f.file_hash, f.original_path, r.path as current_root
+        FROM Files f
+        LEFT JOIN LibraryRoots r ON r.root_id = f.root_id
+        WHERE substr(f.original_path, 1, length(?)) = ?
+        "#,
+	)
+	.bind(root_path)
+	.bind(root_path)
+	.fetch_all(pool)
+	.await?;
+
+	let mut tx = pool.begin().await?;
+	let mut anchored = 0;
+	for row in rows {
+		let original_path: String = row.get("original_path");
+		let current_root: Option<String> = row.get("current_root");
+		let Some(rest) = strip_path_prefix(&original_path, root_path) else {
+			continue;
+		};
+		if current_root.is_some_and(|current| current.len() > root_path.len()) {
+			continue;
+		}
+
+		sqlx::query("UPDATE Files SET root_id = ?, relative_path = ? WHERE file_hash = ?")
+			.bind(root_id)
+			.bind(rest)
+			.bind(row.get::<String, _>("file_hash"))
+			.execute(&mut *tx)
+			.await?;
+		anchored += 1;
+	}
+	tx.commit().await?;
+
+	Ok(anchored)
+}
+
+/// Hash a spread-out sample of remapped files and compare against their stored hash
+async fn verify_sample(
+	rewrites: &[(RemappedPath, bool)],
+	sample_size: usize,
+) -> (usize, Vec<VerificationFailure>) {
+	let existing: Vec<&RemappedPath> = rewrites
+		.iter()
+		.filter(|(_, exists)| *exists)
+		.map(|(rewrite, _)| rewrite)
+		.collect();
+	if existing.is_empty() || sample_size == 0 {
+		return (0, Vec::new());
+	}
+
+	let step = (existing.len() / sample_size).max(1);
+	let sample: Vec<RemappedPath> = existing
+		.into_iter()
+		.step_by(step)
+		.take(sample_size)
+		.cloned()
+		.collect();
+	let verified = sample.len();
+
+	let failures = tokio::task::spawn_blocking(move || {
+		sample
+			.into_iter()
+			.filter_map(|rewrite| {
+				let reason = match calculate_blake3_hash(Path::new(&rewrite.new_path)) {
+					Ok(hash) if hash == rewrite.file_hash => return None,
+					Ok(hash) => format!("Content hash mismatch ({hash})"),
+					Err(e) => format!("Failed to hash file: {e}"),
+				};
+				Some(VerificationFailure {
+					file_hash: rewrite.file_hash,
+					new_path: rewrite.new_path,
+					reason,
+				})
+			})
+			.collect::<Vec<_>>()
+	})
+	.await
+	.unwrap_or_else(|e| {
+		vec![VerificationFailure {
+			file_hash: String::new(),
+			new_path: String::new(),
+			reason: format!("Verification task failed: {e}"),
+		}]
+	});
+
+	(verified, failures)
+}
+
+/// Move every path (library roots and files) below `old_prefix` to live below `new_prefix`
+///
+/// Roots below the prefix get a single update each, and files anchored to them follow through
+/// the `library_roots_relocate` trigger; only files outside a moved root are rewritten here.
+async fn remap_prefix(
+	pool: &SqlitePool,
+	old_prefix: &str,
+	new_prefix: &str,
+	dry_run: bool,
+	verify_sample_size: Option<usize>,
+) -> Result<RemapResult, AppError> {
+	let old_prefix = trim_separators(old_prefix);
+	let new_prefix = trim_separators(new_prefix);
+	if old_prefix.is_empty() || new_prefix.is_empty() {
+		return Err(AppError::Custom(
+			"Path prefixes must not be empty or a bare separator".to_string(),
+		));
+	}
+
+	let mut result = RemapResult {
+		dry_run,
+		..Default::default()
+	};
+
+	let mut roots = load_roots(&mut *pool.acquire().await?).await?;
+	let mut moved_roots = HashSet::new();
+	for (root_id, path) in roots.iter_mut() {
+		if let Some(rest) = strip_path_prefix(path, old_prefix) {
+			*path = format!("{new_prefix}{rest}");
+			moved_roots.insert(*root_id);
+		}
+	}
+	result.roots_updated = moved_roots.len();
+
+	let rows = sqlx::query(
+		"SELECT file_hash, original_path, is_missing, root_id FROM Files WHERE substr(original_path, 1, length(?)) = ?",
+	)
+	.bind(old_prefix)
+	.bind(old_prefix)
+	.fetch_all(pool)
+	.await?;
+
+	// (rewrite, was missing, exists at new path, follows a moved root)
+	let mut rewrites: Vec<(RemappedPath, bool, bool, bool)> = Vec::new();
+	for row in rows {
+		let old_path: String = row.get("original_path");
+		let Some(rest) = strip_path_prefix(&old_path, old_prefix) else {
+			continue;
+		};
+		let new_path = format!("{new_prefix}{rest}");
+		let was_missing = row.get::<i64, _>("is_missing") != 0;
+		let exists = Path::new(&new_path).exists();
+		let follows_root = row
+			.get::<Option<i64>, _>("root_id")
+			.is_some_and(|root_id| moved_roots.contains(&root_id));
+
+		if was_missing {
+			result.previously_missing += 1;
+			if exists {
+				result.now_healthy += 1;
+			}
+		}
+		if !exists {
+			result.still_missing += 1;
+		}
+
+		rewrites.push((
+			RemappedPath {
+				file_hash: row.get("file_hash"),
+				old_path,
+				new_path,
+			},
+			was_missing,
+			exists,
+			follows_root,
+		));
+	}
+	result.matched_files = rewrites.len();
+	result.preview = rewrites
+		.iter()
+		.take(PREVIEW_LIMIT)
+		.map(|(rewrite, ..)| rewrite.clone())
+		.collect();
+
+	if let Some(sample_size) = verify_sample_size {
+		let existing: Vec<(RemappedPath, bool)> = rewrites
+			.iter()
+			.map(|(rewrite, _, exists, _)| (rewrite.clone(), *exists))
+			.collect();
+		let (verified, failures) = verify_sample(&existing, sample_size).await;
+		result.verified_files = verified;
+		result.verification_failures = failures;
+	}
+
+	if dry_run || !result.verification_failures.is_empty() {
+		return Ok(result);
+	}
+
+	let now = unix_now();
+	let mut tx = pool.begin().await?;
+
+	for (root_id, new_path) in roots.iter().filter(|(id, _)| moved_roots.contains(id)) {
+		sqlx::query("UPDATE LibraryRoots SET path = ? WHERE root_id = ?")
+			.bind(new_path)
+			.bind(root_id)
+			.execute(&mut *tx)
+			.await?;
+	}
+
+	for (rewrite, was_missing, exists, follows_root) in &rewrites {
+		if !follows_root {
+			let anchor = anchor_for(&roots, &rewrite.new_path);
+			sqlx::query(
+				"UPDATE Files SET original_path = ?, root_id = ?, relative_path = ? WHERE file_hash = ?",
+			)
+			.bind(&rewrite.new_path)
+			.bind(anchor.as_ref().map(|(root_id, _)| *root_id))
+			.bind(anchor.as_ref().map(|(_, relative_path)| relative_path))
+			.bind(&rewrite.file_hash)
+			.execute(&mut *tx)
+			.await?;
+		}
+		if was_missing == exists {
+			sqlx::query(
+				"UPDATE Files SET is_missing = ?, last_health_check = ? WHERE file_hash = ?",
+			)
+			.bind(!exists as i64)
+			.bind(now)
+			.bind(&rewrite.file_hash)
+			.execute(&mut *tx)
+			.await?;
+		}
+	}
+
+	tx.commit().await?;
+	result.committed = true;
+
+	Ok(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Rewrite `original_path` prefixes after a drive or root folder moved
+///
+/// All rewrites happen in one transaction. With `verify_sample`, that many files at their new
+/// location are hashed first and nothing is written if any of them don't match.
+#[tauri::command]
+pub async fn remap_path_prefix(
+	db: tauri::State<'_, DbPool>,
+	old: String,
+	new: String,
+	dry_run: bool,
+	verify_sample: Option<usize>,
+) -> Result<RemapResult, AppError> {
+	let pool = db.get();
+	remap_prefix(&pool, &old, &new, dry_run, verify_sample).await
+}
+
+/// Register a folder as a library root and anchor the files below it
+#[tauri::command]
+pub async fn add_library_root(
+	db: tauri::State<'_, DbPool>,
+	path: String,
+	label: Option<String>,
+) -> Result<LibraryRoot, AppError> {
+	let pool = db.get();
+	let path = trim_separators(&path).to_string();
+	if path.is_empty() {
+		return Err(AppError::Custom(
+			"Library root path must not be empty".to_string(),
+		));
+	}
+
+	let root_id: i64 = sqlx::query_scalar(
+		"INSERT INTO LibraryRoots (path, label, created_at) VALUES (?, ?, ?) RETURNING root_id",
+	)
+	.bind(&path)
+	.bind(&label)
+	.bind(unix_now())
+	.fetch_one(&pool)
+	.await?;
+
+	anchor_files_to_root(&pool, root_id, &path).await?;
+
+	list_library_roots(db)
+		.await?
+		.into_iter()
+		.find(|root| root.root_id == root_id)
+		.ok_or_else(|| AppError::Custom(format!("Library root {root_id} not found")))
+}
+
+#[tauri::command]
+pub async fn list_library_roots(
+	db: tauri::State<'_, DbPool>,
+) -> Result<Vec<LibraryRoot>, AppError> {
+	let pool = db.get();
+	let rows = sqlx::query(
+		r#"
+        SELECT r.root_id, r.path, r.label, r.created_at,
+               COUNT(f.file_hash) as file_count,
+               COALESCE(SUM(f.is_missing), 0) as missing_count
+        FROM LibraryRoots r
+        LEFT JOIN Files f ON f.root_id = r.root_id
+        GROUP BY r.root_id
+        ORDER BY r.path
+        "#,
+	)
+	.fetch_all(&pool)
+	.await?;
+
+	Ok(rows
+		.into_iter()
+		.map(|row| LibraryRoot {
+			root_id: row.get("root_id"),
+			path: row.get("path"),
+			label: row.get("label"),
+			created_at: row.get("created_at"),
+			file_count: row.get("file_count"),
+			missing_count: row.get("missing_count"),
+		})
+		.collect())
+}
+
+/// Forget a library root; its files keep their absolute paths
+#[tauri::command]
+pub async fn remove_library_root(
+	db: tauri::State<'_, DbPool>,
+	root_id: i64,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+
+	sqlx::query("UPDATE Files SET root_id = NULL, relative_path = NULL WHERE root_id = ?")
+		.bind(root_id)
+		.execute(&mut *tx)
+		.await?;
+	sqlx::query("DELETE FROM LibraryRoots WHERE root_id = ?")
+		.bind(root_id)
+		.execute(&mut *tx)
+		.await?;
+
+	tx.commit().await?;
+	Ok(())
+}
+
+/// Point a library root (and roots nested in it) at its new location; anchored files follow
+/// through their paths relative to the root, so no file rows are rewritten
+#[tauri::command]
+pub async fn relocate_library_root(
+	db: tauri::State<'_, DbPool>,
+	root_id: i64,
+	new_path: String,
+	dry_run: bool,
+	verify_sample: Option<usize>,
+) -> Result<RemapResult, AppError> {
+	let pool = db.get();
+	let old_path: String = sqlx::query_scalar("SELECT path FROM LibraryRoots WHERE root_id = ?")
+		.bind(root_id)
+		.fetch_optional(&pool)
+		.await?
+		.ok_or_else(|| AppError::Custom(format!("Library root {root_id} not found")))?;
+
+	remap_prefix(&pool, &old_path, &new_path, dry_run, verify_sample).await
+}
@@ -0,0 +1,385 @@
+use super::files::{
+	file_record_from_row, generate_thumbnail, get_thumbnail_dir, FileRecord, ProgressEvent,
+	FILE_RECORD_COLUMNS,
+};
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::metadata::{self, keys, MetadataEntry};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Default)]
+pub struct MetadataSettings {
+	/// Create tags from XMP `dc:subject` keywords at import
+	pub keywords_as_tags: bool,
+	/// Read `.xmp` sidecars at import and allow syncing tags back to them
+	pub xmp_sidecars_enabled: bool,
+}
+
+/// Metadata of a file, with the well-known fields pulled out of the raw entries
+#[derive(Debug, Serialize, Clone)]
+pub struct FileMetadata {
+	pub file_hash: String,
+	pub date_taken: Option<String>, // EXIF format: "YYYY:MM:DD HH:MM:SS"
+	pub camera_make: Option<String>,
+	pub camera_model: Option<String>,
+	pub orientation: Option<i64>,
+	pub keywords: Vec<String>,
+	pub prompt: Option<String>,
+	pub negative_prompt: Option<String>,
+	pub generation_settings: Option<String>,
+	pub entries: Vec<MetadataEntry>,
+}
+
+impl FileMetadata {
+	fn from_entries(file_hash: String, entries: Vec<MetadataEntry>) -> Self {
+		let find = |source: &str, key: &str| {
+			entries
+				.iter()
+				.find(|e| e.source == source && e.key == key)
+				.map(|e| e.value.clone())
+		};
+
+		Self {
+			date_taken: find(keys::SOURCE_EXIF, keys::DATE_TAKEN),
+			camera_make: find(keys::SOURCE_EXIF, keys::CAMERA_MAKE),
+			camera_model: find(keys::SOURCE_EXIF, keys::CAMERA_MODEL),
+			orientation: find(keys::SOURCE_EXIF, keys::ORIENTATION).and_then(|v| v.parse().ok()),
+			keywords: entries
+				.iter()
+				.filter(|e| e.source == keys::SOURCE_XMP && e.key == keys::KEYWORD)
+				.map(|e| e.value.clone())
+				.collect(),
+			prompt: find(keys::SOURCE_SD, keys::PROMPT),
+			negative_prompt: find(keys::SOURCE_SD, keys::NEGATIVE_PROMPT),
+			generation_settings: find(keys::SOURCE_SD, keys::GENERATION_SETTINGS),
+			file_hash,
+			entries,
+		}
+	}
+}
+
+#[derive(Debug, Serialize)]
+pub struct MetadataBackfillResult {
+	pub processed: usize,
+	pub with_metadata: usize,
+	pub thumbnails_regenerated: usize,
+	pub errors: usize,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+pub(crate) fn load_metadata_settings(app: &AppHandle) -> Result<MetadataSettings, AppError> {
+	let store = app.store("metadata-settings.json")?;
+	let flag = |key: &str| store.get(key).and_then(|v| v.as_bool()).unwrap_or(false);
+	Ok(MetadataSettings {
+		keywords_as_tags: flag("keywords_as_tags"),
+		xmp_sidecars_enabled: flag("xmp_sidecars_enabled"),
+	})
+}
+
+/// Turn a free-form keyword into a tag name ("Blue Sky" -> "blue_sky")
+pub(crate) fn keyword_to_tag_name(keyword: &str) -> String {
+	keyword
+		.trim()
+		.to_lowercase()
+		.split_whitespace()
+		.collect::<Vec<_>>()
+		.join("_")
+}
+
+/// Replace the stored embedded metadata of a file, leaving filename-derived entries alone
+async fn store_metadata(
+	pool: &SqlitePool,
+	file_hash: &str,
+	entries: &[MetadataEntry],
+) -> Result<(), AppError> {
+	let mut tx = pool.begin().await?;
+
+	sqlx::query("DELETE FROM FileMetadata WHERE file_hash = ? AND source != ?")
+		.bind(file_hash)
+		.bind(keys::SOURCE_FILENAME)
+		.execute(&mut *tx)
+		.await?;
+
+	for entry in entries {
+		sqlx::query("INSERT INTO FileMetadata (file_hash, source, key, value) VALUES (?, ?, ?, ?)")
+			.bind(file_hash)
+			.bind(&entry.source)
+			.bind(&entry.key)
+			.bind(&entry.value)
+			.execute(&mut *tx)
+			.await?;
+	}
+
+	tx.commit().await?;
+	Ok(())
+}
+
+/// Tag a file with keywords from embedded XMP or a sidecar, creating tags as needed
+pub(crate) async fn add_keyword_tags(
+	pool: &SqlitePool,
+	file_hash: &str,
+	keywords: &[String],
+) -> Result<(), AppError> {
+	let tag_names: Vec<String> = keywords
+		.iter()
+		.map(|keyword| keyword_to_tag_name(keyword))
+		.filter(|name| !name.is_empty())
+		.collect();
+	let since_tag_id = super::category_rules::last_tag_id(pool).await?;
+
+	for tag_name in &tag_names {
+		let tag_id: i64 = sqlx::query_scalar(
+			r#"
+            INSERT INTO Tags (name, type)
+            VALUES (?, 'general')
+            ON CONFLICT(name) DO UPDATE SET name=name
+            RETURNING tag_id
+            "#,
+		)
+		.bind(tag_name)
+		.fetch_one(pool)
+		.await?;
+
+		sqlx::query("INSERT OR IGNORE INTO FileTags (file_hash, tag_id) VALUES (?, ?)")
+			.bind(file_hash)
+			.bind(tag_id)
+			.execute(pool)
+			.await?;
+	}
+
+	let names: Vec<(String, Option<u32>)> =
+		tag_names.into_iter().map(|name| (name, None)).collect();
+	super::category_rules::categorize_new_tags(pool, since_tag_id, &names).await?;
+
+	Ok(())
+}
+
+/// Extract embedded metadata from a file, store it, and apply keyword tags if enabled
+pub async fn extract_and_store_metadata(
+	app: &AppHandle,
+	pool: &SqlitePool,
+	file_hash: &str,
+	path: &Path,
+) -> Result<Vec<MetadataEntry>, AppError> {
+	let path = path.to_path_buf();
+	let entries = tokio::task::spawn_blocking(move || metadata::extract_metadata(&path))
+		.await
+		.map_err(|e| AppError::Custom(format!("Task join error: {e}")))?;
+
+	store_metadata(pool, file_hash, &entries).await?;
+
+	if load_metadata_settings(app)?.keywords_as_tags {
+		let keywords: Vec<String> = entries
+			.iter()
+			.filter(|e| e.source == keys::SOURCE_XMP && e.key == keys::KEYWORD)
+			.map(|e| e.value.clone())
+			.collect();
+		add_keyword_tags(pool, file_hash, &keywords).await?;
+	}
+
+	Ok(entries)
+}
+
+async fn load_file_metadata(pool: &SqlitePool, file_hash: &str) -> Result<FileMetadata, AppError> {
+	let entries = sqlx::query(
+		"SELECT source, key, value FROM FileMetadata WHERE file_hash = ? ORDER BY metadata_id",
+	)
+	.bind(file_hash)
+	.fetch_all(pool)
+	.await?
+	.into_iter()
+	.map(|row| MetadataEntry {
+		source: row.get("source"),
+		key: row.get("key"),
+		value: row.get("value"),
+	})
+	.collect();
+
+	Ok(FileMetadata::from_entries(file_hash.to_string(), entries))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_metadata_settings(app: AppHandle) -> Result<MetadataSettings, AppError> {
+	load_metadata_settings(&app)
+}
+
+#[tauri::command]
+pub async fn set_metadata_settings(
+	app: AppHandle,
+	settings: MetadataSettings,
+) -> Result<(), AppError> {
+	let store = app.store("metadata-settings.json")?;
+	store.set("keywords_as_tags", settings.keywords_as_tags);
+	store.set("xmp_sidecars_enabled", settings.xmp_sidecars_enabled);
+	store.save()?;
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn get_file_metadata(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<FileMetadata, AppError> {
+	let pool = db.get();
+	load_file_metadata(&pool, &file_hash).await
+}
+
+/// Re-read embedded metadata for a single file
+#[tauri::command]
+pub async fn extract_file_metadata(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<FileMetadata, AppError> {
+	let pool = db.get();
+	let original_path: String =
+		sqlx::query_scalar("SELECT original_path FROM Files WHERE file_hash = ?")
+			.bind(&file_hash)
+			.fetch_optional(&pool)
+			.await?
+			.ok_or_else(|| AppError::Custom(format!("File with hash {file_hash} not found")))?;
+
+	let entries =
+		extract_and_store_metadata(&app, &pool, &file_hash, Path::new(&original_path)).await?;
+	Ok(FileMetadata::from_entries(file_hash, entries))
+}
+
+/// Extract metadata for files imported before metadata extraction existed
+/// Thumbnails of rotated images are regenerated so they display upright
+/// Emits metadata_progress events
+#[tauri::command]
+pub async fn extract_missing_metadata(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+) -> Result<MetadataBackfillResult, AppError> {
+	let pool = db.get();
+	let rows = sqlx::query(
+		r#"
+        SELECT file_hash, original_path FROM Files
+        WHERE is_missing = 0
+          AND file_hash NOT IN (SELECT DISTINCT file_hash FROM FileMetadata WHERE source != ?)
+        "#,
+	)
+	.bind(keys::SOURCE_FILENAME)
+	.fetch_all(&pool)
+	.await?;
+
+	let thumbnail_dir = get_thumbnail_dir(&app)?;
+	let total = rows.len();
+	let mut result = MetadataBackfillResult {
+		processed: 0,
+		with_metadata: 0,
+		thumbnails_regenerated: 0,
+		errors: 0,
+	};
+
+	for (index, row) in rows.into_iter().enumerate() {
+		let file_hash: String = row.get("file_hash");
+		let original_path = PathBuf::from(row.get::<String, _>("original_path"));
+
+		match extract_and_store_metadata(&app, &pool, &file_hash, &original_path).await {
+			Ok(entries) => {
+				result.processed += 1;
+				if !entries.is_empty() {
+					result.with_metadata += 1;
+				}
+
+				let rotated = FileMetadata::from_entries(file_hash.clone(), entries)
+					.orientation
+					.is_some_and(|o| o > 1);
+				if rotated {
+					let thumbnail_path = thumbnail_dir.join(format!("{file_hash}.webp"));
+					let regenerated = tokio::task::spawn_blocking(move || {
+						std::fs::remove_file(&thumbnail_path).ok();
+						generate_thumbnail(&original_path, &thumbnail_path, 400)
+					})
+					.await
+					.map_err(|e| AppError::Custom(format!("Task join error: {e}")))?;
+					match regenerated {
+						Ok(_) => result.thumbnails_regenerated += 1,
+						Err(e) => eprintln!("Failed to regenerate thumbnail for {file_hash}: {e}"),
+					}
+				}
+			}
+			Err(e) => {
+				eprintln!("Failed to extract metadata for {file_hash}: {e}");
+				result.errors += 1;
+			}
+		}
+
+		app.emit(
+			"metadata_progress",
+			ProgressEvent {
+				stage: "extracting".to_string(),
+				message: format!("Reading metadata {} of {total}", index + 1),
+				file_hash: Some(file_hash),
+				current: Some(index + 1),
+				total: Some(total),
+			},
+		)
+		.ok();
+	}
+
+	Ok(result)
+}
+
+/// Find files whose metadata contains `query`, optionally limited to one key (e.g. "prompt", "Model")
+#[tauri::command]
+pub async fn search_files_by_metadata(
+	db: tauri::State<'_, DbPool>,
+	query: String,
+	key: Option<String>,
+	limit: Option<i64>,
+) -> Result<Vec<FileRecord>, AppError> {
+	let pool = db.get();
+	let pattern = format!(
+		"%{}%",
+		query
+			.trim()
+			.replace('\\', "\\\\")
+			.replace('%', "\\%")
+			.replace('_', "\\_")
+	);
+
+	let sql = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}
+        FROM Files f
+        WHERE f.file_hash IN (
+            SELECT m.file_hash FROM FileMetadata m
+            WHERE m.value LIKE ? ESCAPE '\'
+              AND (? IS NULL OR m.key = ?)
+        )
+          AND {visible}
+        ORDER BY f.date_imported DESC
+        LIMIT ?
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+
+	let rows = sqlx::query(&sql)
+		.bind(&pattern)
+		.bind(&key)
+		.bind(&key)
+		.bind(limit.unwrap_or(-1))
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows.iter().map(file_record_from_row).collect())
+}
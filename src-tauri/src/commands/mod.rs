@@ -2,10 +2,29 @@
 // Commands will be organized by domain: files, tags, faces, etc.
 
 pub mod admin;
+pub mod archive;
+pub mod backup;
 pub mod categories;
+pub mod category_rules;
 pub mod debug_visualization;
 pub mod favorites;
+pub mod filenames;
 pub mod files;
 pub mod health;
+pub mod library;
+pub mod metadata;
+pub mod model_categories;
+pub mod notes;
+pub mod pools;
+pub mod relations;
+pub mod safe_mode;
+pub mod search;
 pub mod settings;
+pub mod sidecar;
+pub mod sources;
+pub mod stats;
+pub mod tag_rules;
+pub mod tag_tree;
 pub mod tags;
+pub mod wiki;
+pub mod works;
@@ -0,0 +1,331 @@
+use super::settings::InferenceConfig;
+use crate::ai::tagger::{self, CategoryParams, InferenceParams};
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Where the AI model's predictions in one label map category go
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct ModelCategoryMapping {
+	pub model_category: u32,
+	pub category_id: i64,
+	/// Tags.type of tags created from the predictions
+	pub tag_type: String,
+	/// `None` uses the inference config: its character threshold for character, artist and
+	/// series tags, its general threshold otherwise
+	pub threshold: Option<f32>,
+	/// `None` uses the inference config, like `threshold`
+	pub mcut_enabled: Option<bool>,
+	/// Take only the most confident label, whatever its confidence (ratings)
+	pub argmax: bool,
+	pub enabled: bool,
+}
+
+/// A category of the loaded label map
+#[derive(Debug, Serialize, Clone)]
+pub struct LabelCategory {
+	pub model_category: u32,
+	pub name: String,
+	pub label_count: usize,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct ModelCategoryConfig {
+	pub model_name: String,
+	/// Categories of the loaded label map; empty when none is loaded
+	pub label_categories: Vec<LabelCategory>,
+	pub mappings: Vec<ModelCategoryMapping>,
+	/// False while the model uses the built-in mapping
+	pub customized: bool,
+}
+
+/// Built-in mapping: label map category, builtin category name, tag type and argmax
+/// Tags.type has no copyright or meta types; copyright tags are `series` as elsewhere.
+const DEFAULT_MAPPINGS: [(u32, &str, &str, bool); 6] = [
+	(tagger::RATING_CATEGORY, "RATING", "rating", true),
+	(tagger::GENERAL_CATEGORY, "GENERAL", "general", false),
+	(tagger::ARTIST_CATEGORY, "ARTIST", "artist", false),
+	(tagger::COPYRIGHT_CATEGORY, "COPYRIGHT", "series", false),
+	(tagger::CHARACTER_CATEGORY, "CHARACTER", "character", false),
+	(tagger::META_CATEGORY, "META", "general", false),
+];
+
+const TAG_TYPES: [&str; 5] = ["general", "character", "artist", "series", "rating"];
+
+/// Lowest confidence MCut may keep for name tags (characters, artists, series)
+const NAME_MCUT_MIN_THRESHOLD: f32 = 0.15;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+async fn default_mappings(pool: &SqlitePool) -> Result<Vec<ModelCategoryMapping>, AppError> {
+	let category_ids: HashMap<String, i64> =
+		sqlx::query("SELECT name, category_id FROM TagCategories WHERE is_builtin = 1")
+			.fetch_all(pool)
+			.await?
+			.iter()
+			.map(|row| (row.get("name"), row.get("category_id")))
+			.collect();
+
+	Ok(DEFAULT_MAPPINGS
+		.iter()
+		.map(
+			|&(model_category, category, tag_type, argmax)| ModelCategoryMapping {
+				model_category,
+				// GENERAL is category 1, the Tags.category_id default
+				category_id: category_ids.get(category).copied().unwrap_or(1),
+				tag_type: tag_type.to_string(),
+				threshold: None,
+				mcut_enabled: None,
+				argmax,
+				enabled: true,
+			},
+		)
+		.collect())
+}
+
+/// Stored mappings of a model, or the built-in ones when it has none
+async fn load_mappings(
+	pool: &SqlitePool,
+	model_name: &str,
+) -> Result<(Vec<ModelCategoryMapping>, bool), AppError> {
+	let rows = sqlx::query(
+		r#"
+        SELECT model_category, category_id, tag_type, threshold, mcut_enabled, argmax, enabled
+        FROM ModelCategoryMappings
+        WHERE model_name = ?
+        ORDER BY model_category
+        "#,
+	)
+	.bind(model_name)
+	.fetch_all(pool)
+	.await?;
+
+	if rows.is_empty() {
+		return Ok((default_mappings(pool).await?, false));
+	}
+
+	let mappings = rows
+		.iter()
+		.map(|row| ModelCategoryMapping {
+			model_category: row.get::<i64, _>("model_category") as u32,
+			category_id: row.get("category_id"),
+			tag_type: row.get("tag_type"),
+			threshold: row.get::<Option<f64>, _>("threshold").map(|t| t as f32),
+			mcut_enabled: row.get("mcut_enabled"),
+			argmax: row.get("argmax"),
+			enabled: row.get("enabled"),
+		})
+		.collect();
+	Ok((mappings, true))
+}
+
+/// Category mappings of the current model
+pub(crate) async fn current_mappings(
+	pool: &SqlitePool,
+) -> Result<Vec<ModelCategoryMapping>, AppError> {
+	Ok(load_mappings(pool, tagger::MODEL_NAME).await?.0)
+}
+
+/// Tagger parameters for the enabled mappings, with the inference config filling in thresholds
+/// and MCut settings the mappings leave unset
+pub(crate) fn inference_params(
+	mappings: &[ModelCategoryMapping],
+	config: &InferenceConfig,
+) -> InferenceParams {
+	let categories = mappings
+		.iter()
+		.filter(|mapping| mapping.enabled)
+		.map(|mapping| {
+			let is_name = matches!(mapping.tag_type.as_str(), "character" | "artist" | "series");
+			let (threshold, mcut_enabled, min_threshold) = if is_name {
+				(
+					config.character_threshold,
+					config.character_mcut_enabled,
+					Some(NAME_MCUT_MIN_THRESHOLD),
+				)
+			} else {
+				(config.general_threshold, config.general_mcut_enabled, None)
+			};
+			CategoryParams {
+				model_category: mapping.model_category,
+				threshold: mapping.threshold.unwrap_or(threshold),
+				mcut_enabled: mapping.mcut_enabled.unwrap_or(mcut_enabled),
+				min_threshold,
+				argmax: mapping.argmax,
+			}
+		})
+		.collect();
+
+	InferenceParams {
+		categories,
+		max_tags: config.max_tags,
+	}
+}
+
+async fn validate_mappings(
+	pool: &SqlitePool,
+	mappings: &mut [ModelCategoryMapping],
+) -> Result<(), AppError> {
+	let category_ids: HashSet<i64> = sqlx::query_scalar("SELECT category_id FROM TagCategories")
+		.fetch_all(pool)
+		.await?
+		.into_iter()
+		.collect();
+
+	let mut seen = HashSet::new();
+	for mapping in mappings.iter_mut() {
+		if !seen.insert(mapping.model_category) {
+			return Err(AppError::Custom(format!(
+				"Model category {} is mapped twice",
+				mapping.model_category
+			)));
+		}
+		mapping.tag_type = mapping.tag_type.trim().to_lowercase();
+		if !TAG_TYPES.contains(&mapping.tag_type.as_str()) {
+			return Err(AppError::Custom(format!(
+				"Invalid tag type '{}' for model category {}",
+				mapping.tag_type, mapping.model_category
+			)));
+		}
+		// Safe mode hides files by their `rating` tags, so ratings must keep that type and
+		// nothing else may take it
+		let is_rating_category = mapping.model_category == tagger::RATING_CATEGORY;
+		if is_rating_category != (mapping.tag_type == "rating") {
+			return Err(AppError::Custom(if is_rating_category {
+				format!(
+					"Model category {} holds ratings and must use the 'rating' tag type",
+					mapping.model_category
+				)
+			} else {
+				format!(
+					"Only model category {} (ratings) may use the 'rating' tag type",
+					tagger::RATING_CATEGORY
+				)
+			}));
+		}
+		if let Some(threshold) = mapping.threshold {
+			if !(0.0..=1.0).contains(&threshold) {
+				return Err(AppError::Custom(format!(
+					"Threshold for model category {} must be between 0 and 1",
+					mapping.model_category
+				)));
+			}
+		}
+		if !category_ids.contains(&mapping.category_id) {
+			return Err(AppError::Custom(format!(
+				"Category with id {} not found",
+				mapping.category_id
+			)));
+		}
+	}
+	Ok(())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Category mappings of a model (the current one by default) and the label map's categories
+#[tauri::command]
+pub async fn get_model_category_mappings(
+	db: tauri::State<'_, DbPool>,
+	model_name: Option<String>,
+) -> Result<ModelCategoryConfig, AppError> {
+	let pool = db.get();
+	let model_name = model_name.unwrap_or_else(|| tagger::MODEL_NAME.to_string());
+	let (mappings, customized) = load_mappings(&pool, &model_name).await?;
+
+	let label_categories = if model_name == tagger::MODEL_NAME {
+		tagger::label_category_counts()
+			.into_iter()
+			.map(|(model_category, label_count)| LabelCategory {
+				model_category,
+				name: tagger::category_name(model_category),
+				label_count,
+			})
+			.collect()
+	} else {
+		Vec::new()
+	};
+
+	Ok(ModelCategoryConfig {
+		model_name,
+		label_categories,
+		mappings,
+		customized,
+	})
+}
+
+/// Replace a model's category mappings; model categories left out are not tagged
+///
+/// A model without mappings uses the built-in ones, so an empty list is rejected: disable
+/// mappings to stop tagging, or use `reset_model_category_mappings` for the defaults.
+#[tauri::command]
+pub async fn set_model_category_mappings(
+	db: tauri::State<'_, DbPool>,
+	model_name: Option<String>,
+	mut mappings: Vec<ModelCategoryMapping>,
+) -> Result<(), AppError> {
+	if mappings.is_empty() {
+		return Err(AppError::Custom(
+			"At least one category mapping is required; disable mappings to stop tagging or \
+			 reset to the built-in mapping"
+				.to_string(),
+		));
+	}
+	let pool = db.get();
+	let model_name = model_name.unwrap_or_else(|| tagger::MODEL_NAME.to_string());
+	validate_mappings(&pool, &mut mappings).await?;
+
+	let mut tx = pool.begin().await?;
+	sqlx::query("DELETE FROM ModelCategoryMappings WHERE model_name = ?")
+		.bind(&model_name)
+		.execute(&mut *tx)
+		.await?;
+	for mapping in &mappings {
+		sqlx::query(
+			r#"
+            INSERT INTO ModelCategoryMappings
+                (model_name, model_category, category_id, tag_type, threshold, mcut_enabled, argmax, enabled)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?)
+            "#,
+		)
+		.bind(&model_name)
+		.bind(mapping.model_category as i64)
+		.bind(mapping.category_id)
+		.bind(&mapping.tag_type)
+		.bind(mapping.threshold.map(f64::from))
+		.bind(mapping.mcut_enabled)
+		.bind(mapping.argmax)
+		.bind(mapping.enabled)
+		.execute(&mut *tx)
+		.await?;
+	}
+	tx.commit().await?;
+
+	Ok(())
+}
+
+/// Go back to the built-in mapping for a model
+#[tauri::command]
+pub async fn reset_model_category_mappings(
+	db: tauri::State<'_, DbPool>,
+	model_name: Option<String>,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let model_name = model_name.unwrap_or_else(|| tagger::MODEL_NAME.to_string());
+	sqlx::query("DELETE FROM ModelCategoryMappings WHERE model_name = ?")
+		.bind(&model_name)
+		.execute(&pool)
+		.await?;
+
+	Ok(())
+}
@@ -0,0 +1,409 @@
+use super::safe_mode::{is_file_visible, visible_files_condition};
+use super::search::{fts_snippet_html, SearchCondition};
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A box with text over an image, like a Danbooru translation note
+///
+/// Coordinates are fractions (0-1) of the upright image, so notes fit any resolution.
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileNote {
+	pub note_id: i64,
+	pub file_hash: String,
+	/// Left edge
+	pub x: f64,
+	/// Top edge
+	pub y: f64,
+	pub width: f64,
+	pub height: f64,
+	pub body: String,
+	/// Language of the body, e.g. `en`
+	pub language: Option<String>,
+	pub created_at: i64, // Unix timestamp
+	pub updated_at: i64, // Unix timestamp
+}
+
+/// Position and contents of a note being created or edited
+#[derive(Debug, Deserialize, Clone)]
+pub struct NoteInput {
+	pub x: f64,
+	pub y: f64,
+	pub width: f64,
+	pub height: f64,
+	pub body: String,
+	pub language: Option<String>,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct NoteSearchResult {
+	pub note: FileNote,
+	/// Part of the body around the match as HTML: escaped, with matches wrapped in `<mark>`
+	pub snippet: String,
+}
+
+/// Contents of a `.notes.json` export; `width`/`height` are the image's pixel size
+#[derive(Debug, Serialize)]
+struct NotesExport<'a> {
+	file_hash: &'a str,
+	width: i64,
+	height: i64,
+	notes: &'a [FileNote],
+}
+
+const NOTE_COLUMNS: &str =
+	"n.note_id, n.file_hash, n.x, n.y, n.width, n.height, n.body, n.language, n.created_at, n.updated_at";
+const DEFAULT_SEARCH_LIMIT: i64 = 100;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+pub(crate) fn note_from_row(row: &SqliteRow) -> FileNote {
+	FileNote {
+		note_id: row.get("note_id"),
+		file_hash: row.get("file_hash"),
+		x: row.get("x"),
+		y: row.get("y"),
+		width: row.get("width"),
+		height: row.get("height"),
+		body: row.get("body"),
+		language: row.get("language"),
+		created_at: row.get("created_at"),
+		updated_at: row.get("updated_at"),
+	}
+}
+
+/// Check the rectangle and body of a note, clipping boxes that overhang the image edge
+fn validate_note(mut note: NoteInput) -> Result<NoteInput, AppError> {
+	let coordinates = [note.x, note.y, note.width, note.height];
+	if coordinates
+		.iter()
+		.any(|c| !c.is_finite() || !(0.0..=1.0).contains(c))
+	{
+		return Err(AppError::Custom(
+			"Note coordinates must be fractions of the image between 0 and 1".to_string(),
+		));
+	}
+	note.width = note.width.min(1.0 - note.x);
+	note.height = note.height.min(1.0 - note.y);
+	if note.width <= 0.0 || note.height <= 0.0 {
+		return Err(AppError::Custom(
+			"Note must have a width and height".to_string(),
+		));
+	}
+
+	note.body = note.body.trim().to_string();
+	if note.body.is_empty() {
+		return Err(AppError::Custom("Note text cannot be empty".to_string()));
+	}
+	note.language = note
+		.language
+		.map(|l| l.trim().to_string())
+		.filter(|l| !l.is_empty());
+	Ok(note)
+}
+
+async fn fetch_note(pool: &SqlitePool, note_id: i64) -> Result<FileNote, AppError> {
+	let row = sqlx::query(&format!(
+		"SELECT {NOTE_COLUMNS} FROM FileNotes n WHERE n.note_id = ?"
+	))
+	.bind(note_id)
+	.fetch_optional(pool)
+	.await?
+	.ok_or_else(|| AppError::Custom(format!("Note not found: {note_id}")))?;
+	Ok(note_from_row(&row))
+}
+
+pub(crate) async fn notes_for_file(
+	pool: &SqlitePool,
+	file_hash: &str,
+) -> Result<Vec<FileNote>, AppError> {
+	let rows = sqlx::query(&format!(
+		"SELECT {NOTE_COLUMNS} FROM FileNotes n WHERE n.file_hash = ? ORDER BY n.y, n.x, n.note_id"
+	))
+	.bind(file_hash)
+	.fetch_all(pool)
+	.await?;
+	Ok(rows.iter().map(note_from_row).collect())
+}
+
+/// Turn free text into an FTS5 query matching all of its words
+///
+/// Words are quoted so characters like `"`, `*` or `-` are never read as FTS5 syntax.
+fn fts_query(text: &str) -> Option<String> {
+	let words: Vec<String> = text
+		.split_whitespace()
+		.map(|word| format!("\"{}\"", word.replace('"', "\"\"")))
+		.collect();
+	(!words.is_empty()).then(|| words.join(" "))
+}
+
+/// `<original>.notes.json` for an exported image, like `.xmp` sidecars
+fn notes_json_path(image_path: &Path) -> PathBuf {
+	let mut path = image_path.as_os_str().to_owned();
+	path.push(".notes.json");
+	PathBuf::from(path)
+}
+
+/// Write a file's notes as JSON to `path`
+async fn write_notes_json(pool: &SqlitePool, file_hash: &str, path: &Path) -> Result<(), AppError> {
+	let (width, height): (i64, i64) =
+		sqlx::query_as("SELECT width, height FROM Files WHERE file_hash = ?")
+			.bind(file_hash)
+			.fetch_optional(pool)
+			.await?
+			.ok_or_else(|| AppError::Custom(format!("File not found: {file_hash}")))?;
+	let notes = notes_for_file(pool, file_hash).await?;
+
+	let json = serde_json::to_string_pretty(&NotesExport {
+		file_hash,
+		width,
+		height,
+		notes: &notes,
+	})
+	.map_err(|e| AppError::Custom(format!("Failed to serialize notes: {e}")))?;
+	std::fs::write(path, json)?;
+	Ok(())
+}
+
+/// Write `<image>.notes.json` next to an exported copy of a file, if it has notes
+/// Returns whether a file was written
+pub(crate) async fn export_notes_beside(
+	pool: &SqlitePool,
+	file_hash: &str,
+	exported_image: &Path,
+) -> Result<bool, AppError> {
+	let has_notes: bool =
+		sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM FileNotes WHERE file_hash = ?)")
+			.bind(file_hash)
+			.fetch_one(pool)
+			.await?;
+	if has_notes {
+		write_notes_json(pool, file_hash, &notes_json_path(exported_image)).await?;
+	}
+	Ok(has_notes)
+}
+
+/// Search conditions for `has:notes` and `note:<words>` (`_` stands for a space)
+pub(crate) fn search_condition(
+	key: &str,
+	value: &str,
+) -> Option<Result<SearchCondition, AppError>> {
+	match key {
+		"has" if value == "notes" => Some(Ok(SearchCondition::new(
+			"f.file_hash IN (SELECT file_hash FROM FileNotes)",
+			Vec::new(),
+		))),
+		"note" => Some(
+			fts_query(&value.replace('_', " "))
+				.map(|query| {
+					SearchCondition::new(
+						r#"f.file_hash IN (
+                    SELECT n.file_hash FROM FileNotesFts
+                    JOIN FileNotes n ON n.note_id = FileNotesFts.rowid
+                    WHERE FileNotesFts MATCH ?
+                )"#,
+						vec![query],
+					)
+				})
+				.ok_or_else(|| AppError::Custom("Empty note search".to_string())),
+		),
+		_ => None,
+	}
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Add a note to a file
+#[tauri::command]
+pub async fn create_note(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	note: NoteInput,
+) -> Result<FileNote, AppError> {
+	let pool = db.get();
+	let note = validate_note(note)?;
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+		.bind(&file_hash)
+		.fetch_one(&pool)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("File not found: {file_hash}")));
+	}
+
+	let now = unix_now();
+	let note_id: i64 = sqlx::query_scalar(
+		r#"
+        INSERT INTO FileNotes (file_hash, x, y, width, height, body, language, created_at, updated_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?)
+        RETURNING note_id
+        "#,
+	)
+	.bind(&file_hash)
+	.bind(note.x)
+	.bind(note.y)
+	.bind(note.width)
+	.bind(note.height)
+	.bind(&note.body)
+	.bind(&note.language)
+	.bind(now)
+	.bind(now)
+	.fetch_one(&pool)
+	.await?;
+
+	fetch_note(&pool, note_id).await
+}
+
+/// Move, resize or rewrite a note
+#[tauri::command]
+pub async fn update_note(
+	db: tauri::State<'_, DbPool>,
+	note_id: i64,
+	note: NoteInput,
+) -> Result<FileNote, AppError> {
+	let pool = db.get();
+	let note = validate_note(note)?;
+	let result = sqlx::query(
+		r#"
+        UPDATE FileNotes
+        SET x = ?, y = ?, width = ?, height = ?, body = ?, language = ?, updated_at = ?
+        WHERE note_id = ?
+        "#,
+	)
+	.bind(note.x)
+	.bind(note.y)
+	.bind(note.width)
+	.bind(note.height)
+	.bind(&note.body)
+	.bind(&note.language)
+	.bind(unix_now())
+	.bind(note_id)
+	.execute(&pool)
+	.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Note not found: {note_id}")));
+	}
+
+	fetch_note(&pool, note_id).await
+}
+
+#[tauri::command]
+pub async fn delete_note(db: tauri::State<'_, DbPool>, note_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
+	let result = sqlx::query("DELETE FROM FileNotes WHERE note_id = ?")
+		.bind(note_id)
+		.execute(&pool)
+		.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Note not found: {note_id}")));
+	}
+	Ok(())
+}
+
+/// Get a file's notes, top to bottom; files hidden by safe mode have none
+#[tauri::command]
+pub async fn get_file_notes(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<Vec<FileNote>, AppError> {
+	let pool = db.get();
+	if !is_file_visible(&pool, &file_hash).await? {
+		return Ok(Vec::new());
+	}
+	notes_for_file(&pool, &file_hash).await
+}
+
+/// Full-text search over note bodies, best matches first
+/// All words must match; `language` restricts results to notes in that language
+#[tauri::command]
+pub async fn search_notes(
+	db: tauri::State<'_, DbPool>,
+	query: String,
+	language: Option<String>,
+	limit: Option<i64>,
+) -> Result<Vec<NoteSearchResult>, AppError> {
+	let pool = db.get();
+	let Some(fts) = fts_query(&query) else {
+		return Ok(Vec::new());
+	};
+	let language = language
+		.map(|l| l.trim().to_string())
+		.filter(|l| !l.is_empty());
+
+	let rows = sqlx::query(&format!(
+		r#"
+        SELECT {NOTE_COLUMNS},
+               snippet(FileNotesFts, 0, char(57344), char(57345), '…', 16) as snippet
+        FROM FileNotesFts
+        JOIN FileNotes n ON n.note_id = FileNotesFts.rowid
+        WHERE FileNotesFts MATCH ?
+          AND (? IS NULL OR n.language = ? COLLATE NOCASE)
+          AND {visible}
+        ORDER BY bm25(FileNotesFts)
+        LIMIT ?
+        "#,
+		visible = visible_files_condition("n.file_hash")
+	))
+	.bind(fts)
+	.bind(&language)
+	.bind(&language)
+	.bind(limit.filter(|l| *l > 0).unwrap_or(DEFAULT_SEARCH_LIMIT))
+	.fetch_all(&pool)
+	.await?;
+
+	Ok(rows
+		.iter()
+		.map(|row| NoteSearchResult {
+			note: note_from_row(row),
+			snippet: fts_snippet_html(&row.get::<String, _>("snippet")),
+		})
+		.collect())
+}
+
+/// Save a file's notes as JSON; without a destination the file goes next to the
+/// original as `<original>.notes.json`. Returns the path written.
+#[tauri::command]
+pub async fn export_file_notes(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	destination: Option<String>,
+) -> Result<String, AppError> {
+	let pool = db.get();
+	// Files hidden by safe mode answer like unknown ones
+	if !is_file_visible(&pool, &file_hash).await? {
+		return Err(AppError::Custom(format!("File not found: {file_hash}")));
+	}
+	let path = match destination {
+		Some(destination) => PathBuf::from(destination),
+		None => {
+			let original: String =
+				sqlx::query_scalar("SELECT original_path FROM Files WHERE file_hash = ?")
+					.bind(&file_hash)
+					.fetch_optional(&pool)
+					.await?
+					.ok_or_else(|| AppError::Custom(format!("File not found: {file_hash}")))?;
+			notes_json_path(Path::new(&original))
+		}
+	};
+
+	write_notes_json(&pool, &file_hash, &path).await?;
+	Ok(path.display().to_string())
+}
@@ -0,0 +1,543 @@
+use super::files::{file_record_from_row, FileRecord, ProgressEvent, FILE_RECORD_COLUMNS};
+use super::notes::export_notes_beside;
+use super::safe_mode::{is_file_visible, visible_files_condition};
+use super::search::SearchCondition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Pool {
+	pub pool_id: i64,
+	pub name: String,
+	pub description: Option<String>,
+	pub member_count: i64,
+	/// First member, for showing the pool
+	pub cover_hash: Option<String>,
+	pub created_at: i64, // Unix timestamp
+	pub updated_at: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct PoolMember {
+	pub member_id: i64,
+	/// 0-based index in the pool
+	pub position: i64,
+	pub file: FileRecord,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PoolExportResult {
+	pub destination: String,
+	pub exported: usize,
+	/// Exported images that got a `.notes.json` beside them
+	pub notes_exported: usize,
+	/// Originals that were missing on disk, by hash
+	pub missing: Vec<String>,
+}
+
+/// Columns selected for a `Pool`, qualified with the `p` alias for `Pools`
+/// Members hidden by safe mode are neither counted nor picked as the cover
+fn pool_columns() -> String {
+	let visible = visible_files_condition("m.file_hash");
+	format!(
+		r#"
+    p.pool_id, p.name, p.description, p.created_at, p.updated_at,
+    (SELECT COUNT(*) FROM PoolMembers m WHERE m.pool_id = p.pool_id AND {visible}) as member_count,
+    (SELECT m.file_hash FROM PoolMembers m WHERE m.pool_id = p.pool_id AND {visible}
+     ORDER BY m.position, m.member_id LIMIT 1) as cover_hash
+"#
+	)
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn pool_from_row(row: &SqliteRow) -> Pool {
+	Pool {
+		pool_id: row.get("pool_id"),
+		name: row.get("name"),
+		description: row.get("description"),
+		member_count: row.get("member_count"),
+		cover_hash: row.get("cover_hash"),
+		created_at: row.get("created_at"),
+		updated_at: row.get("updated_at"),
+	}
+}
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+fn normalize_pool_name(name: &str) -> Result<String, AppError> {
+	let name = name.trim();
+	if name.is_empty() {
+		return Err(AppError::Custom("Pool name cannot be empty".to_string()));
+	}
+	Ok(name.to_string())
+}
+
+fn clean_description(description: Option<String>) -> Option<String> {
+	description
+		.map(|d| d.trim().to_string())
+		.filter(|d| !d.is_empty())
+}
+
+async fn fetch_pool(pool: &SqlitePool, pool_id: i64) -> Result<Pool, AppError> {
+	let query = format!("SELECT {} FROM Pools p WHERE p.pool_id = ?", pool_columns());
+	sqlx::query(&query)
+		.bind(pool_id)
+		.fetch_optional(pool)
+		.await?
+		.map(|row| pool_from_row(&row))
+		.ok_or_else(|| AppError::Custom(format!("Pool {pool_id} not found")))
+}
+
+/// Member IDs of a pool, in order; fails if the pool doesn't exist
+async fn ordered_member_ids(
+	conn: &mut SqliteConnection,
+	pool_id: i64,
+) -> Result<Vec<i64>, AppError> {
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Pools WHERE pool_id = ?)")
+		.bind(pool_id)
+		.fetch_one(&mut *conn)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("Pool {pool_id} not found")));
+	}
+
+	Ok(sqlx::query_scalar(
+		"SELECT member_id FROM PoolMembers WHERE pool_id = ? ORDER BY position, member_id",
+	)
+	.bind(pool_id)
+	.fetch_all(&mut *conn)
+	.await?)
+}
+
+/// Renumber members 0..n in the given order and bump the pool's `updated_at`
+async fn write_positions(
+	conn: &mut SqliteConnection,
+	pool_id: i64,
+	member_ids: &[i64],
+) -> Result<(), AppError> {
+	for (position, member_id) in member_ids.iter().enumerate() {
+		sqlx::query("UPDATE PoolMembers SET position = ? WHERE member_id = ?")
+			.bind(position as i64)
+			.bind(member_id)
+			.execute(&mut *conn)
+			.await?;
+	}
+	sqlx::query("UPDATE Pools SET updated_at = ? WHERE pool_id = ?")
+		.bind(unix_now())
+		.bind(pool_id)
+		.execute(&mut *conn)
+		.await?;
+	Ok(())
+}
+
+/// Move `count` items starting at `from` so the range starts at `to` in the result
+fn move_range<T>(items: &mut Vec<T>, from: usize, count: usize, to: usize) -> Result<(), AppError> {
+	let end = from
+		.checked_add(count)
+		.filter(|end| count > 0 && *end <= items.len())
+		.ok_or_else(|| {
+			AppError::Custom(format!(
+				"Invalid range of {count} items from {from} for a pool of {} items",
+				items.len()
+			))
+		})?;
+	let moved: Vec<T> = items.drain(from..end).collect();
+	let to = to.min(items.len());
+	items.splice(to..to, moved);
+	Ok(())
+}
+
+/// `001_name.ext`, at least three digits and wide enough for the whole pool
+fn export_file_name(index: usize, total: usize, original_path: &Path) -> String {
+	let width = total.to_string().len().max(3);
+	let name = original_path
+		.file_name()
+		.map(|n| n.to_string_lossy().to_string())
+		.unwrap_or_default();
+	format!("{:0width$}_{name}", index + 1)
+}
+
+/// Search condition for `pool:<name>`; spaces in pool names may be written as `_`
+pub(crate) fn search_condition(name: &str) -> SearchCondition {
+	SearchCondition::new(
+		r#"f.file_hash IN (
+            SELECT pm.file_hash FROM PoolMembers pm
+            JOIN Pools p ON p.pool_id = pm.pool_id
+            WHERE p.name = ? OR REPLACE(p.name, ' ', '_') = ? COLLATE NOCASE
+        )"#,
+		vec![name.to_string(), name.to_string()],
+	)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn create_pool(
+	db: tauri::State<'_, DbPool>,
+	name: String,
+	description: Option<String>,
+) -> Result<Pool, AppError> {
+	let pool = db.get();
+	let name = normalize_pool_name(&name)?;
+	let now = unix_now();
+
+	let pool_id: i64 = sqlx::query_scalar(
+		r#"
+        INSERT INTO Pools (name, description, created_at, updated_at)
+        VALUES (?, ?, ?, ?)
+        ON CONFLICT(name) DO NOTHING
+        RETURNING pool_id
+        "#,
+	)
+	.bind(&name)
+	.bind(clean_description(description))
+	.bind(now)
+	.bind(now)
+	.fetch_optional(&pool)
+	.await?
+	.ok_or_else(|| AppError::Custom(format!("A pool named '{name}' already exists")))?;
+
+	fetch_pool(&pool, pool_id).await
+}
+
+#[tauri::command]
+pub async fn get_all_pools(db: tauri::State<'_, DbPool>) -> Result<Vec<Pool>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		"SELECT {} FROM Pools p ORDER BY p.name COLLATE NOCASE",
+		pool_columns()
+	);
+	let rows = sqlx::query(&query).fetch_all(&pool).await?;
+	Ok(rows.iter().map(pool_from_row).collect())
+}
+
+/// Get the pools a file is in
+#[tauri::command]
+pub async fn get_file_pools(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<Vec<Pool>, AppError> {
+	let pool = db.get();
+	if !is_file_visible(&pool, &file_hash).await? {
+		return Ok(Vec::new());
+	}
+	let query = format!(
+		r#"
+        SELECT {columns} FROM Pools p
+        WHERE p.pool_id IN (SELECT pool_id FROM PoolMembers WHERE file_hash = ?)
+        ORDER BY p.name COLLATE NOCASE
+        "#,
+		columns = pool_columns()
+	);
+	let rows = sqlx::query(&query)
+		.bind(&file_hash)
+		.fetch_all(&pool)
+		.await?;
+	Ok(rows.iter().map(pool_from_row).collect())
+}
+
+#[tauri::command]
+pub async fn update_pool(
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+	name: String,
+	description: Option<String>,
+) -> Result<Pool, AppError> {
+	let pool = db.get();
+	let name = normalize_pool_name(&name)?;
+
+	let taken: Option<i64> =
+		sqlx::query_scalar("SELECT pool_id FROM Pools WHERE name = ? AND pool_id != ?")
+			.bind(&name)
+			.bind(pool_id)
+			.fetch_optional(&pool)
+			.await?;
+	if taken.is_some() {
+		return Err(AppError::Custom(format!(
+			"A pool named '{name}' already exists"
+		)));
+	}
+
+	let result =
+		sqlx::query("UPDATE Pools SET name = ?, description = ?, updated_at = ? WHERE pool_id = ?")
+			.bind(&name)
+			.bind(clean_description(description))
+			.bind(unix_now())
+			.bind(pool_id)
+			.execute(&pool)
+			.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Pool {pool_id} not found")));
+	}
+
+	fetch_pool(&pool, pool_id).await
+}
+
+/// Delete a pool; its files are kept
+#[tauri::command]
+pub async fn delete_pool(db: tauri::State<'_, DbPool>, pool_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
+	let result = sqlx::query("DELETE FROM Pools WHERE pool_id = ?")
+		.bind(pool_id)
+		.execute(&pool)
+		.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Pool {pool_id} not found")));
+	}
+	Ok(())
+}
+
+/// Get the members of a pool, in order
+#[tauri::command]
+pub async fn get_pool_members(
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+) -> Result<Vec<PoolMember>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}, pm.member_id, {visible} as visible
+        FROM PoolMembers pm
+        JOIN Files f ON f.file_hash = pm.file_hash
+        WHERE pm.pool_id = ?
+        ORDER BY pm.position, pm.member_id
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let rows = sqlx::query(&query).bind(pool_id).fetch_all(&pool).await?;
+
+	// Members hidden by safe mode are left out after numbering, so positions stay the ones
+	// `move_pool_members` works with
+	Ok(rows
+		.iter()
+		.enumerate()
+		.filter(|(_, row)| row.get::<bool, _>("visible"))
+		.map(|(position, row)| PoolMember {
+			member_id: row.get("member_id"),
+			position: position as i64,
+			file: file_record_from_row(row),
+		})
+		.collect())
+}
+
+/// Insert files into a pool at `position` (0-based), or append them
+/// A file may be added more than once; see `dedupe_pool`
+#[tauri::command]
+pub async fn add_files_to_pool(
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+	file_hashes: Vec<String>,
+	position: Option<usize>,
+) -> Result<Pool, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	let mut member_ids = ordered_member_ids(&mut tx, pool_id).await?;
+	let now = unix_now();
+
+	let mut new_ids = Vec::with_capacity(file_hashes.len());
+	for file_hash in &file_hashes {
+		let exists: bool =
+			sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+				.bind(file_hash)
+				.fetch_one(&mut *tx)
+				.await?;
+		if !exists {
+			return Err(AppError::Custom(format!("File not found: {file_hash}")));
+		}
+
+		let member_id: i64 = sqlx::query_scalar(
+			r#"
+            INSERT INTO PoolMembers (pool_id, file_hash, position, added_at)
+            VALUES (?, ?, 0, ?)
+            RETURNING member_id
+            "#,
+		)
+		.bind(pool_id)
+		.bind(file_hash)
+		.bind(now)
+		.fetch_one(&mut *tx)
+		.await?;
+		new_ids.push(member_id);
+	}
+
+	let index = position.unwrap_or(member_ids.len()).min(member_ids.len());
+	member_ids.splice(index..index, new_ids);
+	write_positions(&mut tx, pool_id, &member_ids).await?;
+	tx.commit().await?;
+
+	fetch_pool(&pool, pool_id).await
+}
+
+/// Move `count` members starting at index `from` so they start at index `to`
+/// (`to` counts positions after the range is taken out, as when dropping a dragged selection)
+#[tauri::command]
+pub async fn move_pool_members(
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+	from: usize,
+	count: usize,
+	to: usize,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	let mut member_ids = ordered_member_ids(&mut tx, pool_id).await?;
+	move_range(&mut member_ids, from, count, to)?;
+	write_positions(&mut tx, pool_id, &member_ids).await?;
+	tx.commit().await?;
+	Ok(())
+}
+
+/// Remove members (by `member_id`) from a pool
+#[tauri::command]
+pub async fn remove_pool_members(
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+	member_ids: Vec<i64>,
+) -> Result<usize, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	let remove: HashSet<i64> = member_ids.into_iter().collect();
+	let (removed, kept): (Vec<i64>, Vec<i64>) = ordered_member_ids(&mut tx, pool_id)
+		.await?
+		.into_iter()
+		.partition(|id| remove.contains(id));
+
+	for member_id in &removed {
+		sqlx::query("DELETE FROM PoolMembers WHERE member_id = ?")
+			.bind(member_id)
+			.execute(&mut *tx)
+			.await?;
+	}
+	write_positions(&mut tx, pool_id, &kept).await?;
+	tx.commit().await?;
+
+	Ok(removed.len())
+}
+
+/// Remove repeated files from a pool, keeping each file's first occurrence
+/// Returns the number of members removed
+#[tauri::command]
+pub async fn dedupe_pool(db: tauri::State<'_, DbPool>, pool_id: i64) -> Result<usize, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ordered_member_ids(&mut tx, pool_id).await?;
+
+	let rows = sqlx::query(
+		"SELECT member_id, file_hash FROM PoolMembers WHERE pool_id = ? ORDER BY position, member_id",
+	)
+	.bind(pool_id)
+	.fetch_all(&mut *tx)
+	.await?;
+
+	let mut seen = HashSet::new();
+	let mut kept = Vec::new();
+	let mut removed = 0;
+	for row in rows {
+		let member_id: i64 = row.get("member_id");
+		if seen.insert(row.get::<String, _>("file_hash")) {
+			kept.push(member_id);
+			continue;
+		}
+		sqlx::query("DELETE FROM PoolMembers WHERE member_id = ?")
+			.bind(member_id)
+			.execute(&mut *tx)
+			.await?;
+		removed += 1;
+	}
+
+	if removed > 0 {
+		write_positions(&mut tx, pool_id, &kept).await?;
+	}
+	tx.commit().await?;
+
+	Ok(removed)
+}
+
+/// Copy a pool's originals into a folder, numbered in pool order (`001_name.jpg`, ...)
+/// Images with region notes get them as `001_name.jpg.notes.json`
+/// Emits pool_export_progress events
+#[tauri::command]
+pub async fn export_pool(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	pool_id: i64,
+	destination: String,
+) -> Result<PoolExportResult, AppError> {
+	let pool = db.get();
+	let info = fetch_pool(&pool, pool_id).await?;
+	let destination = PathBuf::from(destination);
+	std::fs::create_dir_all(&destination)?;
+
+	// Files hidden by safe mode are left out
+	let query = format!(
+		r#"
+        SELECT f.file_hash, f.original_path
+        FROM PoolMembers pm
+        JOIN Files f ON f.file_hash = pm.file_hash
+        WHERE pm.pool_id = ? AND {visible}
+        ORDER BY pm.position, pm.member_id
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let members: Vec<(String, String)> = sqlx::query_as(&query)
+		.bind(pool_id)
+		.fetch_all(&pool)
+		.await?;
+
+	let total = members.len();
+	let mut result = PoolExportResult {
+		destination: destination.display().to_string(),
+		exported: 0,
+		notes_exported: 0,
+		missing: Vec::new(),
+	};
+
+	for (index, (file_hash, original_path)) in members.into_iter().enumerate() {
+		let source = PathBuf::from(&original_path);
+		if source.is_file() {
+			let target = destination.join(export_file_name(index, total, &source));
+			std::fs::copy(&source, &target)?;
+			result.exported += 1;
+			if export_notes_beside(&pool, &file_hash, &target).await? {
+				result.notes_exported += 1;
+			}
+		} else {
+			result.missing.push(file_hash.clone());
+		}
+
+		app.emit(
+			"pool_export_progress",
+			ProgressEvent {
+				stage: "exporting".to_string(),
+				message: format!("Exporting {} of {total} from {}", index + 1, info.name),
+				file_hash: Some(file_hash),
+				current: Some(index + 1),
+				total: Some(total),
+			},
+		)
+		.ok();
+	}
+
+	Ok(result)
+}
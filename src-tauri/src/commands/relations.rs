@@ -0,0 +1,562 @@
+use super::files::{file_record_from_row, FileRecord, FILE_RECORD_COLUMNS};
+use super::safe_mode::visible_files_condition;
+use super::search::SearchCondition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationType {
+	/// `related_hash` is the parent of `file_hash`; a file has at most one parent
+	Parent,
+	/// Alternate versions (censored/uncensored, color variants, resolutions)
+	Variant,
+	/// `file_hash` was made from `related_hash` (crop, edit, upscale)
+	DerivedFrom,
+	/// Taken from the same post or source
+	SameSource,
+}
+
+impl RelationType {
+	const ALL: [RelationType; 4] = [
+		RelationType::Parent,
+		RelationType::Variant,
+		RelationType::DerivedFrom,
+		RelationType::SameSource,
+	];
+
+	pub fn as_str(self) -> &'static str {
+		match self {
+			RelationType::Parent => "parent",
+			RelationType::Variant => "variant",
+			RelationType::DerivedFrom => "derived_from",
+			RelationType::SameSource => "same_source",
+		}
+	}
+
+	fn parse(value: &str) -> Option<Self> {
+		Self::ALL.into_iter().find(|t| t.as_str() == value)
+	}
+
+	/// Symmetric relations are stored once, with the smaller hash in `file_hash`
+	fn is_symmetric(self) -> bool {
+		matches!(self, RelationType::Variant | RelationType::SameSource)
+	}
+}
+
+/// What a related file is to the file being looked at
+#[derive(Debug, Serialize, Clone, Copy, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RelationRole {
+	Parent,
+	Child,
+	Variant,
+	/// The file being looked at was derived from this one
+	Original,
+	Derivative,
+	SameSource,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileRelation {
+	pub relation_id: i64,
+	pub file_hash: String,
+	pub related_hash: String,
+	pub relation_type: RelationType,
+	pub created_at: i64, // Unix timestamp
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct RelatedFile {
+	pub relation_id: i64,
+	pub relation_type: RelationType,
+	pub role: RelationRole,
+	pub file: FileRecord,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct TraversedFile {
+	/// Number of relation hops from the starting file
+	pub depth: i64,
+	pub file: FileRecord,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TraversalDirection {
+	/// Parents and originals, transitively
+	Ancestors,
+	/// Children and derivatives, transitively
+	Descendants,
+	/// Everything reachable through any relation
+	#[default]
+	Connected,
+}
+
+const DEFAULT_MAX_DEPTH: u32 = 10;
+const MAX_DEPTH_LIMIT: u32 = 100;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+pub(crate) fn relation_from_row(row: &SqliteRow) -> Result<FileRelation, AppError> {
+	let relation_type: String = row.get("relation_type");
+	Ok(FileRelation {
+		relation_id: row.get("relation_id"),
+		file_hash: row.get("file_hash"),
+		related_hash: row.get("related_hash"),
+		relation_type: RelationType::parse(&relation_type)
+			.ok_or_else(|| AppError::Custom(format!("Unknown relation type: {relation_type}")))?,
+		created_at: row.get("created_at"),
+	})
+}
+
+fn now() -> Result<i64, AppError> {
+	Ok(SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|e| AppError::Custom(format!("Invalid system time: {e}")))?
+		.as_secs() as i64)
+}
+
+/// Whether `ancestor` is reachable from `file_hash` by following `relation_type` edges
+async fn is_ancestor(
+	conn: &mut SqliteConnection,
+	ancestor: &str,
+	file_hash: &str,
+	relation_type: RelationType,
+) -> Result<bool, AppError> {
+	Ok(sqlx::query_scalar(
+		r#"
+        WITH RECURSIVE up(hash) AS (
+            SELECT ?
+            UNION
+            SELECT r.related_hash FROM FileRelations r
+            JOIN up ON r.file_hash = up.hash
+            WHERE r.relation_type = ?
+        )
+        SELECT EXISTS(SELECT 1 FROM up WHERE hash = ?)
+        "#,
+	)
+	.bind(file_hash)
+	.bind(relation_type.as_str())
+	.bind(ancestor)
+	.fetch_one(&mut *conn)
+	.await?)
+}
+
+/// Insert a relation, normalizing symmetric ones
+///
+/// Returns `Ok(false)` without inserting when the relation exists, would be a self-link,
+/// would create a cycle, or would give a file a second parent.
+pub(crate) async fn insert_relation(
+	conn: &mut SqliteConnection,
+	file_hash: &str,
+	related_hash: &str,
+	relation_type: RelationType,
+	created_at: i64,
+) -> Result<bool, AppError> {
+	if file_hash == related_hash {
+		return Ok(false);
+	}
+	let (file_hash, related_hash) = if relation_type.is_symmetric() && related_hash < file_hash {
+		(related_hash, file_hash)
+	} else {
+		(file_hash, related_hash)
+	};
+
+	if !relation_type.is_symmetric()
+		&& is_ancestor(conn, file_hash, related_hash, relation_type).await?
+	{
+		return Ok(false);
+	}
+
+	if relation_type == RelationType::Parent {
+		let has_parent: bool = sqlx::query_scalar(
+			"SELECT EXISTS(SELECT 1 FROM FileRelations WHERE file_hash = ? AND relation_type = 'parent')",
+		)
+		.bind(file_hash)
+		.fetch_one(&mut *conn)
+		.await?;
+		if has_parent {
+			return Ok(false);
+		}
+	}
+
+	let result = sqlx::query(
+		r#"
+        INSERT OR IGNORE INTO FileRelations (file_hash, related_hash, relation_type, created_at)
+        VALUES (?, ?, ?, ?)
+        "#,
+	)
+	.bind(file_hash)
+	.bind(related_hash)
+	.bind(relation_type.as_str())
+	.bind(created_at)
+	.execute(&mut *conn)
+	.await?;
+
+	Ok(result.rows_affected() > 0)
+}
+
+/// Move every relation of `from_hash` to `to_hash` (when resolving duplicates)
+/// Relations that would become self-links, cycles or a second parent are dropped
+pub(crate) async fn transfer_relations(
+	conn: &mut SqliteConnection,
+	from_hash: &str,
+	to_hash: &str,
+) -> Result<usize, AppError> {
+	let rows = sqlx::query(
+		r#"
+        SELECT relation_id, file_hash, related_hash, relation_type, created_at
+        FROM FileRelations
+        WHERE file_hash = ? OR related_hash = ?
+        ORDER BY created_at, relation_id
+        "#,
+	)
+	.bind(from_hash)
+	.bind(from_hash)
+	.fetch_all(&mut *conn)
+	.await?;
+	let relations = rows
+		.iter()
+		.map(relation_from_row)
+		.collect::<Result<Vec<_>, _>>()?;
+
+	sqlx::query("DELETE FROM FileRelations WHERE file_hash = ? OR related_hash = ?")
+		.bind(from_hash)
+		.bind(from_hash)
+		.execute(&mut *conn)
+		.await?;
+
+	let replace = |hash: &str| {
+		if hash == from_hash {
+			to_hash.to_string()
+		} else {
+			hash.to_string()
+		}
+	};
+
+	let mut transferred = 0;
+	for relation in relations {
+		let file_hash = replace(&relation.file_hash);
+		let related_hash = replace(&relation.related_hash);
+		if insert_relation(
+			conn,
+			&file_hash,
+			&related_hash,
+			relation.relation_type,
+			relation.created_at,
+		)
+		.await?
+		{
+			transferred += 1;
+		}
+	}
+
+	Ok(transferred)
+}
+
+fn parse_hash_prefix(value: &str) -> Result<String, AppError> {
+	let prefix = value.to_lowercase();
+	if prefix.is_empty() || !prefix.chars().all(|c| c.is_ascii_hexdigit()) {
+		return Err(AppError::Custom(format!("Invalid file hash: {value}")));
+	}
+	Ok(format!("{prefix}%"))
+}
+
+/// Search condition for relation operators (`has:parent`, `child_of:<hash>`, ...)
+/// Hashes may be shortened to a prefix; returns `None` for other operators
+pub(crate) fn search_condition(
+	key: &str,
+	value: &str,
+) -> Option<Result<SearchCondition, AppError>> {
+	// One side of relations of a type, optionally with the other side matching a hash prefix
+	let side = |column: &str, relation_type: &str, other: Option<&str>| match other {
+		Some(other) => format!(
+			"SELECT {column} FROM FileRelations WHERE relation_type = '{relation_type}' AND {other} LIKE ?"
+		),
+		None => {
+			format!("SELECT {column} FROM FileRelations WHERE relation_type = '{relation_type}'")
+		}
+	};
+	let condition = |subquery: String, binds: Vec<String>| {
+		SearchCondition::new(format!("f.file_hash IN ({subquery})"), binds)
+	};
+
+	let result = match key {
+		"has" => {
+			let subquery = match value.to_lowercase().as_str() {
+				"parent" => side("file_hash", "parent", None),
+				"children" | "child" => side("related_hash", "parent", None),
+				"variants" | "variant" => format!(
+					"{} UNION {}",
+					side("file_hash", "variant", None),
+					side("related_hash", "variant", None)
+				),
+				"relations" | "relation" => {
+					"SELECT file_hash FROM FileRelations UNION SELECT related_hash FROM FileRelations"
+						.to_string()
+				}
+				_ => return None,
+			};
+			Ok(condition(subquery, Vec::new()))
+		}
+		"child_of" | "parent_of" | "derived_from" | "variant_of" | "same_source_as" => {
+			parse_hash_prefix(value).map(|prefix| match key {
+				"child_of" => condition(
+					side("file_hash", "parent", Some("related_hash")),
+					vec![prefix],
+				),
+				"parent_of" => condition(
+					side("related_hash", "parent", Some("file_hash")),
+					vec![prefix],
+				),
+				"derived_from" => condition(
+					side("file_hash", "derived_from", Some("related_hash")),
+					vec![prefix],
+				),
+				_ => {
+					let relation_type = if key == "variant_of" {
+						"variant"
+					} else {
+						"same_source"
+					};
+					condition(
+						format!(
+							"{} UNION {}",
+							side("file_hash", relation_type, Some("related_hash")),
+							side("related_hash", relation_type, Some("file_hash"))
+						),
+						vec![prefix.clone(), prefix],
+					)
+				}
+			})
+		}
+		_ => return None,
+	};
+
+	Some(result)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Link two files: "`file_hash` <relation_type> `related_hash`"
+/// Setting a parent replaces the file's previous parent
+#[tauri::command]
+pub async fn link_files(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	related_hash: String,
+	relation_type: RelationType,
+) -> Result<FileRelation, AppError> {
+	let pool = db.get();
+	if file_hash == related_hash {
+		return Err(AppError::Custom(
+			"A file cannot be related to itself".to_string(),
+		));
+	}
+
+	let mut tx = pool.begin().await?;
+	for hash in [&file_hash, &related_hash] {
+		let exists: bool =
+			sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+				.bind(hash)
+				.fetch_one(&mut *tx)
+				.await?;
+		if !exists {
+			return Err(AppError::Custom(format!("File not found: {hash}")));
+		}
+	}
+
+	if !relation_type.is_symmetric()
+		&& is_ancestor(&mut tx, &file_hash, &related_hash, relation_type).await?
+	{
+		return Err(AppError::Custom(format!(
+			"Linking would create a {} cycle",
+			relation_type.as_str()
+		)));
+	}
+
+	if relation_type == RelationType::Parent {
+		sqlx::query("DELETE FROM FileRelations WHERE file_hash = ? AND relation_type = 'parent'")
+			.bind(&file_hash)
+			.execute(&mut *tx)
+			.await?;
+	}
+
+	insert_relation(&mut tx, &file_hash, &related_hash, relation_type, now()?).await?;
+
+	let (stored_file, stored_related) = if relation_type.is_symmetric() && related_hash < file_hash
+	{
+		(&related_hash, &file_hash)
+	} else {
+		(&file_hash, &related_hash)
+	};
+	let row = sqlx::query(
+		r#"
+        SELECT relation_id, file_hash, related_hash, relation_type, created_at
+        FROM FileRelations
+        WHERE file_hash = ? AND related_hash = ? AND relation_type = ?
+        "#,
+	)
+	.bind(stored_file)
+	.bind(stored_related)
+	.bind(relation_type.as_str())
+	.fetch_one(&mut *tx)
+	.await?;
+	tx.commit().await?;
+
+	relation_from_row(&row)
+}
+
+#[tauri::command]
+pub async fn unlink_files(db: tauri::State<'_, DbPool>, relation_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
+	let result = sqlx::query("DELETE FROM FileRelations WHERE relation_id = ?")
+		.bind(relation_id)
+		.execute(&pool)
+		.await?;
+
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!(
+			"Relation {relation_id} not found"
+		)));
+	}
+	Ok(())
+}
+
+/// Get the files directly related to a file, with how each relates to it
+#[tauri::command]
+pub async fn get_file_relations(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<Vec<RelatedFile>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}, r.relation_id, r.relation_type, r.file_hash = ? as outgoing
+        FROM FileRelations r
+        JOIN Files f ON f.file_hash = CASE WHEN r.file_hash = ? THEN r.related_hash ELSE r.file_hash END
+        WHERE (r.file_hash = ? OR r.related_hash = ?) AND {visible}
+        ORDER BY r.relation_type, r.created_at
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let rows = sqlx::query(&query)
+		.bind(&file_hash)
+		.bind(&file_hash)
+		.bind(&file_hash)
+		.bind(&file_hash)
+		.fetch_all(&pool)
+		.await?;
+
+	rows.iter()
+		.map(|row| {
+			let relation_type: String = row.get("relation_type");
+			let relation_type = RelationType::parse(&relation_type).ok_or_else(|| {
+				AppError::Custom(format!("Unknown relation type: {relation_type}"))
+			})?;
+			let outgoing: bool = row.get("outgoing");
+			let role = match (relation_type, outgoing) {
+				(RelationType::Parent, true) => RelationRole::Parent,
+				(RelationType::Parent, false) => RelationRole::Child,
+				(RelationType::DerivedFrom, true) => RelationRole::Original,
+				(RelationType::DerivedFrom, false) => RelationRole::Derivative,
+				(RelationType::Variant, _) => RelationRole::Variant,
+				(RelationType::SameSource, _) => RelationRole::SameSource,
+			};
+			Ok(RelatedFile {
+				relation_id: row.get("relation_id"),
+				relation_type,
+				role,
+				file: file_record_from_row(row),
+			})
+		})
+		.collect()
+}
+
+/// Walk relations from a file, nearest first
+/// Ancestors and descendants only follow `parent` and `derived_from`
+#[tauri::command]
+pub async fn traverse_relations(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	direction: Option<TraversalDirection>,
+	relation_types: Option<Vec<RelationType>>,
+	max_depth: Option<u32>,
+) -> Result<Vec<TraversedFile>, AppError> {
+	let pool = db.get();
+	let direction = direction.unwrap_or_default();
+	let max_depth = max_depth.unwrap_or(DEFAULT_MAX_DEPTH).min(MAX_DEPTH_LIMIT);
+
+	let types: Vec<RelationType> = relation_types
+		.unwrap_or_else(|| RelationType::ALL.to_vec())
+		.into_iter()
+		.filter(|t| direction == TraversalDirection::Connected || !t.is_symmetric())
+		.collect();
+	if types.is_empty() {
+		return Ok(Vec::new());
+	}
+	let type_list = types
+		.iter()
+		.map(|t| format!("'{}'", t.as_str()))
+		.collect::<Vec<_>>()
+		.join(", ");
+
+	let up = format!(
+		"SELECT file_hash as src, related_hash as dst FROM FileRelations WHERE relation_type IN ({type_list})"
+	);
+	let down = format!(
+		"SELECT related_hash as src, file_hash as dst FROM FileRelations WHERE relation_type IN ({type_list})"
+	);
+	let edges = match direction {
+		TraversalDirection::Ancestors => up,
+		TraversalDirection::Descendants => down,
+		TraversalDirection::Connected => format!("{up} UNION {down}"),
+	};
+
+	let query = format!(
+		r#"
+        WITH RECURSIVE edges(src, dst) AS ({edges}),
+        walk(hash, depth) AS (
+            SELECT ?, 0
+            UNION
+            SELECT e.dst, w.depth + 1 FROM walk w
+            JOIN edges e ON e.src = w.hash
+            WHERE w.depth < ?
+        )
+        SELECT {FILE_RECORD_COLUMNS}, MIN(w.depth) as depth
+        FROM walk w
+        JOIN Files f ON f.file_hash = w.hash
+        WHERE w.hash != ? AND {visible}
+        GROUP BY f.file_hash
+        ORDER BY depth, f.date_imported
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let rows = sqlx::query(&query)
+		.bind(&file_hash)
+		.bind(max_depth as i64)
+		.bind(&file_hash)
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows
+		.iter()
+		.map(|row| TraversedFile {
+			depth: row.get("depth"),
+			file: file_record_from_row(row),
+		})
+		.collect())
+}
@@ -0,0 +1,316 @@
+use crate::db::DbPool;
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::SqlitePool;
+use std::sync::RwLock;
+use std::time::Duration;
+use tauri::AppHandle;
+use tauri_plugin_store::StoreExt;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Which files stay visible while safe mode is on, by their AI rating tag
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct SafeModeSettings {
+	pub enabled: bool,
+	/// Rating tags (`general`, `sensitive`, `questionable`, `explicit`) whose files stay visible
+	pub allowed_ratings: Vec<String>,
+	/// Keep files without a rating tag (never AI tagged) visible
+	pub allow_unrated: bool,
+}
+
+impl Default for SafeModeSettings {
+	fn default() -> Self {
+		Self {
+			enabled: false,
+			allowed_ratings: vec!["general".to_string()],
+			allow_unrated: true,
+		}
+	}
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SafeModeStatus {
+	#[serde(flatten)]
+	pub settings: SafeModeSettings,
+	pub pin_set: bool,
+	/// Unlocked with the PIN until locked again or the app restarts
+	pub unlocked: bool,
+	/// Whether file listings are being filtered
+	pub active: bool,
+	/// Rating tags in the library
+	pub available_ratings: Vec<String>,
+}
+
+/// A PIN as stored: iterated SHA-256 with a random salt, both hex
+#[derive(Debug, Serialize, Deserialize, Clone)]
+struct PinHash {
+	salt: String,
+	hash: String,
+}
+
+#[derive(Default)]
+struct SafeModeState {
+	settings: SafeModeSettings,
+	pin: Option<PinHash>,
+	unlocked: bool,
+}
+
+impl SafeModeState {
+	fn is_active(&self) -> bool {
+		self.settings.enabled && !self.unlocked
+	}
+}
+
+/// Loaded from the store at startup so queries can check it without an `AppHandle`
+static SAFE_MODE: Lazy<RwLock<SafeModeState>> = Lazy::new(Default::default);
+
+const SAFE_MODE_STORE: &str = "safe-mode-settings.json";
+
+const PIN_HASH_ROUNDS: u32 = 100_000;
+const PIN_LENGTH: std::ops::RangeInclusive<usize> = 4..=12;
+
+/// Wait after a wrong PIN, to slow down guessing
+const WRONG_PIN_DELAY: Duration = Duration::from_secs(1);
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn hash_pin(pin: &str, salt: &str) -> String {
+	let mut digest = Sha256::new()
+		.chain_update(salt)
+		.chain_update(pin)
+		.finalize();
+	for _ in 1..PIN_HASH_ROUNDS {
+		digest = Sha256::new()
+			.chain_update(salt)
+			.chain_update(digest)
+			.finalize();
+	}
+	format!("{digest:x}")
+}
+
+/// Check `pin` against the stored PIN; passes when no PIN is set
+async fn verify_pin(pin: Option<&str>) -> Result<(), AppError> {
+	let Some(stored) = SAFE_MODE
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.pin
+		.clone()
+	else {
+		return Ok(());
+	};
+
+	let matches = pin.is_some_and(|pin| hash_pin(pin.trim(), &stored.salt) == stored.hash);
+	if !matches {
+		tokio::time::sleep(WRONG_PIN_DELAY).await;
+		return Err(AppError::Custom("Wrong PIN".to_string()));
+	}
+	Ok(())
+}
+
+fn save_safe_mode(app: &AppHandle) -> Result<(), AppError> {
+	let (settings, pin) = {
+		let state = SAFE_MODE.read().unwrap_or_else(|e| e.into_inner());
+		(state.settings.clone(), state.pin.clone())
+	};
+	let settings = serde_json::to_value(&settings)
+		.map_err(|e| AppError::Custom(format!("Failed to serialize safe mode settings: {e}")))?;
+	let pin = serde_json::to_value(&pin)
+		.map_err(|e| AppError::Custom(format!("Failed to serialize safe mode PIN: {e}")))?;
+
+	let store = app.store(SAFE_MODE_STORE)?;
+	store.set("safe_mode", settings);
+	store.set("pin_hash", pin);
+	store.save()?;
+	Ok(())
+}
+
+/// Read safe mode settings and PIN from the store; call once at startup
+pub fn load_safe_mode(app: &AppHandle) -> Result<(), AppError> {
+	let store = app.store(SAFE_MODE_STORE)?;
+	let settings = match store.get("safe_mode") {
+		Some(value) => serde_json::from_value(value)
+			.map_err(|e| AppError::Custom(format!("Failed to parse safe mode settings: {e}")))?,
+		None => SafeModeSettings::default(),
+	};
+	let pin = store
+		.get("pin_hash")
+		.and_then(|value| serde_json::from_value(value).ok())
+		.flatten();
+
+	*SAFE_MODE.write().unwrap_or_else(|e| e.into_inner()) = SafeModeState {
+		settings,
+		pin,
+		unlocked: false,
+	};
+	Ok(())
+}
+
+fn sql_string(value: &str) -> String {
+	format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Whether safe mode is currently hiding files
+pub(crate) fn is_safe_mode_active() -> bool {
+	SAFE_MODE
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.is_active()
+}
+
+/// Condition keeping files that safe mode lets through; `file_hash` is the column to test
+///
+/// Files with any rating tag outside the allowed ones are hidden. Always true while safe mode
+/// is off or unlocked, so callers can add it unconditionally.
+pub(crate) fn visible_files_condition(file_hash: &str) -> String {
+	let state = SAFE_MODE.read().unwrap_or_else(|e| e.into_inner());
+	if !state.is_active() {
+		return "1".to_string();
+	}
+
+	let allowed = state
+		.settings
+		.allowed_ratings
+		.iter()
+		.map(|rating| sql_string(rating))
+		.collect::<Vec<_>>()
+		.join(", ");
+	let rated = "SELECT rft.file_hash FROM FileTags rft \
+		JOIN Tags rt ON rt.tag_id = rft.tag_id WHERE rt.type = 'rating'";
+
+	let mut condition = format!("{file_hash} NOT IN ({rated} AND rt.name NOT IN ({allowed}))");
+	if !state.settings.allow_unrated {
+		condition.push_str(&format!(" AND {file_hash} IN ({rated})"));
+	}
+	format!("({condition})")
+}
+
+/// Whether a file exists and safe mode lets it through
+pub(crate) async fn is_file_visible(pool: &SqlitePool, file_hash: &str) -> Result<bool, AppError> {
+	let query = format!(
+		"SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ? AND {})",
+		visible_files_condition("file_hash")
+	);
+	Ok(sqlx::query_scalar(&query)
+		.bind(file_hash)
+		.fetch_one(pool)
+		.await?)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+#[tauri::command]
+pub async fn get_safe_mode_status(
+	db: tauri::State<'_, DbPool>,
+) -> Result<SafeModeStatus, AppError> {
+	let pool = db.get();
+	let available_ratings =
+		sqlx::query_scalar("SELECT name FROM Tags WHERE type = 'rating' ORDER BY name")
+			.fetch_all(&pool)
+			.await?;
+
+	let state = SAFE_MODE.read().unwrap_or_else(|e| e.into_inner());
+	Ok(SafeModeStatus {
+		settings: state.settings.clone(),
+		pin_set: state.pin.is_some(),
+		unlocked: state.unlocked,
+		active: state.is_active(),
+		available_ratings,
+	})
+}
+
+/// Change safe mode settings; while safe mode is filtering and a PIN is set, `pin` must match
+#[tauri::command]
+pub async fn set_safe_mode_settings(
+	app: AppHandle,
+	mut settings: SafeModeSettings,
+	pin: Option<String>,
+) -> Result<(), AppError> {
+	let active = SAFE_MODE
+		.read()
+		.unwrap_or_else(|e| e.into_inner())
+		.is_active();
+	if active {
+		verify_pin(pin.as_deref()).await?;
+	}
+
+	let mut allowed_ratings: Vec<String> = Vec::new();
+	for rating in &settings.allowed_ratings {
+		let rating = rating.trim().to_lowercase();
+		if !rating.is_empty() && !allowed_ratings.contains(&rating) {
+			allowed_ratings.push(rating);
+		}
+	}
+	settings.allowed_ratings = allowed_ratings;
+
+	SAFE_MODE
+		.write()
+		.unwrap_or_else(|e| e.into_inner())
+		.settings = settings;
+	save_safe_mode(&app)
+}
+
+/// Set, change or (with `new_pin` `None`) remove the PIN; `current_pin` must match the old one
+#[tauri::command]
+pub async fn set_safe_mode_pin(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	current_pin: Option<String>,
+	new_pin: Option<String>,
+) -> Result<(), AppError> {
+	verify_pin(current_pin.as_deref()).await?;
+
+	let pin = match new_pin {
+		Some(new_pin) => {
+			let new_pin = new_pin.trim();
+			if !PIN_LENGTH.contains(&new_pin.len()) || !new_pin.chars().all(|c| c.is_ascii_digit())
+			{
+				return Err(AppError::Custom(format!(
+					"PIN must be {} to {} digits",
+					PIN_LENGTH.start(),
+					PIN_LENGTH.end()
+				)));
+			}
+			// SQLite's randomness source saves a dependency for the salt
+			let salt: String = sqlx::query_scalar("SELECT lower(hex(randomblob(16)))")
+				.fetch_one(&db.get())
+				.await?;
+			Some(PinHash {
+				hash: hash_pin(new_pin, &salt),
+				salt,
+			})
+		}
+		None => None,
+	};
+
+	SAFE_MODE.write().unwrap_or_else(|e| e.into_inner()).pin = pin;
+	save_safe_mode(&app)
+}
+
+/// Stop filtering until `lock_safe_mode` or the next start; needs the PIN when one is set
+#[tauri::command]
+pub async fn unlock_safe_mode(pin: Option<String>) -> Result<(), AppError> {
+	verify_pin(pin.as_deref()).await?;
+	SAFE_MODE
+		.write()
+		.unwrap_or_else(|e| e.into_inner())
+		.unlocked = true;
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn lock_safe_mode() -> Result<(), AppError> {
+	SAFE_MODE
+		.write()
+		.unwrap_or_else(|e| e.into_inner())
+		.unlocked = false;
+	Ok(())
+}
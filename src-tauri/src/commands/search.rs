@@ -0,0 +1,698 @@
+use super::files::{
+	file_list_item_from_row, file_record_from_row, newest_first, FileListItem, FileRecord,
+	FILE_RECORD_COLUMNS,
+};
+use super::safe_mode::visible_files_condition;
+use super::tag_tree::with_descendants_sql;
+use super::tags::{tag_from_row, Tag, TAG_COLUMNS};
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::metadata::escape_xml;
+use serde::Serialize;
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A condition on `Files f` and the values bound to its placeholders, in order
+pub(crate) struct SearchCondition {
+	pub sql: String,
+	pub binds: Vec<String>,
+}
+
+impl SearchCondition {
+	pub(crate) fn new(sql: impl Into<String>, binds: Vec<String>) -> Self {
+		Self {
+			sql: sql.into(),
+			binds,
+		}
+	}
+}
+
+/// One whitespace-separated term of a search query
+#[derive(Debug, PartialEq, Eq)]
+struct SearchTerm<'a> {
+	/// Prefixed with `-`
+	negated: bool,
+	text: &'a str,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct FolderHit {
+	pub path: String,
+	/// Files anywhere under the folder
+	pub file_count: i64,
+}
+
+/// What a `search_everything` hit points at
+#[derive(Debug, Serialize, Clone)]
+#[serde(tag = "kind", content = "item", rename_all = "snake_case")]
+pub enum SearchTarget {
+	Tag(Tag),
+	File(FileRecord),
+	Folder(FolderHit),
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SearchHit {
+	#[serde(flatten)]
+	pub target: SearchTarget,
+	/// Where the text matched: `name`, `alias`, `translation` (in another language than the
+	/// alias), `file_name`, `folder`, `note`, `source`, `prompt` or `negative_prompt`
+	pub field: String,
+	/// Matched text as HTML: the text is escaped and matches are wrapped in `<mark>`
+	pub snippet: String,
+	/// Higher is better; comparable across kinds
+	pub score: f64,
+}
+
+/// Words of a `search_everything` query as filters for a trigram FTS5 table
+///
+/// Trigram queries need at least 3 characters, so shorter words become `LIKE` filters.
+struct TrigramFilter {
+	match_query: Option<String>,
+	like_patterns: Vec<String>,
+}
+
+/// One ranked match from a single index, before files are loaded
+struct IndexMatch {
+	key: String,
+	field: String,
+	snippet: String,
+}
+
+const DEFAULT_EVERYTHING_LIMIT: i64 = 50;
+const MARK_START: &str = "<mark>";
+const MARK_END: &str = "</mark>";
+
+/// Match delimiters for FTS5 `snippet()`/`highlight()`, passed as `char(57344)` and
+/// `char(57345)`: private-use characters survive HTML escaping and then become `<mark>` tags
+const FTS_MARK_START: char = '\u{E000}';
+const FTS_MARK_END: char = '\u{E001}';
+
+/// How much a top hit from each index counts in the mixed ranking
+const TAG_WEIGHT: f64 = 1.0;
+const PATH_WEIGHT: f64 = 0.8;
+const NOTE_WEIGHT: f64 = 0.7;
+const PROMPT_WEIGHT: f64 = 0.6;
+const SOURCE_WEIGHT: f64 = 0.5;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn parse_query(query: &str) -> Vec<SearchTerm<'_>> {
+	query
+		.split_whitespace()
+		.map(|token| match token.strip_prefix('-') {
+			Some(rest) if !rest.is_empty() => SearchTerm {
+				negated: true,
+				text: rest,
+			},
+			_ => SearchTerm {
+				negated: false,
+				text: token,
+			},
+		})
+		.collect()
+}
+
+/// Parse `4`, `>=4`, `<3`, ... into an SQL comparison operator and number
+fn parse_comparison(value: &str) -> Option<(&'static str, i64)> {
+	let (op, number) = [">=", "<=", ">", "<", "="]
+		.into_iter()
+		.find_map(|op| value.strip_prefix(op).map(|rest| (op, rest)))
+		.unwrap_or(("=", value));
+	number.parse().ok().map(|n| (op, n))
+}
+
+/// Fill `{op}` and `{n}` of a template from a comparison value; the number is inlined so it
+/// compares numerically even against expressions without column affinity
+fn comparison_condition(
+	key: &str,
+	value: &str,
+	template: &str,
+) -> Result<SearchCondition, AppError> {
+	let (op, n) = parse_comparison(value)
+		.ok_or_else(|| AppError::Custom(format!("Invalid {key} comparison: {value}")))?;
+	Ok(SearchCondition::new(
+		template.replace("{op}", op).replace("{n}", &n.to_string()),
+		Vec::new(),
+	))
+}
+
+/// Condition for a `key:value` operator, or `None` when `key` is not an operator
+fn operator_condition(key: &str, value: &str) -> Option<Result<SearchCondition, AppError>> {
+	match key {
+		"is" if value == "favorite" => Some(Ok(SearchCondition::new(
+			"f.file_hash IN (SELECT fav.file_hash FROM Favorites fav)",
+			Vec::new(),
+		))),
+		"pool" => Some(Ok(super::pools::search_condition(value))),
+		"rating" if value == "none" => Some(Ok(SearchCondition::new(
+			"f.file_hash NOT IN (SELECT file_hash FROM FileStats WHERE rating IS NOT NULL)",
+			Vec::new(),
+		))),
+		"rating" => Some(comparison_condition(
+			key,
+			value,
+			"f.file_hash IN (SELECT file_hash FROM FileStats WHERE rating {op} {n})",
+		)),
+		"views" => Some(comparison_condition(
+			key,
+			value,
+			"COALESCE((SELECT view_count FROM FileStats WHERE file_hash = f.file_hash), 0) {op} {n}",
+		)),
+		_ => super::notes::search_condition(key, value)
+			.or_else(|| super::relations::search_condition(key, value)),
+	}
+}
+
+/// Files tagged with the tag (or the tag an alias or translation stands for) or any narrower
+/// tag below it
+fn tag_condition(name: &str) -> SearchCondition {
+	SearchCondition::new(
+		format!(
+			"f.file_hash IN (SELECT ft.file_hash FROM FileTags ft WHERE ft.tag_id IN ({}))",
+			with_descendants_sql(
+				"SELECT tag_id FROM Tags WHERE name = ? COLLATE NOCASE \
+				UNION SELECT tag_id FROM TagAliases WHERE alias_name = ? \
+				UNION SELECT tag_id FROM TagTranslations WHERE text = ? COLLATE NOCASE"
+			)
+		),
+		vec![name.to_string(), name.to_string(), name.to_string()],
+	)
+}
+
+fn term_condition(term: &SearchTerm) -> Result<SearchCondition, AppError> {
+	let operator = term
+		.text
+		.split_once(':')
+		.filter(|(key, value)| !key.is_empty() && !value.is_empty())
+		.and_then(|(key, value)| operator_condition(&key.to_lowercase(), value));
+
+	let condition = match operator {
+		Some(condition) => condition?,
+		// Anything that isn't an operator is a tag name (tags may contain ':')
+		None => tag_condition(term.text),
+	};
+
+	Ok(if term.negated {
+		SearchCondition::new(format!("NOT ({})", condition.sql), condition.binds)
+	} else {
+		condition
+	})
+}
+
+/// A word as a quoted FTS5 string, so no character is read as query syntax
+fn fts_phrase(word: &str) -> String {
+	format!("\"{}\"", word.replace('"', "\"\""))
+}
+
+/// FTS5 query for unicode61 tables: every word, matched as a word prefix
+fn prefix_query(words: &[&str]) -> String {
+	words
+		.iter()
+		.map(|word| format!("{}*", fts_phrase(word)))
+		.collect::<Vec<_>>()
+		.join(" ")
+}
+
+fn trigram_filter(words: &[&str]) -> TrigramFilter {
+	let (long, short): (Vec<&str>, Vec<&str>) =
+		words.iter().partition(|word| word.chars().count() >= 3);
+	TrigramFilter {
+		match_query: (!long.is_empty()).then(|| {
+			long.iter()
+				.map(|word| fts_phrase(word))
+				.collect::<Vec<_>>()
+				.join(" ")
+		}),
+		like_patterns: short
+			.iter()
+			.map(|word| {
+				let escaped = word
+					.replace('\\', "\\\\")
+					.replace('%', "\\%")
+					.replace('_', "\\_");
+				format!("%{escaped}%")
+			})
+			.collect(),
+	}
+}
+
+impl TrigramFilter {
+	/// WHERE clause over `columns` of `table` and the values to bind, in order
+	fn where_clause(&self, table: &str, columns: &[&str]) -> (String, Vec<String>) {
+		let mut conditions = Vec::new();
+		let mut binds = Vec::new();
+		if let Some(query) = &self.match_query {
+			conditions.push(format!("{table} MATCH ?"));
+			binds.push(query.clone());
+		}
+		for pattern in &self.like_patterns {
+			let any_column: Vec<String> = columns
+				.iter()
+				.map(|column| format!("{table}.{column} LIKE ? ESCAPE '\\'"))
+				.collect();
+			conditions.push(format!("({})", any_column.join(" OR ")));
+			binds.extend(columns.iter().map(|_| pattern.clone()));
+		}
+		(conditions.join(" AND "), binds)
+	}
+
+	/// Leading ORDER BY terms; bm25 only works with MATCH, so `LIKE`-only results are
+	/// ordered by the query's other terms alone
+	fn rank_order(&self, table: &str) -> String {
+		if self.match_query.is_some() {
+			format!("bm25({table}), ")
+		} else {
+			String::new()
+		}
+	}
+}
+
+/// HTML-escape `text` and wrap case-insensitive occurrences of `words` in `<mark>`
+/// Text whose lowercase form has a different length is returned unmarked
+fn mark_words(text: &str, words: &[&str]) -> String {
+	let lower = text.to_lowercase();
+	if lower.len() != text.len() {
+		return escape_xml(text);
+	}
+
+	let mut marked = vec![false; text.len()];
+	for word in words {
+		let word = word.to_lowercase();
+		for (start, _) in lower.match_indices(&word) {
+			marked[start..start + word.len()].fill(true);
+		}
+	}
+
+	let mut result = String::with_capacity(text.len());
+	let mut segment_start = 0;
+	let mut open = false;
+	for (index, _) in text.char_indices() {
+		if marked[index] != open {
+			result.push_str(&escape_xml(&text[segment_start..index]));
+			result.push_str(if open { MARK_END } else { MARK_START });
+			segment_start = index;
+			open = marked[index];
+		}
+	}
+	result.push_str(&escape_xml(&text[segment_start..]));
+	if open {
+		result.push_str(MARK_END);
+	}
+	result
+}
+
+/// HTML-escape an FTS5 snippet delimited by `FTS_MARK_START`/`FTS_MARK_END` and turn the
+/// delimiters into `<mark>` tags
+pub(crate) fn fts_snippet_html(snippet: &str) -> String {
+	escape_xml(snippet)
+		.replace(FTS_MARK_START, MARK_START)
+		.replace(FTS_MARK_END, MARK_END)
+}
+
+fn contains_all(text: &str, words: &[&str]) -> bool {
+	let text = text.to_lowercase();
+	words.iter().all(|word| text.contains(&word.to_lowercase()))
+}
+
+/// Score of the `position`-th best hit from an index; the top hit of each index scores its
+/// weight, so indexes with incomparable bm25 scales still interleave
+fn position_score(weight: f64, position: usize) -> f64 {
+	weight / (position + 1) as f64
+}
+
+async fn search_tag_index(
+	pool: &SqlitePool,
+	words: &[&str],
+	limit: i64,
+) -> Result<Vec<SearchHit>, AppError> {
+	let filter = trigram_filter(words);
+	let (where_clause, binds) = filter.where_clause("TagsFts", &["name", "alias"]);
+	let query = format!(
+		r#"
+        SELECT {TAG_COLUMNS},
+               (SELECT COUNT(*) FROM FileTags ft WHERE ft.tag_id = t.tag_id) as file_count
+        FROM TagsFts
+        JOIN Tags t ON t.tag_id = TagsFts.rowid
+        WHERE {where_clause}
+        ORDER BY {rank}file_count DESC, length(t.name)
+        LIMIT ?
+        "#,
+		rank = filter.rank_order("TagsFts"),
+	);
+
+	let mut query_builder = sqlx::query(&query);
+	for bind in binds {
+		query_builder = query_builder.bind(bind);
+	}
+	let rows = query_builder.bind(limit).fetch_all(pool).await?;
+
+	let mut hits: Vec<SearchHit> = rows
+		.iter()
+		.enumerate()
+		.map(|(position, row)| {
+			let tag = tag_from_row(row);
+			let (field, text) = match &tag.alias {
+				Some(alias) if !contains_all(&tag.name, words) && contains_all(alias, words) => {
+					("alias", alias.clone())
+				}
+				_ => ("name", tag.name.clone()),
+			};
+			SearchHit {
+				snippet: mark_words(&text, words),
+				field: field.to_string(),
+				score: position_score(TAG_WEIGHT, position),
+				target: SearchTarget::Tag(tag),
+			}
+		})
+		.collect();
+
+	// Translations in languages other than the one shown; a tag found by name or alias
+	// keeps that hit
+	let (where_clause, binds) = filter.where_clause("TagTranslationsFts", &["text"]);
+	let query = format!(
+		r#"
+        SELECT {TAG_COLUMNS}, tr.text as translation,
+               (SELECT COUNT(*) FROM FileTags ft WHERE ft.tag_id = t.tag_id) as file_count
+        FROM TagTranslationsFts
+        JOIN TagTranslations tr ON tr.translation_id = TagTranslationsFts.rowid
+        JOIN Tags t ON t.tag_id = tr.tag_id
+        WHERE {where_clause}
+        ORDER BY {rank}file_count DESC, length(t.name)
+        LIMIT ?
+        "#,
+		rank = filter.rank_order("TagTranslationsFts"),
+	);
+
+	let mut query_builder = sqlx::query(&query);
+	for bind in binds {
+		query_builder = query_builder.bind(bind);
+	}
+	let rows = query_builder.bind(limit).fetch_all(pool).await?;
+
+	let mut found: HashSet<i64> = hits
+		.iter()
+		.filter_map(|hit| match &hit.target {
+			SearchTarget::Tag(tag) => Some(tag.tag_id),
+			_ => None,
+		})
+		.collect();
+	for (position, row) in rows.iter().enumerate() {
+		let tag = tag_from_row(row);
+		if found.insert(tag.tag_id) {
+			hits.push(SearchHit {
+				snippet: mark_words(&row.get::<String, _>("translation"), words),
+				field: "translation".to_string(),
+				score: position_score(TAG_WEIGHT, position),
+				target: SearchTarget::Tag(tag),
+			});
+		}
+	}
+
+	Ok(hits)
+}
+
+/// Path matches split into file name matches (by hash) and folder matches (by folder path)
+async fn search_path_index(
+	pool: &SqlitePool,
+	words: &[&str],
+	limit: i64,
+) -> Result<(Vec<IndexMatch>, Vec<IndexMatch>), AppError> {
+	let filter = trigram_filter(words);
+	let (where_clause, binds) = filter.where_clause("FilePathsFts", &["original_path"]);
+	let query = format!(
+		r#"
+        SELECT file_hash, original_path
+        FROM FilePathsFts
+        WHERE {where_clause}
+        ORDER BY {rank}length(original_path)
+        LIMIT ?
+        "#,
+		rank = filter.rank_order("FilePathsFts"),
+	);
+
+	let mut query_builder = sqlx::query(&query);
+	for bind in binds {
+		query_builder = query_builder.bind(bind);
+	}
+	let rows = query_builder.bind(limit).fetch_all(pool).await?;
+
+	let mut files = Vec::new();
+	let mut folders: Vec<IndexMatch> = Vec::new();
+	for row in &rows {
+		let file_hash: String = row.get("file_hash");
+		let original_path: String = row.get("original_path");
+		let path = Path::new(&original_path);
+		let file_name = path
+			.file_name()
+			.map(|n| n.to_string_lossy().to_string())
+			.unwrap_or_default();
+
+		if contains_all(&file_name, words) {
+			files.push(IndexMatch {
+				key: file_hash,
+				field: "file_name".to_string(),
+				snippet: mark_words(&file_name, words),
+			});
+		} else if let Some(folder) = path.parent().map(|p| p.to_string_lossy().to_string()) {
+			if !folders.iter().any(|f| f.key == folder) {
+				folders.push(IndexMatch {
+					snippet: mark_words(&folder, words),
+					key: folder,
+					field: "folder".to_string(),
+				});
+			}
+		}
+	}
+
+	Ok((files, folders))
+}
+
+/// Run a query over a unicode61 index returning `file_hash`, `field` and `snippet`, best first
+async fn search_file_index(
+	pool: &SqlitePool,
+	query: &str,
+	words: &[&str],
+	limit: i64,
+) -> Result<Vec<IndexMatch>, AppError> {
+	let rows = sqlx::query(query)
+		.bind(prefix_query(words))
+		.bind(limit)
+		.fetch_all(pool)
+		.await?;
+	Ok(rows
+		.iter()
+		.map(|row| IndexMatch {
+			key: row.get("file_hash"),
+			field: row.get("field"),
+			snippet: fts_snippet_html(&row.get::<String, _>("snippet")),
+		})
+		.collect())
+}
+
+async fn count_files_under(pool: &SqlitePool, folder: &str) -> Result<i64, AppError> {
+	let separator = if folder.contains('\\') && !folder.contains('/') {
+		'\\'
+	} else {
+		'/'
+	};
+	let prefix = format!("{}{separator}", folder.trim_end_matches(separator));
+	let query = format!(
+		"SELECT COUNT(*) FROM Files f WHERE substr(f.original_path, 1, ?) = ? AND {}",
+		visible_files_condition("f.file_hash")
+	);
+	let count: i64 = sqlx::query_scalar(&query)
+		.bind(prefix.chars().count() as i64)
+		.bind(&prefix)
+		.fetch_one(pool)
+		.await?;
+	Ok(count)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Search files with a query of tag names and operators, all of which must match
+///
+/// A tag also matches files tagged with narrower tags below it (`swimsuit` finds
+/// `school_swimsuit`). `-term` negates a term. Operators: `is:favorite`, `pool:<name>`, `rating:>=4`, `rating:none`,
+/// `views:<3`, `has:notes`, `note:<words>`, `has:parent`, `has:children`, `has:variants`,
+/// `has:relations`, `child_of:<hash>`, `parent_of:<hash>`, `variant_of:<hash>`,
+/// `derived_from:<hash>`, `same_source_as:<hash>`.
+#[tauri::command]
+pub async fn search_files(
+	db: tauri::State<'_, DbPool>,
+	query: String,
+	collapse_works: Option<bool>,
+	offset: Option<i64>,
+	limit: Option<i64>,
+) -> Result<Vec<FileListItem>, AppError> {
+	let pool = db.get();
+	let mut conditions = parse_query(&query)
+		.iter()
+		.map(term_condition)
+		.collect::<Result<Vec<_>, _>>()?;
+	conditions.push(SearchCondition::new(
+		visible_files_condition("f.file_hash"),
+		Vec::new(),
+	));
+
+	let sql: Vec<&str> = conditions.iter().map(|c| c.sql.as_str()).collect();
+	let where_clause = format!("WHERE {}", sql.join(" AND "));
+	let sql = format!(
+		"{} LIMIT ? OFFSET ?",
+		newest_first(
+			&format!("SELECT {FILE_RECORD_COLUMNS} FROM Files f {where_clause}"),
+			collapse_works.unwrap_or(false),
+		)
+	);
+
+	let mut query_builder = sqlx::query(&sql);
+	for bind in conditions.iter().flat_map(|c| &c.binds) {
+		query_builder = query_builder.bind(bind);
+	}
+	let rows = query_builder
+		.bind(limit.filter(|l| *l > 0).unwrap_or(-1))
+		.bind(offset.unwrap_or(0))
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows.iter().map(file_list_item_from_row).collect())
+}
+
+/// Search tags, file names, folders, region notes, source URLs and prompts at once
+///
+/// Every word must match. Hits of all kinds come back in one list, best first, each with a
+/// highlighted snippet of the text that matched. Tag names, translations and paths match anywhere
+/// inside words (including CJK text); notes, sources and prompts match word prefixes.
+#[tauri::command]
+pub async fn search_everything(
+	db: tauri::State<'_, DbPool>,
+	text: String,
+	limit: Option<i64>,
+) -> Result<Vec<SearchHit>, AppError> {
+	let pool = db.get();
+	let words: Vec<&str> = text.split_whitespace().collect();
+	if words.is_empty() {
+		return Ok(Vec::new());
+	}
+	let limit = limit.filter(|l| *l > 0).unwrap_or(DEFAULT_EVERYTHING_LIMIT);
+
+	let mut hits = search_tag_index(&pool, &words, limit).await?;
+	let (name_matches, folder_matches) = search_path_index(&pool, &words, limit).await?;
+
+	for (position, folder) in folder_matches.into_iter().enumerate() {
+		// Folders whose files safe mode hides all are left out
+		let file_count = count_files_under(&pool, &folder.key).await?;
+		if file_count == 0 {
+			continue;
+		}
+		hits.push(SearchHit {
+			target: SearchTarget::Folder(FolderHit {
+				file_count,
+				path: folder.key,
+			}),
+			field: folder.field,
+			snippet: folder.snippet,
+			score: position_score(PATH_WEIGHT, position),
+		});
+	}
+
+	let note_matches = search_file_index(
+		&pool,
+		r#"
+        SELECT n.file_hash, 'note' as field,
+               snippet(FileNotesFts, 0, char(57344), char(57345), '…', 16) as snippet
+        FROM FileNotesFts
+        JOIN FileNotes n ON n.note_id = FileNotesFts.rowid
+        WHERE FileNotesFts MATCH ?
+        ORDER BY bm25(FileNotesFts)
+        LIMIT ?
+        "#,
+		&words,
+		limit,
+	)
+	.await?;
+	let prompt_matches = search_file_index(
+		&pool,
+		r#"
+        SELECT m.file_hash, m.key as field,
+               snippet(FilePromptsFts, 0, char(57344), char(57345), '…', 16) as snippet
+        FROM FilePromptsFts
+        JOIN FileMetadata m ON m.metadata_id = FilePromptsFts.rowid
+        WHERE FilePromptsFts MATCH ?
+        ORDER BY bm25(FilePromptsFts)
+        LIMIT ?
+        "#,
+		&words,
+		limit,
+	)
+	.await?;
+	let source_matches = search_file_index(
+		&pool,
+		r#"
+        SELECT s.file_hash, 'source' as field,
+               highlight(FileSourcesFts, 0, char(57344), char(57345)) as snippet
+        FROM FileSourcesFts
+        JOIN FileSources s ON s.source_id = FileSourcesFts.rowid
+        WHERE FileSourcesFts MATCH ?
+        ORDER BY bm25(FileSourcesFts)
+        LIMIT ?
+        "#,
+		&words,
+		limit,
+	)
+	.await?;
+
+	// A file matched in several places is listed once, under its best match
+	let mut best: HashMap<String, (IndexMatch, f64)> = HashMap::new();
+	for (weight, matches) in [
+		(PATH_WEIGHT, name_matches),
+		(NOTE_WEIGHT, note_matches),
+		(PROMPT_WEIGHT, prompt_matches),
+		(SOURCE_WEIGHT, source_matches),
+	] {
+		for (position, file_match) in matches.into_iter().enumerate() {
+			let score = position_score(weight, position);
+			if best.get(&file_match.key).map_or(true, |(_, s)| score > *s) {
+				best.insert(file_match.key.clone(), (file_match, score));
+			}
+		}
+	}
+
+	if !best.is_empty() {
+		let placeholders = best.keys().map(|_| "?").collect::<Vec<_>>().join(",");
+		let query = format!(
+			"SELECT {FILE_RECORD_COLUMNS} FROM Files f WHERE f.file_hash IN ({placeholders}) AND {}",
+			visible_files_condition("f.file_hash")
+		);
+		let mut query_builder = sqlx::query(&query);
+		for file_hash in best.keys() {
+			query_builder = query_builder.bind(file_hash);
+		}
+		for row in query_builder.fetch_all(&pool).await? {
+			let file = file_record_from_row(&row);
+			if let Some((file_match, score)) = best.remove(&file.file_hash) {
+				hits.push(SearchHit {
+					target: SearchTarget::File(file),
+					field: file_match.field,
+					snippet: file_match.snippet,
+					score,
+				});
+			}
+		}
+	}
+
+	// Stable sort: on equal scores tags come first, then folders, then files
+	hits.sort_by(|a, b| b.score.total_cmp(&a.score));
+	hits.truncate(limit as usize);
+	Ok(hits)
+}
@@ -2,12 +2,16 @@ use super::debug_visualization::{
 	analyze_threshold_effects, generate_confidence_histogram, generate_filtered_tags_info,
 	generate_preprocess_visualization,
 };
+use crate::csv_table::{self, CsvReport, LineDiagnostic};
+use crate::db::DbPool;
 use crate::error::AppError;
 use image::GenericImageView;
 use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
-use sqlx::{Row, SqlitePool};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::HashMap;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
 use tauri::{AppHandle, Emitter, Manager};
 use tauri_plugin_store::StoreExt;
 
@@ -21,6 +25,9 @@ pub struct ModelUploadResult {
 	pub message: String,
 	pub file_path: Option<String>,
 	pub calculated_hash: Option<String>,
+	/// Rows of an uploaded label map that could not be read
+	#[serde(default)]
+	pub diagnostics: Vec<LineDiagnostic>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
@@ -169,6 +176,7 @@ pub async fn upload_tag_model_file(
 			message: format!("Source file does not exist: {file_path}"),
 			file_path: None,
 			calculated_hash: None,
+			diagnostics: Vec::new(),
 		});
 	}
 
@@ -182,6 +190,7 @@ pub async fn upload_tag_model_file(
 			message: e.to_string(),
 			file_path: None,
 			calculated_hash: Some(calculated_hash),
+			diagnostics: Vec::new(),
 		});
 	}
 
@@ -199,6 +208,7 @@ pub async fn upload_tag_model_file(
 		message: "Model file uploaded successfully".to_string(),
 		file_path: Some(target_path.display().to_string()),
 		calculated_hash: Some(calculated_hash),
+		diagnostics: Vec::new(),
 	})
 }
 
@@ -217,6 +227,7 @@ pub async fn upload_label_map_file(
 			message: format!("Source file does not exist: {file_path}"),
 			file_path: None,
 			calculated_hash: None,
+			diagnostics: Vec::new(),
 		});
 	}
 
@@ -230,6 +241,21 @@ pub async fn upload_label_map_file(
 			message: e.to_string(),
 			file_path: None,
 			calculated_hash: Some(calculated_hash),
+			diagnostics: Vec::new(),
+		});
+	}
+
+	// Check the rows before replacing the current label map
+	let file = std::io::BufReader::new(std::fs::File::open(source_path)?);
+	let label_map = crate::ai::tagger::parse_label_map(file)?;
+	let invalid_rows = label_map.report.invalid_rows;
+	if label_map.labels.is_empty() {
+		return Ok(ModelUploadResult {
+			success: false,
+			message: "Label map has no valid rows".to_string(),
+			file_path: None,
+			calculated_hash: Some(calculated_hash),
+			diagnostics: label_map.report.diagnostics,
 		});
 	}
 
@@ -242,11 +268,17 @@ pub async fn upload_label_map_file(
 		.await
 		.map_err(|e| AppError::Custom(format!("Failed to copy label map file: {e}")))?;
 
+	let message = if invalid_rows == 0 {
+		"Label map file uploaded successfully".to_string()
+	} else {
+		format!("Label map file uploaded; {invalid_rows} rows could not be read")
+	};
 	Ok(ModelUploadResult {
 		success: true,
-		message: "Label map file uploaded successfully".to_string(),
+		message,
 		file_path: Some(target_path.display().to_string()),
 		calculated_hash: Some(calculated_hash),
+		diagnostics: label_map.report.diagnostics,
 	})
 }
 
@@ -490,7 +522,7 @@ pub async fn debug_model_preprocess(
 	}
 
 	// Step 2: Load image to get dimensions
-	let image = match image::open(path) {
+	let image = match crate::metadata::open_image_oriented(path) {
 		Ok(img) => {
 			result.original_size = img.dimensions();
 			result.preprocessing_steps.push(format!(
@@ -679,33 +711,31 @@ pub async fn debug_model_postprocess(
 		get_inference_config(app.clone()).await.unwrap_or_default()
 	};
 
-	// Convert settings config to AI tagger params
-	let inference_params = crate::ai::tagger::InferenceParams {
-		general_threshold: config_used.general_threshold,
-		character_threshold: config_used.character_threshold,
-		general_mcut_enabled: config_used.general_mcut_enabled,
-		character_mcut_enabled: config_used.character_mcut_enabled,
-		max_tags: config_used.max_tags,
+	// Convert settings config to AI tagger params, per category of the model
+	let mappings = {
+		let db = app.state::<DbPool>();
+		super::model_categories::current_mappings(&db.get()).await?
 	};
+	let inference_params = super::model_categories::inference_params(&mappings, &config_used);
 
 	// Run inference with custom parameters using the new debug function
 	match crate::ai::tagger::classify_image_debug_with_params(path, &inference_params).await {
 		Ok(category_predictions) => {
 			// Convert category predictions to expected formats
 			let rating_predictions: Vec<(String, f32)> = category_predictions
-				.rating
+				.category(crate::ai::tagger::RATING_CATEGORY)
 				.iter()
 				.map(|p| (p.name.clone(), p.confidence))
 				.collect();
 
 			let general_predictions: Vec<(String, f32)> = category_predictions
-				.general
+				.category(crate::ai::tagger::GENERAL_CATEGORY)
 				.iter()
 				.map(|p| (p.name.clone(), p.confidence))
 				.collect();
 
 			let character_predictions: Vec<(String, f32)> = category_predictions
-				.character
+				.category(crate::ai::tagger::CHARACTER_CATEGORY)
 				.iter()
 				.map(|p| (p.name.clone(), p.confidence))
 				.collect();
@@ -837,17 +867,32 @@ pub struct TranslationUploadResult {
 	pub valid_entries: usize,
 	pub invalid_entries: usize,
 	pub available_languages: Vec<String>,
+	/// Rows that could not be read, with their line numbers
+	#[serde(default)]
+	pub diagnostics: Vec<LineDiagnostic>,
 }
 
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct TranslationStatus {
 	pub current_language: Option<String>,
+	/// Languages tried in order when a tag has no translation in `current_language`
+	#[serde(default)]
+	pub fallback_languages: Vec<String>,
 	pub available_languages: Vec<String>,
 	pub dictionary_loaded: bool,
 	pub dictionary_path: Option<String>,
 	pub total_translations: usize,
 }
 
+/// A translation entered by the user; dictionary refreshes never replace it
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TranslationOverride {
+	pub tag_id: i64,
+	pub tag_name: String,
+	pub language_code: String,
+	pub text: String,
+}
+
 #[derive(Debug, Serialize, Deserialize, Clone)]
 pub struct AliasRefreshProgress {
 	pub current: usize,
@@ -857,7 +902,7 @@ pub struct AliasRefreshProgress {
 }
 
 /// Get translations directory in app data directory
-fn get_translations_dir(app: AppHandle) -> Result<PathBuf, AppError> {
+pub(crate) fn get_translations_dir(app: AppHandle) -> Result<PathBuf, AppError> {
 	let app_data_dir = app
 		.path()
 		.app_data_dir()
@@ -873,111 +918,293 @@ fn get_translations_dir(app: AppHandle) -> Result<PathBuf, AppError> {
 /// Translation entry: (tag_name, translated_name, language_code)
 type TranslationEntry = (String, String, String);
 
+/// Columns of a translation dictionary, in order when it has no header row
+const TRANSLATION_COLUMNS: [&str; 3] = ["name", "translated_name", "language_code"];
+
+/// Normalize a BCP 47 language tag to its usual case (`ZH_hant` -> `zh-Hant`, `pt-br` ->
+/// `pt-BR`), rejecting anything that isn't one
+fn normalize_language_code(code: &str) -> Result<String, String> {
+	let code = code.trim();
+	let invalid = || format!("invalid language_code '{code}'");
+	let mut subtags = code.split(['-', '_']);
+	let language = subtags
+		.next()
+		.filter(|l| matches!(l.len(), 2..=3) && l.chars().all(|c| c.is_ascii_alphabetic()))
+		.ok_or_else(invalid)?;
+
+	let mut normalized = language.to_ascii_lowercase();
+	let mut in_extension = false;
+	for subtag in subtags {
+		if subtag.is_empty()
+			|| subtag.len() > 8
+			|| !subtag.chars().all(|c| c.is_ascii_alphanumeric())
+		{
+			return Err(invalid());
+		}
+		let alphabetic = subtag.chars().all(|c| c.is_ascii_alphabetic());
+		normalized.push('-');
+		// Everything after a singleton (`-u-`, `-x-`) is an extension, kept lowercase
+		in_extension |= subtag.len() == 1;
+		if !in_extension && subtag.len() == 4 && alphabetic {
+			// Script
+			normalized.push_str(&subtag[..1].to_ascii_uppercase());
+			normalized.push_str(&subtag[1..].to_ascii_lowercase());
+		} else if !in_extension
+			&& ((subtag.len() == 2 && alphabetic)
+				|| (subtag.len() == 3 && subtag.chars().all(|c| c.is_ascii_digit())))
+		{
+			// Region
+			normalized.push_str(&subtag.to_ascii_uppercase());
+		} else {
+			normalized.push_str(&subtag.to_ascii_lowercase());
+		}
+	}
+	Ok(normalized)
+}
+
+/// Languages to fall back to when none is chosen: the tag with subtags dropped from the end
+/// (`zh-Hant-TW` -> `zh-Hant`, `zh`)
+fn default_fallback_languages(language_code: &str) -> Vec<String> {
+	let subtags: Vec<&str> = language_code.split('-').collect();
+	(1..subtags.len())
+		.rev()
+		// A tag can't end with a singleton (`en-x-twain` falls back to `en`, not `en-x`)
+		.filter(|&len| subtags[len - 1].len() > 1)
+		.map(|len| subtags[..len].join("-"))
+		.collect()
+}
+
 /// Parse translation dictionary CSV file with fault tolerance
-/// CSV format: name,translated_name,language_code
+/// CSV format: name,translated_name,language_code; quoted fields may contain commas, quotes and
+/// line breaks. Invalid rows are skipped and described in the report.
 async fn parse_translation_csv(
 	file_path: &Path,
-) -> Result<(Vec<TranslationEntry>, usize), AppError> {
-	use tokio::io::AsyncReadExt;
-
-	let mut file = tokio::fs::File::open(file_path)
-		.await
-		.map_err(|e| AppError::Custom(format!("Failed to open translation file: {e}")))?;
-
-	let mut contents = String::new();
-	file.read_to_string(&mut contents)
+) -> Result<(Vec<TranslationEntry>, CsvReport), AppError> {
+	let contents = tokio::fs::read(file_path)
 		.await
 		.map_err(|e| AppError::Custom(format!("Failed to read translation file: {e}")))?;
 
 	let mut translations = Vec::new();
-	let mut invalid_count = 0;
+	let report = csv_table::read_rows(contents.as_slice(), &TRANSLATION_COLUMNS, |row| {
+		let tag_name = row.get("name").ok_or("empty tag name")?;
+		let translated_name = row.get("translated_name").ok_or("empty translated_name")?;
+		let language_code = normalize_language_code(row.get("language_code").unwrap_or(""))?;
+
+		translations.push((
+			tag_name.to_string(),
+			translated_name.to_string(),
+			language_code,
+		));
+		Ok(())
+	})?;
 
-	for (line_num, line) in contents.lines().enumerate() {
-		// Skip empty lines
-		if line.trim().is_empty() {
-			continue;
-		}
+	Ok((translations, report))
+}
 
-		// Skip header line if present
-		if line_num == 0 && line.to_lowercase().contains("name") {
-			continue;
+/// The selected language followed by its fallbacks, or nothing when no language is selected
+async fn get_language_chain(app: &AppHandle) -> Result<Vec<String>, AppError> {
+	let Some(language_code) = get_translation_language(app.clone()).await? else {
+		return Ok(Vec::new());
+	};
+	let mut chain = vec![language_code.clone()];
+	for fallback in get_translation_fallbacks(app.clone()).await? {
+		if !chain.contains(&fallback) {
+			chain.push(fallback);
 		}
+	}
+	Ok(chain)
+}
 
-		// Parse CSV line (simple CSV parsing, handles quoted fields)
-		let fields: Vec<String> = line
-			.split(',')
-			.map(|s| s.trim().trim_matches('"').to_string())
-			.collect();
-
-		if fields.len() < 3 {
-			invalid_count += 1;
-			eprintln!(
-				"Warning: Line {} has insufficient fields, skipping",
-				line_num + 1
-			);
-			continue;
-		}
+/// Show each tag's translation in the first language of `chain` it has one in as its alias
+/// (tags without one show their name), for every tag or only `tag_id`
+/// Returns how many of those tags have a translation.
+async fn apply_translation_language(
+	conn: &mut SqliteConnection,
+	chain: &[String],
+	tag_id: Option<i64>,
+) -> Result<usize, AppError> {
+	let chain = serde_json::to_string(chain)
+		.map_err(|e| AppError::Custom(format!("Failed to encode languages: {e}")))?;
+
+	// Only touch tags whose alias changes, so the search index and autocomplete aren't
+	// rebuilt for every tag. The bare `text` comes from the row with the lowest chain position.
+	sqlx::query(
+		r#"
+        UPDATE Tags SET alias = best.text
+        FROM (
+            SELECT tr.tag_id, tr.text, MIN(c.key) as position
+            FROM TagTranslations tr
+            JOIN json_each(?) c ON c.value = tr.lang
+            WHERE ? IS NULL OR tr.tag_id = ?
+            GROUP BY tr.tag_id
+        ) best
+        WHERE best.tag_id = Tags.tag_id AND Tags.alias IS NOT best.text
+        "#,
+	)
+	.bind(&chain)
+	.bind(tag_id)
+	.bind(tag_id)
+	.execute(&mut *conn)
+	.await?;
+	sqlx::query(
+		r#"
+        UPDATE Tags SET alias = NULL
+        WHERE alias IS NOT NULL AND (? IS NULL OR tag_id = ?) AND NOT EXISTS(
+            SELECT 1 FROM TagTranslations tr
+            JOIN json_each(?) c ON c.value = tr.lang
+            WHERE tr.tag_id = Tags.tag_id
+        )
+        "#,
+	)
+	.bind(tag_id)
+	.bind(tag_id)
+	.bind(&chain)
+	.execute(&mut *conn)
+	.await?;
+
+	let translated: i64 = sqlx::query_scalar(
+		r#"
+        SELECT COUNT(DISTINCT tr.tag_id) FROM TagTranslations tr
+        JOIN json_each(?) c ON c.value = tr.lang
+        WHERE ? IS NULL OR tr.tag_id = ?
+        "#,
+	)
+	.bind(&chain)
+	.bind(tag_id)
+	.bind(tag_id)
+	.fetch_one(&mut *conn)
+	.await?;
+	Ok(translated as usize)
+}
 
-		let tag_name = fields[0].trim().to_string();
-		let translated_name = fields[1].trim().to_string();
-		let language_code = fields[2].trim().to_lowercase();
+/// Set by `cancel_translation_refresh`; checked between batches of a running refresh
+static REFRESH_CANCELLED: AtomicBool = AtomicBool::new(false);
 
-		// Validate tag_name
-		if tag_name.is_empty() {
-			invalid_count += 1;
-			eprintln!(
-				"Warning: Line {} has empty tag name, skipping",
-				line_num + 1
-			);
-			continue;
-		}
+/// Dictionary entries staged per statement (3 parameters each)
+const STAGE_BATCH_SIZE: usize = 300;
 
-		// Validate language code (ISO 639-1 format: 2 letters)
-		if language_code.len() != 2 || !language_code.chars().all(|c| c.is_ascii_lowercase()) {
-			invalid_count += 1;
-			eprintln!(
-				"Warning: Line {} has invalid language_code, skipping",
-				line_num + 1
-			);
-			continue;
-		}
+fn check_refresh_cancelled() -> Result<(), AppError> {
+	if REFRESH_CANCELLED.load(Ordering::Relaxed) {
+		return Err(AppError::Custom(
+			"Translation refresh cancelled".to_string(),
+		));
+	}
+	Ok(())
+}
 
-		if translated_name.is_empty() {
-			invalid_count += 1;
-			eprintln!(
-				"Warning: Line {} has empty translated_name, skipping",
-				line_num + 1
-			);
-			continue;
+/// Stage the dictionary in a temp table and merge it into TagTranslations in one transaction
+async fn store_dictionary_translations(
+	pool: &SqlitePool,
+	translations: &[TranslationEntry],
+	chain: &[String],
+	app: &AppHandle,
+) -> Result<usize, AppError> {
+	let total = translations.len();
+	let mut tx = pool.begin().await?;
+
+	// Temp tables belong to the connection, which the pool may hand out again
+	sqlx::query(
+		r#"
+        CREATE TEMP TABLE IF NOT EXISTS DictionaryTranslations (
+            name TEXT NOT NULL,
+            lang TEXT NOT NULL,
+            text TEXT NOT NULL,
+            PRIMARY KEY (name, lang)
+        )
+        "#,
+	)
+	.execute(&mut *tx)
+	.await?;
+	sqlx::query("DELETE FROM temp.DictionaryTranslations")
+		.execute(&mut *tx)
+		.await?;
+
+	// Later entries for the same tag and language replace earlier ones
+	for (batch_idx, batch) in translations.chunks(STAGE_BATCH_SIZE).enumerate() {
+		check_refresh_cancelled()?;
+		let query = format!(
+			"INSERT OR REPLACE INTO temp.DictionaryTranslations (name, lang, text) VALUES {}",
+			vec!["(?, ?, ?)"; batch.len()].join(", ")
+		);
+		let mut query_builder = sqlx::query(&query);
+		for (name, translated, lang) in batch {
+			query_builder = query_builder.bind(name).bind(lang).bind(translated);
 		}
+		query_builder.execute(&mut *tx).await?;
 
-		translations.push((tag_name, translated_name, language_code));
+		app.emit(
+			"translation-progress",
+			AliasRefreshProgress {
+				current: ((batch_idx + 1) * STAGE_BATCH_SIZE).min(total),
+				total,
+				completed: false,
+				error: None,
+			},
+		)
+		.ok();
 	}
+	check_refresh_cancelled()?;
+
+	// Merge: drop dictionary translations no longer in the dictionary, update changed ones and
+	// add new ones. User translations are left alone and win over the dictionary.
+	sqlx::query(
+		r#"
+        DELETE FROM TagTranslations
+        WHERE source = 'dictionary' AND NOT EXISTS(
+            SELECT 1 FROM temp.DictionaryTranslations d
+            JOIN Tags t ON t.name = d.name
+            WHERE t.tag_id = TagTranslations.tag_id AND d.lang = TagTranslations.lang
+        )
+        "#,
+	)
+	.execute(&mut *tx)
+	.await?;
+	sqlx::query(
+		r#"
+        UPDATE TagTranslations SET text = d.text
+        FROM temp.DictionaryTranslations d
+        JOIN Tags t ON t.name = d.name
+        WHERE TagTranslations.tag_id = t.tag_id AND TagTranslations.lang = d.lang
+            AND TagTranslations.source = 'dictionary' AND TagTranslations.text != d.text
+        "#,
+	)
+	.execute(&mut *tx)
+	.await?;
+	sqlx::query(
+		r#"
+        INSERT OR IGNORE INTO TagTranslations (tag_id, lang, text, source)
+        SELECT t.tag_id, d.lang, d.text, 'dictionary'
+        FROM temp.DictionaryTranslations d
+        JOIN Tags t ON t.name = d.name
+        "#,
+	)
+	.execute(&mut *tx)
+	.await?;
+	sqlx::query("DELETE FROM temp.DictionaryTranslations")
+		.execute(&mut *tx)
+		.await?;
+
+	let translated = apply_translation_language(&mut tx, chain, None).await?;
+	check_refresh_cancelled()?;
+	tx.commit().await?;
 
-	Ok((translations, invalid_count))
+	Ok(translated)
 }
 
-/// Refresh alias field in Tags table for a specific language
+/// Store the dictionary's translations in every language, then show the languages of `chain`
+/// (the selected language and its fallbacks) as aliases
 ///
-/// Race-condition handling strategy:
-/// 1. Use a single UPDATE statement with CASE WHEN to atomically set aliases
-/// 2. Tags not in the dictionary get alias = NULL
-/// 3. Tags in the dictionary get their translated name
-/// 4. New tags inserted during this operation will have alias = NULL initially,
-///    which is correct - they can be translated on next refresh
+/// Runs in one transaction: on an error or `cancel_translation_refresh`, nothing changes.
+/// Progress and errors are emitted as `translation-progress` events. Dictionary entries for
+/// tags that don't exist yet are skipped, so this is run again after importing new tags.
 async fn refresh_tag_aliases_for_language(
 	pool: &SqlitePool,
 	translations: &[TranslationEntry],
-	language_code: &str,
+	chain: &[String],
 	app: &AppHandle,
 ) -> Result<usize, AppError> {
-	// Filter translations for the specified language
-	let filtered: Vec<_> = translations
-		.iter()
-		.filter(|(_, _, lc)| lc == language_code)
-		.collect();
-
-	let total = filtered.len();
+	let total = translations.len();
+	REFRESH_CANCELLED.store(false, Ordering::Relaxed);
 
 	// Emit start progress
 	app.emit(
@@ -991,80 +1218,30 @@ async fn refresh_tag_aliases_for_language(
 	)
 	.ok();
 
-	// Build a map for quick lookup
-	let translation_map: std::collections::HashMap<&str, &str> = filtered
-		.iter()
-		.map(|(name, translated, _)| (name.as_str(), translated.as_str()))
-		.collect();
-
-	// Step 1: Clear all aliases first (atomic operation)
-	// This ensures a clean slate before applying new translations
-	let _ = sqlx::query("UPDATE Tags SET alias = NULL")
-		.execute(pool)
-		.await;
-
-	app.emit(
-		"translation-progress",
-		AliasRefreshProgress {
-			current: 0,
-			total,
-			completed: false,
-			error: None,
-		},
-	)
-	.ok();
-
-	// Step 2: Apply translations in batches
-	// Each UPDATE is atomic, so even if new tags are inserted between batches,
-	// they simply won't be updated (which is correct - they'll have NULL alias)
-	let mut updated_count = 0;
-	const BATCH_SIZE: usize = 100;
-
-	let names: Vec<&str> = translation_map.keys().copied().collect();
-	for (batch_idx, batch) in names.chunks(BATCH_SIZE).enumerate() {
-		for name in batch {
-			if let Some(translated) = translation_map.get(name) {
-				let result = sqlx::query("UPDATE Tags SET alias = ? WHERE name = ?")
-					.bind(*translated)
-					.bind(*name)
-					.execute(pool)
-					.await;
-
-				if let Ok(r) = result {
-					if r.rows_affected() > 0 {
-						updated_count += 1;
-					}
-				}
-			}
-		}
-
-		// Emit progress event after each batch
-		let current = ((batch_idx + 1) * BATCH_SIZE).min(total);
-		app.emit(
-			"translation-progress",
-			AliasRefreshProgress {
-				current,
-				total,
-				completed: current >= total,
-				error: None,
-			},
-		)
-		.ok();
-	}
+	let result = store_dictionary_translations(pool, translations, chain, app).await;
 
 	// Emit final progress event
 	app.emit(
 		"translation-progress",
 		AliasRefreshProgress {
-			current: total,
+			current: if result.is_ok() { total } else { 0 },
 			total,
 			completed: true,
-			error: None,
+			error: result.as_ref().err().map(|e| e.to_string()),
 		},
 	)
 	.ok();
 
-	Ok(updated_count)
+	result
+}
+
+/// Languages with a stored translation, from the dictionary or the user
+async fn get_stored_languages(pool: &SqlitePool) -> Result<Vec<String>, AppError> {
+	Ok(
+		sqlx::query_scalar("SELECT DISTINCT lang FROM TagTranslations ORDER BY lang")
+			.fetch_all(pool)
+			.await?,
+	)
 }
 
 /// Get available languages from translations
@@ -1082,7 +1259,7 @@ fn get_available_languages(translations: &[TranslationEntry]) -> Vec<String> {
 #[tauri::command]
 pub async fn upload_translation_dictionary(
 	app: AppHandle,
-	_pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_path: String,
 ) -> Result<TranslationUploadResult, AppError> {
 	let source_path = Path::new(&file_path);
@@ -1095,11 +1272,12 @@ pub async fn upload_translation_dictionary(
 			valid_entries: 0,
 			invalid_entries: 0,
 			available_languages: vec![],
+			diagnostics: Vec::new(),
 		});
 	}
 
 	// Parse translation CSV to validate and get available languages
-	let (translations, invalid_count) = parse_translation_csv(source_path).await?;
+	let (translations, report) = parse_translation_csv(source_path).await?;
 
 	if translations.is_empty() {
 		return Ok(TranslationUploadResult {
@@ -1107,8 +1285,9 @@ pub async fn upload_translation_dictionary(
 			message: "No valid translation entries found in file".to_string(),
 			file_path: None,
 			valid_entries: 0,
-			invalid_entries: invalid_count,
+			invalid_entries: report.invalid_rows,
 			available_languages: vec![],
+			diagnostics: report.diagnostics,
 		});
 	}
 
@@ -1135,6 +1314,10 @@ pub async fn upload_translation_dictionary(
 		.await
 		.map_err(|e| AppError::Custom(format!("Failed to copy translation file: {e}")))?;
 
+	// Store every language now, so switching language later doesn't read the file
+	let chain = get_language_chain(&app).await?;
+	refresh_tag_aliases_for_language(&db.get(), &translations, &chain, &app).await?;
+
 	Ok(TranslationUploadResult {
 		success: true,
 		message: format!(
@@ -1144,16 +1327,18 @@ pub async fn upload_translation_dictionary(
 		),
 		file_path: Some(target_path.display().to_string()),
 		valid_entries: translations.len(),
-		invalid_entries: invalid_count,
+		invalid_entries: report.invalid_rows,
 		available_languages,
+		diagnostics: report.diagnostics,
 	})
 }
 
 #[tauri::command]
 pub async fn get_translation_status(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 ) -> Result<TranslationStatus, AppError> {
+	let pool = db.get();
 	let translations_dir = get_translations_dir(app.clone())?;
 	let dictionary_path = translations_dir.join("translations.csv");
 
@@ -1165,12 +1350,18 @@ pub async fn get_translation_status(
 		if let Ok((translations, _)) = parse_translation_csv(&dictionary_path).await {
 			available_languages = get_available_languages(&translations);
 		}
+		for language in get_stored_languages(&pool).await? {
+			if !available_languages.contains(&language) {
+				available_languages.push(language);
+			}
+		}
+		available_languages.sort();
 
 		// Count translations in database
 		if let Ok(row) = sqlx::query(
 			"SELECT COUNT(*) as count FROM Tags WHERE alias IS NOT NULL AND alias != ''",
 		)
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await
 		{
 			if let Ok(count) = row.try_get::<i64, _>(0) {
@@ -1188,6 +1379,7 @@ pub async fn get_translation_status(
 
 	Ok(TranslationStatus {
 		current_language,
+		fallback_languages: get_translation_fallbacks(app.clone()).await?,
 		available_languages,
 		dictionary_loaded: dictionary_path.exists(),
 		dictionary_path: if dictionary_path.exists() {
@@ -1199,39 +1391,55 @@ pub async fn get_translation_status(
 	})
 }
 
+/// Show tag aliases in `language_code` (a BCP 47 tag, e.g. `zh-Hant`), falling back to the
+/// fallback languages
+/// Translations are already stored for every language, so this is a single update.
 #[tauri::command]
 pub async fn set_translation_language(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	language_code: String,
 ) -> Result<usize, AppError> {
-	// Load dictionary and apply translations for the selected language
-	let translations_dir = get_translations_dir(app.clone())?;
-	let dictionary_path = translations_dir.join("translations.csv");
+	let pool = db.get();
+	let language_code = normalize_language_code(&language_code).map_err(AppError::Custom)?;
+	let stored = get_stored_languages(&pool).await?.contains(&language_code);
 
-	if !dictionary_path.exists() {
-		return Err(AppError::Custom(
-			"No translation dictionary loaded".to_string(),
-		));
-	}
+	// A dictionary uploaded before every language was stored has to be read once
+	let translations = if stored {
+		None
+	} else {
+		let translations_dir = get_translations_dir(app.clone())?;
+		let dictionary_path = translations_dir.join("translations.csv");
 
-	let (translations, _) = parse_translation_csv(&dictionary_path).await?;
-	let available_languages = get_available_languages(&translations);
+		if !dictionary_path.exists() {
+			return Err(AppError::Custom(
+				"No translation dictionary loaded".to_string(),
+			));
+		}
 
-	if !available_languages.contains(&language_code) {
-		return Err(AppError::Custom(format!(
-			"Language '{language_code}' not found in dictionary. Available: {available_languages:?}"
-		)));
-	}
+		let (translations, _) = parse_translation_csv(&dictionary_path).await?;
+		let available_languages = get_available_languages(&translations);
+
+		if !available_languages.contains(&language_code) {
+			return Err(AppError::Custom(format!(
+				"Language '{language_code}' not found in dictionary. Available: {available_languages:?}"
+			)));
+		}
+		Some(translations)
+	};
 
 	// Save language preference
 	let store = app.store("translation-settings.json")?;
 	store.set("language_code", language_code.clone());
 	store.save()?;
 
-	// Apply translations (this clears old aliases and applies new ones)
-	let updated =
-		refresh_tag_aliases_for_language(pool.inner(), &translations, &language_code, &app).await?;
+	let chain = get_language_chain(&app).await?;
+	let updated = match translations {
+		Some(translations) => {
+			refresh_tag_aliases_for_language(&pool, &translations, &chain, &app).await?
+		}
+		None => apply_translation_language(&mut *pool.acquire().await?, &chain, None).await?,
+	};
 
 	Ok(updated)
 }
@@ -1245,15 +1453,71 @@ pub async fn get_translation_language(app: AppHandle) -> Result<Option<String>,
 	Ok(language_code)
 }
 
+/// Languages tried in order when a tag has no translation in the selected language; unless
+/// chosen with `set_translation_fallbacks`, the selected language with subtags dropped
+/// (`zh-Hant-TW` -> `zh-Hant`, `zh`). Tags without any show their name.
+#[tauri::command]
+pub async fn get_translation_fallbacks(app: AppHandle) -> Result<Vec<String>, AppError> {
+	let store = app.store("translation-settings.json")?;
+	if let Some(fallbacks) = store.get("fallback_languages").and_then(|v| {
+		v.as_array().map(|languages| {
+			languages
+				.iter()
+				.filter_map(|l| l.as_str().map(|s| s.to_string()))
+				.collect::<Vec<_>>()
+		})
+	}) {
+		return Ok(fallbacks);
+	}
+
+	Ok(get_translation_language(app)
+		.await?
+		.map(|language_code| default_fallback_languages(&language_code))
+		.unwrap_or_default())
+}
+
+/// Choose the fallback languages (e.g. `["zh"]` for `zh-Hant`), or go back to the default
+/// with `None`, and re-apply aliases. Returns how many tags have a translation.
+#[tauri::command]
+pub async fn set_translation_fallbacks(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	fallback_languages: Option<Vec<String>>,
+) -> Result<usize, AppError> {
+	let pool = db.get();
+	let store = app.store("translation-settings.json")?;
+	match fallback_languages {
+		Some(fallback_languages) => {
+			let fallback_languages = fallback_languages
+				.iter()
+				.map(|code| normalize_language_code(code))
+				.collect::<Result<Vec<_>, _>>()
+				.map_err(AppError::Custom)?;
+			store.set("fallback_languages", fallback_languages);
+		}
+		None => {
+			store.delete("fallback_languages");
+		}
+	}
+	store.save()?;
+
+	let chain = get_language_chain(&app).await?;
+	apply_translation_language(&mut *pool.acquire().await?, &chain, None).await
+}
+
 #[tauri::command]
 pub async fn remove_translation_dictionary(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 ) -> Result<(), AppError> {
-	// Clear all aliases in database
-	let _ = sqlx::query("UPDATE Tags SET alias = NULL")
-		.execute(pool.inner())
-		.await;
+	let pool = db.get();
+	// Clear dictionary translations and all aliases; user translations are kept
+	let mut tx = pool.begin().await?;
+	sqlx::query("DELETE FROM TagTranslations WHERE source = 'dictionary'")
+		.execute(&mut *tx)
+		.await?;
+	apply_translation_language(&mut tx, &[], None).await?;
+	tx.commit().await?;
 
 	// Remove translation file
 	let translations_dir = get_translations_dir(app.clone())?;
@@ -1272,22 +1536,23 @@ pub async fn remove_translation_dictionary(
 	Ok(())
 }
 
-/// Refresh translations for the current language
+/// Stop a running translation refresh; it rolls back and reports the cancellation as its error
+#[tauri::command]
+pub async fn cancel_translation_refresh() -> Result<(), AppError> {
+	REFRESH_CANCELLED.store(true, Ordering::Relaxed);
+	Ok(())
+}
+
+/// Reload the dictionary's translations and re-apply the current language
 /// This is useful after importing new images/tags to apply translations to new tags
 #[tauri::command]
 pub async fn refresh_translations(
 	app: AppHandle,
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 ) -> Result<usize, AppError> {
-	// Get current language
-	let store = app.store("translation-settings.json")?;
-	let language_code = store
-		.get("language_code")
-		.and_then(|v| v.as_str().map(|s| s.to_string()));
-
-	let Some(language_code) = language_code else {
-		return Ok(0); // No language selected, nothing to refresh
-	};
+	let pool = db.get();
+	// Get current language; without one, translations are still stored for search
+	let chain = get_language_chain(&app).await?;
 
 	// Load dictionary
 	let translations_dir = get_translations_dir(app.clone())?;
@@ -1300,8 +1565,214 @@ pub async fn refresh_translations(
 	let (translations, _) = parse_translation_csv(&dictionary_path).await?;
 
 	// Apply translations
-	let updated =
-		refresh_tag_aliases_for_language(pool.inner(), &translations, &language_code, &app).await?;
+	let updated = refresh_tag_aliases_for_language(&pool, &translations, &chain, &app).await?;
 
 	Ok(updated)
 }
+
+// ============================================================================
+// Translation Overrides
+// ============================================================================
+
+/// Re-read one tag's alias after one of its translations changed
+async fn apply_translation_to_tag(
+	app: &AppHandle,
+	conn: &mut SqliteConnection,
+	tag_id: i64,
+) -> Result<(), AppError> {
+	let chain = get_language_chain(app).await?;
+	apply_translation_language(conn, &chain, Some(tag_id)).await?;
+	Ok(())
+}
+
+/// The uploaded dictionary's entries, or none when no dictionary is uploaded
+async fn read_dictionary(app: &AppHandle) -> Result<Vec<TranslationEntry>, AppError> {
+	let dictionary_path = get_translations_dir(app.clone())?.join("translations.csv");
+	if !dictionary_path.exists() {
+		return Ok(Vec::new());
+	}
+	Ok(parse_translation_csv(&dictionary_path).await?.0)
+}
+
+/// Set a tag's translation in `language_code`, replacing the dictionary's
+#[tauri::command]
+pub async fn set_translation_override(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+	language_code: String,
+	text: String,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let language_code = normalize_language_code(&language_code).map_err(AppError::Custom)?;
+	let text = text.trim();
+	if text.is_empty() {
+		return Err(AppError::Custom(
+			"Translation cannot be empty; clear the override instead".to_string(),
+		));
+	}
+
+	let mut tx = pool.begin().await?;
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Tags WHERE tag_id = ?)")
+		.bind(tag_id)
+		.fetch_one(&mut *tx)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("Tag with id {tag_id} not found")));
+	}
+
+	sqlx::query(
+		r#"
+        INSERT INTO TagTranslations (tag_id, lang, text, source) VALUES (?, ?, ?, 'user')
+        ON CONFLICT(tag_id, lang) DO UPDATE SET text = excluded.text, source = 'user'
+        "#,
+	)
+	.bind(tag_id)
+	.bind(&language_code)
+	.bind(text)
+	.execute(&mut *tx)
+	.await?;
+	apply_translation_to_tag(&app, &mut tx, tag_id).await?;
+	tx.commit().await?;
+
+	Ok(())
+}
+
+/// Remove a tag's translation override in `language_code`, going back to the dictionary's
+/// translation if it has one
+#[tauri::command]
+pub async fn clear_translation_override(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+	language_code: String,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let language_code = normalize_language_code(&language_code).map_err(AppError::Custom)?;
+	let tag_name: Option<String> = sqlx::query_scalar("SELECT name FROM Tags WHERE tag_id = ?")
+		.bind(tag_id)
+		.fetch_optional(&pool)
+		.await?;
+	let Some(tag_name) = tag_name else {
+		return Err(AppError::Custom(format!("Tag with id {tag_id} not found")));
+	};
+
+	// The last dictionary entry wins, as in a refresh
+	let dictionary_text = read_dictionary(&app)
+		.await?
+		.into_iter()
+		.rev()
+		.find(|(name, _, lang)| *name == tag_name && *lang == language_code)
+		.map(|(_, text, _)| text);
+
+	let mut tx = pool.begin().await?;
+	sqlx::query("DELETE FROM TagTranslations WHERE tag_id = ? AND lang = ? AND source = 'user'")
+		.bind(tag_id)
+		.bind(&language_code)
+		.execute(&mut *tx)
+		.await?;
+	if let Some(text) = dictionary_text {
+		sqlx::query(
+			"INSERT OR IGNORE INTO TagTranslations (tag_id, lang, text, source) VALUES (?, ?, ?, 'dictionary')",
+		)
+		.bind(tag_id)
+		.bind(&language_code)
+		.bind(text)
+		.execute(&mut *tx)
+		.await?;
+	}
+	apply_translation_to_tag(&app, &mut tx, tag_id).await?;
+	tx.commit().await?;
+
+	Ok(())
+}
+
+/// Translation overrides, optionally in one language, by tag name
+#[tauri::command]
+pub async fn list_translation_overrides(
+	db: tauri::State<'_, DbPool>,
+	language_code: Option<String>,
+) -> Result<Vec<TranslationOverride>, AppError> {
+	let pool = db.get();
+	let rows = sqlx::query(
+		r#"
+        SELECT tr.tag_id, t.name, tr.lang, tr.text
+        FROM TagTranslations tr
+        JOIN Tags t ON t.tag_id = tr.tag_id
+        WHERE tr.source = 'user' AND (? IS NULL OR tr.lang = ?)
+        ORDER BY t.name, tr.lang
+        "#,
+	)
+	.bind(&language_code)
+	.bind(&language_code)
+	.fetch_all(&pool)
+	.await?;
+
+	Ok(rows
+		.iter()
+		.map(|row| TranslationOverride {
+			tag_id: row.get("tag_id"),
+			tag_name: row.get("name"),
+			language_code: row.get("lang"),
+			text: row.get("text"),
+		})
+		.collect())
+}
+
+/// Write the dictionary with overrides applied as `name,translated_name,language_code` CSV
+/// Overrides for entries missing from the dictionary are added at the end. Returns the number
+/// of entries written.
+#[tauri::command]
+pub async fn export_translation_dictionary(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	destination: String,
+) -> Result<usize, AppError> {
+	let pool = db.get();
+	let overrides: Vec<(String, String, String)> = sqlx::query_as(
+		r#"
+        SELECT t.name, tr.lang, tr.text
+        FROM TagTranslations tr
+        JOIN Tags t ON t.tag_id = tr.tag_id
+        WHERE tr.source = 'user'
+        ORDER BY t.name, tr.lang
+        "#,
+	)
+	.fetch_all(&pool)
+	.await?;
+
+	// One entry per tag and language, in dictionary order; the last dictionary entry wins
+	let mut entries: Vec<TranslationEntry> = Vec::new();
+	let mut positions: HashMap<(String, String), usize> = HashMap::new();
+	for (name, text, lang) in read_dictionary(&app).await? {
+		match positions.get(&(name.clone(), lang.clone())) {
+			Some(&position) => entries[position].1 = text,
+			None => {
+				positions.insert((name.clone(), lang.clone()), entries.len());
+				entries.push((name, text, lang));
+			}
+		}
+	}
+	for (name, lang, text) in overrides {
+		match positions.get(&(name.clone(), lang.clone())) {
+			Some(&position) => entries[position].1 = text,
+			None => entries.push((name, text, lang)),
+		}
+	}
+
+	let mut writer = csv::Writer::from_path(&destination)
+		.map_err(|e| AppError::Custom(format!("Failed to create {destination}: {e}")))?;
+	let write_error =
+		|e: csv::Error| AppError::Custom(format!("Failed to write {destination}: {e}"));
+	writer
+		.write_record(TRANSLATION_COLUMNS)
+		.map_err(write_error)?;
+	for (name, text, lang) in &entries {
+		writer
+			.write_record([name, text, lang])
+			.map_err(write_error)?;
+	}
+	writer.flush()?;
+
+	Ok(entries.len())
+}
@@ -0,0 +1,412 @@
+use super::files::ProgressEvent;
+use super::metadata::{add_keyword_tags, keyword_to_tag_name, load_metadata_settings};
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::metadata::{self, SidecarFields};
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// What to do with a sidecar that another tool changed since our last sync
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum SidecarConflictStrategy {
+	/// Leave the sidecar alone and report the conflict
+	#[default]
+	Skip,
+	/// Overwrite the sidecar with the library's tags, rating and favorite
+	PreferLibrary,
+	/// Apply the sidecar's tags, rating and favorite to the library, then rewrite it
+	PreferSidecar,
+}
+
+#[derive(Debug, Serialize, Clone)]
+pub struct SidecarConflict {
+	pub file_hash: String,
+	pub sidecar_path: String,
+	pub sidecar_modified: i64,    // Unix milliseconds
+	pub last_synced: Option<i64>, // Unix milliseconds, None if never synced
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct SidecarSyncResult {
+	pub written: usize,
+	pub unchanged: usize,
+	/// Sidecars whose changes were applied to the library
+	pub imported: usize,
+	/// Files skipped because the original is missing
+	pub skipped_missing: usize,
+	pub conflicts: Vec<SidecarConflict>,
+	pub errors: Vec<String>,
+}
+
+struct SyncState {
+	synced_mtime: i64,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Sidecar we write: `photo.jpg` -> `photo.jpg.xmp` (digiKam/darktable convention)
+fn sidecar_path_for(original: &Path) -> PathBuf {
+	let mut path = original.as_os_str().to_owned();
+	path.push(".xmp");
+	PathBuf::from(path)
+}
+
+/// Existing sidecar for an original, also accepting the `photo.xmp` convention
+fn find_sidecar(original: &Path) -> Option<PathBuf> {
+	[sidecar_path_for(original), original.with_extension("xmp")]
+		.into_iter()
+		.find(|path| path.is_file())
+}
+
+fn mtime_millis(path: &Path) -> Option<i64> {
+	std::fs::metadata(path)
+		.ok()?
+		.modified()
+		.ok()?
+		.duration_since(UNIX_EPOCH)
+		.ok()
+		.map(|d| d.as_millis() as i64)
+}
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+async fn load_sync_state(
+	pool: &SqlitePool,
+	file_hash: &str,
+) -> Result<Option<SyncState>, AppError> {
+	let row = sqlx::query("SELECT synced_mtime FROM XmpSidecars WHERE file_hash = ?")
+		.bind(file_hash)
+		.fetch_optional(pool)
+		.await?;
+
+	Ok(row.map(|row| SyncState {
+		synced_mtime: row.get("synced_mtime"),
+	}))
+}
+
+async fn record_sync(
+	pool: &SqlitePool,
+	file_hash: &str,
+	sidecar_path: &Path,
+	rating: Option<i64>,
+) -> Result<(), AppError> {
+	let synced_mtime = mtime_millis(sidecar_path).unwrap_or(0);
+
+	sqlx::query(
+		r#"
+        INSERT INTO XmpSidecars (file_hash, sidecar_path, synced_mtime, synced_at, rating)
+        VALUES (?, ?, ?, ?, ?)
+        ON CONFLICT(file_hash) DO UPDATE SET
+            sidecar_path = excluded.sidecar_path,
+            synced_mtime = excluded.synced_mtime,
+            synced_at = excluded.synced_at,
+            rating = excluded.rating
+        "#,
+	)
+	.bind(file_hash)
+	.bind(sidecar_path.display().to_string())
+	.bind(synced_mtime)
+	.bind(unix_now())
+	.bind(rating)
+	.execute(pool)
+	.await?;
+
+	Ok(())
+}
+
+/// Current tags, rating and favorite flag of a file in the library
+async fn library_fields(pool: &SqlitePool, file_hash: &str) -> Result<SidecarFields, AppError> {
+	let keywords: Vec<String> = sqlx::query_scalar(
+		r#"
+        SELECT t.name FROM Tags t
+        INNER JOIN FileTags ft ON t.tag_id = ft.tag_id
+        WHERE ft.file_hash = ?
+        ORDER BY t.name
+        "#,
+	)
+	.bind(file_hash)
+	.fetch_all(pool)
+	.await?;
+
+	let favorite: Option<i64> =
+		sqlx::query_scalar("SELECT favorite_id FROM Favorites WHERE file_hash = ?")
+			.bind(file_hash)
+			.fetch_optional(pool)
+			.await?;
+
+	let rating = super::stats::load_rating(pool, file_hash).await?;
+
+	Ok(SidecarFields {
+		keywords,
+		rating,
+		favorite: favorite.is_some(),
+	})
+}
+
+fn read_sidecar(path: &Path) -> Result<SidecarFields, AppError> {
+	let xmp = std::fs::read_to_string(path)?;
+	Ok(metadata::read_sidecar_fields(&xmp))
+}
+
+/// Write our fields into the sidecar, keeping whatever else other tools stored there
+/// Returns false if the sidecar already had the same content
+fn write_sidecar(path: &Path, fields: &SidecarFields) -> Result<bool, AppError> {
+	let existing = std::fs::read_to_string(path).ok();
+	let base = existing.clone().unwrap_or_else(metadata::empty_xmp_packet);
+
+	let updated = metadata::update_xmp_packet(&base, fields)
+		.or_else(|| metadata::update_xmp_packet(&metadata::empty_xmp_packet(), fields))
+		.ok_or_else(|| AppError::Custom("Failed to build XMP packet".to_string()))?;
+
+	if existing.as_deref() == Some(updated.as_str()) {
+		return Ok(false);
+	}
+
+	// Write next to the sidecar first so a crash never leaves a truncated file behind
+	let mut staging = path.as_os_str().to_owned();
+	staging.push(".tmp");
+	let staging = PathBuf::from(staging);
+	std::fs::write(&staging, updated)?;
+	std::fs::rename(&staging, path)?;
+
+	Ok(true)
+}
+
+/// Apply sidecar fields to the library
+///
+/// Tags are added; when `authoritative` is set, tags missing from the sidecar are removed too
+/// (used when the sidecar was synced before, so a missing keyword means it was deleted).
+async fn apply_sidecar_fields(
+	pool: &SqlitePool,
+	file_hash: &str,
+	fields: &SidecarFields,
+	authoritative: bool,
+) -> Result<(), AppError> {
+	add_keyword_tags(pool, file_hash, &fields.keywords).await?;
+
+	// xmp:Rating -1 means "rejected", which has no star equivalent
+	let rating = fields.rating.filter(|r| (0..=5).contains(r));
+	if rating.is_some() || authoritative {
+		super::stats::set_rating(pool, file_hash, rating).await?;
+	}
+
+	if fields.favorite {
+		sqlx::query("INSERT OR IGNORE INTO Favorites (file_hash) VALUES (?)")
+			.bind(file_hash)
+			.execute(pool)
+			.await?;
+	}
+
+	if authoritative {
+		if !fields.favorite {
+			sqlx::query("DELETE FROM Favorites WHERE file_hash = ?")
+				.bind(file_hash)
+				.execute(pool)
+				.await?;
+		}
+
+		let keep: HashSet<String> = fields
+			.keywords
+			.iter()
+			.map(|keyword| keyword_to_tag_name(keyword))
+			.collect();
+		let current = sqlx::query(
+			r#"
+            SELECT t.tag_id, t.name FROM Tags t
+            INNER JOIN FileTags ft ON t.tag_id = ft.tag_id
+            WHERE ft.file_hash = ?
+            "#,
+		)
+		.bind(file_hash)
+		.fetch_all(pool)
+		.await?;
+
+		for row in current {
+			let name: String = row.get("name");
+			if keep.contains(&name) {
+				continue;
+			}
+			sqlx::query("DELETE FROM FileTags WHERE file_hash = ? AND tag_id = ?")
+				.bind(file_hash)
+				.bind(row.get::<i64, _>("tag_id"))
+				.execute(pool)
+				.await?;
+		}
+	}
+
+	Ok(())
+}
+
+/// Read an existing sidecar while importing a file, if sidecars are enabled
+pub async fn read_sidecar_on_import(
+	app: &AppHandle,
+	pool: &SqlitePool,
+	file_hash: &str,
+	original_path: &Path,
+) -> Result<(), AppError> {
+	if !load_metadata_settings(app)?.xmp_sidecars_enabled {
+		return Ok(());
+	}
+	let Some(sidecar_path) = find_sidecar(original_path) else {
+		return Ok(());
+	};
+
+	let fields = read_sidecar(&sidecar_path)?;
+	apply_sidecar_fields(pool, file_hash, &fields, false).await?;
+	record_sync(pool, file_hash, &sidecar_path, fields.rating).await
+}
+
+async fn sync_file(
+	pool: &SqlitePool,
+	file_hash: &str,
+	original_path: &Path,
+	strategy: SidecarConflictStrategy,
+	result: &mut SidecarSyncResult,
+) -> Result<(), AppError> {
+	let state = load_sync_state(pool, file_hash).await?;
+	let existing = find_sidecar(original_path);
+
+	// A sidecar we never synced, or one modified since, was written by another tool
+	let conflict = existing.as_ref().and_then(|path| {
+		let modified = mtime_millis(path)?;
+		let changed = state
+			.as_ref()
+			.map_or(true, |state| state.synced_mtime != modified);
+		changed.then_some((path.clone(), modified))
+	});
+
+	let sidecar_path = existing.unwrap_or_else(|| sidecar_path_for(original_path));
+
+	if let Some((path, modified)) = conflict {
+		match strategy {
+			SidecarConflictStrategy::Skip => {
+				result.conflicts.push(SidecarConflict {
+					file_hash: file_hash.to_string(),
+					sidecar_path: path.display().to_string(),
+					sidecar_modified: modified,
+					last_synced: state.map(|s| s.synced_mtime),
+				});
+				return Ok(());
+			}
+			SidecarConflictStrategy::PreferLibrary => {}
+			SidecarConflictStrategy::PreferSidecar => {
+				let fields = read_sidecar(&path)?;
+				apply_sidecar_fields(pool, file_hash, &fields, state.is_some()).await?;
+				record_sync(pool, file_hash, &path, fields.rating).await?;
+				result.imported += 1;
+			}
+		}
+	}
+
+	let fields = library_fields(pool, file_hash).await?;
+	let path = sidecar_path.clone();
+	let fields_for_write = fields.clone();
+	let written = tokio::task::spawn_blocking(move || write_sidecar(&path, &fields_for_write))
+		.await
+		.map_err(|e| AppError::Custom(format!("Task join error: {e}")))??;
+
+	if written {
+		result.written += 1;
+	} else {
+		result.unchanged += 1;
+	}
+	record_sync(pool, file_hash, &sidecar_path, fields.rating).await
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Write tags, rating and favorite label to `.xmp` sidecars next to the originals
+///
+/// Syncs the given files, or the whole library when `file_hashes` is None. Sidecars changed by
+/// another tool since the last sync are handled according to `conflict_strategy`.
+/// Emits sidecar_sync_progress events
+#[tauri::command]
+pub async fn sync_xmp_sidecars(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	file_hashes: Option<Vec<String>>,
+	conflict_strategy: Option<SidecarConflictStrategy>,
+) -> Result<SidecarSyncResult, AppError> {
+	let pool = db.get();
+	if !load_metadata_settings(&app)?.xmp_sidecars_enabled {
+		return Err(AppError::Custom(
+			"XMP sidecars are disabled in settings".to_string(),
+		));
+	}
+	let strategy = conflict_strategy.unwrap_or_default();
+
+	let files: Vec<(String, String, i64)> = match file_hashes {
+		Some(hashes) => {
+			let mut files = Vec::with_capacity(hashes.len());
+			for hash in hashes {
+				let row = sqlx::query(
+					"SELECT file_hash, original_path, is_missing FROM Files WHERE file_hash = ?",
+				)
+				.bind(&hash)
+				.fetch_optional(&pool)
+				.await?
+				.ok_or_else(|| AppError::Custom(format!("File with hash {hash} not found")))?;
+				files.push((
+					row.get("file_hash"),
+					row.get("original_path"),
+					row.get("is_missing"),
+				));
+			}
+			files
+		}
+		None => {
+			sqlx::query_as("SELECT file_hash, original_path, is_missing FROM Files")
+				.fetch_all(&pool)
+				.await?
+		}
+	};
+
+	let mut result = SidecarSyncResult::default();
+	let total = files.len();
+
+	for (index, (file_hash, original_path, is_missing)) in files.into_iter().enumerate() {
+		let original_path = PathBuf::from(original_path);
+		if is_missing != 0 || !original_path.exists() {
+			result.skipped_missing += 1;
+			continue;
+		}
+
+		if let Err(e) = sync_file(&pool, &file_hash, &original_path, strategy, &mut result).await {
+			result
+				.errors
+				.push(format!("{}: {e}", original_path.display()));
+		}
+
+		app.emit(
+			"sidecar_sync_progress",
+			ProgressEvent {
+				stage: "syncing".to_string(),
+				message: format!("Syncing sidecar {} of {total}", index + 1),
+				file_hash: Some(file_hash),
+				current: Some(index + 1),
+				total: Some(total),
+			},
+		)
+		.ok();
+	}
+
+	Ok(result)
+}
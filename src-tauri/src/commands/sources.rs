@@ -0,0 +1,239 @@
+use super::files::{file_record_from_row, FileRecord, FILE_RECORD_COLUMNS};
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use crate::source_urls::{normalize_domain, parse_source_url, ParsedSource};
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqlitePool};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileSource {
+	pub source_id: i64,
+	pub file_hash: String,
+	pub url: String,
+	pub domain: String,
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	pub page: Option<i64>,
+	pub added_at: i64, // Unix timestamp
+}
+
+/// A page of a multi-image pixiv post
+#[derive(Debug, Serialize, Clone)]
+pub struct PixivPage {
+	pub page: Option<i64>,
+	pub file: FileRecord,
+}
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn file_source_from_row(row: &SqliteRow) -> FileSource {
+	FileSource {
+		source_id: row.get("source_id"),
+		file_hash: row.get("file_hash"),
+		url: row.get("url"),
+		domain: row.get("domain"),
+		site: row.get("site"),
+		post_id: row.get("post_id"),
+		page: row.get("page"),
+		added_at: row.get("added_at"),
+	}
+}
+
+/// Add a source URL to a file; adding the same URL twice is a no-op
+/// `page` overrides the page parsed from the URL (e.g. from a `_p3` filename)
+/// Returns whether a new source was added
+pub(crate) async fn add_source(
+	pool: &SqlitePool,
+	file_hash: &str,
+	url: &str,
+	page: Option<u32>,
+) -> Result<bool, AppError> {
+	let ParsedSource {
+		url,
+		domain,
+		site,
+		post_id,
+		page: url_page,
+	} = parse_source_url(url).map_err(AppError::Custom)?;
+
+	let added_at = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|e| AppError::Custom(format!("Invalid system time: {e}")))?
+		.as_secs() as i64;
+
+	let result = sqlx::query(
+		r#"
+        INSERT OR IGNORE INTO FileSources (file_hash, url, domain, site, post_id, page, added_at)
+        VALUES (?, ?, ?, ?, ?, ?, ?)
+        "#,
+	)
+	.bind(file_hash)
+	.bind(&url)
+	.bind(&domain)
+	.bind(&site)
+	.bind(&post_id)
+	.bind(page.or(url_page).map(i64::from))
+	.bind(added_at)
+	.execute(pool)
+	.await?;
+
+	Ok(result.rows_affected() > 0)
+}
+
+async fn list_sources(pool: &SqlitePool, file_hash: &str) -> Result<Vec<FileSource>, AppError> {
+	let rows = sqlx::query(
+		r#"
+        SELECT source_id, file_hash, url, domain, site, post_id, page, added_at
+        FROM FileSources
+        WHERE file_hash = ?
+        ORDER BY added_at, source_id
+        "#,
+	)
+	.bind(file_hash)
+	.fetch_all(pool)
+	.await?;
+
+	Ok(rows.iter().map(file_source_from_row).collect())
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Add a source URL to a file
+#[tauri::command]
+pub async fn add_file_source(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	url: String,
+	page: Option<u32>,
+) -> Result<Vec<FileSource>, AppError> {
+	let pool = db.get();
+	add_source(&pool, &file_hash, &url, page).await?;
+	list_sources(&pool, &file_hash).await
+}
+
+#[tauri::command]
+pub async fn remove_file_source(
+	db: tauri::State<'_, DbPool>,
+	source_id: i64,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let result = sqlx::query("DELETE FROM FileSources WHERE source_id = ?")
+		.bind(source_id)
+		.execute(&pool)
+		.await?;
+
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Source {source_id} not found")));
+	}
+	Ok(())
+}
+
+#[tauri::command]
+pub async fn get_file_sources(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<Vec<FileSource>, AppError> {
+	let pool = db.get();
+	list_sources(&pool, &file_hash).await
+}
+
+/// Find files by source domain (subdomains included), site and/or post ID
+#[tauri::command]
+pub async fn search_files_by_source(
+	db: tauri::State<'_, DbPool>,
+	domain: Option<String>,
+	site: Option<String>,
+	post_id: Option<String>,
+) -> Result<Vec<FileRecord>, AppError> {
+	let pool = db.get();
+	let domain = domain
+		.map(|d| normalize_domain(&d))
+		.filter(|d| !d.is_empty());
+	let site = site
+		.map(|s| s.trim().to_lowercase())
+		.filter(|s| !s.is_empty());
+	let post_id = post_id
+		.map(|p| p.trim().to_string())
+		.filter(|p| !p.is_empty());
+
+	if domain.is_none() && site.is_none() && post_id.is_none() {
+		return Err(AppError::Custom(
+			"Specify a domain, site or post ID to search by".to_string(),
+		));
+	}
+
+	let query = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}
+        FROM Files f
+        WHERE EXISTS (
+            SELECT 1 FROM FileSources s
+            WHERE s.file_hash = f.file_hash
+              AND (?1 IS NULL OR s.domain = ?1 OR s.domain LIKE '%.' || ?1)
+              AND (?2 IS NULL OR s.site = ?2)
+              AND (?3 IS NULL OR s.post_id = ?3)
+        )
+          AND {visible}
+        ORDER BY f.date_imported DESC
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+
+	let rows = sqlx::query(&query)
+		.bind(&domain)
+		.bind(&site)
+		.bind(&post_id)
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows.iter().map(file_record_from_row).collect())
+}
+
+/// Find every file of a pixiv illust, across all pages of a multi-page post
+/// Files are ordered by page; files whose source has no page sort first
+#[tauri::command]
+pub async fn find_pixiv_illust(
+	db: tauri::State<'_, DbPool>,
+	illust_id: String,
+) -> Result<Vec<PixivPage>, AppError> {
+	let pool = db.get();
+	let illust_id = illust_id.trim();
+	if illust_id.is_empty() || !illust_id.chars().all(|c| c.is_ascii_digit()) {
+		return Err(AppError::Custom(format!(
+			"Invalid pixiv illust ID: {illust_id}"
+		)));
+	}
+
+	let query = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}, MIN(s.page) as page
+        FROM Files f
+        JOIN FileSources s ON s.file_hash = f.file_hash
+        WHERE s.site = 'pixiv' AND s.post_id = ? AND {visible}
+        GROUP BY f.file_hash
+        ORDER BY page, f.original_path
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+
+	let rows = sqlx::query(&query).bind(illust_id).fetch_all(&pool).await?;
+
+	Ok(rows
+		.iter()
+		.map(|row| PixivPage {
+			page: row.get("page"),
+			file: file_record_from_row(row),
+		})
+		.collect())
+}
@@ -0,0 +1,333 @@
+use super::files::{file_list_item_from_row, FileListItem, FILE_RECORD_COLUMNS};
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqlitePool};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct FileStats {
+	pub file_hash: String,
+	/// 0-5 stars; `None` when unrated
+	pub rating: Option<i64>,
+	pub view_count: i64,
+	pub last_viewed_at: Option<i64>, // Unix timestamp
+}
+
+/// Filters for `get_files_by_stats`; all set fields must match
+#[derive(Debug, Deserialize, Clone, Default)]
+pub struct StatsFilter {
+	pub min_rating: Option<i64>,
+	pub max_rating: Option<i64>,
+	pub unrated_only: Option<bool>,
+	pub min_views: Option<i64>,
+	pub max_views: Option<i64>,
+	/// Unix timestamps bounding `last_viewed_at`
+	pub viewed_after: Option<i64>,
+	pub viewed_before: Option<i64>,
+	pub never_viewed: Option<bool>,
+}
+
+#[derive(Debug, Deserialize, Clone, Copy, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StatsSort {
+	Rating,
+	ViewCount,
+	LastViewed,
+	#[default]
+	DateImported,
+}
+
+const MAX_RATING: i64 = 5;
+const DEFAULT_REDISCOVER_MIN_RATING: i64 = 4;
+const DEFAULT_REDISCOVER_UNSEEN_DAYS: i64 = 30;
+const DEFAULT_REDISCOVER_LIMIT: i64 = 50;
+
+/// Files joined with their stats; unviewed files count as 0 views
+const STATS_BASE_QUERY: &str = "FROM Files f LEFT JOIN FileStats s ON s.file_hash = f.file_hash";
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+fn validate_rating(rating: Option<i64>) -> Result<(), AppError> {
+	match rating {
+		Some(r) if !(0..=MAX_RATING).contains(&r) => Err(AppError::Custom(format!(
+			"Rating must be between 0 and {MAX_RATING}, got {r}"
+		))),
+		_ => Ok(()),
+	}
+}
+
+pub(crate) async fn load_rating(
+	pool: &SqlitePool,
+	file_hash: &str,
+) -> Result<Option<i64>, AppError> {
+	let rating: Option<Option<i64>> =
+		sqlx::query_scalar("SELECT rating FROM FileStats WHERE file_hash = ?")
+			.bind(file_hash)
+			.fetch_optional(pool)
+			.await?;
+	Ok(rating.flatten())
+}
+
+/// Set or clear (`None`) a file's star rating
+pub(crate) async fn set_rating(
+	pool: &SqlitePool,
+	file_hash: &str,
+	rating: Option<i64>,
+) -> Result<(), AppError> {
+	validate_rating(rating)?;
+	sqlx::query(
+		r#"
+        INSERT INTO FileStats (file_hash, rating) VALUES (?, ?)
+        ON CONFLICT(file_hash) DO UPDATE SET rating = excluded.rating
+        "#,
+	)
+	.bind(file_hash)
+	.bind(rating)
+	.execute(pool)
+	.await?;
+	Ok(())
+}
+
+/// Count a view of a file
+pub(crate) async fn record_view(pool: &SqlitePool, file_hash: &str) -> Result<(), AppError> {
+	sqlx::query(
+		r#"
+        INSERT INTO FileStats (file_hash, view_count, last_viewed_at) VALUES (?, 1, ?)
+        ON CONFLICT(file_hash) DO UPDATE SET
+            view_count = view_count + 1,
+            last_viewed_at = excluded.last_viewed_at
+        "#,
+	)
+	.bind(file_hash)
+	.bind(unix_now())
+	.execute(pool)
+	.await?;
+	Ok(())
+}
+
+/// Conditions and binds for a `StatsFilter` on `STATS_BASE_QUERY`
+fn filter_conditions(filter: &StatsFilter) -> (Vec<&'static str>, Vec<i64>) {
+	let mut conditions = Vec::new();
+	let mut binds = Vec::new();
+	let mut push = |condition: &'static str, value: Option<i64>| {
+		if let Some(value) = value {
+			conditions.push(condition);
+			binds.push(value);
+		}
+	};
+
+	push("s.rating >= ?", filter.min_rating);
+	push("s.rating <= ?", filter.max_rating);
+	push("COALESCE(s.view_count, 0) >= ?", filter.min_views);
+	push("COALESCE(s.view_count, 0) <= ?", filter.max_views);
+	push("s.last_viewed_at >= ?", filter.viewed_after);
+	push("s.last_viewed_at <= ?", filter.viewed_before);
+
+	if filter.unrated_only.unwrap_or(false) {
+		conditions.push("s.rating IS NULL");
+	}
+	if filter.never_viewed.unwrap_or(false) {
+		conditions.push("s.last_viewed_at IS NULL");
+	}
+
+	(conditions, binds)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Set a file's star rating (0-5), or clear it with `None`
+#[tauri::command]
+pub async fn set_file_rating(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+	rating: Option<i64>,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+		.bind(&file_hash)
+		.fetch_one(&pool)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("File not found: {file_hash}")));
+	}
+	set_rating(&pool, &file_hash, rating).await
+}
+
+/// Count a view; the image viewer calls this when it opens a file
+#[tauri::command]
+pub async fn record_file_view(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<(), AppError> {
+	let pool = db.get();
+	record_view(&pool, &file_hash).await
+}
+
+/// Get rating and view statistics for multiple files
+/// Files without stats are returned unrated with no views
+#[tauri::command]
+pub async fn get_file_stats(
+	db: tauri::State<'_, DbPool>,
+	file_hashes: Vec<String>,
+) -> Result<Vec<FileStats>, AppError> {
+	let pool = db.get();
+	let mut stats = Vec::with_capacity(file_hashes.len());
+
+	for file_hash in file_hashes {
+		let row = sqlx::query(
+			"SELECT rating, view_count, last_viewed_at FROM FileStats WHERE file_hash = ?",
+		)
+		.bind(&file_hash)
+		.fetch_optional(&pool)
+		.await?;
+
+		stats.push(match row {
+			Some(row) => FileStats {
+				file_hash,
+				rating: row.get("rating"),
+				view_count: row.get("view_count"),
+				last_viewed_at: row.get("last_viewed_at"),
+			},
+			None => FileStats {
+				file_hash,
+				rating: None,
+				view_count: 0,
+				last_viewed_at: None,
+			},
+		});
+	}
+
+	Ok(stats)
+}
+
+/// Filter and sort files by rating and view statistics
+/// Unrated and never-viewed files sort last when descending
+#[tauri::command]
+pub async fn get_files_by_stats(
+	db: tauri::State<'_, DbPool>,
+	filter: Option<StatsFilter>,
+	sort: Option<StatsSort>,
+	descending: Option<bool>,
+	collapse_works: Option<bool>,
+	offset: Option<i64>,
+	limit: Option<i64>,
+) -> Result<Vec<FileListItem>, AppError> {
+	let pool = db.get();
+	let filter = filter.unwrap_or_default();
+	validate_rating(filter.min_rating)?;
+	validate_rating(filter.max_rating)?;
+
+	let (conditions, binds) = filter_conditions(&filter);
+	let visible = visible_files_condition("f.file_hash");
+	let where_clause = conditions
+		.iter()
+		.copied()
+		.chain([visible.as_str()])
+		.collect::<Vec<_>>()
+		.join(" AND ");
+
+	let base = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}, s.rating, COALESCE(s.view_count, 0) as view_count,
+               s.last_viewed_at
+        {STATS_BASE_QUERY}
+        WHERE {where_clause}
+        "#
+	);
+	let base = if collapse_works.unwrap_or(false) {
+		super::works::collapse_works_query(&base)
+	} else {
+		base
+	};
+
+	let column = match sort.unwrap_or_default() {
+		StatsSort::Rating => "rating",
+		StatsSort::ViewCount => "view_count",
+		StatsSort::LastViewed => "last_viewed_at",
+		StatsSort::DateImported => "date_imported",
+	};
+	let direction = if descending.unwrap_or(true) {
+		"DESC"
+	} else {
+		"ASC"
+	};
+	let query =
+		format!("{base} ORDER BY {column} {direction}, date_imported DESC LIMIT ? OFFSET ?");
+
+	let mut query_builder = sqlx::query(&query);
+	for bind in binds {
+		query_builder = query_builder.bind(bind);
+	}
+	let rows = query_builder
+		.bind(limit.filter(|l| *l > 0).unwrap_or(-1))
+		.bind(offset.unwrap_or(0))
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows.iter().map(file_list_item_from_row).collect())
+}
+
+/// Highly rated images that were rarely viewed, or not viewed for a while
+///
+/// Files rated at least `min_rating` (default 4) and unseen for `unseen_days` (default 30),
+/// fewest views first; ties are shuffled so repeated calls surface different images.
+#[tauri::command]
+pub async fn rediscover_files(
+	db: tauri::State<'_, DbPool>,
+	min_rating: Option<i64>,
+	unseen_days: Option<i64>,
+	limit: Option<i64>,
+	collapse_works: Option<bool>,
+) -> Result<Vec<FileListItem>, AppError> {
+	let pool = db.get();
+	let min_rating = min_rating.unwrap_or(DEFAULT_REDISCOVER_MIN_RATING);
+	validate_rating(Some(min_rating))?;
+	let unseen_since =
+		unix_now() - unseen_days.unwrap_or(DEFAULT_REDISCOVER_UNSEEN_DAYS).max(0) * 86400;
+
+	let base = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}, s.rating, s.view_count, s.last_viewed_at
+        {STATS_BASE_QUERY}
+        WHERE s.rating >= ?
+          AND (s.last_viewed_at IS NULL OR s.last_viewed_at < ?)
+          AND f.is_missing = 0
+          AND {visible}
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let base = if collapse_works.unwrap_or(false) {
+		super::works::collapse_works_query(&base)
+	} else {
+		base
+	};
+	let query = format!(
+		"{base} ORDER BY view_count ASC, rating DESC, COALESCE(last_viewed_at, 0) ASC, RANDOM() LIMIT ?"
+	);
+
+	let rows = sqlx::query(&query)
+		.bind(min_rating)
+		.bind(unseen_since)
+		.bind(limit.filter(|l| *l > 0).unwrap_or(DEFAULT_REDISCOVER_LIMIT))
+		.fetch_all(&pool)
+		.await?;
+
+	Ok(rows.iter().map(file_list_item_from_row).collect())
+}
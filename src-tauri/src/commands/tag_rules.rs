@@ -0,0 +1,584 @@
+use super::files::ProgressEvent;
+use super::tags::{tag_from_row, Tag, TAG_COLUMNS};
+use crate::csv_table;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::Serialize;
+use serde_json::Value;
+use sqlx::{Row, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Alias and implication rules of a tag
+#[derive(Debug, Serialize, Clone)]
+pub struct TagRules {
+	/// Names that stand for this tag
+	pub aliases: Vec<String>,
+	/// Tags added along with this one
+	pub implies: Vec<Tag>,
+	/// Tags that add this one
+	pub implied_by: Vec<Tag>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct TagImportSummary {
+	/// Rows read from the dump
+	pub rows_read: usize,
+	/// Library tags whose type, category or deprecated flag changed
+	pub tags_updated: usize,
+	/// Tags created because a library tag implies them
+	pub tags_created: usize,
+	pub aliases_added: usize,
+	/// Aliases not added because a tag already has that name
+	pub aliases_skipped: usize,
+	pub implications_added: usize,
+	/// Rows for tags that aren't in the library
+	pub skipped_unknown_tag: usize,
+	/// Rows that could not be read
+	pub invalid_rows: usize,
+	/// Why rows could not be read, with their line or record number
+	pub errors: Vec<String>,
+}
+
+/// A row of a Danbooru tags dump
+#[derive(Debug, Clone)]
+struct DanbooruTag {
+	name: String,
+	/// `Tags.type` and builtin category name
+	kind: (&'static str, &'static str),
+	aliases: Vec<String>,
+	implications: Vec<String>,
+	/// `None` when the dump has no deprecated column
+	is_deprecated: Option<bool>,
+}
+
+/// A library tag as it was before the import
+struct LocalTag {
+	tag_id: i64,
+	tag_type: String,
+	category_id: i64,
+	is_deprecated: bool,
+}
+
+/// Columns of a CSV dump without a header row
+const DEFAULT_COLUMNS: [&str; 5] = ["name", "category", "post_count", "aliases", "implications"];
+
+/// Errors listed in an import summary; further ones are only counted
+const MAX_REPORTED_ERRORS: usize = 100;
+const IMPORT_PROGRESS_INTERVAL: usize = 10000;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Lowercase with spaces as `_`, the form of Danbooru tag names
+fn name_key(name: &str) -> String {
+	name.trim().to_lowercase().replace(' ', "_")
+}
+
+/// `Tags.type` and builtin category name for a Danbooru category, by number or name
+/// Tags.type has no copyright or meta types; copyright tags are `series` as elsewhere.
+fn map_category(value: &str) -> Option<(&'static str, &'static str)> {
+	match value.trim().to_lowercase().as_str() {
+		"0" | "general" => Some(("general", "GENERAL")),
+		"1" | "artist" => Some(("artist", "ARTIST")),
+		"3" | "copyright" => Some(("series", "COPYRIGHT")),
+		"4" | "character" => Some(("character", "CHARACTER")),
+		"5" | "meta" => Some(("general", "META")),
+		_ => None,
+	}
+}
+
+fn parse_flag(value: &str) -> Option<bool> {
+	match value.trim().to_lowercase().as_str() {
+		"t" | "true" | "1" | "yes" => Some(true),
+		"f" | "false" | "0" | "no" => Some(false),
+		_ => None,
+	}
+}
+
+/// Tag names in a list field; names never contain whitespace or commas
+fn split_names(value: &str) -> Vec<String> {
+	value
+		.split(|c: char| c == ',' || c.is_whitespace())
+		.filter(|name| !name.is_empty())
+		.map(name_key)
+		.collect()
+}
+
+fn parse_row(
+	name: &str,
+	category: &str,
+	aliases: Vec<String>,
+	implications: Vec<String>,
+	is_deprecated: Option<&str>,
+) -> Result<DanbooruTag, String> {
+	let name = name_key(name);
+	if name.is_empty() {
+		return Err("empty tag name".to_string());
+	}
+	let kind = map_category(category).ok_or_else(|| format!("unknown category '{category}'"))?;
+	let is_deprecated = match is_deprecated.map(str::trim).filter(|v| !v.is_empty()) {
+		Some(value) => Some(parse_flag(value).ok_or_else(|| format!("invalid flag '{value}'"))?),
+		None => None,
+	};
+
+	Ok(DanbooruTag {
+		aliases: aliases.into_iter().filter(|a| *a != name).collect(),
+		implications: implications.into_iter().filter(|i| *i != name).collect(),
+		name,
+		kind,
+		is_deprecated,
+	})
+}
+
+/// A JSON lines row; list fields may be arrays or comma/space separated strings
+fn parse_json_row(value: &Value) -> Result<DanbooruTag, String> {
+	let text = |key: &str| match value.get(key) {
+		Some(Value::String(s)) => Some(s.clone()),
+		Some(Value::Number(n)) => Some(n.to_string()),
+		Some(Value::Bool(b)) => Some(b.to_string()),
+		_ => None,
+	};
+	let names = |key: &str| match value.get(key) {
+		Some(Value::Array(items)) => items
+			.iter()
+			.filter_map(Value::as_str)
+			.flat_map(split_names)
+			.collect(),
+		Some(Value::String(s)) => split_names(s),
+		_ => Vec::new(),
+	};
+
+	let name = text("name").ok_or("missing name")?;
+	let category = text("category").ok_or("missing category")?;
+	let is_deprecated = text("is_deprecated").or_else(|| text("deprecated"));
+	parse_row(
+		&name,
+		&category,
+		names("aliases"),
+		names("implications"),
+		is_deprecated.as_deref(),
+	)
+}
+
+/// Stream rows of a CSV (with or without a header row) or JSON lines dump, calling `row` for
+/// each valid one; rows that can't be read are described in `errors`
+/// Returns the number of invalid rows.
+fn read_tag_dump(
+	path: &Path,
+	errors: &mut Vec<String>,
+	mut row: impl FnMut(DanbooruTag),
+) -> Result<usize, AppError> {
+	let is_csv = path
+		.extension()
+		.is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+	if is_csv {
+		let file = File::open(path)
+			.map_err(|e| AppError::Custom(format!("Failed to open tags dump: {e}")))?;
+		let report = csv_table::read_rows(file, &DEFAULT_COLUMNS, |fields| {
+			row(parse_row(
+				fields.get("name").unwrap_or(""),
+				fields.get("category").unwrap_or(""),
+				split_names(fields.get("aliases").unwrap_or("")),
+				split_names(fields.get("implications").unwrap_or("")),
+				fields
+					.get("is_deprecated")
+					.or_else(|| fields.get("deprecated")),
+			)?);
+			Ok(())
+		})?;
+		let room = MAX_REPORTED_ERRORS.saturating_sub(errors.len());
+		errors.extend(
+			report
+				.diagnostics
+				.iter()
+				.take(room)
+				.map(ToString::to_string),
+		);
+		return Ok(report.invalid_rows);
+	}
+
+	let mut invalid_rows = 0;
+	let mut record_error = |message: String| {
+		invalid_rows += 1;
+		if errors.len() < MAX_REPORTED_ERRORS {
+			errors.push(message);
+		}
+	};
+
+	let reader = BufReader::new(File::open(path)?);
+	for (index, line) in reader.lines().enumerate() {
+		let line_number = index + 1;
+		let line = match line {
+			Ok(line) => line,
+			Err(e) => {
+				record_error(format!("Line {line_number}: {e}"));
+				continue;
+			}
+		};
+		if line.trim().is_empty() {
+			continue;
+		}
+		let parsed = serde_json::from_str::<Value>(&line)
+			.map_err(|e| e.to_string())
+			.and_then(|value| parse_json_row(&value));
+		match parsed {
+			Ok(parsed) => row(parsed),
+			Err(e) => record_error(format!("Line {line_number}: {e}")),
+		}
+	}
+	Ok(invalid_rows)
+}
+
+/// The tag an alias stands for, or `name` itself when it isn't an alias
+pub(crate) async fn canonical_tag_name(pool: &SqlitePool, name: &str) -> Result<String, AppError> {
+	let canonical: Option<String> = sqlx::query_scalar(
+		"SELECT t.name FROM TagAliases a JOIN Tags t ON t.tag_id = a.tag_id WHERE a.alias_name = ?",
+	)
+	.bind(name.trim())
+	.fetch_optional(pool)
+	.await?;
+	Ok(canonical.unwrap_or_else(|| name.to_string()))
+}
+
+/// Add the tags implied by a file's tags (and the tags those imply), or by every file's tags
+/// when `file_hash` is `None`; returns the number of tags added
+pub(crate) async fn add_implied_tags(
+	pool: &SqlitePool,
+	file_hash: Option<&str>,
+) -> Result<u64, AppError> {
+	let filter = if file_hash.is_some() {
+		"WHERE ft.file_hash = ?"
+	} else {
+		""
+	};
+	let query = format!(
+		r#"
+        WITH RECURSIVE implied(file_hash, tag_id) AS (
+            SELECT ft.file_hash, i.implied_tag_id FROM FileTags ft
+            JOIN TagImplications i ON i.tag_id = ft.tag_id
+            {filter}
+            UNION
+            SELECT implied.file_hash, i.implied_tag_id FROM implied
+            JOIN TagImplications i ON i.tag_id = implied.tag_id
+        )
+        INSERT OR IGNORE INTO FileTags (file_hash, tag_id)
+        SELECT file_hash, tag_id FROM implied
+        "#
+	);
+	let mut query_builder = sqlx::query(&query);
+	if let Some(file_hash) = file_hash {
+		query_builder = query_builder.bind(file_hash);
+	}
+	Ok(query_builder.execute(pool).await?.rows_affected())
+}
+
+fn emit_progress(
+	app: &AppHandle,
+	stage: &str,
+	message: String,
+	current: usize,
+	total: Option<usize>,
+) {
+	app.emit(
+		"tag_import_progress",
+		ProgressEvent {
+			stage: stage.to_string(),
+			message,
+			file_hash: None,
+			current: Some(current),
+			total,
+		},
+	)
+	.ok();
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get a tag's aliases and implications
+#[tauri::command]
+pub async fn get_tag_rules(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+) -> Result<TagRules, AppError> {
+	let pool = db.get();
+	let aliases: Vec<String> = sqlx::query_scalar(
+		"SELECT alias_name FROM TagAliases WHERE tag_id = ? ORDER BY alias_name",
+	)
+	.bind(tag_id)
+	.fetch_all(&pool)
+	.await?;
+
+	let related = |join_column: &str, filter_column: &str| {
+		format!(
+			r#"
+            SELECT {TAG_COLUMNS}
+            FROM TagImplications i
+            JOIN Tags t ON t.tag_id = i.{join_column}
+            WHERE i.{filter_column} = ?
+            ORDER BY COALESCE(t.alias, t.name) ASC
+            "#
+		)
+	};
+	let implies = sqlx::query(&related("implied_tag_id", "tag_id"))
+		.bind(tag_id)
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(tag_from_row)
+		.collect();
+	let implied_by = sqlx::query(&related("tag_id", "implied_tag_id"))
+		.bind(tag_id)
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(tag_from_row)
+		.collect();
+
+	Ok(TagRules {
+		aliases,
+		implies,
+		implied_by,
+	})
+}
+
+/// Add implied tags to every file that lacks them; returns the number of tags added
+#[tauri::command]
+pub async fn apply_tag_implications(db: tauri::State<'_, DbPool>) -> Result<u64, AppError> {
+	let pool = db.get();
+	add_implied_tags(&pool, None).await
+}
+
+/// Import canonical types, categories, deprecation, aliases and implications for library tags
+/// from a Danbooru-style tags dump
+///
+/// The dump is CSV (`name,category,post_count,aliases,implications`, header row optional) or
+/// JSON lines with the same fields and optionally `is_deprecated`. Categories are Danbooru
+/// numbers or names. The file is streamed twice, keeping only rows for library tags and the
+/// tags they imply in memory. Rating tags and tags in custom categories keep their category,
+/// and implied tags missing from the library are created (and go through the category rules).
+/// Emits tag_import_progress events.
+#[tauri::command]
+pub async fn import_danbooru_tags(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	path: String,
+) -> Result<TagImportSummary, AppError> {
+	let pool = db.get();
+	let local: HashMap<String, LocalTag> =
+		sqlx::query("SELECT tag_id, name, type, category_id, is_deprecated FROM Tags")
+			.fetch_all(&pool)
+			.await?
+			.iter()
+			.map(|row| {
+				(
+					name_key(row.get("name")),
+					LocalTag {
+						tag_id: row.get("tag_id"),
+						tag_type: row.get("type"),
+						category_id: row.get("category_id"),
+						is_deprecated: row.get("is_deprecated"),
+					},
+				)
+			})
+			.collect();
+	let categories = sqlx::query("SELECT category_id, name, is_builtin FROM TagCategories")
+		.fetch_all(&pool)
+		.await?;
+	let category_ids: HashMap<String, i64> = categories
+		.iter()
+		.map(|row| (row.get("name"), row.get("category_id")))
+		.collect();
+	let builtin: HashSet<i64> = categories
+		.iter()
+		.filter(|row| row.get::<bool, _>("is_builtin"))
+		.map(|row| row.get("category_id"))
+		.collect();
+
+	let path = PathBuf::from(path);
+	let progress_app = app.clone();
+	let (local, rows, implied_rows, mut summary) = tokio::task::spawn_blocking(move || {
+		let mut summary = TagImportSummary::default();
+		let mut errors = Vec::new();
+		let mut rows = Vec::new();
+		let mut wanted: HashSet<String> = HashSet::new();
+
+		// 1. Rows for library tags, and the names they imply
+		summary.invalid_rows = read_tag_dump(&path, &mut errors, |row| {
+			summary.rows_read += 1;
+			if summary.rows_read % IMPORT_PROGRESS_INTERVAL == 0 {
+				emit_progress(
+					&progress_app,
+					"reading",
+					format!("Read {} tags", summary.rows_read),
+					summary.rows_read,
+					None,
+				);
+			}
+			if !local.contains_key(&row.name) {
+				summary.skipped_unknown_tag += 1;
+				return;
+			}
+			wanted.extend(
+				row.implications
+					.iter()
+					.filter(|name| !local.contains_key(*name))
+					.cloned(),
+			);
+			rows.push(row);
+		})?;
+		summary.errors = errors;
+
+		// 2. Rows for implied tags that aren't in the library yet, for their categories
+		let mut implied_rows = Vec::new();
+		if !wanted.is_empty() {
+			read_tag_dump(&path, &mut Vec::new(), |row| {
+				if wanted.remove(&row.name) {
+					implied_rows.push(row);
+				}
+			})?;
+			summary.skipped_unknown_tag -= implied_rows.len();
+		}
+		// Implied tags missing from the dump are general tags
+		implied_rows.extend(wanted.into_iter().map(|name| DanbooruTag {
+			name,
+			kind: ("general", "GENERAL"),
+			aliases: Vec::new(),
+			implications: Vec::new(),
+			is_deprecated: None,
+		}));
+
+		Ok::<_, AppError>((local, rows, implied_rows, summary))
+	})
+	.await
+	.map_err(|e| AppError::Custom(format!("Tag import task failed: {e}")))??;
+
+	let since_tag_id = super::category_rules::last_tag_id(&pool).await?;
+	let mut tx = pool.begin().await?;
+	let mut tag_ids: HashMap<&str, i64> = local
+		.iter()
+		.map(|(name, tag)| (name.as_str(), tag.tag_id))
+		.collect();
+	for row in &implied_rows {
+		let tag_id: i64 = sqlx::query_scalar(
+			r#"
+            INSERT INTO Tags (name, type, category_id, is_deprecated) VALUES (?, ?, ?, ?)
+            ON CONFLICT(name) DO UPDATE SET name=name
+            RETURNING tag_id
+            "#,
+		)
+		.bind(&row.name)
+		.bind(row.kind.0)
+		.bind(category_ids.get(row.kind.1).copied().unwrap_or(1))
+		.bind(row.is_deprecated.unwrap_or(false))
+		.fetch_one(&mut *tx)
+		.await?;
+		tag_ids.insert(&row.name, tag_id);
+		// The upsert returns existing tags too; only new ones are past `since_tag_id`
+		if tag_id > since_tag_id {
+			summary.tags_created += 1;
+		}
+	}
+
+	let total = rows.len();
+	for (index, row) in rows.iter().enumerate() {
+		let tag = &local[&row.name];
+
+		// Rating tags come from the tagger, and custom categories were chosen by the user
+		if tag.tag_type != "rating" {
+			let category_id = if builtin.contains(&tag.category_id) {
+				category_ids
+					.get(row.kind.1)
+					.copied()
+					.unwrap_or(tag.category_id)
+			} else {
+				tag.category_id
+			};
+			let is_deprecated = row.is_deprecated.unwrap_or(tag.is_deprecated);
+			if tag.tag_type != row.kind.0
+				|| tag.category_id != category_id
+				|| tag.is_deprecated != is_deprecated
+			{
+				sqlx::query(
+					"UPDATE Tags SET type = ?, category_id = ?, is_deprecated = ? WHERE tag_id = ?",
+				)
+				.bind(row.kind.0)
+				.bind(category_id)
+				.bind(is_deprecated)
+				.bind(tag.tag_id)
+				.execute(&mut *tx)
+				.await?;
+				summary.tags_updated += 1;
+			}
+		}
+
+		for alias in &row.aliases {
+			// An alias named like a tag would make that tag unreachable by name
+			let is_tag: bool =
+				sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Tags WHERE name = ?)")
+					.bind(alias)
+					.fetch_one(&mut *tx)
+					.await?;
+			if is_tag {
+				summary.aliases_skipped += 1;
+				continue;
+			}
+			let result = sqlx::query(
+				r#"
+                INSERT INTO TagAliases (alias_name, tag_id) VALUES (?, ?)
+                ON CONFLICT(alias_name) DO UPDATE SET tag_id = excluded.tag_id
+                WHERE tag_id != excluded.tag_id
+                "#,
+			)
+			.bind(alias)
+			.bind(tag.tag_id)
+			.execute(&mut *tx)
+			.await?;
+			summary.aliases_added += result.rows_affected() as usize;
+		}
+
+		// Every implied tag has an id by now
+		for implied in &row.implications {
+			let Some(&implied_tag_id) = tag_ids.get(implied.as_str()) else {
+				continue;
+			};
+			let result = sqlx::query(
+				"INSERT OR IGNORE INTO TagImplications (tag_id, implied_tag_id) VALUES (?, ?)",
+			)
+			.bind(tag.tag_id)
+			.bind(implied_tag_id)
+			.execute(&mut *tx)
+			.await?;
+			summary.implications_added += result.rows_affected() as usize;
+		}
+
+		if (index + 1) % IMPORT_PROGRESS_INTERVAL == 0 || index + 1 == total {
+			emit_progress(
+				&app,
+				"importing",
+				format!("Updated {} of {total} tags", index + 1),
+				index + 1,
+				Some(total),
+			);
+		}
+	}
+	tx.commit().await?;
+
+	let created: Vec<(String, Option<u32>)> = implied_rows
+		.iter()
+		.map(|row| (row.name.clone(), None))
+		.collect();
+	super::category_rules::categorize_new_tags(&pool, since_tag_id, &created).await?;
+
+	Ok(summary)
+}
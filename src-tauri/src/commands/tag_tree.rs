@@ -0,0 +1,265 @@
+use super::tags::{tag_from_row, Tag, TAG_COLUMNS};
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection};
+use std::collections::HashMap;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A tag with its narrower tags (`clothing > swimsuit > school_swimsuit`)
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TagTreeNode {
+	/// `file_count` counts files tagged with this tag itself
+	#[serde(flatten)]
+	pub tag: Tag,
+	/// Files tagged with this tag or any tag below it, each counted once
+	pub total_file_count: i64,
+	pub children: Vec<TagTreeNode>,
+}
+
+/// Guards the tree walk against a cycle written to the database by hand
+const MAX_TREE_DEPTH: i64 = 64;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// A subquery selecting the tags of `seed` (a query selecting `tag_id`s) and every tag below them
+/// Used by searches, so that searching for a tag also finds files tagged with narrower tags.
+pub(crate) fn with_descendants_sql(seed: &str) -> String {
+	format!(
+		"WITH RECURSIVE expanded(tag_id) AS ({seed} \
+		UNION SELECT c.tag_id FROM Tags c JOIN expanded e ON c.parent_tag_id = e.tag_id) \
+		SELECT tag_id FROM expanded"
+	)
+}
+
+/// Whether `tag_id` is `root_id` or a tag below it
+pub(crate) async fn is_in_subtree(
+	conn: &mut SqliteConnection,
+	tag_id: i64,
+	root_id: i64,
+) -> Result<bool, AppError> {
+	Ok(sqlx::query_scalar(
+		r#"
+        WITH RECURSIVE up(tag_id) AS (
+            SELECT ?
+            UNION
+            SELECT t.parent_tag_id FROM Tags t
+            JOIN up ON t.tag_id = up.tag_id
+            WHERE t.parent_tag_id IS NOT NULL
+        )
+        SELECT EXISTS(SELECT 1 FROM up WHERE tag_id = ?)
+        "#,
+	)
+	.bind(tag_id)
+	.bind(root_id)
+	.fetch_one(&mut *conn)
+	.await?)
+}
+
+async fn ensure_tag_exists(conn: &mut SqliteConnection, tag_id: i64) -> Result<(), AppError> {
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Tags WHERE tag_id = ?)")
+		.bind(tag_id)
+		.fetch_one(&mut *conn)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("Tag with id {tag_id} not found")));
+	}
+	Ok(())
+}
+
+/// Put `tag_id` (and so everything below it) under `parent_tag_id`, or make it a root
+async fn set_parent(
+	conn: &mut SqliteConnection,
+	tag_id: i64,
+	parent_tag_id: Option<i64>,
+) -> Result<(), AppError> {
+	ensure_tag_exists(conn, tag_id).await?;
+	if let Some(parent_tag_id) = parent_tag_id {
+		ensure_tag_exists(conn, parent_tag_id).await?;
+		if is_in_subtree(conn, parent_tag_id, tag_id).await? {
+			return Err(AppError::Custom(format!(
+				"Cannot move tag {tag_id} under {parent_tag_id}: it would be its own ancestor"
+			)));
+		}
+	}
+
+	sqlx::query("UPDATE Tags SET parent_tag_id = ? WHERE tag_id = ?")
+		.bind(parent_tag_id)
+		.bind(tag_id)
+		.execute(&mut *conn)
+		.await?;
+	Ok(())
+}
+
+fn build_nodes(
+	parent: Option<i64>,
+	children: &mut HashMap<Option<i64>, Vec<(Tag, i64)>>,
+) -> Vec<TagTreeNode> {
+	children
+		.remove(&parent)
+		.unwrap_or_default()
+		.into_iter()
+		.map(|(tag, total_file_count)| {
+			let tag_id = tag.tag_id;
+			TagTreeNode {
+				tag,
+				total_file_count,
+				children: build_nodes(Some(tag_id), children),
+			}
+		})
+		.collect()
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Move tags, each with everything below it, under `parent_tag_id` (or to the top level)
+/// Nothing is moved if any move would create a cycle.
+#[tauri::command]
+pub async fn move_tag_subtrees(
+	db: tauri::State<'_, DbPool>,
+	tag_ids: Vec<i64>,
+	parent_tag_id: Option<i64>,
+) -> Result<usize, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	for &tag_id in &tag_ids {
+		set_parent(&mut tx, tag_id, parent_tag_id).await?;
+	}
+	tx.commit().await?;
+
+	Ok(tag_ids.len())
+}
+
+/// Move every child of `tag_id` under `new_parent_tag_id` (or to the top level),
+/// keeping `tag_id` itself in place
+#[tauri::command]
+pub async fn move_tag_children(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+	new_parent_tag_id: Option<i64>,
+) -> Result<usize, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ensure_tag_exists(&mut tx, tag_id).await?;
+
+	let children: Vec<i64> = sqlx::query_scalar("SELECT tag_id FROM Tags WHERE parent_tag_id = ?")
+		.bind(tag_id)
+		.fetch_all(&mut *tx)
+		.await?;
+	for &child in &children {
+		set_parent(&mut tx, child, new_parent_tag_id).await?;
+	}
+	tx.commit().await?;
+
+	Ok(children.len())
+}
+
+/// The tag hierarchy below `root_tag_id`, or every tree when it is omitted
+/// Tags without a parent or children are left out of the full listing.
+#[tauri::command]
+pub async fn get_tag_tree(
+	db: tauri::State<'_, DbPool>,
+	root_tag_id: Option<i64>,
+) -> Result<Vec<TagTreeNode>, AppError> {
+	let pool = db.get();
+	let roots = match root_tag_id {
+		Some(_) => "t.tag_id = ?",
+		None => {
+			"t.parent_tag_id IS NULL AND EXISTS(SELECT 1 FROM Tags c WHERE c.parent_tag_id = t.tag_id)"
+		}
+	};
+
+	// `closure` pairs every tag in the trees with itself and each tag below it
+	let query = format!(
+		r#"
+        WITH RECURSIVE subtree(tag_id, depth) AS (
+            SELECT t.tag_id, 0 FROM Tags t WHERE {roots}
+            UNION ALL
+            SELECT t.tag_id, s.depth + 1 FROM Tags t
+            JOIN subtree s ON t.parent_tag_id = s.tag_id
+            WHERE s.depth < ?
+        ),
+        closure(ancestor_id, tag_id) AS (
+            SELECT tag_id, tag_id FROM subtree
+            UNION
+            SELECT c.ancestor_id, t.tag_id FROM closure c
+            JOIN Tags t ON t.parent_tag_id = c.tag_id
+        ),
+        totals(tag_id, total_file_count) AS (
+            SELECT c.ancestor_id, COUNT(DISTINCT ft.file_hash) FROM closure c
+            JOIN FileTags ft ON ft.tag_id = c.tag_id
+            GROUP BY c.ancestor_id
+        )
+        SELECT {TAG_COLUMNS}, s.depth,
+               (SELECT COUNT(*) FROM FileTags ft WHERE ft.tag_id = t.tag_id) as file_count,
+               COALESCE(tt.total_file_count, 0) as total_file_count
+        FROM subtree s
+        JOIN Tags t ON t.tag_id = s.tag_id
+        LEFT JOIN totals tt ON tt.tag_id = t.tag_id
+        ORDER BY COALESCE(t.alias, t.name) ASC
+        "#
+	);
+	let mut query_builder = sqlx::query(&query);
+	if let Some(root_tag_id) = root_tag_id {
+		query_builder = query_builder.bind(root_tag_id);
+	}
+	let rows = query_builder.bind(MAX_TREE_DEPTH).fetch_all(&pool).await?;
+
+	// Roots are keyed under `None` so a requested root with a parent still starts the tree
+	let mut children: HashMap<Option<i64>, Vec<(Tag, i64)>> = HashMap::new();
+	for row in &rows {
+		let tag = tag_from_row(row);
+		let parent = if row.get::<i64, _>("depth") == 0 {
+			None
+		} else {
+			tag.parent_tag_id
+		};
+		children
+			.entry(parent)
+			.or_default()
+			.push((tag, row.get("total_file_count")));
+	}
+
+	Ok(build_nodes(None, &mut children))
+}
+
+/// Tags above `tag_id`, top level first (for breadcrumbs)
+#[tauri::command]
+pub async fn get_tag_ancestors(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+) -> Result<Vec<Tag>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		r#"
+        WITH RECURSIVE up(tag_id, depth) AS (
+            SELECT parent_tag_id, 1 FROM Tags WHERE tag_id = ? AND parent_tag_id IS NOT NULL
+            UNION ALL
+            SELECT t.parent_tag_id, up.depth + 1 FROM Tags t
+            JOIN up ON t.tag_id = up.tag_id
+            WHERE t.parent_tag_id IS NOT NULL AND up.depth < ?
+        )
+        SELECT {TAG_COLUMNS}
+        FROM up
+        JOIN Tags t ON t.tag_id = up.tag_id
+        ORDER BY up.depth DESC
+        "#
+	);
+	let tags = sqlx::query(&query)
+		.bind(tag_id)
+		.bind(MAX_TREE_DEPTH)
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(tag_from_row)
+		.collect();
+
+	Ok(tags)
+}
@@ -1,6 +1,11 @@
+use super::tag_rules::{add_implied_tags, canonical_tag_name};
+use crate::autocomplete;
+use crate::db::DbPool;
 use crate::error::AppError;
 use serde::{Deserialize, Serialize};
-use sqlx::SqlitePool;
+use sqlx::sqlite::SqliteRow;
+use sqlx::Row;
+use std::collections::HashMap;
 
 // ============================================================================
 // Types
@@ -16,6 +21,46 @@ pub struct Tag {
 	#[serde(skip_serializing_if = "Option::is_none")]
 	pub alias: Option<String>,
 	pub file_count: Option<i64>,
+	/// Whether the tag has a wiki page
+	#[serde(default)]
+	pub has_wiki: bool,
+	/// Broader tag in the hierarchy (`swimsuit` for `school_swimsuit`)
+	#[serde(default, skip_serializing_if = "Option::is_none")]
+	pub parent_tag_id: Option<i64>,
+	/// Replaced on Danbooru; kept on files but no longer suggested
+	#[serde(default)]
+	pub is_deprecated: bool,
+}
+
+/// Columns selected for a `Tag`, qualified with the `t` alias for `Tags`; add `file_count`
+/// where it is known
+pub(crate) const TAG_COLUMNS: &str = "t.tag_id, t.name, t.type, t.category_id, t.alias, \
+	t.parent_tag_id, t.is_deprecated, EXISTS(SELECT 1 FROM TagWikis w WHERE w.tag_id = t.tag_id) as has_wiki";
+
+/// Autocomplete candidates fetched per requested result, for re-ranking with current file counts
+const AUTOCOMPLETE_CANDIDATE_FACTOR: usize = 4;
+
+/// Most autocomplete results returned at once; keeps the candidate list within SQLite's
+/// bound-variable limit
+const MAX_AUTOCOMPLETE_LIMIT: i64 = 200;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Map a row selected with `TAG_COLUMNS` (and optionally `file_count`) to a `Tag`
+pub(crate) fn tag_from_row(row: &SqliteRow) -> Tag {
+	Tag {
+		tag_id: row.get("tag_id"),
+		name: row.get("name"),
+		tag_type: row.get("type"),
+		category_id: row.get("category_id"),
+		alias: row.get("alias"),
+		file_count: row.try_get("file_count").ok(),
+		has_wiki: row.get("has_wiki"),
+		parent_tag_id: row.get("parent_tag_id"),
+		is_deprecated: row.get("is_deprecated"),
+	}
 }
 
 // ============================================================================
@@ -23,28 +68,21 @@ pub struct Tag {
 // ============================================================================
 
 #[tauri::command]
-pub async fn get_all_tags(pool: tauri::State<'_, SqlitePool>) -> Result<Vec<Tag>, AppError> {
-	let tags = sqlx::query!(
+pub async fn get_all_tags(db: tauri::State<'_, DbPool>) -> Result<Vec<Tag>, AppError> {
+	let pool = db.get();
+	let tags = sqlx::query(&format!(
 		r#"
-        SELECT t.tag_id, t.name, t.type, t.category_id, t.alias,
-               COUNT(ft.file_hash) as "file_count: i64"
+        SELECT {TAG_COLUMNS}, COUNT(ft.file_hash) as file_count
         FROM Tags t
         LEFT JOIN FileTags ft ON t.tag_id = ft.tag_id
         GROUP BY t.tag_id
         ORDER BY COALESCE(t.alias, t.name) ASC
         "#
-	)
-	.fetch_all(pool.inner())
+	))
+	.fetch_all(&pool)
 	.await?
-	.into_iter()
-	.map(|row| Tag {
-		tag_id: row.tag_id,
-		name: row.name,
-		tag_type: row.r#type,
-		category_id: row.category_id,
-		alias: row.alias,
-		file_count: Some(row.file_count),
-	})
+	.iter()
+	.map(tag_from_row)
 	.collect();
 
 	Ok(tags)
@@ -52,30 +90,24 @@ pub async fn get_all_tags(pool: tauri::State<'_, SqlitePool>) -> Result<Vec<Tag>
 
 #[tauri::command]
 pub async fn get_file_tags(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 ) -> Result<Vec<Tag>, AppError> {
-	let tags = sqlx::query!(
+	let pool = db.get();
+	let tags = sqlx::query(&format!(
 		r#"
-        SELECT t.tag_id, t.name, t.type, t.category_id, t.alias
+        SELECT {TAG_COLUMNS}
         FROM Tags t
         INNER JOIN FileTags ft ON t.tag_id = ft.tag_id
         WHERE ft.file_hash = ?
         ORDER BY COALESCE(t.alias, t.name) ASC
-        "#,
-		file_hash
-	)
-	.fetch_all(pool.inner())
+        "#
+	))
+	.bind(&file_hash)
+	.fetch_all(&pool)
 	.await?
-	.into_iter()
-	.map(|row| Tag {
-		tag_id: row.tag_id.unwrap_or(0),
-		name: row.name,
-		tag_type: row.r#type,
-		category_id: row.category_id,
-		alias: row.alias,
-		file_count: None,
-	})
+	.iter()
+	.map(tag_from_row)
 	.collect();
 
 	Ok(tags)
@@ -83,12 +115,14 @@ pub async fn get_file_tags(
 
 #[tauri::command]
 pub async fn add_tag_to_file(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 	tag_name: String,
 	tag_type: Option<String>,
 ) -> Result<i64, AppError> {
+	let pool = db.get();
 	let tag_type = tag_type.unwrap_or_else(|| "general".to_string());
+	let tag_name = canonical_tag_name(&pool, &tag_name).await?;
 
 	// Get or create tag
 	let _tag = sqlx::query!(
@@ -99,12 +133,12 @@ pub async fn add_tag_to_file(
 		tag_name,
 		tag_type
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
 
 	// Get the tag (either existing or newly inserted)
 	let tag = sqlx::query!("SELECT tag_id FROM Tags WHERE name = ?", tag_name)
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 	// Add file-tag association (ignore if already exists)
@@ -116,8 +150,9 @@ pub async fn add_tag_to_file(
 		file_hash,
 		tag.tag_id
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
+	add_implied_tags(&pool, Some(&file_hash)).await?;
 
 	tag.tag_id
 		.ok_or_else(|| AppError::Custom("Failed to get tag_id".to_string()))
@@ -125,15 +160,18 @@ pub async fn add_tag_to_file(
 
 #[tauri::command]
 pub async fn add_tags_to_files(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 	tag_names: Vec<String>,
 	tag_type: Option<String>,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	let tag_type = tag_type.unwrap_or_else(|| "general".to_string());
 	let mut added_count = 0;
 
 	for tag_name in tag_names {
+		let tag_name = canonical_tag_name(&pool, &tag_name).await?;
+
 		// Get or create tag
 		let tag = sqlx::query!(
 			r#"
@@ -145,7 +183,7 @@ pub async fn add_tags_to_files(
 			tag_name,
 			tag_type
 		)
-		.fetch_one(pool.inner())
+		.fetch_one(&pool)
 		.await?;
 
 		// Add to all files
@@ -158,7 +196,7 @@ pub async fn add_tags_to_files(
 				file_hash,
 				tag.tag_id
 			)
-			.execute(pool.inner())
+			.execute(&pool)
 			.await?;
 
 			if result.rows_affected() > 0 {
@@ -166,22 +204,26 @@ pub async fn add_tags_to_files(
 			}
 		}
 	}
+	for file_hash in &file_hashes {
+		add_implied_tags(&pool, Some(file_hash)).await?;
+	}
 
 	Ok(added_count)
 }
 
 #[tauri::command]
 pub async fn remove_tag_from_file(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hash: String,
 	tag_id: i64,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	sqlx::query!(
 		"DELETE FROM FileTags WHERE file_hash = ? AND tag_id = ?",
 		file_hash,
 		tag_id
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
 
 	Ok(())
@@ -189,10 +231,11 @@ pub async fn remove_tag_from_file(
 
 #[tauri::command]
 pub async fn remove_tag_from_files(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	file_hashes: Vec<String>,
 	tag_id: i64,
 ) -> Result<usize, AppError> {
+	let pool = db.get();
 	let mut removed_count = 0;
 
 	for file_hash in file_hashes {
@@ -201,7 +244,7 @@ pub async fn remove_tag_from_files(
 			file_hash,
 			tag_id
 		)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 		removed_count += result.rows_affected() as usize;
@@ -212,10 +255,11 @@ pub async fn remove_tag_from_files(
 
 #[tauri::command]
 pub async fn create_tag(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	name: String,
 	category_id: i64,
 ) -> Result<i64, AppError> {
+	let pool = db.get();
 	// Validate name is not empty
 	if name.trim().is_empty() {
 		return Err(AppError::Custom("Tag name cannot be empty".to_string()));
@@ -231,7 +275,7 @@ pub async fn create_tag(
 		name,
 		category_id
 	)
-	.fetch_one(pool.inner())
+	.fetch_one(&pool)
 	.await?;
 
 	tag.tag_id
@@ -240,11 +284,12 @@ pub async fn create_tag(
 
 #[tauri::command]
 pub async fn update_tag(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	tag_id: i64,
 	name: String,
 	category_id: i64,
 ) -> Result<(), AppError> {
+	let pool = db.get();
 	// Validate name is not empty
 	if name.trim().is_empty() {
 		return Err(AppError::Custom("Tag name cannot be empty".to_string()));
@@ -261,65 +306,106 @@ pub async fn update_tag(
 		category_id,
 		tag_id
 	)
-	.execute(pool.inner())
+	.execute(&pool)
 	.await?;
 
 	Ok(())
 }
 
 #[tauri::command]
-pub async fn delete_tag(pool: tauri::State<'_, SqlitePool>, tag_id: i64) -> Result<(), AppError> {
+pub async fn delete_tag(db: tauri::State<'_, DbPool>, tag_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
 	// Check if tag exists
 	let tag = sqlx::query!("SELECT name FROM Tags WHERE tag_id = ?", tag_id)
-		.fetch_optional(pool.inner())
+		.fetch_optional(&pool)
 		.await?;
 
 	let _tag = tag.ok_or_else(|| AppError::Custom(format!("Tag with id {tag_id} not found")))?;
 
 	// Delete tag (file associations will be deleted by foreign key constraint)
 	sqlx::query!("DELETE FROM Tags WHERE tag_id = ?", tag_id)
-		.execute(pool.inner())
+		.execute(&pool)
 		.await?;
 
 	Ok(())
 }
 
+/// Autocomplete tags by name, alias or alias reading (pinyin/romaji), in any word order and
+/// tolerating typos; close matches on tags with many files come first. Deprecated tags are
+/// not suggested.
 #[tauri::command]
 pub async fn search_tags(
-	pool: tauri::State<'_, SqlitePool>,
+	db: tauri::State<'_, DbPool>,
 	prefix: String,
 	limit: Option<i64>,
 ) -> Result<Vec<Tag>, AppError> {
-	let limit = limit.unwrap_or(20);
-	let search_pattern = format!("{prefix}%");
+	let pool = db.get();
+	let limit = limit.unwrap_or(20).clamp(1, MAX_AUTOCOMPLETE_LIMIT);
 
-	let tags = sqlx::query!(
+	// Nothing typed yet: the most used tags
+	if prefix.trim().is_empty() {
+		let rows = sqlx::query(&format!(
+			r#"
+            SELECT {TAG_COLUMNS}, COUNT(ft.file_hash) as file_count
+            FROM Tags t
+            LEFT JOIN FileTags ft ON t.tag_id = ft.tag_id
+            WHERE t.is_deprecated = 0
+            GROUP BY t.tag_id
+            ORDER BY COUNT(ft.file_hash) DESC, COALESCE(t.alias, t.name) ASC
+            LIMIT ?
+            "#
+		))
+		.bind(limit)
+		.fetch_all(&pool)
+		.await?;
+		return Ok(rows.iter().map(tag_from_row).collect());
+	}
+
+	// The index ranks with file counts from when it was built; re-rank a wider set of
+	// candidates with current counts
+	// Scanning the index takes a while with large tag sets, so keep it off the async runtime
+	let index = autocomplete::current_index(&pool).await?;
+	let candidate_limit = limit as usize * AUTOCOMPLETE_CANDIDATE_FACTOR;
+	let candidates = tokio::task::spawn_blocking(move || index.search(&prefix, candidate_limit))
+		.await
+		.map_err(|e| AppError::Custom(format!("Autocomplete search failed: {e}")))?;
+	if candidates.is_empty() {
+		return Ok(Vec::new());
+	}
+
+	let placeholders = candidates.iter().map(|_| "?").collect::<Vec<_>>().join(",");
+	let query = format!(
 		r#"
-        SELECT t.tag_id, t.name, t.type, t.category_id, t.alias,
-               COUNT(ft.file_hash) as file_count
+        SELECT {TAG_COLUMNS},
+               (SELECT COUNT(*) FROM FileTags ft WHERE ft.tag_id = t.tag_id) as file_count
         FROM Tags t
-        LEFT JOIN FileTags ft ON t.tag_id = ft.tag_id
-        WHERE t.name LIKE ? OR t.alias LIKE ?
-        GROUP BY t.tag_id
-        ORDER BY COUNT(ft.file_hash) DESC, COALESCE(t.alias, t.name) ASC
-        LIMIT ?
-        "#,
-		search_pattern,
-		search_pattern,
-		limit
-	)
-	.fetch_all(pool.inner())
-	.await?
-	.into_iter()
-	.map(|row| Tag {
-		tag_id: row.tag_id,
-		name: row.name,
-		tag_type: row.r#type,
-		category_id: row.category_id,
-		alias: row.alias,
-		file_count: Some(row.file_count),
-	})
-	.collect();
-
-	Ok(tags)
+        WHERE t.tag_id IN ({placeholders}) AND t.is_deprecated = 0
+        "#
+	);
+	let mut query_builder = sqlx::query(&query);
+	for candidate in &candidates {
+		query_builder = query_builder.bind(candidate.tag_id);
+	}
+	let tags: HashMap<i64, Tag> = query_builder
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(|row| {
+			let tag = tag_from_row(row);
+			(tag.tag_id, tag)
+		})
+		.collect();
+
+	let mut ranked: Vec<(f64, Tag)> = candidates
+		.iter()
+		.filter_map(|candidate| {
+			let tag = tags.get(&candidate.tag_id)?.clone();
+			let score = autocomplete::rank_score(candidate.quality, tag.file_count.unwrap_or(0));
+			Some((score, tag))
+		})
+		.collect();
+	ranked.sort_by(|a, b| b.0.total_cmp(&a.0));
+	ranked.truncate(limit as usize);
+
+	Ok(ranked.into_iter().map(|(_, tag)| tag).collect())
 }
@@ -0,0 +1,620 @@
+use super::files::ProgressEvent;
+use crate::db::DbPool;
+use crate::error::AppError;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::collections::{HashMap, HashSet};
+use std::fs::File;
+use std::io::{BufRead, BufReader, Read};
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct WikiLink {
+	pub url: String,
+	pub label: Option<String>,
+}
+
+/// A tag's wiki page
+#[derive(Debug, Serialize, Clone)]
+pub struct TagWiki {
+	pub tag_id: i64,
+	pub tag_name: String,
+	/// Markdown; may contain `[[tag_name]]` links to other tags' pages
+	pub body: String,
+	/// `user` or `danbooru`
+	pub source: String,
+	pub updated_at: i64, // Unix timestamp
+	pub links: Vec<WikiLink>,
+	/// Example images, in page order
+	pub example_hashes: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Default)]
+pub struct WikiImportSummary {
+	/// Pages read from the dump
+	pub pages_read: usize,
+	pub imported: usize,
+	/// Pages for tags that aren't in the library
+	pub skipped_unknown_tag: usize,
+	/// Pages for tags whose wiki was written here (kept unless `overwrite` is set)
+	pub skipped_user_edited: usize,
+	pub skipped_deleted_or_empty: usize,
+	/// Rows that could not be read
+	pub invalid_rows: usize,
+	/// Why rows could not be read, with their line or record number
+	pub errors: Vec<String>,
+}
+
+/// A page from a Danbooru `wiki_pages` dump; other fields are ignored
+#[derive(Debug, Deserialize)]
+struct DanbooruWikiPage {
+	title: String,
+	#[serde(default)]
+	body: String,
+	#[serde(default)]
+	is_deleted: bool,
+}
+
+/// An imported page ready to write
+struct ImportedPage {
+	tag_id: i64,
+	body: String,
+	links: Vec<WikiLink>,
+}
+
+const SOURCE_USER: &str = "user";
+const SOURCE_DANBOORU: &str = "danbooru";
+
+/// Errors listed in an import summary; further ones are only counted
+const MAX_REPORTED_ERRORS: usize = 100;
+const IMPORT_PROGRESS_INTERVAL: usize = 1000;
+
+static DTEXT_HEADER: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r"(?m)^h([1-6])(?:#[\w-]+)?\.\s*").unwrap());
+/// `"label":[url]` or `"label":url`; urls may be relative to the Danbooru site
+static DTEXT_LINK: Lazy<Regex> = Lazy::new(|| {
+	Regex::new(r#""([^"\n]+)":(?:\[([^\]\s]+)\]|((?:https?://|/)[^\s<>"\]]+))"#).unwrap()
+});
+/// Base for relative links in Danbooru wiki pages
+const DANBOORU_URL: &str = "https://danbooru.donmai.us";
+static BARE_URL: Lazy<Regex> =
+	Lazy::new(|| Regex::new(r#"(^|[\s(<])(https?://[^\s<>")\]]+)"#).unwrap());
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn unix_now() -> i64 {
+	SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0)
+}
+
+fn clean_links(links: Vec<WikiLink>) -> Result<Vec<WikiLink>, AppError> {
+	let mut seen = HashSet::new();
+	let mut cleaned = Vec::new();
+	for link in links {
+		let url = link.url.trim().to_string();
+		let parsed = url::Url::parse(&url)
+			.map_err(|e| AppError::Custom(format!("Invalid link {url}: {e}")))?;
+		if !matches!(parsed.scheme(), "http" | "https") {
+			return Err(AppError::Custom(format!(
+				"Links must be http(s) URLs: {url}"
+			)));
+		}
+		if seen.insert(url.clone()) {
+			cleaned.push(WikiLink {
+				url,
+				label: link
+					.label
+					.map(|l| l.trim().to_string())
+					.filter(|l| !l.is_empty()),
+			});
+		}
+	}
+	Ok(cleaned)
+}
+
+async fn tag_exists(pool: &SqlitePool, tag_id: i64) -> Result<(), AppError> {
+	let exists: bool = sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Tags WHERE tag_id = ?)")
+		.bind(tag_id)
+		.fetch_one(pool)
+		.await?;
+	if !exists {
+		return Err(AppError::Custom(format!("Tag with id {tag_id} not found")));
+	}
+	Ok(())
+}
+
+/// Create an empty page for a tag unless it has one
+/// Imports still fill in a page without text or links, so examples can be added first.
+async fn ensure_wiki(conn: &mut SqliteConnection, tag_id: i64) -> Result<(), AppError> {
+	sqlx::query(
+		"INSERT OR IGNORE INTO TagWikis (tag_id, body, source, updated_at) VALUES (?, '', ?, ?)",
+	)
+	.bind(tag_id)
+	.bind(SOURCE_USER)
+	.bind(unix_now())
+	.execute(&mut *conn)
+	.await?;
+	Ok(())
+}
+
+async fn write_links(
+	conn: &mut SqliteConnection,
+	tag_id: i64,
+	links: &[WikiLink],
+) -> Result<(), AppError> {
+	sqlx::query("DELETE FROM TagWikiLinks WHERE tag_id = ?")
+		.bind(tag_id)
+		.execute(&mut *conn)
+		.await?;
+	for (position, link) in links.iter().enumerate() {
+		sqlx::query("INSERT INTO TagWikiLinks (tag_id, url, label, position) VALUES (?, ?, ?, ?)")
+			.bind(tag_id)
+			.bind(&link.url)
+			.bind(&link.label)
+			.bind(position as i64)
+			.execute(&mut *conn)
+			.await?;
+	}
+	Ok(())
+}
+
+async fn load_wiki(pool: &SqlitePool, tag_id: i64) -> Result<Option<TagWiki>, AppError> {
+	let Some(row) = sqlx::query(
+		r#"
+        SELECT w.tag_id, t.name, w.body, w.source, w.updated_at
+        FROM TagWikis w
+        JOIN Tags t ON t.tag_id = w.tag_id
+        WHERE w.tag_id = ?
+        "#,
+	)
+	.bind(tag_id)
+	.fetch_optional(pool)
+	.await?
+	else {
+		return Ok(None);
+	};
+
+	let links =
+		sqlx::query("SELECT url, label FROM TagWikiLinks WHERE tag_id = ? ORDER BY position")
+			.bind(tag_id)
+			.fetch_all(pool)
+			.await?
+			.iter()
+			.map(|row| WikiLink {
+				url: row.get("url"),
+				label: row.get("label"),
+			})
+			.collect();
+	let example_hashes = sqlx::query_scalar(
+		"SELECT file_hash FROM TagWikiExamples WHERE tag_id = ? ORDER BY position",
+	)
+	.bind(tag_id)
+	.fetch_all(pool)
+	.await?;
+
+	Ok(Some(TagWiki {
+		tag_id: row.get("tag_id"),
+		tag_name: row.get("name"),
+		body: row.get("body"),
+		source: row.get("source"),
+		updated_at: row.get("updated_at"),
+		links,
+		example_hashes,
+	}))
+}
+
+/// Convert Danbooru DText to Markdown and collect its external links
+///
+/// Covers headers, bold/italic/strike and links; `[[tag]]` wiki links are kept as they are.
+fn dtext_to_markdown(dtext: &str) -> (String, Vec<WikiLink>) {
+	let link_url = |captures: &regex::Captures| {
+		let url = captures
+			.get(2)
+			.or(captures.get(3))
+			.map_or("", |m| m.as_str());
+		if url.starts_with('/') {
+			format!("{DANBOORU_URL}{url}")
+		} else {
+			url.to_string()
+		}
+	};
+
+	// Relative links point into Danbooru itself and stay in the text only
+	let labelled = DTEXT_LINK
+		.captures_iter(dtext)
+		.map(|captures| (link_url(&captures), Some(captures[1].to_string())))
+		.filter(|(url, _)| !url.starts_with(DANBOORU_URL));
+	let bare = BARE_URL
+		.captures_iter(dtext)
+		.map(|captures| (captures[2].to_string(), None));
+
+	let mut seen = HashSet::new();
+	let links = labelled
+		.chain(bare)
+		.filter(|(url, _)| {
+			url::Url::parse(url).is_ok_and(|u| matches!(u.scheme(), "http" | "https"))
+				&& seen.insert(url.clone())
+		})
+		.map(|(url, label)| WikiLink { url, label })
+		.collect();
+
+	let markdown = dtext.replace("\r\n", "\n");
+	let markdown = DTEXT_HEADER.replace_all(&markdown, |captures: &regex::Captures| {
+		format!("{} ", "#".repeat(captures[1].parse().unwrap_or(1)))
+	});
+	let markdown = DTEXT_LINK.replace_all(&markdown, |captures: &regex::Captures| {
+		format!("[{}]({})", &captures[1], link_url(captures))
+	});
+	let markdown = [
+		("[b]", "**"),
+		("[/b]", "**"),
+		("[i]", "*"),
+		("[/i]", "*"),
+		("[s]", "~~"),
+		("[/s]", "~~"),
+		("[u]", ""),
+		("[/u]", ""),
+	]
+	.iter()
+	.fold(markdown.to_string(), |text, (from, to)| {
+		text.replace(from, to)
+	});
+
+	(markdown.trim().to_string(), links)
+}
+
+/// `t`, `true`, `1` as used for booleans in database exports
+fn parse_flag(value: &str) -> bool {
+	matches!(
+		value.trim().to_lowercase().as_str(),
+		"t" | "true" | "1" | "yes"
+	)
+}
+
+/// Lowercase with spaces as `_`, the form of Danbooru titles
+fn title_key(name: &str) -> String {
+	name.trim().to_lowercase().replace(' ', "_")
+}
+
+/// Read pages from a CSV (with a header row) or JSON array / JSON lines dump, calling `page`
+/// for each one; rows that can't be read are described in `errors`
+/// Returns the number of rows that could not be read
+fn read_wiki_dump(
+	path: &Path,
+	errors: &mut Vec<String>,
+	mut page: impl FnMut(DanbooruWikiPage),
+) -> Result<usize, AppError> {
+	let mut invalid_rows = 0;
+	let mut record_error = |message: String| {
+		invalid_rows += 1;
+		if errors.len() < MAX_REPORTED_ERRORS {
+			errors.push(message);
+		}
+	};
+
+	let is_csv = path
+		.extension()
+		.is_some_and(|ext| ext.eq_ignore_ascii_case("csv"));
+	if is_csv {
+		let mut reader = csv::ReaderBuilder::new()
+			.flexible(true)
+			.from_path(path)
+			.map_err(|e| AppError::Custom(format!("Failed to open wiki dump: {e}")))?;
+		let headers = reader
+			.headers()
+			.map_err(|e| AppError::Custom(format!("Failed to read wiki dump header: {e}")))?
+			.clone();
+		let column = |name: &str| {
+			headers
+				.iter()
+				.position(|h| h.trim().eq_ignore_ascii_case(name))
+		};
+		let title_column = column("title")
+			.ok_or_else(|| AppError::Custom("Wiki dump has no title column".to_string()))?;
+		let body_column = column("body");
+		let deleted_column = column("is_deleted");
+
+		for (index, record) in reader.records().enumerate() {
+			let record = match record {
+				Ok(record) => record,
+				Err(e) => {
+					record_error(format!("Record {}: {e}", index + 1));
+					continue;
+				}
+			};
+			let field = |column: Option<usize>| column.and_then(|c| record.get(c)).unwrap_or("");
+			page(DanbooruWikiPage {
+				title: field(Some(title_column)).to_string(),
+				body: field(body_column).to_string(),
+				is_deleted: parse_flag(field(deleted_column)),
+			});
+		}
+		return Ok(invalid_rows);
+	}
+
+	let mut reader = BufReader::new(File::open(path)?);
+	let starts_with_array = loop {
+		let buffer = reader.fill_buf()?;
+		match buffer.iter().position(|b| !b.is_ascii_whitespace()) {
+			Some(index) => {
+				let is_array = buffer[index] == b'[';
+				break is_array;
+			}
+			None if buffer.is_empty() => break false,
+			None => {
+				let len = buffer.len();
+				reader.consume(len);
+			}
+		}
+	};
+
+	if starts_with_array {
+		let mut contents = String::new();
+		reader.read_to_string(&mut contents)?;
+		let pages: Vec<serde_json::Value> = serde_json::from_str(&contents)
+			.map_err(|e| AppError::Custom(format!("Failed to parse wiki dump: {e}")))?;
+		for (index, value) in pages.into_iter().enumerate() {
+			match serde_json::from_value(value) {
+				Ok(parsed) => page(parsed),
+				Err(e) => record_error(format!("Record {}: {e}", index + 1)),
+			}
+		}
+	} else {
+		for (index, line) in reader.lines().enumerate() {
+			let line = line?;
+			if line.trim().is_empty() {
+				continue;
+			}
+			match serde_json::from_str(&line) {
+				Ok(parsed) => page(parsed),
+				Err(e) => record_error(format!("Line {}: {e}", index + 1)),
+			}
+		}
+	}
+	Ok(invalid_rows)
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Get a tag's wiki page, if it has one
+#[tauri::command]
+pub async fn get_tag_wiki(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+) -> Result<Option<TagWiki>, AppError> {
+	let pool = db.get();
+	load_wiki(&pool, tag_id).await
+}
+
+/// Create or replace a tag's wiki text and links; example images are kept
+#[tauri::command]
+pub async fn save_tag_wiki(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+	body: String,
+	links: Vec<WikiLink>,
+) -> Result<TagWiki, AppError> {
+	let pool = db.get();
+	tag_exists(&pool, tag_id).await?;
+	let links = clean_links(links)?;
+
+	let mut tx = pool.begin().await?;
+	sqlx::query(
+		r#"
+        INSERT INTO TagWikis (tag_id, body, source, updated_at) VALUES (?, ?, ?, ?)
+        ON CONFLICT(tag_id) DO UPDATE SET
+            body = excluded.body, source = excluded.source, updated_at = excluded.updated_at
+        "#,
+	)
+	.bind(tag_id)
+	.bind(body.trim())
+	.bind(SOURCE_USER)
+	.bind(unix_now())
+	.execute(&mut *tx)
+	.await?;
+	write_links(&mut tx, tag_id, &links).await?;
+	tx.commit().await?;
+
+	load_wiki(&pool, tag_id)
+		.await?
+		.ok_or_else(|| AppError::Custom(format!("Wiki for tag {tag_id} not found")))
+}
+
+/// Set a tag's example images, in order; creates an empty page if the tag has none
+#[tauri::command]
+pub async fn set_tag_wiki_examples(
+	db: tauri::State<'_, DbPool>,
+	tag_id: i64,
+	file_hashes: Vec<String>,
+) -> Result<TagWiki, AppError> {
+	let pool = db.get();
+	tag_exists(&pool, tag_id).await?;
+
+	let mut tx = pool.begin().await?;
+	ensure_wiki(&mut tx, tag_id).await?;
+	sqlx::query("DELETE FROM TagWikiExamples WHERE tag_id = ?")
+		.bind(tag_id)
+		.execute(&mut *tx)
+		.await?;
+
+	let mut seen = HashSet::new();
+	let unique = file_hashes.iter().filter(|h| seen.insert(h.as_str()));
+	for (position, file_hash) in unique.enumerate() {
+		let exists: bool =
+			sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM Files WHERE file_hash = ?)")
+				.bind(file_hash)
+				.fetch_one(&mut *tx)
+				.await?;
+		if !exists {
+			return Err(AppError::Custom(format!("File not found: {file_hash}")));
+		}
+		sqlx::query("INSERT INTO TagWikiExamples (tag_id, file_hash, position) VALUES (?, ?, ?)")
+			.bind(tag_id)
+			.bind(file_hash)
+			.bind(position as i64)
+			.execute(&mut *tx)
+			.await?;
+	}
+	sqlx::query("UPDATE TagWikis SET updated_at = ? WHERE tag_id = ?")
+		.bind(unix_now())
+		.bind(tag_id)
+		.execute(&mut *tx)
+		.await?;
+	tx.commit().await?;
+
+	load_wiki(&pool, tag_id)
+		.await?
+		.ok_or_else(|| AppError::Custom(format!("Wiki for tag {tag_id} not found")))
+}
+
+/// Delete a tag's wiki page with its links and examples
+#[tauri::command]
+pub async fn delete_tag_wiki(db: tauri::State<'_, DbPool>, tag_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
+	sqlx::query("DELETE FROM TagWikis WHERE tag_id = ?")
+		.bind(tag_id)
+		.execute(&pool)
+		.await?;
+	Ok(())
+}
+
+/// Import descriptions from a Danbooru `wiki_pages` dump (CSV with a header row, JSON array
+/// or JSON lines) for tags in the library
+///
+/// DText is converted to Markdown and its external links become page links. Pages with text
+/// or links written here are kept unless `overwrite` is set; earlier imports are always
+/// refreshed.
+/// Emits wiki_import_progress events.
+#[tauri::command]
+pub async fn import_danbooru_wiki(
+	app: AppHandle,
+	db: tauri::State<'_, DbPool>,
+	path: String,
+	overwrite: Option<bool>,
+) -> Result<WikiImportSummary, AppError> {
+	let pool = db.get();
+	let overwrite = overwrite.unwrap_or(false);
+
+	let tags: HashMap<String, i64> = sqlx::query("SELECT tag_id, name FROM Tags")
+		.fetch_all(&pool)
+		.await?
+		.iter()
+		.map(|row| (title_key(row.get("name")), row.get("tag_id")))
+		.collect();
+	// Pages with only example images have nothing written here to keep
+	let user_edited: HashSet<i64> = sqlx::query_scalar::<_, i64>(
+		r#"
+        SELECT w.tag_id FROM TagWikis w
+        WHERE w.source = ?
+          AND (w.body != '' OR EXISTS (SELECT 1 FROM TagWikiLinks l WHERE l.tag_id = w.tag_id))
+        "#,
+	)
+	.bind(SOURCE_USER)
+	.fetch_all(&pool)
+	.await?
+	.into_iter()
+	.collect();
+
+	// Parse off the async runtime; only pages for known tags are kept in memory
+	let path = PathBuf::from(path);
+	let progress_app = app.clone();
+	let (pages, mut summary) = tokio::task::spawn_blocking(move || {
+		let mut summary = WikiImportSummary::default();
+		let mut errors = Vec::new();
+		let mut pages = Vec::new();
+
+		summary.invalid_rows = read_wiki_dump(&path, &mut errors, |page| {
+			summary.pages_read += 1;
+			if summary.pages_read % IMPORT_PROGRESS_INTERVAL == 0 {
+				progress_app
+					.emit(
+						"wiki_import_progress",
+						ProgressEvent {
+							stage: "reading".to_string(),
+							message: format!("Read {} wiki pages", summary.pages_read),
+							file_hash: None,
+							current: Some(summary.pages_read),
+							total: None,
+						},
+					)
+					.ok();
+			}
+
+			let Some(&tag_id) = tags.get(&title_key(&page.title)) else {
+				summary.skipped_unknown_tag += 1;
+				return;
+			};
+			if page.is_deleted || page.body.trim().is_empty() {
+				summary.skipped_deleted_or_empty += 1;
+				return;
+			}
+			if !overwrite && user_edited.contains(&tag_id) {
+				summary.skipped_user_edited += 1;
+				return;
+			}
+			let (body, links) = dtext_to_markdown(&page.body);
+			pages.push(ImportedPage {
+				tag_id,
+				body,
+				links,
+			});
+		})?;
+
+		summary.errors = errors;
+		Ok::<_, AppError>((pages, summary))
+	})
+	.await
+	.map_err(|e| AppError::Custom(format!("Wiki import task failed: {e}")))??;
+
+	let total = pages.len();
+	let now = unix_now();
+	let mut tx = pool.begin().await?;
+	for (index, page) in pages.into_iter().enumerate() {
+		sqlx::query(
+			r#"
+            INSERT INTO TagWikis (tag_id, body, source, updated_at) VALUES (?, ?, ?, ?)
+            ON CONFLICT(tag_id) DO UPDATE SET
+                body = excluded.body, source = excluded.source, updated_at = excluded.updated_at
+            "#,
+		)
+		.bind(page.tag_id)
+		.bind(&page.body)
+		.bind(SOURCE_DANBOORU)
+		.bind(now)
+		.execute(&mut *tx)
+		.await?;
+		write_links(&mut tx, page.tag_id, &page.links).await?;
+		summary.imported += 1;
+
+		if (index + 1) % IMPORT_PROGRESS_INTERVAL == 0 || index + 1 == total {
+			app.emit(
+				"wiki_import_progress",
+				ProgressEvent {
+					stage: "importing".to_string(),
+					message: format!("Imported {} of {total} wiki pages", index + 1),
+					file_hash: None,
+					current: Some(index + 1),
+					total: Some(total),
+				},
+			)
+			.ok();
+		}
+	}
+	tx.commit().await?;
+
+	Ok(summary)
+}
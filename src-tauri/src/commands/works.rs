@@ -0,0 +1,507 @@
+use super::files::{file_record_from_row, FileRecord, FILE_RECORD_COLUMNS};
+use super::safe_mode::visible_files_condition;
+use crate::db::DbPool;
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::sqlite::SqliteRow;
+use sqlx::{Row, SqliteConnection, SqlitePool};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct Work {
+	pub work_id: i64,
+	pub title: Option<String>,
+	/// Site and post ID of works grouped automatically from filenames
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	/// Explicit cover, or the first member when none was chosen
+	pub cover_hash: Option<String>,
+	pub member_count: i64,
+	pub created_at: i64, // Unix timestamp
+}
+
+/// Columns selected for a `Work`, qualified with the `w` alias for `Works`
+const WORK_COLUMNS: &str = r#"
+    w.work_id, w.title, w.site, w.post_id, w.created_at,
+    COALESCE(w.cover_hash, (
+        SELECT m.file_hash FROM WorkMembers m
+        WHERE m.work_id = w.work_id
+        ORDER BY m.position, m.file_hash
+        LIMIT 1
+    )) as cover_hash,
+    (SELECT COUNT(*) FROM WorkMembers m WHERE m.work_id = w.work_id) as member_count
+"#;
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+fn work_from_row(row: &SqliteRow) -> Work {
+	Work {
+		work_id: row.get("work_id"),
+		title: row.get("title"),
+		site: row.get("site"),
+		post_id: row.get("post_id"),
+		cover_hash: row.get("cover_hash"),
+		member_count: row.get("member_count"),
+		created_at: row.get("created_at"),
+	}
+}
+
+fn now() -> Result<i64, AppError> {
+	Ok(SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.map_err(|e| AppError::Custom(format!("Invalid system time: {e}")))?
+		.as_secs() as i64)
+}
+
+async fn fetch_work(pool: &SqlitePool, work_id: i64) -> Result<Work, AppError> {
+	let query = format!("SELECT {WORK_COLUMNS} FROM Works w WHERE w.work_id = ?");
+	sqlx::query(&query)
+		.bind(work_id)
+		.fetch_optional(pool)
+		.await?
+		.map(|row| work_from_row(&row))
+		.ok_or_else(|| AppError::Custom(format!("Work {work_id} not found")))
+}
+
+async fn ensure_work_exists(conn: &mut SqliteConnection, work_id: i64) -> Result<(), AppError> {
+	let exists: Option<i64> = sqlx::query_scalar("SELECT work_id FROM Works WHERE work_id = ?")
+		.bind(work_id)
+		.fetch_optional(&mut *conn)
+		.await?;
+	match exists {
+		Some(_) => Ok(()),
+		None => Err(AppError::Custom(format!("Work {work_id} not found"))),
+	}
+}
+
+/// Member hashes of a work, in order
+async fn member_hashes(conn: &mut SqliteConnection, work_id: i64) -> Result<Vec<String>, AppError> {
+	Ok(sqlx::query_scalar(
+		"SELECT file_hash FROM WorkMembers WHERE work_id = ? ORDER BY position, file_hash",
+	)
+	.bind(work_id)
+	.fetch_all(&mut *conn)
+	.await?)
+}
+
+/// Rewrite positions to 0..n in the given order
+async fn write_positions(
+	conn: &mut SqliteConnection,
+	work_id: i64,
+	file_hashes: &[String],
+) -> Result<(), AppError> {
+	for (position, file_hash) in file_hashes.iter().enumerate() {
+		sqlx::query(
+			r#"
+            INSERT INTO WorkMembers (work_id, file_hash, position) VALUES (?, ?, ?)
+            ON CONFLICT(work_id, file_hash) DO UPDATE SET position = excluded.position
+            "#,
+		)
+		.bind(work_id)
+		.bind(file_hash)
+		.bind(position as i64)
+		.execute(&mut *conn)
+		.await?;
+	}
+	Ok(())
+}
+
+/// Add files to a work at an index (appended when `None`)
+/// Files in another work are moved; works left empty are deleted
+async fn insert_members(
+	conn: &mut SqliteConnection,
+	work_id: i64,
+	file_hashes: &[String],
+	index: Option<usize>,
+) -> Result<(), AppError> {
+	for file_hash in file_hashes {
+		let exists: Option<String> =
+			sqlx::query_scalar("SELECT file_hash FROM Files WHERE file_hash = ?")
+				.bind(file_hash)
+				.fetch_optional(&mut *conn)
+				.await?;
+		if exists.is_none() {
+			return Err(AppError::Custom(format!("File not found: {file_hash}")));
+		}
+	}
+
+	let mut members: Vec<String> = member_hashes(conn, work_id)
+		.await?
+		.into_iter()
+		.filter(|hash| !file_hashes.contains(hash))
+		.collect();
+	let index = index.unwrap_or(members.len()).min(members.len());
+	let mut new_members = Vec::new();
+	for file_hash in file_hashes {
+		if !new_members.contains(file_hash) {
+			new_members.push(file_hash.clone());
+		}
+	}
+	members.splice(index..index, new_members);
+
+	for file_hash in file_hashes {
+		sqlx::query("DELETE FROM WorkMembers WHERE file_hash = ? AND work_id != ?")
+			.bind(file_hash)
+			.bind(work_id)
+			.execute(&mut *conn)
+			.await?;
+		sqlx::query("UPDATE Works SET cover_hash = NULL WHERE cover_hash = ? AND work_id != ?")
+			.bind(file_hash)
+			.bind(work_id)
+			.execute(&mut *conn)
+			.await?;
+	}
+	write_positions(conn, work_id, &members).await?;
+	delete_empty_works(conn).await
+}
+
+async fn delete_empty_works(conn: &mut SqliteConnection) -> Result<(), AppError> {
+	sqlx::query(
+		"DELETE FROM Works WHERE work_id NOT IN (SELECT DISTINCT work_id FROM WorkMembers)",
+	)
+	.execute(&mut *conn)
+	.await?;
+	Ok(())
+}
+
+/// Put a file in the automatic work of its post, positioned by page
+/// Files already in a work (e.g. grouped by hand) stay where they are
+pub(crate) async fn add_to_post_work(
+	pool: &SqlitePool,
+	file_hash: &str,
+	site: &str,
+	post_id: &str,
+	page: u32,
+) -> Result<(), AppError> {
+	let mut tx = pool.begin().await?;
+
+	// Check first so a file that is already grouped doesn't leave an empty work behind
+	let in_work: bool =
+		sqlx::query_scalar("SELECT EXISTS(SELECT 1 FROM WorkMembers WHERE file_hash = ?)")
+			.bind(file_hash)
+			.fetch_one(&mut *tx)
+			.await?;
+	if in_work {
+		return Ok(());
+	}
+
+	sqlx::query(
+		r#"
+        INSERT INTO Works (site, post_id, created_at) VALUES (?, ?, ?)
+        ON CONFLICT(site, post_id) WHERE post_id IS NOT NULL DO NOTHING
+        "#,
+	)
+	.bind(site)
+	.bind(post_id)
+	.bind(now()?)
+	.execute(&mut *tx)
+	.await?;
+
+	let work_id: i64 =
+		sqlx::query_scalar("SELECT work_id FROM Works WHERE site = ? AND post_id = ?")
+			.bind(site)
+			.bind(post_id)
+			.fetch_one(&mut *tx)
+			.await?;
+
+	sqlx::query(
+		"INSERT OR IGNORE INTO WorkMembers (work_id, file_hash, position) VALUES (?, ?, ?)",
+	)
+	.bind(work_id)
+	.bind(file_hash)
+	.bind(i64::from(page))
+	.execute(&mut *tx)
+	.await?;
+
+	tx.commit().await?;
+	Ok(())
+}
+
+/// Wrap a `SELECT {FILE_RECORD_COLUMNS} FROM Files f ...` query so each work is returned once
+///
+/// The representative is the cover if it matches, otherwise the first matching member.
+/// Rows gain `work_id` and `work_member_count` (NULL for files outside any work).
+/// The result has no ORDER BY; order on the plain column names.
+pub(crate) fn collapse_works_query(base_query: &str) -> String {
+	format!(
+		r#"
+        SELECT * FROM (
+            SELECT matched.*, wm.work_id,
+                   CASE WHEN wm.work_id IS NOT NULL THEN (
+                       SELECT COUNT(*) FROM WorkMembers c WHERE c.work_id = wm.work_id
+                   ) END as work_member_count,
+                   ROW_NUMBER() OVER (
+                       PARTITION BY wm.work_id,
+                                    CASE WHEN wm.work_id IS NULL THEN matched.file_hash END
+                       ORDER BY matched.file_hash = w.cover_hash DESC, wm.position, matched.file_hash
+                   ) as work_rank
+            FROM ({base_query}) matched
+            LEFT JOIN WorkMembers wm ON wm.file_hash = matched.file_hash
+            LEFT JOIN Works w ON w.work_id = wm.work_id
+        )
+        WHERE work_rank = 1
+        "#
+	)
+}
+
+/// Hash of the member at `index` (0-based, in work order)
+pub(crate) async fn work_member_at(
+	pool: &SqlitePool,
+	work_id: i64,
+	index: i64,
+) -> Result<Option<String>, AppError> {
+	Ok(sqlx::query_scalar(
+		r#"
+        SELECT file_hash FROM WorkMembers
+        WHERE work_id = ?
+        ORDER BY position, file_hash
+        LIMIT 1 OFFSET ?
+        "#,
+	)
+	.bind(work_id)
+	.bind(index)
+	.fetch_optional(pool)
+	.await?)
+}
+
+/// Hash of the cover of a work
+pub(crate) async fn work_cover(
+	pool: &SqlitePool,
+	work_id: i64,
+) -> Result<Option<String>, AppError> {
+	let query = format!("SELECT {WORK_COLUMNS} FROM Works w WHERE w.work_id = ?");
+	let row = sqlx::query(&query)
+		.bind(work_id)
+		.fetch_optional(pool)
+		.await?;
+	Ok(row.and_then(|row| row.get("cover_hash")))
+}
+
+// ============================================================================
+// Tauri Commands
+// ============================================================================
+
+/// Group files into a new work, in the given order
+/// Files already in another work are moved to the new one
+#[tauri::command]
+pub async fn create_work(
+	db: tauri::State<'_, DbPool>,
+	title: Option<String>,
+	file_hashes: Vec<String>,
+) -> Result<Work, AppError> {
+	let pool = db.get();
+	if file_hashes.is_empty() {
+		return Err(AppError::Custom(
+			"A work needs at least one file".to_string(),
+		));
+	}
+	let title = title
+		.map(|t| t.trim().to_string())
+		.filter(|t| !t.is_empty());
+
+	let mut tx = pool.begin().await?;
+	let work_id: i64 =
+		sqlx::query_scalar("INSERT INTO Works (title, created_at) VALUES (?, ?) RETURNING work_id")
+			.bind(&title)
+			.bind(now()?)
+			.fetch_one(&mut *tx)
+			.await?;
+	insert_members(&mut tx, work_id, &file_hashes, None).await?;
+	tx.commit().await?;
+
+	fetch_work(&pool, work_id).await
+}
+
+#[tauri::command]
+pub async fn get_work(db: tauri::State<'_, DbPool>, work_id: i64) -> Result<Work, AppError> {
+	let pool = db.get();
+	fetch_work(&pool, work_id).await
+}
+
+/// Get the work a file belongs to, if any
+#[tauri::command]
+pub async fn get_work_for_file(
+	db: tauri::State<'_, DbPool>,
+	file_hash: String,
+) -> Result<Option<Work>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		r#"
+        SELECT {WORK_COLUMNS}
+        FROM Works w
+        JOIN WorkMembers wm ON wm.work_id = w.work_id
+        WHERE wm.file_hash = ?
+        "#
+	);
+	let row = sqlx::query(&query)
+		.bind(&file_hash)
+		.fetch_optional(&pool)
+		.await?;
+	Ok(row.map(|row| work_from_row(&row)))
+}
+
+/// Get the files of a work, in order
+#[tauri::command]
+pub async fn get_work_members(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+) -> Result<Vec<FileRecord>, AppError> {
+	let pool = db.get();
+	let query = format!(
+		r#"
+        SELECT {FILE_RECORD_COLUMNS}
+        FROM WorkMembers wm
+        JOIN Files f ON f.file_hash = wm.file_hash
+        WHERE wm.work_id = ? AND {visible}
+        ORDER BY wm.position, wm.file_hash
+        "#,
+		visible = visible_files_condition("f.file_hash")
+	);
+	let rows = sqlx::query(&query).bind(work_id).fetch_all(&pool).await?;
+	Ok(rows.iter().map(file_record_from_row).collect())
+}
+
+/// Add files to a work at `index` (0-based), or at the end
+#[tauri::command]
+pub async fn add_files_to_work(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+	file_hashes: Vec<String>,
+	index: Option<usize>,
+) -> Result<Work, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ensure_work_exists(&mut tx, work_id).await?;
+	insert_members(&mut tx, work_id, &file_hashes, index).await?;
+	tx.commit().await?;
+	fetch_work(&pool, work_id).await
+}
+
+/// Remove files from a work; the work is deleted when it has no members left
+/// Returns the remaining work, if any
+#[tauri::command]
+pub async fn remove_files_from_work(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+	file_hashes: Vec<String>,
+) -> Result<Option<Work>, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ensure_work_exists(&mut tx, work_id).await?;
+
+	for file_hash in &file_hashes {
+		sqlx::query("DELETE FROM WorkMembers WHERE work_id = ? AND file_hash = ?")
+			.bind(work_id)
+			.bind(file_hash)
+			.execute(&mut *tx)
+			.await?;
+		sqlx::query("UPDATE Works SET cover_hash = NULL WHERE work_id = ? AND cover_hash = ?")
+			.bind(work_id)
+			.bind(file_hash)
+			.execute(&mut *tx)
+			.await?;
+	}
+	delete_empty_works(&mut tx).await?;
+	tx.commit().await?;
+
+	match fetch_work(&pool, work_id).await {
+		Ok(work) => Ok(Some(work)),
+		Err(_) => Ok(None),
+	}
+}
+
+/// Set the order of a work's members; `file_hashes` must list every member exactly once
+#[tauri::command]
+pub async fn reorder_work(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+	file_hashes: Vec<String>,
+) -> Result<Work, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ensure_work_exists(&mut tx, work_id).await?;
+
+	let mut current = member_hashes(&mut tx, work_id).await?;
+	let mut requested = file_hashes.clone();
+	current.sort();
+	requested.sort();
+	if current != requested {
+		return Err(AppError::Custom(
+			"New order must list every member of the work exactly once".to_string(),
+		));
+	}
+
+	write_positions(&mut tx, work_id, &file_hashes).await?;
+	tx.commit().await?;
+	fetch_work(&pool, work_id).await
+}
+
+/// Choose the cover of a work; `None` falls back to the first member
+#[tauri::command]
+pub async fn set_work_cover(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+	file_hash: Option<String>,
+) -> Result<Work, AppError> {
+	let pool = db.get();
+	let mut tx = pool.begin().await?;
+	ensure_work_exists(&mut tx, work_id).await?;
+
+	if let Some(file_hash) = &file_hash {
+		let is_member = member_hashes(&mut tx, work_id).await?.contains(file_hash);
+		if !is_member {
+			return Err(AppError::Custom(format!(
+				"File {file_hash} is not a member of work {work_id}"
+			)));
+		}
+	}
+
+	sqlx::query("UPDATE Works SET cover_hash = ? WHERE work_id = ?")
+		.bind(&file_hash)
+		.bind(work_id)
+		.execute(&mut *tx)
+		.await?;
+	tx.commit().await?;
+	fetch_work(&pool, work_id).await
+}
+
+#[tauri::command]
+pub async fn rename_work(
+	db: tauri::State<'_, DbPool>,
+	work_id: i64,
+	title: Option<String>,
+) -> Result<Work, AppError> {
+	let pool = db.get();
+	let title = title
+		.map(|t| t.trim().to_string())
+		.filter(|t| !t.is_empty());
+	let result = sqlx::query("UPDATE Works SET title = ? WHERE work_id = ?")
+		.bind(&title)
+		.bind(work_id)
+		.execute(&pool)
+		.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Work {work_id} not found")));
+	}
+	fetch_work(&pool, work_id).await
+}
+
+/// Ungroup a work; its files are kept
+#[tauri::command]
+pub async fn delete_work(db: tauri::State<'_, DbPool>, work_id: i64) -> Result<(), AppError> {
+	let pool = db.get();
+	let result = sqlx::query("DELETE FROM Works WHERE work_id = ?")
+		.bind(work_id)
+		.execute(&pool)
+		.await?;
+	if result.rows_affected() == 0 {
+		return Err(AppError::Custom(format!("Work {work_id} not found")));
+	}
+	Ok(())
+}
@@ -0,0 +1,299 @@
+// CSV tables (label maps, translation dictionaries, tag dumps): RFC 4180 quoting, UTF-8 BOM and
+// CRLF handling, columns found by header name and problems reported per line
+
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::collections::{HashMap, VecDeque};
+use std::fmt;
+use std::io::Read;
+use std::rc::Rc;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// A problem with one line of a CSV file, shown to the user
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct LineDiagnostic {
+	/// 1-based line where the row starts
+	pub line: u64,
+	pub message: String,
+}
+
+impl fmt::Display for LineDiagnostic {
+	fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+		write!(f, "Line {}: {}", self.line, self.message)
+	}
+}
+
+#[derive(Debug, Serialize, Clone, Default)]
+pub struct CsvReport {
+	/// Data rows read, valid or not
+	pub rows_read: usize,
+	pub invalid_rows: usize,
+	/// Why rows were invalid; only the first `MAX_DIAGNOSTICS` are listed
+	pub diagnostics: Vec<LineDiagnostic>,
+}
+
+/// A data row, with fields looked up by column name
+pub struct CsvRow<'a> {
+	pub line: u64,
+	/// Position among data rows, counting invalid ones
+	pub index: usize,
+	record: &'a csv::StringRecord,
+	columns: &'a HashMap<String, usize>,
+}
+
+impl CsvRow<'_> {
+	/// A field by column name, trimmed; `None` when the column is missing or the field empty
+	pub fn get(&self, column: &str) -> Option<&str> {
+		self.columns
+			.get(column)
+			.and_then(|&index| self.record.get(index))
+			.map(str::trim)
+			.filter(|field| !field.is_empty())
+	}
+}
+
+/// Byte offsets of newlines read but not yet passed by a record
+/// The csv crate's own line numbers are off after blank lines, CRLF and quoted line breaks.
+struct NewlineTracker<R> {
+	inner: R,
+	offset: u64,
+	newlines: Rc<RefCell<VecDeque<u64>>>,
+}
+
+impl<R: Read> Read for NewlineTracker<R> {
+	fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+		let read = self.inner.read(buf)?;
+		let mut newlines = self.newlines.borrow_mut();
+		for (index, _) in buf[..read].iter().enumerate().filter(|(_, &b)| b == b'\n') {
+			newlines.push_back(self.offset + index as u64);
+		}
+		self.offset += read as u64;
+		Ok(read)
+	}
+}
+
+/// Diagnostics kept in a report; further invalid rows are only counted
+pub const MAX_DIAGNOSTICS: usize = 100;
+
+// ============================================================================
+// Reading
+// ============================================================================
+
+/// Read CSV data, calling `row` for each data row; an `Err` from `row` marks the row invalid
+///
+/// The first row is a header when it names one of `columns` (case-insensitive), and its names
+/// map the columns; otherwise `columns` is the column order. Rows with only empty fields are
+/// skipped.
+pub fn read_rows<R: Read>(
+	data: R,
+	columns: &[&str],
+	mut row: impl FnMut(&CsvRow) -> Result<(), String>,
+) -> Result<CsvReport, AppError> {
+	let newlines = Rc::new(RefCell::new(VecDeque::new()));
+	let mut reader = csv::ReaderBuilder::new()
+		.has_headers(false)
+		.flexible(true)
+		.from_reader(NewlineTracker {
+			inner: data,
+			offset: 0,
+			newlines: newlines.clone(),
+		});
+
+	let mut column_map: HashMap<String, usize> = columns
+		.iter()
+		.enumerate()
+		.map(|(index, name)| (name.to_string(), index))
+		.collect();
+	let mut report = CsvReport::default();
+	let mut lines_passed = 0;
+	let mut record = csv::StringRecord::new();
+	let mut first = true;
+
+	loop {
+		let result = reader.read_record(&mut record);
+		// The csv crate's record positions don't skip blank lines, so count lines up to where
+		// the record ends (the reader is past its terminator) and back over its own line breaks
+		let end = reader.position().byte();
+		let mut ends_with_newline = false;
+		{
+			let mut newlines = newlines.borrow_mut();
+			while let Some(offset) = newlines.front().copied().filter(|&offset| offset < end) {
+				newlines.pop_front();
+				lines_passed += 1;
+				ends_with_newline = offset + 1 == end;
+			}
+		}
+		let inner_newlines = match result {
+			Ok(true) => record
+				.iter()
+				.map(|field| field.matches('\n').count() as u64)
+				.sum(),
+			_ => 0,
+		};
+		let line = lines_passed + 1 - ends_with_newline as u64 - inner_newlines;
+
+		let diagnostic = match result {
+			Ok(false) => break,
+			Ok(true) if first => {
+				first = false;
+				let is_header = record
+					.iter()
+					.any(|field| columns.iter().any(|c| field.trim().eq_ignore_ascii_case(c)));
+				if is_header {
+					column_map = record
+						.iter()
+						.enumerate()
+						.map(|(index, name)| (name.trim().to_lowercase(), index))
+						.collect();
+					continue;
+				}
+				None
+			}
+			Ok(true) => None,
+			Err(e) => match e.kind() {
+				csv::ErrorKind::Io(_) => {
+					return Err(AppError::Custom(format!("Failed to read CSV: {e}")));
+				}
+				csv::ErrorKind::Utf8 { .. } => Some("invalid UTF-8".to_string()),
+				_ => Some(e.to_string()),
+			},
+		};
+		first = false;
+
+		let diagnostic = match diagnostic {
+			Some(message) => Some(message),
+			None if record.iter().all(|field| field.trim().is_empty()) => continue,
+			None => row(&CsvRow {
+				line,
+				index: report.rows_read,
+				record: &record,
+				columns: &column_map,
+			})
+			.err(),
+		};
+		report.rows_read += 1;
+		if let Some(message) = diagnostic {
+			report.invalid_rows += 1;
+			if report.diagnostics.len() < MAX_DIAGNOSTICS {
+				report.diagnostics.push(LineDiagnostic { line, message });
+			}
+		}
+	}
+
+	Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+	use super::*;
+
+	/// `(line, index, fields)` of a row passed to the callback
+	type Row = (u64, usize, Vec<Option<String>>);
+
+	/// Every row passed to the callback, and the report
+	fn read(data: &str, columns: &[&str]) -> (Vec<Row>, CsvReport) {
+		let mut rows = Vec::new();
+		let report = read_rows(data.as_bytes(), columns, |row| {
+			let fields = columns
+				.iter()
+				.map(|column| row.get(column).map(str::to_string))
+				.collect();
+			rows.push((row.line, row.index, fields));
+			Ok(())
+		})
+		.unwrap();
+		(rows, report)
+	}
+
+	fn fields(values: &[&str]) -> Vec<Option<String>> {
+		values.iter().map(|v| Some(v.to_string())).collect()
+	}
+
+	#[test]
+	fn strips_utf8_bom_before_header() {
+		let (rows, report) = read("\u{feff}name,text\nmiku,初音ミク\n", &["name", "text"]);
+		assert_eq!(rows, vec![(2, 0, fields(&["miku", "初音ミク"]))]);
+		assert_eq!(report.rows_read, 1);
+		assert_eq!(report.invalid_rows, 0);
+	}
+
+	#[test]
+	fn handles_crlf_line_endings() {
+		let (rows, _) = read("name,text\r\na,1\r\nb,2\r\n", &["name", "text"]);
+		assert_eq!(
+			rows,
+			vec![(2, 0, fields(&["a", "1"])), (3, 1, fields(&["b", "2"]))]
+		);
+	}
+
+	#[test]
+	fn keeps_commas_and_newlines_in_quoted_fields() {
+		let (rows, _) = read(
+			"name,text\n\"a,b\",\"first\nsecond\"\nc,d\n",
+			&["name", "text"],
+		);
+		assert_eq!(
+			rows,
+			vec![
+				(2, 0, fields(&["a,b", "first\nsecond"])),
+				(4, 1, fields(&["c", "d"])),
+			]
+		);
+	}
+
+	#[test]
+	fn unescapes_doubled_quotes() {
+		let (rows, _) = read("name,text\nquote,\"say \"\"hi\"\"\"\n", &["name", "text"]);
+		assert_eq!(rows, vec![(2, 0, fields(&["quote", "say \"hi\""]))]);
+	}
+
+	#[test]
+	fn skips_blank_lines_and_keeps_line_numbers() {
+		let (rows, report) = read("name,text\n\na,1\n,\n\r\nb,2\n", &["name", "text"]);
+		assert_eq!(
+			rows,
+			vec![(3, 0, fields(&["a", "1"])), (6, 1, fields(&["b", "2"]))]
+		);
+		assert_eq!(report.rows_read, 2);
+	}
+
+	#[test]
+	fn uses_column_order_without_header() {
+		let (rows, _) = read(
+			"0,1girl,0,100\n1,solo,0,90\n",
+			&["tag_id", "name", "category", "count"],
+		);
+		assert_eq!(
+			rows,
+			vec![
+				(1, 0, fields(&["0", "1girl", "0", "100"])),
+				(2, 1, fields(&["1", "solo", "0", "90"])),
+			]
+		);
+	}
+
+	#[test]
+	fn maps_header_columns_by_name_in_any_order() {
+		let (rows, _) = read("Text,Extra,NAME\nhello,x,world\n", &["name", "text"]);
+		assert_eq!(rows, vec![(2, 0, fields(&["world", "hello"]))]);
+	}
+
+	#[test]
+	fn reports_rows_the_callback_rejects() {
+		let report = read_rows("name\na\nb\n".as_bytes(), &["name"], |row| {
+			match row.get("name") {
+				Some("b") => Err("rejected".to_string()),
+				_ => Ok(()),
+			}
+		})
+		.unwrap();
+		assert_eq!(report.rows_read, 2);
+		assert_eq!(report.invalid_rows, 1);
+		assert_eq!(report.diagnostics[0].line, 3);
+		assert_eq!(report.diagnostics[0].message, "rejected");
+	}
+}
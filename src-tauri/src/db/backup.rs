@@ -0,0 +1,328 @@
+use crate::error::AppError;
+use serde::{Deserialize, Serialize};
+use sqlx::migrate::Migrator;
+use sqlx::sqlite::SqliteConnectOptions;
+use sqlx::{ConnectOptions, Connection, SqlitePool};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Why a backup was taken
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum BackupKind {
+	Manual,
+	Scheduled,
+	PreMigration,
+	PreRestore,
+}
+
+impl BackupKind {
+	pub fn as_str(&self) -> &'static str {
+		match self {
+			BackupKind::Manual => "manual",
+			BackupKind::Scheduled => "scheduled",
+			BackupKind::PreMigration => "pre-migration",
+			BackupKind::PreRestore => "pre-restore",
+		}
+	}
+
+	fn parse(value: &str) -> Option<Self> {
+		match value {
+			"manual" => Some(BackupKind::Manual),
+			"scheduled" => Some(BackupKind::Scheduled),
+			"pre-migration" => Some(BackupKind::PreMigration),
+			"pre-restore" => Some(BackupKind::PreRestore),
+			_ => None,
+		}
+	}
+
+	/// Automatic backups are subject to retention, manual ones are kept until deleted
+	pub fn is_automatic(&self) -> bool {
+		!matches!(self, BackupKind::Manual)
+	}
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct BackupInfo {
+	pub file_name: String,
+	pub file_path: String,
+	pub kind: BackupKind,
+	pub size_bytes: u64,
+	pub created_at: i64, // Unix timestamp
+}
+
+/// Retention rules for automatic backups
+#[derive(Debug, Clone, Copy)]
+pub struct RetentionPolicy {
+	/// Keep at most this many automatic backups (0 = unlimited)
+	pub max_backups: u32,
+	/// Delete automatic backups older than this many days
+	pub max_age_days: Option<u32>,
+}
+
+// Backup file names look like: album-20251207-153000123-scheduled.db
+const BACKUP_PREFIX: &str = "album-";
+const BACKUP_EXTENSION: &str = ".db";
+
+// ============================================================================
+// Helper Functions
+// ============================================================================
+
+/// Backups live in a `backups` directory next to the database file
+pub fn backup_dir_for(db_path: &Path) -> PathBuf {
+	db_path
+		.parent()
+		.map(|parent| parent.join("backups"))
+		.unwrap_or_else(|| PathBuf::from("backups"))
+}
+
+/// Resolve the on-disk path of the main database behind a pool
+async fn database_file(pool: &SqlitePool) -> Result<PathBuf, AppError> {
+	let rows: Vec<(i64, String, String)> = sqlx::query_as("PRAGMA database_list")
+		.fetch_all(pool)
+		.await?;
+
+	rows.into_iter()
+		.find(|(_, name, file)| name == "main" && !file.is_empty())
+		.map(|(_, _, file)| PathBuf::from(file))
+		.ok_or_else(|| AppError::Custom("Database is not backed by a file".to_string()))
+}
+
+/// Validate a backup file name coming from the frontend and resolve it inside the backup directory
+pub fn resolve_backup_path(backup_dir: &Path, file_name: &str) -> Result<PathBuf, AppError> {
+	let is_plain_name = !file_name.contains(['/', '\\']) && file_name != "." && file_name != "..";
+	if !is_plain_name
+		|| !file_name.starts_with(BACKUP_PREFIX)
+		|| !file_name.ends_with(BACKUP_EXTENSION)
+	{
+		return Err(AppError::Custom(format!(
+			"Invalid backup file name: {file_name}"
+		)));
+	}
+
+	let path = backup_dir.join(file_name);
+	if !path.is_file() {
+		return Err(AppError::Custom(format!("Backup not found: {file_name}")));
+	}
+
+	Ok(path)
+}
+
+fn backup_info_from_path(path: &Path) -> Option<BackupInfo> {
+	let file_name = path.file_name()?.to_str()?.to_string();
+	let stem = file_name
+		.strip_prefix(BACKUP_PREFIX)?
+		.strip_suffix(BACKUP_EXTENSION)?;
+
+	// stem: {date}-{time}-{kind}, kind itself may contain '-'
+	let mut parts = stem.splitn(3, '-');
+	let _date = parts.next()?;
+	let _time = parts.next()?;
+	let kind = BackupKind::parse(parts.next()?)?;
+
+	let metadata = std::fs::metadata(path).ok()?;
+	let created_at = metadata
+		.modified()
+		.ok()
+		.and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+		.map(|d| d.as_secs() as i64)
+		.unwrap_or(0);
+
+	Some(BackupInfo {
+		file_name,
+		file_path: path.display().to_string(),
+		kind,
+		size_bytes: metadata.len(),
+		created_at,
+	})
+}
+
+// ============================================================================
+// Backup Operations
+// ============================================================================
+
+/// Check whether the migrator would apply anything to this database
+/// Returns false for a fresh database (nothing worth backing up yet)
+pub async fn has_pending_migrations(
+	pool: &SqlitePool,
+	migrator: &Migrator,
+) -> Result<bool, sqlx::Error> {
+	let table_exists: Option<String> = sqlx::query_scalar(
+		"SELECT name FROM sqlite_master WHERE type = 'table' AND name = '_sqlx_migrations'",
+	)
+	.fetch_optional(pool)
+	.await?;
+
+	if table_exists.is_none() {
+		return Ok(false);
+	}
+
+	let applied: HashSet<i64> =
+		sqlx::query_scalar("SELECT version FROM _sqlx_migrations WHERE success = 1")
+			.fetch_all(pool)
+			.await?
+			.into_iter()
+			.collect();
+
+	Ok(migrator
+		.iter()
+		.filter(|m| !m.migration_type.is_down_migration())
+		.any(|m| !applied.contains(&m.version)))
+}
+
+/// Take a consistent snapshot of the live database using `VACUUM INTO`
+pub async fn create_backup(pool: &SqlitePool, kind: BackupKind) -> Result<BackupInfo, AppError> {
+	let db_path = database_file(pool).await?;
+	let backup_dir = backup_dir_for(&db_path);
+	std::fs::create_dir_all(&backup_dir)?;
+
+	let timestamp = chrono::Local::now().format("%Y%m%d-%H%M%S%3f");
+	let backup_path = backup_dir.join(format!(
+		"{BACKUP_PREFIX}{timestamp}-{}{BACKUP_EXTENSION}",
+		kind.as_str()
+	));
+
+	sqlx::query("VACUUM INTO ?")
+		.bind(backup_path.display().to_string())
+		.execute(pool)
+		.await?;
+
+	backup_info_from_path(&backup_path).ok_or_else(|| {
+		AppError::Custom(format!(
+			"Backup was written but could not be read back: {}",
+			backup_path.display()
+		))
+	})
+}
+
+/// List backups in a directory, newest first
+pub fn list_backups(backup_dir: &Path) -> Result<Vec<BackupInfo>, AppError> {
+	if !backup_dir.exists() {
+		return Ok(Vec::new());
+	}
+
+	let mut backups: Vec<BackupInfo> = std::fs::read_dir(backup_dir)?
+		.flatten()
+		.filter_map(|entry| backup_info_from_path(&entry.path()))
+		.collect();
+
+	// File names sort chronologically; use them as a tie-breaker for equal mtimes
+	backups.sort_by(|a, b| {
+		b.created_at
+			.cmp(&a.created_at)
+			.then_with(|| b.file_name.cmp(&a.file_name))
+	});
+
+	Ok(backups)
+}
+
+/// Delete automatic backups that fall outside the retention policy
+/// Returns the number of deleted backups
+pub fn prune_backups(backup_dir: &Path, policy: RetentionPolicy) -> Result<usize, AppError> {
+	let now = SystemTime::now()
+		.duration_since(UNIX_EPOCH)
+		.unwrap_or(Duration::ZERO)
+		.as_secs() as i64;
+	let max_age_secs = policy.max_age_days.map(|days| days as i64 * 24 * 60 * 60);
+
+	let mut deleted = 0;
+	let automatic = list_backups(backup_dir)?
+		.into_iter()
+		.filter(|b| b.kind.is_automatic());
+
+	for (index, backup) in automatic.enumerate() {
+		let over_count = policy.max_backups > 0 && index >= policy.max_backups as usize;
+		let too_old = max_age_secs.is_some_and(|max_age| now - backup.created_at > max_age);
+
+		if over_count || too_old {
+			match std::fs::remove_file(&backup.file_path) {
+				Ok(()) => deleted += 1,
+				Err(e) => eprintln!("Failed to delete old backup {}: {e}", backup.file_name),
+			}
+		}
+	}
+
+	Ok(deleted)
+}
+
+/// Run SQLite's integrity check against a backup file without touching the live database
+pub async fn verify_backup(path: &Path) -> Result<(), AppError> {
+	let mut conn = SqliteConnectOptions::new()
+		.filename(path)
+		.read_only(true)
+		.connect()
+		.await?;
+
+	let result: String = sqlx::query_scalar("PRAGMA integrity_check")
+		.fetch_one(&mut conn)
+		.await?;
+	conn.close().await?;
+
+	if result != "ok" {
+		return Err(AppError::Custom(format!(
+			"Backup failed integrity check: {result}"
+		)));
+	}
+
+	Ok(())
+}
+
+/// Copy a backup next to the database and bring it up to the current schema
+/// A failing migration only ever touches the staged copy, never the live database
+pub async fn stage_restore(
+	backup_path: &Path,
+	db_path: &Path,
+	migrator: &Migrator,
+) -> Result<PathBuf, AppError> {
+	let staged_path = db_path.with_extension("db.restore-staged");
+	std::fs::copy(backup_path, &staged_path)?;
+
+	let result = async {
+		let mut conn = SqliteConnectOptions::new()
+			.filename(&staged_path)
+			.connect()
+			.await?;
+		let migrated = migrator
+			.run(&mut conn)
+			.await
+			.map_err(|e| AppError::Custom(format!("Failed to migrate the restored database: {e}")));
+		conn.close().await?;
+		migrated
+	}
+	.await;
+
+	match result {
+		Ok(()) => Ok(staged_path),
+		Err(e) => {
+			std::fs::remove_file(&staged_path).ok();
+			Err(e)
+		}
+	}
+}
+
+/// Replace the database file with a backup
+/// The caller must have closed every connection to `db_path` beforehand
+pub fn swap_database_file(backup_path: &Path, db_path: &Path) -> Result<(), AppError> {
+	// Copy next to the target first so the final rename is atomic
+	let staging_path = db_path.with_extension("db.restore-tmp");
+	std::fs::copy(backup_path, &staging_path)?;
+
+	// Stale journal files belong to the old database and must not be replayed onto the new one
+	for suffix in ["-wal", "-shm", "-journal"] {
+		let mut sidecar = db_path.as_os_str().to_owned();
+		sidecar.push(suffix);
+		let sidecar = PathBuf::from(sidecar);
+		if sidecar.exists() {
+			std::fs::remove_file(&sidecar)?;
+		}
+	}
+
+	std::fs::rename(&staging_path, db_path)?;
+
+	Ok(())
+}
@@ -1,3 +1,5 @@
+pub mod backup;
+
 use sqlx::{
 	sqlite::{SqliteConnectOptions, SqlitePoolOptions},
 	SqlitePool,
@@ -5,11 +7,44 @@ use sqlx::{
 use std::env;
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
+use std::sync::RwLock;
 
-/// Initialize SQLite connection pool
-/// Automatically creates database if it doesn't exist or is corrupted
-pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error> {
-	// Determine database path
+/// Managed database state
+/// Wraps the connection pool so it can be closed and reopened at runtime
+/// (e.g. when restoring a backup) without restarting the app
+pub struct DbPool {
+	pool: RwLock<SqlitePool>,
+	db_path: PathBuf,
+}
+
+impl DbPool {
+	pub fn new(pool: SqlitePool, db_path: PathBuf) -> Self {
+		Self {
+			pool: RwLock::new(pool),
+			db_path,
+		}
+	}
+
+	/// Get a handle to the current pool (cheap clone)
+	pub fn get(&self) -> SqlitePool {
+		self.pool.read().unwrap_or_else(|e| e.into_inner()).clone()
+	}
+
+	/// Swap in a new pool, returning the previous one
+	pub fn replace(&self, pool: SqlitePool) -> SqlitePool {
+		let mut guard = self.pool.write().unwrap_or_else(|e| e.into_inner());
+		std::mem::replace(&mut *guard, pool)
+	}
+
+	/// Path of the database file backing this pool
+	pub fn db_path(&self) -> &Path {
+		&self.db_path
+	}
+}
+
+/// Resolve the database file path
+/// Uses DATABASE_URL if set, otherwise {app_data_dir}/album.db
+pub fn resolve_db_path(app_data_dir: &Path) -> PathBuf {
 	let db_path = if let Ok(url) = env::var("DATABASE_URL") {
 		// Parse DATABASE_URL
 		if url.starts_with("sqlite:") {
@@ -40,7 +75,7 @@ pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error>
 	};
 
 	// Normalize the path (resolve .. and . components)
-	let db_path = db_path.canonicalize().unwrap_or_else(|_| {
+	db_path.canonicalize().unwrap_or_else(|_| {
 		// If canonicalize fails (e.g., path doesn't exist yet),
 		// resolve manually by joining and normalizing
 		let mut normalized = PathBuf::new();
@@ -58,8 +93,12 @@ pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error>
 			}
 		}
 		normalized
-	});
+	})
+}
 
+/// Initialize SQLite connection pool
+/// Automatically creates database if it doesn't exist or is corrupted
+pub async fn init_pool(db_path: &Path) -> Result<SqlitePool, sqlx::Error> {
 	// Ensure parent directory exists
 	if let Some(parent) = db_path.parent() {
 		std::fs::create_dir_all(parent).map_err(|e| {
@@ -108,7 +147,7 @@ pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error>
 					// Delete corrupted database file
 					if db_path.exists() {
 						eprintln!("Removing corrupted database file: {db_path:?}");
-						if let Err(rm_err) = std::fs::remove_file(&db_path) {
+						if let Err(rm_err) = std::fs::remove_file(db_path) {
 							eprintln!(
 								"Warning: Failed to remove corrupted database file: {rm_err}"
 							);
@@ -137,7 +176,7 @@ pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error>
 			if db_path.exists() {
 				eprintln!("Database file exists but connection failed (possibly corrupted): {e}");
 				eprintln!("Removing corrupted database file: {db_path:?}");
-				if let Err(rm_err) = std::fs::remove_file(&db_path) {
+				if let Err(rm_err) = std::fs::remove_file(db_path) {
 					eprintln!("Warning: Failed to remove corrupted database file: {rm_err}");
 				}
 			} else {
@@ -165,8 +204,23 @@ pub async fn init_pool(app_data_dir: PathBuf) -> Result<SqlitePool, sqlx::Error>
 }
 
 /// Run pending database migrations
+/// Takes a rolling backup first if any migration is about to be applied to an existing database
 pub async fn run_migrations(pool: &SqlitePool) -> Result<(), sqlx::Error> {
-	sqlx::migrate!("../migrations").run(pool).await?;
+	let migrator = sqlx::migrate!("../migrations");
+
+	if backup::has_pending_migrations(pool, &migrator).await? {
+		match backup::create_backup(pool, backup::BackupKind::PreMigration).await {
+			Ok(info) => eprintln!("Pre-migration backup created: {}", info.file_name),
+			Err(e) => {
+				eprintln!("Failed to create pre-migration backup: {e}");
+				return Err(sqlx::Error::Protocol(format!(
+					"Refusing to migrate without a backup: {e}"
+				)));
+			}
+		}
+	}
+
+	migrator.run(pool).await?;
 
 	Ok(())
 }
@@ -0,0 +1,263 @@
+// Filename pattern engine: recognizes how pixiv, boorus and Twitter downloaders name files
+//
+// Patterns are tried in order, user patterns first; the first match wins.
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::{Deserialize, Serialize};
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// Information recovered from a filename
+#[derive(Debug, Serialize, Deserialize, Clone, Default, PartialEq, Eq)]
+pub struct FilenameMatch {
+	/// Name of the pattern that matched
+	pub pattern: String,
+	/// Site the file came from, e.g. "pixiv" or "danbooru"
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	/// Page within a multi-image post, as numbered by the site
+	pub page: Option<u32>,
+	pub artists: Vec<String>,
+	pub characters: Vec<String>,
+	pub series: Vec<String>,
+	/// Unsplit tag string (Danbooru joins characters and copyrights without a separator)
+	pub tag_string: Option<String>,
+	pub md5: Option<String>,
+	pub source_url: Option<String>,
+}
+
+/// A way of recognizing filenames
+pub trait FilenamePattern: Send + Sync {
+	fn name(&self) -> &str;
+
+	/// Parse a filename without its extension
+	fn parse(&self, stem: &str) -> Option<FilenameMatch>;
+}
+
+/// User-defined pattern, stored in settings
+///
+/// Recognized named captures: `post_id`, `page`, `artist`, `character`, `series`, `tags`, `md5`.
+/// `source_url` may reference captures as `{post_id}`, `{artist}`, ...
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct UserPatternDefinition {
+	pub name: String,
+	pub regex: String,
+	pub site: Option<String>,
+	pub source_url: Option<String>,
+	#[serde(default = "default_true")]
+	pub enabled: bool,
+}
+
+fn default_true() -> bool {
+	true
+}
+
+// ============================================================================
+// Regex Patterns
+// ============================================================================
+
+/// A pattern driven by a regex with named captures; used for built-ins and user patterns
+pub struct RegexPattern {
+	name: String,
+	site: Option<String>,
+	regex: Regex,
+	source_url: Option<String>,
+	/// Multiple artists are joined with this in the `artist` capture
+	artist_separator: Option<&'static str>,
+}
+
+impl RegexPattern {
+	pub fn new(
+		name: &str,
+		site: Option<&str>,
+		regex: &str,
+		source_url: Option<&str>,
+	) -> Result<Self, regex::Error> {
+		Ok(Self {
+			name: name.to_string(),
+			site: site.map(str::to_string),
+			regex: Regex::new(regex)?,
+			source_url: source_url.map(str::to_string),
+			artist_separator: None,
+		})
+	}
+
+	pub fn from_definition(definition: &UserPatternDefinition) -> Result<Self, regex::Error> {
+		Self::new(
+			&definition.name,
+			definition.site.as_deref(),
+			&definition.regex,
+			definition.source_url.as_deref(),
+		)
+	}
+
+	fn with_artist_separator(mut self, separator: &'static str) -> Self {
+		self.artist_separator = Some(separator);
+		self
+	}
+}
+
+impl FilenamePattern for RegexPattern {
+	fn name(&self) -> &str {
+		&self.name
+	}
+
+	fn parse(&self, stem: &str) -> Option<FilenameMatch> {
+		let captures = self.regex.captures(stem)?;
+		let capture = |name: &str| {
+			captures
+				.name(name)
+				.map(|m| m.as_str().to_string())
+				.filter(|s| !s.is_empty())
+		};
+
+		let artists = match (capture("artist"), self.artist_separator) {
+			(Some(artist), Some(separator)) => {
+				artist.split(separator).map(str::to_string).collect()
+			}
+			(Some(artist), None) => vec![artist],
+			(None, _) => Vec::new(),
+		};
+
+		let mut result = FilenameMatch {
+			pattern: self.name.clone(),
+			site: self.site.clone(),
+			post_id: capture("post_id"),
+			page: capture("page").and_then(|p| p.parse().ok()),
+			artists,
+			characters: capture("character").into_iter().collect(),
+			series: capture("series").into_iter().collect(),
+			tag_string: capture("tags"),
+			md5: capture("md5").map(|m| m.to_lowercase()),
+			source_url: None,
+		};
+
+		result.source_url = self.source_url.as_ref().and_then(|template| {
+			let mut url = template.clone();
+			for name in self.regex.capture_names().flatten() {
+				let placeholder = format!("{{{name}}}");
+				if url.contains(&placeholder) {
+					url = url.replace(&placeholder, &capture(name)?);
+				}
+			}
+			Some(url)
+		});
+
+		Some(result)
+	}
+}
+
+/// Built-in patterns, most specific first
+static BUILTIN_PATTERNS: Lazy<Vec<RegexPattern>> = Lazy::new(|| {
+	let patterns = [
+		// 12345678_p0, 12345678_p3_master1200, 12345678_p0_square1200
+		RegexPattern::new(
+			"pixiv",
+			Some("pixiv"),
+			r"^(?P<post_id>\d{4,10})_p(?P<page>\d+)(?:_(?:master|square|custom)\d+)?$",
+			Some("https://www.pixiv.net/artworks/{post_id}"),
+		),
+		// 12345678_ugoira0
+		RegexPattern::new(
+			"pixiv_ugoira",
+			Some("pixiv"),
+			r"^(?P<post_id>\d{4,10})_ugoira(?P<page>\d+)(?:x\d+)?$",
+			Some("https://www.pixiv.net/artworks/{post_id}"),
+		),
+		// __character_series_drawn_by_artist__0123456789abcdef0123456789abcdef
+		RegexPattern::new(
+			"danbooru",
+			Some("danbooru"),
+			r"^__(?:(?P<tags>.*?)_)?drawn_by_(?P<artist>.+?)__(?P<md5>[0-9a-fA-F]{32})$",
+			Some("https://danbooru.donmai.us/posts?md5={md5}"),
+		)
+		.map(|p| p.with_artist_separator("_and_")),
+		// __character_series__md5 (no artist tag on the post)
+		RegexPattern::new(
+			"danbooru_no_artist",
+			Some("danbooru"),
+			r"^__(?P<tags>.+?)__(?P<md5>[0-9a-fA-F]{32})$",
+			Some("https://danbooru.donmai.us/posts?md5={md5}"),
+		),
+		// danbooru_1234567_md5 / danbooru 1234567
+		RegexPattern::new(
+			"danbooru_id",
+			Some("danbooru"),
+			r"^danbooru[_\- ](?P<post_id>\d+)(?:[_\- ](?P<md5>[0-9a-fA-F]{32}))?$",
+			Some("https://danbooru.donmai.us/posts/{post_id}"),
+		),
+		// gelbooru_1234567 / gelbooru 1234567 - anything
+		RegexPattern::new(
+			"gelbooru",
+			Some("gelbooru"),
+			r"^gelbooru[_\- ](?P<post_id>\d+)(?:[_\- ](?P<md5>[0-9a-fA-F]{32}))?(?:[_\- ].*)?$",
+			Some("https://gelbooru.com/index.php?page=post&s=view&id={post_id}"),
+		),
+		// artist-1234567890123456789-img1, artist_1234567890123456789_1 (gallery-dl, media downloaders)
+		RegexPattern::new(
+			"twitter",
+			Some("twitter"),
+			r"^(?P<artist>[A-Za-z0-9_]{1,15})[\-_](?P<post_id>\d{15,20})(?:[\-_](?:img|photo)?(?P<page>\d{1,2}))?(?:[\-_].*)?$",
+			Some("https://x.com/{artist}/status/{post_id}"),
+		),
+		// Bare md5, as saved by Gelbooru and most other boorus
+		RegexPattern::new("booru_md5", None, r"^(?P<md5>[0-9a-fA-F]{32})$", None),
+	];
+
+	patterns
+		.into_iter()
+		.map(|p| p.expect("built-in filename pattern must compile"))
+		.collect()
+});
+
+// ============================================================================
+// Parser
+// ============================================================================
+
+/// Runs user patterns, then built-ins, against filenames
+pub struct FilenameParser {
+	user_patterns: Vec<Box<dyn FilenamePattern>>,
+}
+
+impl FilenameParser {
+	/// Build a parser from user pattern definitions; invalid regexes are reported as errors
+	pub fn new(definitions: &[UserPatternDefinition]) -> Result<Self, String> {
+		let mut user_patterns: Vec<Box<dyn FilenamePattern>> = Vec::new();
+		for definition in definitions.iter().filter(|d| d.enabled) {
+			let pattern = RegexPattern::from_definition(definition)
+				.map_err(|e| format!("Invalid pattern '{}': {e}", definition.name))?;
+			user_patterns.push(Box::new(pattern));
+		}
+		Ok(Self { user_patterns })
+	}
+
+	/// Add a custom pattern implementation, tried after user regexes and before built-ins
+	pub fn register(&mut self, pattern: Box<dyn FilenamePattern>) {
+		self.user_patterns.push(pattern);
+	}
+
+	/// Parse a file name (with or without extension)
+	pub fn parse(&self, file_name: &str) -> Option<FilenameMatch> {
+		let stem = std::path::Path::new(file_name)
+			.file_stem()
+			.and_then(|s| s.to_str())
+			.unwrap_or(file_name);
+
+		self.user_patterns
+			.iter()
+			.map(|p| p.as_ref() as &dyn FilenamePattern)
+			.chain(BUILTIN_PATTERNS.iter().map(|p| p as &dyn FilenamePattern))
+			.find_map(|pattern| pattern.parse(stem))
+	}
+}
+
+/// Names of the built-in patterns, in the order they are tried
+pub fn builtin_pattern_names() -> Vec<String> {
+	BUILTIN_PATTERNS
+		.iter()
+		.map(|p| p.name().to_string())
+		.collect()
+}
@@ -26,11 +26,16 @@ macro_rules! ai_error {
 
 // Module declarations
 pub mod ai;
+pub mod autocomplete;
 pub mod commands;
+pub mod csv_table;
 pub mod db;
 pub mod error;
+pub mod filename_patterns;
 pub mod health_check;
+pub mod metadata;
 pub mod protocols;
+pub mod source_urls;
 
 use tauri::Manager;
 
@@ -85,11 +90,17 @@ pub fn run() {
 			let models_dir = app_data_dir.join("models");
 			std::fs::create_dir_all(&models_dir).expect("Failed to create models directory");
 
+			// Safe mode filters queries from the first one on
+			if let Err(e) = commands::safe_mode::load_safe_mode(app.app_handle()) {
+				eprintln!("Failed to load safe mode settings: {e}");
+			}
+
 			// Initialize database connection pool
 			let app_handle = app.app_handle().clone();
 			let app_handle_for_thumbnails = app.app_handle().clone();
 			tauri::async_runtime::block_on(async move {
-				let pool = db::init_pool(app_data_dir)
+				let db_path = db::resolve_db_path(&app_data_dir);
+				let pool = db::init_pool(&db_path)
 					.await
 					.expect("Failed to initialize database pool");
 
@@ -98,13 +109,17 @@ pub fn run() {
 					.await
 					.expect("Failed to run database migrations");
 
+				// Store pool in app state before spawning background work, so tasks
+				// pick up the pool a backup restore swaps in
+				app_handle.manage(db::DbPool::new(pool, db_path));
+
 				// Regenerate missing thumbnails in background
-				let pool_clone = pool.clone();
 				let app_handle_for_health = app_handle.clone();
 				tokio::spawn(async move {
+					let pool = app_handle_for_thumbnails.state::<db::DbPool>().get();
 					if let Err(e) = commands::files::regenerate_missing_thumbnails(
 						&app_handle_for_thumbnails,
-						&pool_clone,
+						&pool,
 					)
 					.await
 					{
@@ -114,19 +129,19 @@ pub fn run() {
 
 				// Run health check in background after startup
 				// No delay needed - health check runs asynchronously and won't block UI
-				let pool_for_health = pool.clone();
 				tokio::spawn(async move {
+					let pool = app_handle_for_health.state::<db::DbPool>().get();
 					let health_checker = health_check::ImageHealthChecker::new();
 					if let Err(e) = health_checker
-						.check_all_files_health(&pool_for_health, &app_handle_for_health)
+						.check_all_files_health(&pool, &app_handle_for_health)
 						.await
 					{
 						eprintln!("Failed to run startup health check: {e}");
 					}
 				});
 
-				// Store pool in app state
-				app_handle.manage(pool);
+				// Scheduled backups and retention
+				tokio::spawn(commands::backup::run_backup_scheduler(app_handle.clone()));
 			});
 
 			Ok(())
@@ -143,6 +158,7 @@ pub fn run() {
 			commands::files::test_ai_model,
 			commands::files::delete_file,
 			commands::files::delete_files_batch,
+			commands::files::merge_duplicate_files,
 			// Tag operations
 			commands::tags::get_all_tags,
 			commands::tags::get_file_tags,
@@ -163,6 +179,12 @@ pub fn run() {
 			commands::categories::reorder_categories,
 			commands::categories::assign_tag_to_category,
 			commands::categories::bulk_assign_tags_to_category,
+			// Category rule commands
+			commands::category_rules::get_category_rules,
+			commands::category_rules::create_category_rule,
+			commands::category_rules::update_category_rule,
+			commands::category_rules::delete_category_rule,
+			commands::category_rules::apply_category_rules,
 			// Favorites commands
 			commands::favorites::toggle_favorite,
 			commands::favorites::get_favorite_status,
@@ -190,16 +212,130 @@ pub fn run() {
 			commands::settings::get_ai_settings,
 			commands::settings::set_ai_settings,
 			commands::settings::is_ai_enabled,
+			// Model category mapping commands
+			commands::model_categories::get_model_category_mappings,
+			commands::model_categories::set_model_category_mappings,
+			commands::model_categories::reset_model_category_mappings,
 			// Translation commands
 			commands::settings::upload_translation_dictionary,
 			commands::settings::get_translation_status,
 			commands::settings::set_translation_language,
 			commands::settings::get_translation_language,
+			commands::settings::get_translation_fallbacks,
+			commands::settings::set_translation_fallbacks,
 			commands::settings::remove_translation_dictionary,
 			commands::settings::refresh_translations,
+			commands::settings::cancel_translation_refresh,
+			commands::settings::set_translation_override,
+			commands::settings::clear_translation_override,
+			commands::settings::list_translation_overrides,
+			commands::settings::export_translation_dictionary,
+			// Safe mode commands
+			commands::safe_mode::get_safe_mode_status,
+			commands::safe_mode::set_safe_mode_settings,
+			commands::safe_mode::set_safe_mode_pin,
+			commands::safe_mode::unlock_safe_mode,
+			commands::safe_mode::lock_safe_mode,
 			// Admin commands
 			commands::admin::clear_database,
 			commands::admin::get_database_stats,
+			// Backup commands
+			commands::backup::get_backup_settings,
+			commands::backup::set_backup_settings,
+			commands::backup::create_database_backup,
+			commands::backup::list_database_backups,
+			commands::backup::delete_database_backup,
+			commands::backup::restore_database_backup,
+			// Library archive commands
+			commands::archive::inspect_library_archive,
+			commands::archive::export_library,
+			commands::archive::import_library,
+			// Library root commands
+			commands::library::remap_path_prefix,
+			commands::library::add_library_root,
+			commands::library::list_library_roots,
+			commands::library::remove_library_root,
+			commands::library::relocate_library_root,
+			// Metadata commands
+			commands::metadata::get_metadata_settings,
+			commands::metadata::set_metadata_settings,
+			commands::metadata::get_file_metadata,
+			commands::metadata::extract_file_metadata,
+			commands::metadata::extract_missing_metadata,
+			commands::metadata::search_files_by_metadata,
+			// XMP sidecar commands
+			commands::sidecar::sync_xmp_sidecars,
+			// Filename pattern commands
+			commands::filenames::get_filename_pattern_settings,
+			commands::filenames::set_filename_pattern_settings,
+			commands::filenames::get_builtin_filename_patterns,
+			commands::filenames::test_filename_pattern,
+			commands::filenames::parse_library_filenames,
+			// Source URL commands
+			commands::sources::add_file_source,
+			commands::sources::remove_file_source,
+			commands::sources::get_file_sources,
+			commands::sources::search_files_by_source,
+			commands::sources::find_pixiv_illust,
+			// Work grouping commands
+			commands::works::create_work,
+			commands::works::get_work,
+			commands::works::get_work_for_file,
+			commands::works::get_work_members,
+			commands::works::add_files_to_work,
+			commands::works::remove_files_from_work,
+			commands::works::reorder_work,
+			commands::works::set_work_cover,
+			commands::works::rename_work,
+			commands::works::delete_work,
+			// File relation commands
+			commands::relations::link_files,
+			commands::relations::unlink_files,
+			commands::relations::get_file_relations,
+			commands::relations::traverse_relations,
+			// Search commands
+			commands::search::search_files,
+			commands::search::search_everything,
+			// Pool commands
+			commands::pools::create_pool,
+			commands::pools::get_all_pools,
+			commands::pools::get_file_pools,
+			commands::pools::update_pool,
+			commands::pools::delete_pool,
+			commands::pools::get_pool_members,
+			commands::pools::add_files_to_pool,
+			commands::pools::move_pool_members,
+			commands::pools::remove_pool_members,
+			commands::pools::dedupe_pool,
+			commands::pools::export_pool,
+			// Tag hierarchy commands
+			commands::tag_tree::move_tag_subtrees,
+			commands::tag_tree::move_tag_children,
+			commands::tag_tree::get_tag_tree,
+			commands::tag_tree::get_tag_ancestors,
+			// Tag rule commands
+			commands::tag_rules::get_tag_rules,
+			commands::tag_rules::apply_tag_implications,
+			commands::tag_rules::import_danbooru_tags,
+			// Tag wiki commands
+			commands::wiki::get_tag_wiki,
+			commands::wiki::save_tag_wiki,
+			commands::wiki::set_tag_wiki_examples,
+			commands::wiki::delete_tag_wiki,
+			commands::wiki::import_danbooru_wiki,
+			// Region note commands
+			commands::notes::create_note,
+			commands::notes::update_note,
+			commands::notes::delete_note,
+			commands::notes::get_file_notes,
+			commands::notes::search_notes,
+			commands::notes::export_file_notes,
+			// Rating and view statistics commands
+			commands::stats::set_file_rating,
+			commands::stats::record_file_view,
+			commands::stats::get_file_stats,
+			commands::stats::get_files_by_stats,
+			commands::stats::rediscover_files,
 		])
 		.run(tauri::generate_context!())
 		.expect("error while running tauri application");
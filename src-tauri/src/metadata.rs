@@ -0,0 +1,677 @@
+// Embedded image metadata: EXIF, XMP and PNG text chunks
+//
+// Extraction never fails an import; unreadable metadata just yields fewer entries.
+
+use crate::error::AppError;
+use image::{DynamicImage, ImageDecoder, ImageReader};
+use serde::{Deserialize, Serialize};
+use std::fs::File;
+use std::io::{BufReader, Read, Seek, SeekFrom};
+use std::path::Path;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+/// One metadata field as stored in the FileMetadata table
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct MetadataEntry {
+	/// Where the value came from: "exif", "xmp", "png" or "sd" (parsed Stable Diffusion parameters)
+	pub source: String,
+	pub key: String,
+	pub value: String,
+}
+
+impl MetadataEntry {
+	fn new(source: &str, key: &str, value: impl Into<String>) -> Self {
+		Self {
+			source: source.to_string(),
+			key: key.to_string(),
+			value: value.into(),
+		}
+	}
+}
+
+/// Sources and keys with a fixed meaning across formats
+pub mod keys {
+	pub const SOURCE_EXIF: &str = "exif";
+	pub const SOURCE_XMP: &str = "xmp";
+	pub const SOURCE_PNG: &str = "png";
+	pub const SOURCE_SD: &str = "sd";
+	/// Written by the filename parser, not by `extract_metadata`
+	pub const SOURCE_FILENAME: &str = "filename";
+
+	pub const DATE_TAKEN: &str = "DateTimeOriginal";
+	pub const CAMERA_MAKE: &str = "Make";
+	pub const CAMERA_MODEL: &str = "Model";
+	pub const ORIENTATION: &str = "Orientation";
+	pub const KEYWORD: &str = "subject";
+	pub const RATING: &str = "Rating";
+	pub const PROMPT: &str = "prompt";
+	pub const NEGATIVE_PROMPT: &str = "negative_prompt";
+	pub const GENERATION_SETTINGS: &str = "settings";
+}
+
+/// EXIF fields worth keeping, stored under their EXIF tag name
+const EXIF_FIELDS: &[exif::Tag] = &[
+	exif::Tag::DateTimeOriginal,
+	exif::Tag::DateTime,
+	exif::Tag::Make,
+	exif::Tag::Model,
+	exif::Tag::LensModel,
+	exif::Tag::Software,
+	exif::Tag::Artist,
+	exif::Tag::Copyright,
+	exif::Tag::ImageDescription,
+	exif::Tag::Orientation,
+];
+
+/// Largest XMP packet read from a container; anything bigger is not a real packet
+const MAX_XMP_PACKET_BYTES: u64 = 4 * 1024 * 1024;
+
+/// Namespace header that starts a JPEG APP1 segment carrying XMP
+const JPEG_XMP_HEADER: &[u8] = b"http://ns.adobe.com/xap/1.0/\0";
+
+/// TIFF tag holding the XMP packet
+const TIFF_XMP_TAG: u16 = 700;
+
+// ============================================================================
+// Orientation
+// ============================================================================
+
+/// Open an image and rotate/flip it according to its EXIF orientation
+pub fn open_image_oriented(path: &Path) -> Result<DynamicImage, AppError> {
+	let mut decoder = ImageReader::open(path)?
+		.with_guessed_format()?
+		.into_decoder()
+		.map_err(|e| AppError::Custom(format!("Failed to load image: {e}")))?;
+	let orientation = decoder
+		.orientation()
+		.unwrap_or(image::metadata::Orientation::NoTransforms);
+
+	let mut image = DynamicImage::from_decoder(decoder)
+		.map_err(|e| AppError::Custom(format!("Failed to load image: {e}")))?;
+	image.apply_orientation(orientation);
+
+	Ok(image)
+}
+
+/// Image dimensions as displayed, i.e. swapped for 90/270 degree EXIF orientations
+pub fn oriented_dimensions(path: &Path) -> Result<(u32, u32), AppError> {
+	let mut decoder = ImageReader::open(path)?
+		.with_guessed_format()?
+		.into_decoder()
+		.map_err(|e| AppError::Custom(format!("Failed to get image dimensions: {e}")))?;
+	let (width, height) = decoder.dimensions();
+
+	use image::metadata::Orientation;
+	match decoder.orientation() {
+		Ok(
+			Orientation::Rotate90
+			| Orientation::Rotate270
+			| Orientation::Rotate90FlipH
+			| Orientation::Rotate270FlipH,
+		) => Ok((height, width)),
+		_ => Ok((width, height)),
+	}
+}
+
+// ============================================================================
+// Extraction
+// ============================================================================
+
+/// Extract all supported metadata from an image file
+pub fn extract_metadata(path: &Path) -> Vec<MetadataEntry> {
+	let mut entries = Vec::new();
+	let mut xmp_packets = Vec::new();
+
+	if let Ok(exif_entries) = read_exif(path) {
+		entries.extend(exif_entries);
+	}
+
+	let is_png = path
+		.extension()
+		.and_then(|e| e.to_str())
+		.is_some_and(|e| e.eq_ignore_ascii_case("png"));
+	if is_png {
+		if let Ok(chunks) = read_png_text(path) {
+			for (keyword, text) in chunks {
+				if keyword == "XML:com.adobe.xmp" {
+					xmp_packets.push(text);
+					continue;
+				}
+				if keyword == "parameters" {
+					entries.extend(parse_sd_parameters(&text));
+				}
+				entries.push(MetadataEntry::new(keys::SOURCE_PNG, &keyword, text));
+			}
+		}
+	}
+
+	if xmp_packets.is_empty() {
+		if let Some(packet) = read_xmp_segment(path) {
+			xmp_packets.push(packet);
+		}
+	}
+	for packet in xmp_packets {
+		entries.extend(parse_xmp(&packet));
+	}
+
+	entries
+}
+
+fn read_exif(path: &Path) -> Result<Vec<MetadataEntry>, AppError> {
+	let mut decoder = ImageReader::open(path)?
+		.with_guessed_format()?
+		.into_decoder()
+		.map_err(|e| AppError::Custom(format!("Failed to open image: {e}")))?;
+	let Some(raw) = decoder.exif_metadata().ok().flatten() else {
+		return Ok(Vec::new());
+	};
+
+	let exif = exif::Reader::new()
+		.read_raw(raw)
+		.map_err(|e| AppError::Custom(format!("Failed to parse EXIF: {e}")))?;
+
+	let mut entries = Vec::new();
+	for tag in EXIF_FIELDS {
+		let Some(field) = exif.get_field(*tag, exif::In::PRIMARY) else {
+			continue;
+		};
+		let value = match &field.value {
+			exif::Value::Ascii(parts) => parts
+				.iter()
+				.map(|p| {
+					String::from_utf8_lossy(p)
+						.trim_end_matches('\0')
+						.trim()
+						.to_string()
+				})
+				.filter(|p| !p.is_empty())
+				.collect::<Vec<_>>()
+				.join(" "),
+			other => match other.get_uint(0) {
+				Some(number) => number.to_string(),
+				None => field.display_value().to_string(),
+			},
+		};
+		if !value.is_empty() {
+			entries.push(MetadataEntry::new(
+				keys::SOURCE_EXIF,
+				&tag.to_string(),
+				value,
+			));
+		}
+	}
+
+	Ok(entries)
+}
+
+/// Read tEXt, zTXt and iTXt chunks that precede the image data
+fn read_png_text(path: &Path) -> Result<Vec<(String, String)>, AppError> {
+	let decoder = png::Decoder::new(BufReader::new(File::open(path)?));
+	let reader = decoder
+		.read_info()
+		.map_err(|e| AppError::Custom(format!("Failed to read PNG: {e}")))?;
+	let info = reader.info();
+
+	let mut chunks = Vec::new();
+	for chunk in &info.uncompressed_latin1_text {
+		chunks.push((chunk.keyword.clone(), chunk.text.clone()));
+	}
+	for chunk in &info.compressed_latin1_text {
+		if let Ok(text) = chunk.get_text() {
+			chunks.push((chunk.keyword.clone(), text));
+		}
+	}
+	for chunk in &info.utf8_text {
+		if let Ok(text) = chunk.get_text() {
+			chunks.push((chunk.keyword.clone(), text));
+		}
+	}
+
+	Ok(chunks)
+}
+
+/// Read the XMP packet from a JPEG APP1 segment, WebP `XMP ` chunk or TIFF tag 700
+///
+/// Only segment headers are walked, so image data is never read.
+fn read_xmp_segment(path: &Path) -> Option<String> {
+	let mut reader = BufReader::new(File::open(path).ok()?);
+	let mut magic = [0u8; 12];
+	reader.read_exact(&mut magic).ok()?;
+	reader.seek(SeekFrom::Start(0)).ok()?;
+
+	let packet = if magic.starts_with(&[0xFF, 0xD8]) {
+		read_jpeg_xmp(&mut reader)?
+	} else if magic.starts_with(b"RIFF") && &magic[8..12] == b"WEBP" {
+		read_webp_xmp(&mut reader)?
+	} else if magic.starts_with(b"II*\0") || magic.starts_with(b"MM\0*") {
+		read_tiff_xmp(&mut reader, magic[0] == b'I')?
+	} else {
+		return None;
+	};
+
+	Some(String::from_utf8_lossy(&packet).into_owned())
+}
+
+fn read_jpeg_xmp<R: Read + Seek>(reader: &mut R) -> Option<Vec<u8>> {
+	reader.seek(SeekFrom::Start(2)).ok()?;
+	loop {
+		let mut marker = [0u8; 2];
+		reader.read_exact(&mut marker).ok()?;
+		if marker[0] != 0xFF {
+			return None;
+		}
+		match marker[1] {
+			// Fill byte before the real marker
+			0xFF => {
+				reader.seek(SeekFrom::Current(-1)).ok()?;
+				continue;
+			}
+			// Standalone markers carry no length
+			0x01 | 0xD0..=0xD7 => continue,
+			// Start of scan or end of image: metadata segments are over
+			0xD9 | 0xDA => return None,
+			_ => {}
+		}
+
+		let mut length = [0u8; 2];
+		reader.read_exact(&mut length).ok()?;
+		let payload_len = u16::from_be_bytes(length).checked_sub(2)? as u64;
+
+		if marker[1] == 0xE1 && payload_len > JPEG_XMP_HEADER.len() as u64 {
+			let payload = read_bounded(reader, payload_len)?;
+			if let Some(packet) = payload.strip_prefix(JPEG_XMP_HEADER) {
+				return Some(packet.to_vec());
+			}
+		} else {
+			reader.seek(SeekFrom::Current(payload_len as i64)).ok()?;
+		}
+	}
+}
+
+fn read_webp_xmp<R: Read + Seek>(reader: &mut R) -> Option<Vec<u8>> {
+	reader.seek(SeekFrom::Start(12)).ok()?;
+	loop {
+		let mut header = [0u8; 8];
+		reader.read_exact(&mut header).ok()?;
+		let size = u32::from_le_bytes(header[4..8].try_into().ok()?) as u64;
+		if &header[..4] == b"XMP " {
+			return read_bounded(reader, size);
+		}
+		// Chunks are padded to an even length
+		reader
+			.seek(SeekFrom::Current((size + size % 2) as i64))
+			.ok()?;
+	}
+}
+
+fn read_tiff_xmp<R: Read + Seek>(reader: &mut R, little_endian: bool) -> Option<Vec<u8>> {
+	let u16_at = |bytes: &[u8]| {
+		let bytes = [bytes[0], bytes[1]];
+		if little_endian {
+			u16::from_le_bytes(bytes)
+		} else {
+			u16::from_be_bytes(bytes)
+		}
+	};
+	let u32_at = |bytes: &[u8]| {
+		let bytes = [bytes[0], bytes[1], bytes[2], bytes[3]];
+		if little_endian {
+			u32::from_le_bytes(bytes)
+		} else {
+			u32::from_be_bytes(bytes)
+		}
+	};
+
+	let mut header = [0u8; 8];
+	reader.read_exact(&mut header).ok()?;
+	reader
+		.seek(SeekFrom::Start(u32_at(&header[4..8]) as u64))
+		.ok()?;
+
+	let mut count = [0u8; 2];
+	reader.read_exact(&mut count).ok()?;
+	for _ in 0..u16_at(&count) {
+		let mut entry = [0u8; 12];
+		reader.read_exact(&mut entry).ok()?;
+		if u16_at(&entry[0..2]) != TIFF_XMP_TAG {
+			continue;
+		}
+		// Stored as BYTE or UNDEFINED, so the count is the length in bytes
+		let length = u32_at(&entry[4..8]) as u64;
+		if length <= 4 {
+			return Some(entry[8..8 + length as usize].to_vec());
+		}
+		reader
+			.seek(SeekFrom::Start(u32_at(&entry[8..12]) as u64))
+			.ok()?;
+		return read_bounded(reader, length);
+	}
+
+	None
+}
+
+/// Read exactly `length` bytes, refusing lengths no XMP packet reaches
+fn read_bounded<R: Read>(reader: &mut R, length: u64) -> Option<Vec<u8>> {
+	if length > MAX_XMP_PACKET_BYTES {
+		return None;
+	}
+	let mut bytes = vec![0u8; length as usize];
+	reader.read_exact(&mut bytes).ok()?;
+	Some(bytes)
+}
+
+// ============================================================================
+// XMP
+// ============================================================================
+
+/// Extract `dc:subject` keywords and `xmp:Rating` from an XMP packet
+pub fn parse_xmp(xmp: &str) -> Vec<MetadataEntry> {
+	let mut entries: Vec<MetadataEntry> = parse_xmp_keywords(xmp)
+		.into_iter()
+		.map(|keyword| MetadataEntry::new(keys::SOURCE_XMP, keys::KEYWORD, keyword))
+		.collect();
+
+	if let Some(rating) = parse_xmp_rating(xmp) {
+		entries.push(MetadataEntry::new(
+			keys::SOURCE_XMP,
+			keys::RATING,
+			rating.to_string(),
+		));
+	}
+
+	entries
+}
+
+/// Keywords from the `dc:subject` bag
+pub fn parse_xmp_keywords(xmp: &str) -> Vec<String> {
+	let Some(start) = xmp.find("<dc:subject") else {
+		return Vec::new();
+	};
+	let Some(length) = xmp[start..].find("</dc:subject>") else {
+		return Vec::new();
+	};
+	let subject = &xmp[start..start + length];
+
+	let mut keywords = Vec::new();
+	let mut rest = subject;
+	while let Some(open) = rest.find("<rdf:li") {
+		rest = &rest[open..];
+		let Some(content_start) = rest.find('>') else {
+			break;
+		};
+		let Some(content_end) = rest.find("</rdf:li>") else {
+			break;
+		};
+		if content_start < content_end {
+			let keyword = unescape_xml(rest[content_start + 1..content_end].trim());
+			if !keyword.is_empty() {
+				keywords.push(keyword);
+			}
+		}
+		rest = &rest[content_end + "</rdf:li>".len()..];
+	}
+
+	keywords
+}
+
+/// `xmp:Rating`, written either as an attribute or an element
+pub fn parse_xmp_rating(xmp: &str) -> Option<i64> {
+	parse_xmp_property(xmp, "xmp:Rating")?.parse().ok()
+}
+
+/// `xmp:Label` (e.g. a color label, or "Favorite")
+pub fn parse_xmp_label(xmp: &str) -> Option<String> {
+	parse_xmp_property(xmp, "xmp:Label").filter(|label| !label.is_empty())
+}
+
+/// Value of a simple property, written either as `name="value"` or `<name>value</name>`
+fn parse_xmp_property(xmp: &str, name: &str) -> Option<String> {
+	let attribute = format!("{name}=\"");
+	if let Some(start) = xmp.find(&attribute) {
+		let value = &xmp[start + attribute.len()..];
+		return Some(unescape_xml(value[..value.find('"')?].trim()));
+	}
+	let element = format!("<{name}>");
+	if let Some(start) = xmp.find(&element) {
+		let value = &xmp[start + element.len()..];
+		return Some(unescape_xml(value[..value.find('<')?].trim()));
+	}
+	None
+}
+
+pub fn unescape_xml(value: &str) -> String {
+	value
+		.replace("&lt;", "<")
+		.replace("&gt;", ">")
+		.replace("&quot;", "\"")
+		.replace("&apos;", "'")
+		.replace("&amp;", "&")
+}
+
+pub fn escape_xml(value: &str) -> String {
+	value
+		.replace('&', "&amp;")
+		.replace('<', "&lt;")
+		.replace('>', "&gt;")
+		.replace('"', "&quot;")
+		.replace('\'', "&apos;")
+}
+
+// ============================================================================
+// XMP Writing
+// ============================================================================
+
+const NS_DC: &str = "http://purl.org/dc/elements/1.1/";
+const NS_XMP: &str = "http://ns.adobe.com/xap/1.0/";
+
+/// Label written for favorites; other labels (e.g. colors from digiKam) are left alone
+pub const FAVORITE_LABEL: &str = "Favorite";
+
+/// The fields we own in a sidecar
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SidecarFields {
+	pub keywords: Vec<String>,
+	pub rating: Option<i64>,
+	pub favorite: bool,
+}
+
+/// Read our fields from a sidecar packet
+pub fn read_sidecar_fields(xmp: &str) -> SidecarFields {
+	SidecarFields {
+		keywords: parse_xmp_keywords(xmp),
+		rating: parse_xmp_rating(xmp),
+		favorite: parse_xmp_label(xmp).is_some_and(|l| l.eq_ignore_ascii_case(FAVORITE_LABEL)),
+	}
+}
+
+/// A minimal XMP packet for a new sidecar
+pub fn empty_xmp_packet() -> String {
+	format!(
+		"<?xpacket begin=\"\u{feff}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n\
+		<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">\n\
+		\x20<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">\n\
+		\x20 <rdf:Description rdf:about=\"\" xmlns:dc=\"{NS_DC}\" xmlns:xmp=\"{NS_XMP}\">\n\
+		\x20 </rdf:Description>\n\
+		\x20</rdf:RDF>\n\
+		</x:xmpmeta>\n\
+		<?xpacket end=\"w\"?>\n"
+	)
+}
+
+/// Write our fields into an existing packet, keeping everything else (e.g. darktable history)
+///
+/// `dc:subject` and `xmp:Rating` are replaced (a -1 "rejected" rating is kept while the library
+/// has none), and `xmp:Label` is set to `FAVORITE_LABEL` or cleared only if it currently holds
+/// that label.
+/// Returns None if the packet has no `rdf:Description` to write into.
+pub fn update_xmp_packet(xmp: &str, fields: &SidecarFields) -> Option<String> {
+	let mut xmp = remove_xmp_element(xmp, "dc:subject");
+
+	let mut properties = String::new();
+	if !fields.keywords.is_empty() {
+		properties.push_str("\n   <dc:subject>\n    <rdf:Bag>\n");
+		for keyword in &fields.keywords {
+			properties.push_str(&format!("     <rdf:li>{}</rdf:li>\n", escape_xml(keyword)));
+		}
+		properties.push_str("    </rdf:Bag>\n   </dc:subject>");
+	}
+
+	// A cleared library rating clears the sidecar's too, except "rejected" (-1), which has no
+	// star equivalent in the library
+	let rejected = parse_xmp_rating(&xmp) == Some(-1);
+	if fields.rating.is_some() || !rejected {
+		xmp = remove_xmp_property(&xmp, "xmp:Rating");
+	}
+	if let Some(rating) = fields.rating {
+		properties.push_str(&format!("\n   <xmp:Rating>{rating}</xmp:Rating>"));
+	}
+
+	let has_favorite_label =
+		parse_xmp_label(&xmp).is_some_and(|l| l.eq_ignore_ascii_case(FAVORITE_LABEL));
+	if fields.favorite {
+		xmp = remove_xmp_property(&xmp, "xmp:Label");
+		properties.push_str(&format!("\n   <xmp:Label>{FAVORITE_LABEL}</xmp:Label>"));
+	} else if has_favorite_label {
+		xmp = remove_xmp_property(&xmp, "xmp:Label");
+	}
+
+	// Insert right after the opening tag of the first rdf:Description
+	let start = xmp.find("<rdf:Description")?;
+	let tag_end = start + xmp[start..].find('>')?;
+	let mut open_tag = xmp[start..tag_end].to_string();
+	let self_closing = open_tag.ends_with('/');
+	if self_closing {
+		open_tag.pop();
+	}
+	for (prefix, namespace) in [("dc", NS_DC), ("xmp", NS_XMP)] {
+		if !open_tag.contains(&format!("xmlns:{prefix}=")) {
+			open_tag.push_str(&format!(" xmlns:{prefix}=\"{namespace}\""));
+		}
+	}
+
+	let closing = if self_closing {
+		"\n  </rdf:Description>"
+	} else {
+		""
+	};
+
+	Some(format!(
+		"{}{open_tag}>{properties}{closing}{}",
+		&xmp[..start],
+		&xmp[tag_end + 1..]
+	))
+}
+
+/// Remove every `<name ...>...</name>` / `<name/>` element
+fn remove_xmp_element(xmp: &str, name: &str) -> String {
+	let open = format!("<{name}");
+	let close = format!("</{name}>");
+	let mut result = xmp.to_string();
+
+	let mut search_from = 0;
+	while let Some(offset) = result[search_from..].find(&open) {
+		let start = search_from + offset;
+		// Make sure we matched the whole name, not a prefix of a longer one
+		let next = result[start + open.len()..].chars().next();
+		if !matches!(next, Some('>' | '/' | ' ' | '\n' | '\r' | '\t')) {
+			search_from = start + open.len();
+			continue;
+		}
+		let Some(tag_end) = result[start..].find('>').map(|i| start + i) else {
+			break;
+		};
+		let end = if result[..tag_end].ends_with('/') {
+			tag_end + 1
+		} else {
+			match result[tag_end..].find(&close) {
+				Some(i) => tag_end + i + close.len(),
+				None => break,
+			}
+		};
+		// Drop the indentation in front of the element as well
+		let line_start = result[..start].trim_end().len();
+		result.replace_range(line_start..end, "");
+		search_from = line_start;
+	}
+
+	result
+}
+
+/// Remove a simple property in both attribute (` name="..."`) and element form
+fn remove_xmp_property(xmp: &str, name: &str) -> String {
+	let mut result = remove_xmp_element(xmp, name);
+	let attribute = format!("{name}=\"");
+
+	while let Some(start) = result.find(&attribute) {
+		let Some(value_end) = result[start + attribute.len()..].find('"') else {
+			break;
+		};
+		let end = start + attribute.len() + value_end + 1;
+		let start = result[..start].trim_end().len();
+		result.replace_range(start..end, "");
+	}
+
+	result
+}
+
+// ============================================================================
+// Stable Diffusion
+// ============================================================================
+
+/// Split an A1111-style `parameters` chunk into prompt, negative prompt and settings
+///
+/// ```text
+/// masterpiece, 1girl, ...
+/// Negative prompt: lowres, ...
+/// Steps: 28, Sampler: Euler a, CFG scale: 7, Seed: 1234, ...
+/// ```
+pub fn parse_sd_parameters(text: &str) -> Vec<MetadataEntry> {
+	let mut lines: Vec<&str> = text.lines().collect();
+
+	let settings = match lines.last() {
+		Some(last) if last.trim_start().starts_with("Steps:") => lines.pop(),
+		_ => None,
+	};
+
+	let negative_index = lines
+		.iter()
+		.position(|line| line.trim_start().starts_with("Negative prompt:"));
+	let (prompt_lines, negative_lines) = match negative_index {
+		Some(index) => lines.split_at(index),
+		None => (&lines[..], &[][..]),
+	};
+
+	let mut entries = Vec::new();
+
+	let prompt = prompt_lines.join("\n").trim().to_string();
+	if !prompt.is_empty() {
+		entries.push(MetadataEntry::new(keys::SOURCE_SD, keys::PROMPT, prompt));
+	}
+
+	let negative = negative_lines
+		.join("\n")
+		.trim_start()
+		.trim_start_matches("Negative prompt:")
+		.trim()
+		.to_string();
+	if !negative.is_empty() {
+		entries.push(MetadataEntry::new(
+			keys::SOURCE_SD,
+			keys::NEGATIVE_PROMPT,
+			negative,
+		));
+	}
+
+	if let Some(settings) = settings {
+		entries.push(MetadataEntry::new(
+			keys::SOURCE_SD,
+			keys::GENERATION_SETTINGS,
+			settings.trim(),
+		));
+	}
+
+	entries
+}
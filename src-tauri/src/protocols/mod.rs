@@ -19,6 +19,7 @@ pub fn register_protocols(app: &tauri::App) -> Result<(), Box<dyn std::error::Er
 /// URL format:
 /// - app-asset://localhost/thumbnails/{hash}.webp
 /// - app-asset://localhost/originals/{hash}
+/// - app-asset://localhost/works/{work_id}/{index} (0-based page, or `cover`)
 pub async fn handle_asset_protocol(
 	app: &AppHandle,
 	request: &Request<Vec<u8>>,
@@ -31,7 +32,7 @@ pub async fn handle_asset_protocol(
 
 	// Handle thumbnails
 	if path.starts_with("/thumbnails/") {
-		return handle_thumbnail_request(app, path);
+		return handle_thumbnail_request(app, path).await;
 	}
 
 	// Handle original images
@@ -39,6 +40,11 @@ pub async fn handle_asset_protocol(
 		return handle_original_request(app, path).await;
 	}
 
+	// Handle pages of a work
+	if path.starts_with("/works/") {
+		return handle_work_page_request(app, path).await;
+	}
+
 	eprintln!("❌ Path doesn't match any known pattern");
 	Ok(Response::builder()
 		.status(StatusCode::NOT_FOUND)
@@ -46,7 +52,7 @@ pub async fn handle_asset_protocol(
 }
 
 /// Handle thumbnail requests
-fn handle_thumbnail_request(
+async fn handle_thumbnail_request(
 	app: &AppHandle,
 	path: &str,
 ) -> Result<Response<Vec<u8>>, Box<dyn std::error::Error>> {
@@ -71,6 +77,15 @@ fn handle_thumbnail_request(
 			.body(b"Invalid hash format".to_vec())?);
 	}
 
+	// Files hidden by safe mode answer like unknown ones
+	let pool = app.state::<crate::db::DbPool>().get();
+	if !crate::commands::safe_mode::is_file_visible(&pool, hash_part).await? {
+		eprintln!("❌ File hidden or not in database");
+		return Ok(Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(b"Thumbnail not found".to_vec())?);
+	}
+
 	// Get thumbnail path
 	let app_data_dir = app.path().app_data_dir()?;
 	let thumbnail_path = app_data_dir.join("thumbnails").join(file_name);
@@ -115,17 +130,72 @@ async fn handle_original_request(
 			.body(b"Invalid hash format".to_vec())?);
 	}
 
+	serve_original(app, file_hash).await
+}
+
+/// Handle work page requests, serving the original of the page at an index
+async fn handle_work_page_request(
+	app: &AppHandle,
+	path: &str,
+) -> Result<Response<Vec<u8>>, Box<dyn std::error::Error>> {
+	let mut parts = path.trim_start_matches("/works/").split('/');
+	let work_id = parts.next().and_then(|id| id.parse::<i64>().ok());
+	let page = parts.next();
+	eprintln!("📄 Requesting work {work_id:?} page {page:?}");
+
+	let (Some(work_id), Some(page), None) = (work_id, page, parts.next()) else {
+		eprintln!("❌ Invalid work page path");
+		return Ok(Response::builder()
+			.status(StatusCode::BAD_REQUEST)
+			.body(b"Invalid work page path".to_vec())?);
+	};
+
+	let pool = app.state::<crate::db::DbPool>().get();
+	let file_hash = match page {
+		"cover" => crate::commands::works::work_cover(&pool, work_id).await?,
+		index => match index.parse::<i64>() {
+			Ok(index) if index >= 0 => {
+				crate::commands::works::work_member_at(&pool, work_id, index).await?
+			}
+			_ => {
+				eprintln!("❌ Invalid page index");
+				return Ok(Response::builder()
+					.status(StatusCode::BAD_REQUEST)
+					.body(b"Invalid page index".to_vec())?);
+			}
+		},
+	};
+
+	let Some(file_hash) = file_hash else {
+		eprintln!("❌ Work page not found");
+		return Ok(Response::builder()
+			.status(StatusCode::NOT_FOUND)
+			.body(b"Work page not found".to_vec())?);
+	};
+
+	serve_original(app, &file_hash).await
+}
+
+/// Read an original image from disk by hash
+async fn serve_original(
+	app: &AppHandle,
+	file_hash: &str,
+) -> Result<Response<Vec<u8>>, Box<dyn std::error::Error>> {
 	// Get pool from app state
-	let pool = app.state::<sqlx::SqlitePool>();
+	let pool = app.state::<crate::db::DbPool>().get();
 
-	let file_record: Option<(String,)> =
-		sqlx::query_as("SELECT original_path FROM Files WHERE file_hash = ?")
-			.bind(file_hash)
-			.fetch_optional(pool.inner())
-			.await?;
+	// Files hidden by safe mode answer like unknown ones
+	let query = format!(
+		"SELECT original_path FROM Files WHERE file_hash = ? AND {}",
+		crate::commands::safe_mode::visible_files_condition("file_hash")
+	);
+	let file_record: Option<(String,)> = sqlx::query_as(&query)
+		.bind(file_hash)
+		.fetch_optional(&pool)
+		.await?;
 
 	let Some((original_path,)) = file_record else {
-		eprintln!("❌ File not found in database");
+		eprintln!("❌ File hidden or not in database");
 		return Ok(Response::builder()
 			.status(StatusCode::NOT_FOUND)
 			.body(b"File not found".to_vec())?);
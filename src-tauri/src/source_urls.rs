@@ -0,0 +1,127 @@
+// Source URL parsing: domain, site and post ID for the sites our files usually come from
+
+use once_cell::sync::Lazy;
+use regex::Regex;
+use serde::Serialize;
+use url::Url;
+
+// ============================================================================
+// Types
+// ============================================================================
+
+#[derive(Debug, Serialize, Clone, Default, PartialEq, Eq)]
+pub struct ParsedSource {
+	/// Normalized URL (parsed and re-serialized)
+	pub url: String,
+	/// Lowercase host without "www."
+	pub domain: String,
+	pub site: Option<String>,
+	pub post_id: Option<String>,
+	pub page: Option<u32>,
+}
+
+struct SiteRule {
+	site: &'static str,
+	/// Domains this rule applies to (matched as suffix, so subdomains count)
+	domains: &'static [&'static str],
+	/// Matched against path + query; captures `post_id` and optionally `page`
+	regex: Regex,
+}
+
+static SITE_RULES: Lazy<Vec<SiteRule>> = Lazy::new(|| {
+	let rule = |site, domains, regex: &str| SiteRule {
+		site,
+		domains,
+		regex: Regex::new(regex).expect("built-in source pattern must compile"),
+	};
+
+	vec![
+		// /artworks/123, /en/artworks/123, member_illust.php?illust_id=123
+		rule(
+			"pixiv",
+			&["pixiv.net"],
+			r"(?:/artworks/|[?&]illust_id=)(?P<post_id>\d+)",
+		),
+		// i.pximg.net/img-original/img/2024/01/01/00/00/00/123_p1.png
+		rule("pixiv", &["pximg.net"], r"/(?P<post_id>\d+)_p(?P<page>\d+)"),
+		rule("danbooru", &["donmai.us"], r"/posts/(?P<post_id>\d+)"),
+		rule("gelbooru", &["gelbooru.com"], r"[?&]id=(?P<post_id>\d+)"),
+		rule("safebooru", &["safebooru.org"], r"[?&]id=(?P<post_id>\d+)"),
+		rule("yandere", &["yande.re"], r"/post/show/(?P<post_id>\d+)"),
+		rule(
+			"konachan",
+			&["konachan.com", "konachan.net"],
+			r"/post/show/(?P<post_id>\d+)",
+		),
+		// /user/status/123/photo/2
+		rule(
+			"twitter",
+			&["twitter.com", "x.com"],
+			r"/status/(?P<post_id>\d+)(?:/photo/(?P<page>\d+))?",
+		),
+		rule("fanbox", &["fanbox.cc"], r"/posts/(?P<post_id>\d+)"),
+	]
+});
+
+// ============================================================================
+// Parsing
+// ============================================================================
+
+/// Parse a source URL; only http(s) URLs are accepted
+pub fn parse_source_url(url: &str) -> Result<ParsedSource, String> {
+	let parsed = Url::parse(url.trim()).map_err(|e| format!("Invalid URL '{url}': {e}"))?;
+	if !matches!(parsed.scheme(), "http" | "https") {
+		return Err(format!("Unsupported URL scheme: {}", parsed.scheme()));
+	}
+
+	let host = parsed
+		.host_str()
+		.ok_or_else(|| format!("URL has no host: {url}"))?
+		.to_lowercase();
+	let domain = host.strip_prefix("www.").unwrap_or(&host).to_string();
+
+	let path_and_query = match parsed.query() {
+		Some(query) => format!("{}?{query}", parsed.path()),
+		None => parsed.path().to_string(),
+	};
+
+	let mut result = ParsedSource {
+		url: parsed.to_string(),
+		domain,
+		..Default::default()
+	};
+
+	let matching_rule = SITE_RULES.iter().find_map(|rule| {
+		let on_domain = rule
+			.domains
+			.iter()
+			.any(|d| result.domain == *d || result.domain.ends_with(&format!(".{d}")));
+		if !on_domain {
+			return None;
+		}
+		rule.regex
+			.captures(&path_and_query)
+			.map(|captures| (rule.site, captures))
+	});
+
+	if let Some((site, captures)) = matching_rule {
+		result.site = Some(site.to_string());
+		result.post_id = captures.name("post_id").map(|m| m.as_str().to_string());
+		result.page = captures.name("page").and_then(|m| m.as_str().parse().ok());
+	}
+
+	Ok(result)
+}
+
+/// Normalize a domain typed by the user ("https://www.Pixiv.net/" -> "pixiv.net")
+pub fn normalize_domain(domain: &str) -> String {
+	let domain = domain.trim().to_lowercase();
+	let domain = domain
+		.split("://")
+		.last()
+		.unwrap_or(&domain)
+		.split('/')
+		.next()
+		.unwrap_or_default();
+	domain.strip_prefix("www.").unwrap_or(domain).to_string()
+}
@@ -13,6 +13,7 @@ import { Button } from "@/components/ui/button";
 import { Dialog, DialogContent, DialogHeader, DialogTitle } from "@/components/ui/dialog";
 import { ScrollArea } from "@/components/ui/scroll-area";
 import { useCategories } from "@/lib/hooks/useCategories";
+import { useRecordFileView } from "@/lib/hooks/useFiles";
 import { useAddTag, useRemoveTag, useRunAITagging } from "@/lib/hooks/useTagManagement";
 import { useFileTags } from "@/lib/hooks/useTags";
 import { getTagCategoryColor } from "@/lib/utils";
@@ -38,6 +39,7 @@ export function ImageViewer({ file, allFiles, onClose, onNavigate }: ImageViewer
 	const addTagMutation = useAddTag();
 	const removeTagMutation = useRemoveTag();
 	const aiTagMutation = useRunAITagging();
+	const { mutate: recordView } = useRecordFileView();
 	const [newTags, setNewTags] = useState<string[]>([]);
 	const transformRef = useRef<ReactZoomPanPinchRef | null>(null);
 	const imageContainerRef = useRef<HTMLDivElement>(null);
@@ -94,6 +96,12 @@ export function ImageViewer({ file, allFiles, onClose, onNavigate }: ImageViewer
 		setImageDimensions(null);
 	}, [file.file_hash]);
 
+	// Views are counted here, not when originals are served, so thumbnail fallbacks and
+	// reloads don't add views
+	useEffect(() => {
+		recordView(file.file_hash);
+	}, [file.file_hash, recordView]);
+
 	// Apply initial scale after image dimensions are set and container is ready
 	useEffect(() => {
 		if (imageDimensions && transformRef.current && imageContainerRef.current && !imageLoading) {
@@ -128,6 +128,16 @@ export function LabelMapUploadArea() {
 							}
 						>
 							{uploadMutation.data.message}
+							{uploadMutation.data.diagnostics &&
+								uploadMutation.data.diagnostics.length > 0 && (
+									<ul className="mt-2 text-xs space-y-1 max-h-32 overflow-y-auto">
+										{uploadMutation.data.diagnostics.map((d) => (
+											<li key={d.line}>
+												Line {d.line}: {d.message}
+											</li>
+										))}
+									</ul>
+								)}
 						</AlertDescription>
 					</Alert>
 				)}
@@ -140,13 +140,21 @@ export function TranslationDictionaryUploadArea() {
 									{uploadMutation.data.available_languages.length > 0 && (
 										<p>
 											可用语言：
-											{uploadMutation.data.available_languages
-												.map((l) => l.toUpperCase())
-												.join(", ")}
+											{uploadMutation.data.available_languages.join(", ")}
 										</p>
 									)}
 								</div>
 							)}
+							{uploadMutation.data.diagnostics &&
+								uploadMutation.data.diagnostics.length > 0 && (
+									<ul className="mt-2 text-xs space-y-1 max-h-32 overflow-y-auto">
+										{uploadMutation.data.diagnostics.map((d) => (
+											<li key={d.line}>
+												第 {d.line} 行：{d.message}
+											</li>
+										))}
+									</ul>
+								)}
 						</AlertDescription>
 					</Alert>
 				)}
@@ -6,6 +6,7 @@ import { Card, CardContent, CardDescription, CardHeader, CardTitle } from "@/com
 import {
 	useRefreshTranslations,
 	useRemoveTranslationDictionary,
+	useSetTranslationFallbacks,
 	useSetTranslationLanguage,
 	useTranslationStatus,
 } from "@/lib/hooks/useSettings";
@@ -14,6 +15,12 @@ import { TranslationDictionaryUploadArea } from "./TranslationDictionaryUploadAr
 
 const LANGUAGE_NAMES: Record<string, string> = {
 	zh: "中文 (Chinese)",
+	"zh-Hans": "简体中文 (Simplified Chinese)",
+	"zh-Hant": "繁體中文 (Traditional Chinese)",
+	"zh-CN": "中文 - 中国大陆 (Chinese, Mainland)",
+	"zh-TW": "中文 - 台灣 (Chinese, Taiwan)",
+	"zh-HK": "中文 - 香港 (Chinese, Hong Kong)",
+	yue: "粵語 (Cantonese)",
 	ja: "日本語 (Japanese)",
 	ko: "한국어 (Korean)",
 	es: "Español (Spanish)",
@@ -21,6 +28,7 @@ const LANGUAGE_NAMES: Record<string, string> = {
 	de: "Deutsch (German)",
 	ru: "Русский (Russian)",
 	pt: "Português (Portuguese)",
+	"pt-BR": "Português do Brasil (Brazilian Portuguese)",
 	it: "Italiano (Italian)",
 	ar: "العربية (Arabic)",
 	vi: "Tiếng Việt (Vietnamese)",
@@ -32,6 +40,7 @@ export function TranslationSettingsPage() {
 	const setLanguageMutation = useSetTranslationLanguage();
 	const removeMutation = useRemoveTranslationDictionary();
 	const refreshMutation = useRefreshTranslations();
+	const fallbacksMutation = useSetTranslationFallbacks();
 
 	const handleLanguageSelect = async (langCode: string) => {
 		try {
@@ -63,8 +72,30 @@ export function TranslationSettingsPage() {
 		}
 	};
 
+	// Clicking a language adds it to the end of the fallback chain, or removes it
+	const handleFallbackToggle = async (langCode: string) => {
+		const current = status?.fallback_languages ?? [];
+		const next = current.includes(langCode)
+			? current.filter((lang) => lang !== langCode)
+			: [...current, langCode];
+		try {
+			await fallbacksMutation.mutateAsync(next);
+		} catch (error) {
+			console.error("Failed to set fallback languages:", error);
+		}
+	};
+
+	const handleFallbackReset = async () => {
+		try {
+			await fallbacksMutation.mutateAsync(null);
+		} catch (error) {
+			console.error("Failed to reset fallback languages:", error);
+		}
+	};
+
+	// Language codes are BCP 47 tags, whose case is meaningful (zh-Hant, pt-BR)
 	const getLanguageName = (code: string) => {
-		return LANGUAGE_NAMES[code] || code.toUpperCase();
+		return LANGUAGE_NAMES[code] || code;
 	};
 
 	return (
@@ -168,6 +199,49 @@ export function TranslationSettingsPage() {
 							})}
 						</div>
 
+						{/* Fallback languages */}
+						{status.current_language && (
+							<div className="pt-2 border-t space-y-2">
+								<p className="text-sm font-medium">回退语言 (Fallback)</p>
+								<p className="text-xs text-muted-foreground">
+									标签没有 {getLanguageName(status.current_language)}{" "}
+									翻译时，按顺序使用以下语言，都没有时显示原始名称：
+									{[
+										getLanguageName(status.current_language),
+										...status.fallback_languages.map(getLanguageName),
+										"原始名称",
+									].join(" → ")}
+								</p>
+								<div className="flex flex-wrap gap-2">
+									{status.available_languages
+										.filter((lang) => lang !== status.current_language)
+										.map((lang) => {
+											const position = status.fallback_languages.indexOf(lang);
+											return (
+												<Button
+													key={lang}
+													size="sm"
+													variant={position >= 0 ? "default" : "outline"}
+													onClick={() => handleFallbackToggle(lang)}
+													disabled={fallbacksMutation.isPending}
+												>
+													{position >= 0 && `${position + 1}. `}
+													{getLanguageName(lang)}
+												</Button>
+											);
+										})}
+								</div>
+								<Button
+									variant="ghost"
+									size="sm"
+									onClick={handleFallbackReset}
+									disabled={fallbacksMutation.isPending}
+								>
+									恢复默认 (Reset)
+								</Button>
+							</div>
+						)}
+
 						{/* Refresh button */}
 						{status.current_language && (
 							<div className="pt-2 border-t">
@@ -255,7 +329,7 @@ export function TranslationSettingsPage() {
 					</p>
 					<p className="text-sm">
 						• <code className="bg-muted px-1 rounded">language_code</code>:
-						语言代码（ISO 639-1，如 "zh", "ja"）
+						语言代码（BCP 47，如 "zh", "zh-Hant", "pt-BR", "yue"）
 					</p>
 					<p className="text-sm mt-2 text-muted-foreground">
 						一个 CSV 文件可以包含多种语言的翻译。上传后选择要显示的语言即可应用翻译。
@@ -14,6 +14,33 @@ export interface UpdateCategoryRequest {
 	sort_order?: number;
 }
 
+export interface CategoryRule {
+	rule_id: number;
+	category_id: number;
+	name_pattern: string | null;
+	model_category: number | null;
+	tag_type: string | null;
+	priority: number;
+	enabled: boolean;
+}
+
+export interface CategoryRuleRequest {
+	category_id: number;
+	name_pattern?: string | null;
+	model_category?: number | null;
+	tag_type?: string | null;
+	priority?: number;
+	enabled?: boolean;
+}
+
+export interface CategoryAssignment {
+	tag_id: number;
+	tag_name: string;
+	from_category_id: number;
+	to_category_id: number;
+	rule_id: number;
+}
+
 export function useCategories() {
 	return useQuery({
 		queryKey: ["categories"],
@@ -166,3 +193,69 @@ export function useBulkAssignTagsToCategory() {
 		},
 	});
 }
+
+export function useCategoryRules() {
+	return useQuery({
+		queryKey: ["category_rules"],
+		queryFn: async () => {
+			const rules = await invoke<CategoryRule[]>("get_category_rules");
+			return rules;
+		},
+	});
+}
+
+export function useCreateCategoryRule() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async (request: CategoryRuleRequest) => {
+			return await invoke<number>("create_category_rule", { request });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["category_rules"] });
+		},
+	});
+}
+
+export function useUpdateCategoryRule() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async ({ ruleId, request }: { ruleId: number; request: CategoryRuleRequest }) => {
+			await invoke<void>("update_category_rule", { ruleId, request });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["category_rules"] });
+		},
+	});
+}
+
+export function useDeleteCategoryRule() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async (ruleId: number) => {
+			await invoke<void>("delete_category_rule", { ruleId });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["category_rules"] });
+		},
+	});
+}
+
+/** Run the category rules over all tags; with dryRun, only preview the changes */
+export function useApplyCategoryRules() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async (dryRun: boolean) => {
+			return await invoke<CategoryAssignment[]>("apply_category_rules", { dryRun });
+		},
+		onSuccess: (_assignments, dryRun) => {
+			if (!dryRun) {
+				queryClient.invalidateQueries({ queryKey: ["tags"] });
+				queryClient.invalidateQueries({ queryKey: ["categories"] });
+			}
+		},
+	});
+}
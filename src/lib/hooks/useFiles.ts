@@ -77,3 +77,12 @@ export function useDeleteFilesBatch() {
 		},
 	});
 }
+
+/** Count a view of a file; call once when the viewer opens it */
+export function useRecordFileView() {
+	return useMutation({
+		mutationFn: async (fileHash: string) => {
+			await invoke<void>("record_file_view", { fileHash });
+		},
+	});
+}
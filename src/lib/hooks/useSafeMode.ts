@@ -0,0 +1,81 @@
+import { useMutation, useQuery, useQueryClient } from "@tanstack/react-query";
+import { invoke } from "@tauri-apps/api/core";
+
+export interface SafeModeSettings {
+	enabled: boolean;
+	/** Rating tags whose files stay visible */
+	allowed_ratings: string[];
+	/** Keep files without a rating tag visible */
+	allow_unrated: boolean;
+}
+
+export interface SafeModeStatus extends SafeModeSettings {
+	pin_set: boolean;
+	/** Unlocked with the PIN until locked again or the app restarts */
+	unlocked: boolean;
+	/** Whether file listings are being filtered */
+	active: boolean;
+	/** Rating tags in the library */
+	available_ratings: string[];
+}
+
+export function useSafeModeStatus() {
+	return useQuery({
+		queryKey: ["safe_mode"],
+		queryFn: async () => {
+			return await invoke<SafeModeStatus>("get_safe_mode_status");
+		},
+	});
+}
+
+/** Safe mode filters every file listing on the backend, so all cached queries go stale */
+function useInvalidateAll() {
+	const queryClient = useQueryClient();
+	return () => queryClient.invalidateQueries();
+}
+
+export function useSetSafeModeSettings() {
+	const invalidateAll = useInvalidateAll();
+
+	return useMutation({
+		mutationFn: async ({ settings, pin }: { settings: SafeModeSettings; pin?: string }) => {
+			await invoke<void>("set_safe_mode_settings", { settings, pin });
+		},
+		onSuccess: invalidateAll,
+	});
+}
+
+export function useSetSafeModePin() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async ({ currentPin, newPin }: { currentPin?: string; newPin?: string }) => {
+			await invoke<void>("set_safe_mode_pin", { currentPin, newPin });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["safe_mode"] });
+		},
+	});
+}
+
+export function useUnlockSafeMode() {
+	const invalidateAll = useInvalidateAll();
+
+	return useMutation({
+		mutationFn: async (pin?: string) => {
+			await invoke<void>("unlock_safe_mode", { pin });
+		},
+		onSuccess: invalidateAll,
+	});
+}
+
+export function useLockSafeMode() {
+	const invalidateAll = useInvalidateAll();
+
+	return useMutation({
+		mutationFn: async () => {
+			await invoke<void>("lock_safe_mode");
+		},
+		onSuccess: invalidateAll,
+	});
+}
@@ -3,11 +3,17 @@ import { invoke } from "@tauri-apps/api/core";
 import { open } from "@tauri-apps/plugin-dialog";
 
 // Types for backend responses
+export interface LineDiagnostic {
+	line: number;
+	message: string;
+}
+
 export interface ModelUploadResult {
 	success: boolean;
 	message: string;
 	file_path?: string;
 	calculated_hash?: string;
+	diagnostics?: LineDiagnostic[];
 }
 
 export interface ModelStatus {
@@ -30,6 +36,32 @@ export interface InferenceConfig {
 	max_tags: number;
 }
 
+/** Where the model's predictions in one label map category go */
+export interface ModelCategoryMapping {
+	model_category: number;
+	category_id: number;
+	tag_type: string;
+	/** null uses the inference config threshold */
+	threshold: number | null;
+	/** null uses the inference config MCut setting */
+	mcut_enabled: boolean | null;
+	argmax: boolean;
+	enabled: boolean;
+}
+
+export interface LabelCategory {
+	model_category: number;
+	name: string;
+	label_count: number;
+}
+
+export interface ModelCategoryConfig {
+	model_name: string;
+	label_categories: LabelCategory[];
+	mappings: ModelCategoryMapping[];
+	customized: boolean;
+}
+
 // Hooks for model management
 export function useUploadTagModel() {
 	return useMutation({
@@ -91,11 +123,47 @@ export function useSetInferenceConfig() {
 	});
 }
 
+// Hooks for model category mappings
+export function useModelCategoryMappings() {
+	return useQuery({
+		queryKey: ["model_category_mappings"],
+		queryFn: async (): Promise<ModelCategoryConfig> => {
+			return await invoke<ModelCategoryConfig>("get_model_category_mappings");
+		},
+	});
+}
+
+export function useSetModelCategoryMappings() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async (mappings: ModelCategoryMapping[]): Promise<void> => {
+			await invoke("set_model_category_mappings", { mappings });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["model_category_mappings"] });
+		},
+	});
+}
+
+export function useResetModelCategoryMappings() {
+	const queryClient = useQueryClient();
+
+	return useMutation({
+		mutationFn: async (): Promise<void> => {
+			await invoke("reset_model_category_mappings");
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["model_category_mappings"] });
+		},
+	});
+}
+
 // Hooks for debugging - Enhanced types with visualization data
 export interface PredictionDetail {
 	name: string;
 	confidence: number;
-	category: string; // rating/general/character
+	category: string; // general/artist/copyright/character/meta/rating
 	tag_id: number;
 	index: number; // 在输出数组中的位置
 }
@@ -241,10 +309,20 @@ export interface TranslationUploadResult {
 	valid_entries: number;
 	invalid_entries: number;
 	available_languages: string[];
+	diagnostics?: LineDiagnostic[];
+}
+
+export interface TranslationOverride {
+	tag_id: number;
+	tag_name: string;
+	language_code: string;
+	text: string;
 }
 
 export interface TranslationStatus {
 	current_language?: string;
+	/** Languages tried in order when a tag has no translation in current_language */
+	fallback_languages: string[];
 	available_languages: string[];
 	dictionary_loaded: boolean;
 	dictionary_path?: string;
@@ -262,7 +340,10 @@ export function useUploadTranslationDictionary() {
 			return result;
 		},
 		onSuccess: () => {
+			// Uploading applies the dictionary to the current language right away
 			queryClient.invalidateQueries({ queryKey: ["translation_status"] });
+			queryClient.invalidateQueries({ queryKey: ["tags"] });
+			queryClient.invalidateQueries({ queryKey: ["all_tags"] });
 		},
 	});
 }
@@ -294,6 +375,21 @@ export function useSetTranslationLanguage() {
 	});
 }
 
+export function useSetTranslationFallbacks() {
+	const queryClient = useQueryClient();
+	return useMutation({
+		// null goes back to the default chain (the current language with subtags dropped)
+		mutationFn: async (fallbackLanguages: string[] | null): Promise<number> => {
+			return await invoke<number>("set_translation_fallbacks", { fallbackLanguages });
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["translation_status"] });
+			queryClient.invalidateQueries({ queryKey: ["tags"] });
+			queryClient.invalidateQueries({ queryKey: ["all_tags"] });
+		},
+	});
+}
+
 export function useRemoveTranslationDictionary() {
 	const queryClient = useQueryClient();
 	return useMutation({
@@ -322,3 +418,62 @@ export function useRefreshTranslations() {
 		},
 	});
 }
+
+export function useCancelTranslationRefresh() {
+	return useMutation({
+		mutationFn: async (): Promise<void> => {
+			await invoke("cancel_translation_refresh");
+		},
+	});
+}
+
+export function useTranslationOverrides(languageCode?: string) {
+	return useQuery({
+		queryKey: ["translation_overrides", languageCode],
+		queryFn: async (): Promise<TranslationOverride[]> => {
+			return await invoke<TranslationOverride[]>("list_translation_overrides", {
+				languageCode: languageCode ?? null,
+			});
+		},
+	});
+}
+
+export function useSetTranslationOverride() {
+	const queryClient = useQueryClient();
+	return useMutation({
+		mutationFn: async (args: {
+			tagId: number;
+			languageCode: string;
+			text: string;
+		}): Promise<void> => {
+			await invoke("set_translation_override", args);
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["translation_overrides"] });
+			queryClient.invalidateQueries({ queryKey: ["tags"] });
+			queryClient.invalidateQueries({ queryKey: ["all_tags"] });
+		},
+	});
+}
+
+export function useClearTranslationOverride() {
+	const queryClient = useQueryClient();
+	return useMutation({
+		mutationFn: async (args: { tagId: number; languageCode: string }): Promise<void> => {
+			await invoke("clear_translation_override", args);
+		},
+		onSuccess: () => {
+			queryClient.invalidateQueries({ queryKey: ["translation_overrides"] });
+			queryClient.invalidateQueries({ queryKey: ["tags"] });
+			queryClient.invalidateQueries({ queryKey: ["all_tags"] });
+		},
+	});
+}
+
+export function useExportTranslationDictionary() {
+	return useMutation({
+		mutationFn: async (destination: string): Promise<number> => {
+			return await invoke<number>("export_translation_dictionary", { destination });
+		},
+	});
+}